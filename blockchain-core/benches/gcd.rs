@@ -0,0 +1,44 @@
+// benches/gcd.rs
+//! `lehmer_gcd` contra `euclid_gcd` em 10 milhões de pares de 128 bits, os mesmos para os dois.
+use blockchain_core::math::{euclid_gcd, lehmer_gcd};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::hint::black_box;
+use std::time::Duration;
+
+const PAIRS: u64 = 10_000_000;
+const SEED: u64 = 0x5eed;
+
+// splitmix64, para os pares não dependerem da feature `mining`
+fn next(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// Os pares saem do gerador durante a medição: guardar 10 milhões custaria 320 MB, e gerar um custa
+// bem menos que um MDC
+fn run(gcd: fn(u128, u128) -> u128) -> u128 {
+    let mut state = SEED;
+    let mut acc = 0;
+    for _ in 0..PAIRS {
+        let a = (next(&mut state) as u128) << 64 | next(&mut state) as u128;
+        let b = (next(&mut state) as u128) << 64 | next(&mut state) as u128;
+        acc ^= gcd(black_box(a), black_box(b));
+    }
+    acc
+}
+
+fn gcd(c: &mut Criterion) {
+    assert_eq!(run(lehmer_gcd), run(euclid_gcd));
+    let mut group = c.benchmark_group("gcd");
+    group.sample_size(10).measurement_time(Duration::from_secs(90)).throughput(Throughput::Elements(PAIRS));
+    for (name, f) in [("lehmer", lehmer_gcd as fn(u128, u128) -> u128), ("euclid", euclid_gcd)] {
+        group.bench_function(name, |bench| bench.iter(|| run(f)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, gcd);
+criterion_main!(benches);
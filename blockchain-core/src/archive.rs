@@ -0,0 +1,132 @@
+// src/archive.rs
+use serde::{Deserialize, Serialize};
+
+use crate::block::{Block, VerifyError};
+use crate::merkle::{compute_merkle_root, merkle_proof, verify_merkle_proof, ProofStep};
+use crate::rules::{validate_block, RuleSchedule};
+
+/// Blocos removidos entre dois checkpoints, resumidos pela raiz de Merkle dos seus hashes.
+/// `last_hash` e `last_proof` ligam o trecho ao `prev_hash` do checkpoint seguinte.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrunedSegment {
+    pub from_index: u64,
+    pub to_index: u64,
+    pub merkle_root: String,
+    pub last_hash: String,
+    pub last_proof: Vec<ProofStep>,
+}
+
+impl PrunedSegment {
+    pub fn block_count(&self) -> u64 {
+        self.to_index - self.from_index + 1
+    }
+}
+
+/// Cadeia para arquivamento: gênesis, um bloco a cada `interval` índices e a ponta.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompressedChain {
+    pub interval: u64,
+    pub checkpoints: Vec<Block>,
+    // segments[i] fica entre checkpoints[i] e checkpoints[i + 1], quando há blocos entre eles
+    pub segments: Vec<PrunedSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchiveError {
+    Empty,
+    NotGenesis,
+    Checkpoint { index: u64, error: VerifyError },
+    Gap { after: u64 },
+    Segment { from_index: u64, reason: &'static str },
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveError::Empty => write!(f, "compressed chain is empty"),
+            ArchiveError::NotGenesis => write!(f, "first checkpoint is not the genesis block"),
+            ArchiveError::Checkpoint { index, error } => write!(f, "checkpoint {}: {}", index, error),
+            ArchiveError::Gap { after } => write!(f, "blocks after checkpoint {} are missing", after),
+            ArchiveError::Segment { from_index, reason } => {
+                write!(f, "pruned segment starting at {}: {}", from_index, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl CompressedChain {
+    /// Comprime uma cadeia já validada. `interval` menor que 1 é tratado como 1.
+    pub fn compress(blocks: &[Block], interval: u64) -> Self {
+        let interval = interval.max(1);
+        let last = blocks.len().saturating_sub(1);
+        let mut checkpoints = Vec::new();
+        let mut segments = Vec::new();
+        let mut pruned: Vec<&Block> = Vec::new();
+
+        for (position, block) in blocks.iter().enumerate() {
+            if position != 0 && position != last && !block.index.is_multiple_of(interval) {
+                pruned.push(block);
+                continue;
+            }
+            if !pruned.is_empty() {
+                let leaves: Vec<String> = pruned.iter().map(|b| b.hash.clone()).collect();
+                segments.push(PrunedSegment {
+                    from_index: pruned[0].index,
+                    to_index: pruned[pruned.len() - 1].index,
+                    merkle_root: compute_merkle_root(&leaves),
+                    last_hash: leaves[leaves.len() - 1].clone(),
+                    last_proof: merkle_proof(&leaves, leaves.len() - 1).unwrap_or_default(),
+                });
+                pruned.clear();
+            }
+            checkpoints.push(block.clone());
+        }
+        CompressedChain { interval, checkpoints, segments }
+    }
+
+    pub fn height(&self) -> u64 {
+        self.checkpoints.last().map_or(0, |tip| tip.index + 1)
+    }
+
+    pub fn pruned(&self) -> u64 {
+        self.segments.iter().map(PrunedSegment::block_count).sum()
+    }
+
+    /// Confere o que sobrou: cada checkpoint é válido por si só, checkpoints vizinhos
+    /// seguem as regras completas e cada trecho removido se liga ao checkpoint seguinte.
+    pub fn verify(&self, rules: &RuleSchedule) -> Result<(), ArchiveError> {
+        let genesis = self.checkpoints.first().ok_or(ArchiveError::Empty)?;
+        if genesis.hash != Block::genesis().hash {
+            return Err(ArchiveError::NotGenesis);
+        }
+        let mut segments = self.segments.iter().peekable();
+        for pair in self.checkpoints.windows(2) {
+            let (prev, block) = (&pair[0], &pair[1]);
+            let checkpoint = |error| ArchiveError::Checkpoint { index: block.index, error };
+            if block.index == prev.index + 1 {
+                validate_block(block, prev, rules).map_err(checkpoint)?;
+                continue;
+            }
+            block.verify_contents().map_err(checkpoint)?;
+            let segment = segments
+                .next_if(|s| s.from_index == prev.index + 1)
+                .ok_or(ArchiveError::Gap { after: prev.index })?;
+            let invalid = |reason| ArchiveError::Segment { from_index: segment.from_index, reason };
+            if segment.to_index + 1 != block.index {
+                return Err(invalid("does not end right before the next checkpoint"));
+            }
+            if segment.last_hash != block.prev_hash {
+                return Err(invalid("last hash does not match the next checkpoint's prev_hash"));
+            }
+            if !verify_merkle_proof(&segment.merkle_root, &segment.last_hash, &segment.last_proof) {
+                return Err(invalid("merkle proof does not match the root"));
+            }
+        }
+        if segments.next().is_some() {
+            return Err(ArchiveError::Segment { from_index: 0, reason: "segment without a checkpoint" });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,501 @@
+// src/block.rs
+use num::{BigUint, Integer, One};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::fork::prime_work;
+use crate::math::MathError;
+use crate::transaction::{tx_root, Transaction, EMPTY_TX_ROOT};
+use crate::width::{PrimeValue, PrimeWidth};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Block {
+    pub index: u64,
+    pub prev_hash: String,
+    // Número até 2^53 e string decimal acima, no JSON; a largura máxima é a das regras da cadeia
+    pub prime: PrimeValue,
+    pub a: u64,
+    pub b: u64,
+    pub c: u64,
+    pub d: u64,
+    pub hash: String,
+    // Blocos anteriores ao versionamento não têm o campo e seguem as regras v1
+    #[serde(default = "default_rules_version")]
+    pub rules_version: u32,
+    // Unix em milissegundos; 0 nos blocos anteriores ao campo
+    #[serde(default)]
+    pub timestamp: u64,
+    // Raiz de Merkle dos ids das transações; faz parte do cabeçalho para provas sem o corpo
+    #[serde(default = "default_tx_root")]
+    pub tx_root: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transactions: Vec<Transaction>,
+    // Regras v4: o hash, lido como inteiro de 256 bits, precisa ficar abaixo de 2^256 / hash_scale
+    #[serde(default)]
+    pub hash_scale: u64,
+    // Regras v5: trabalho do bloco (dígitos do primo, ver `fork::block_work`) gravado no cabeçalho; 0 antes
+    #[serde(default)]
+    pub work: u64,
+    // Regras v6: endereço creditado com a recompensa (vazio queima) e a recompensa do calendário de emissão
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub coinbase: String,
+    #[serde(default)]
+    pub reward: u64,
+}
+
+fn default_rules_version() -> u32 {
+    1
+}
+
+fn default_tx_root() -> String {
+    EMPTY_TX_ROOT.to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    IndexMismatch { expected: u64, found: u64 },
+    PrevHashMismatch { expected: String, found: String },
+    NotCoprime,
+    WitnessMismatch { expected: Option<PrimeValue>, found: PrimeValue },
+    NotPrime(PrimeValue),
+    PrimeWidth { width: PrimeWidth, found: PrimeValue },
+    HashMismatch { expected: String, found: String },
+    RulesVersion { expected: u32, found: u32 },
+    DigitStructure(String),
+    TimestampRegression { prev: u64, found: u64 },
+    TxRootMismatch { expected: String, found: String },
+    TxSignature { txid: String },
+    NotFareyNeighbors { determinant: i128 },
+    HashScale { rules_version: u32, found: u64 },
+    HashAboveTarget { hash_scale: u64 },
+    BlockWork { rules_version: u32, expected: u64, found: u64 },
+    BlockReward { rules_version: u32, expected: u64, found: u64 },
+    CoinbaseBeforeV6 { rules_version: u32 },
+    Arithmetic(MathError),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::IndexMismatch { expected, found } => {
+                write!(f, "invalid index: expected {}, found {}", expected, found)
+            }
+            VerifyError::PrevHashMismatch { expected, found } => {
+                write!(f, "invalid prev_hash: expected {}, found {}", expected, found)
+            }
+            VerifyError::NotCoprime => write!(f, "gcd(a, b) and gcd(c, d) must both be 1"),
+            VerifyError::WitnessMismatch { expected: Some(n), found } => {
+                write!(f, "prime {} does not match a*d + b*c = {}", found, n)
+            }
+            VerifyError::WitnessMismatch { expected: None, found } => {
+                write!(f, "prime {} does not match a*d + b*c (overflow)", found)
+            }
+            VerifyError::NotPrime(n) => write!(f, "{} is not prime", n),
+            VerifyError::PrimeWidth { width, found } => write!(
+                f,
+                "prime {} ({} digits) exceeds the chain's {} width (at most {})",
+                found,
+                found.digits(),
+                width,
+                width.max_value()
+            ),
+            VerifyError::HashMismatch { expected, found } => {
+                write!(f, "invalid hash: expected {}, found {}", expected, found)
+            }
+            VerifyError::RulesVersion { expected, found } => {
+                write!(f, "invalid rules version: expected {}, found {}", expected, found)
+            }
+            VerifyError::DigitStructure(reason) => write!(f, "invalid digit structure: {}", reason),
+            VerifyError::TimestampRegression { prev, found } => {
+                write!(f, "timestamp {} is earlier than the previous block's {}", found, prev)
+            }
+            VerifyError::TxRootMismatch { expected, found } => {
+                write!(f, "invalid tx_root: expected {}, found {}", expected, found)
+            }
+            VerifyError::TxSignature { txid } => {
+                write!(f, "transaction {} has a signature that does not match the sender key", txid)
+            }
+            VerifyError::NotFareyNeighbors { determinant } => {
+                write!(f, "a/b and c/d are not Farey neighbours: a*d - b*c = {}", determinant)
+            }
+            VerifyError::HashScale { rules_version, found } => {
+                write!(f, "hash_scale {} is not allowed by rules v{}", found, rules_version)
+            }
+            VerifyError::HashAboveTarget { hash_scale } => {
+                write!(f, "hash is not below the target 2^256 / {}", hash_scale)
+            }
+            VerifyError::BlockWork { rules_version, expected, found } => {
+                write!(f, "work {} does not match rules v{}: expected {}", found, rules_version, expected)
+            }
+            VerifyError::BlockReward { rules_version, expected, found } => {
+                write!(f, "reward {} does not match rules v{}: expected {}", found, rules_version, expected)
+            }
+            VerifyError::CoinbaseBeforeV6 { rules_version } => {
+                write!(f, "coinbase is not allowed by rules v{}", rules_version)
+            }
+            VerifyError::Arithmetic(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl VerifyError {
+    /// Nome estável do invariante violado.
+    pub fn invariant(&self) -> &'static str {
+        match self {
+            VerifyError::IndexMismatch { .. } => "index_sequence",
+            VerifyError::PrevHashMismatch { .. } => "prev_hash_link",
+            VerifyError::NotCoprime => "coprime_witness",
+            VerifyError::WitnessMismatch { .. } => "witness_sum",
+            VerifyError::NotPrime(_) => "primality",
+            VerifyError::PrimeWidth { .. } => "prime_width",
+            VerifyError::HashMismatch { .. } => "block_hash",
+            VerifyError::RulesVersion { .. } => "rules_version",
+            VerifyError::DigitStructure(_) => "digit_structure",
+            VerifyError::TimestampRegression { .. } => "timestamp_order",
+            VerifyError::TxRootMismatch { .. } => "tx_root",
+            VerifyError::TxSignature { .. } => "tx_signature",
+            VerifyError::NotFareyNeighbors { .. } => "farey_determinant",
+            VerifyError::HashScale { .. } => "hash_scale",
+            VerifyError::HashAboveTarget { .. } => "hash_target",
+            VerifyError::BlockWork { .. } => "block_work",
+            VerifyError::BlockReward { .. } | VerifyError::CoinbaseBeforeV6 { .. } => "block_reward",
+            VerifyError::Arithmetic(_) => "arithmetic",
+        }
+    }
+}
+
+/// SHA-256 sobre os campos do cabeçalho (tudo menos o próprio `hash`).
+/// A partir das regras v2 a versão também entra no hash; o timestamp entra quando presente,
+/// a raiz das transações quando o bloco tem alguma, e `hash_scale`, `work`, `coinbase` e `reward` quando
+/// não são zero (ou vazios).
+pub fn compute_hash(block: &Block) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(block.index.to_le_bytes());
+    hasher.update(block.prev_hash.as_bytes());
+    hasher.update(block.prime.to_le_bytes());
+    for x in [block.a, block.b, block.c, block.d] {
+        hasher.update(x.to_le_bytes());
+    }
+    if block.rules_version >= 2 {
+        hasher.update(block.rules_version.to_le_bytes());
+    }
+    if block.timestamp != 0 {
+        hasher.update(block.timestamp.to_le_bytes());
+    }
+    if block.tx_root != EMPTY_TX_ROOT {
+        hasher.update(block.tx_root.as_bytes());
+    }
+    if block.hash_scale != 0 {
+        hasher.update(block.hash_scale.to_le_bytes());
+    }
+    if block.work != 0 {
+        hasher.update(block.work.to_le_bytes());
+    }
+    if !block.coinbase.is_empty() {
+        hasher.update(block.coinbase.as_bytes());
+    }
+    if block.reward != 0 {
+        hasher.update(block.reward.to_le_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// `hash * scale < 2^256`, isto é, o hash fica abaixo do alvo `2^256 / scale`. Hash que não é hex
+/// de 32 bytes nunca atinge o alvo.
+pub fn meets_hash_target(hash: &str, scale: u64) -> bool {
+    match hex::decode(hash) {
+        Ok(bytes) if bytes.len() == 32 => BigUint::from_bytes_be(&bytes) * scale < BigUint::one() << 256u32,
+        _ => false,
+    }
+}
+
+/// `a*d + b*c`, calculado em u128 (os produtos de u64 nunca estouram); `None` só se a soma passar de u128.
+pub fn witness(a: u64, b: u64, c: u64, d: u64) -> Option<PrimeValue> {
+    (a as u128 * d as u128).checked_add(b as u128 * c as u128).map(PrimeValue::new)
+}
+
+impl Block {
+    pub fn genesis() -> Self {
+        Block {
+            index: 0,
+            prev_hash: "0".into(),
+            prime: PrimeValue::U64(2),
+            a: 1, b: 1, c: 1, d: 1,
+            hash: "genesis".into(),
+            rules_version: 1,
+            timestamp: 0,
+            tx_root: default_tx_root(),
+            transactions: Vec::new(),
+            hash_scale: 0,
+            work: 0,
+            coinbase: String::new(),
+            reward: 0,
+        }
+    }
+
+    /// Valida o bloco como sucessor imediato de `prev` pelas regras base (v1).
+    /// Use `rules::validate_block` para aplicar as regras da versão do bloco.
+    pub fn verify(&self, prev: &Block) -> Result<(), VerifyError> {
+        let expected = prev.index.checked_add(1).ok_or(VerifyError::Arithmetic(MathError::Overflow("index")))?;
+        if self.index != expected {
+            return Err(VerifyError::IndexMismatch { expected, found: self.index });
+        }
+        if self.prev_hash != prev.hash {
+            return Err(VerifyError::PrevHashMismatch {
+                expected: prev.hash.clone(),
+                found: self.prev_hash.clone(),
+            });
+        }
+        if self.timestamp < prev.timestamp {
+            return Err(VerifyError::TimestampRegression { prev: prev.timestamp, found: self.timestamp });
+        }
+        self.verify_contents()
+    }
+
+    /// Checagens que não dependem do bloco anterior: testemunha, primalidade, raiz e assinaturas das
+    /// transações e hash.
+    pub fn verify_contents(&self) -> Result<(), VerifyError> {
+        if self.a.gcd(&self.b) != 1 || self.c.gcd(&self.d) != 1 {
+            return Err(VerifyError::NotCoprime);
+        }
+        let expected = witness(self.a, self.b, self.c, self.d);
+        if expected != Some(self.prime) {
+            return Err(VerifyError::WitnessMismatch { expected, found: self.prime });
+        }
+        if !self.prime.is_prime() {
+            return Err(VerifyError::NotPrime(self.prime));
+        }
+        let root = tx_root(&self.transactions);
+        if self.tx_root != root {
+            return Err(VerifyError::TxRootMismatch { expected: root, found: self.tx_root.clone() });
+        }
+        // Mesma conferência do mempool, para os blocos que chegam sem passar por ele
+        if let Some(tx) = self.transactions.iter().find(|tx| !tx.verify_signature()) {
+            return Err(VerifyError::TxSignature { txid: tx.id() });
+        }
+        let hash = compute_hash(self);
+        if self.hash != hash {
+            return Err(VerifyError::HashMismatch { expected: hash, found: self.hash.clone() });
+        }
+        Ok(())
+    }
+}
+
+/// Monta um bloco sobre `prev`, calculando `prime` e `hash` quando não informados.
+#[derive(Debug, Clone)]
+pub struct BlockBuilder {
+    index: u64,
+    prev_hash: String,
+    witness: (u64, u64, u64, u64),
+    prime: Option<PrimeValue>,
+    hash: Option<String>,
+    rules_version: u32,
+    timestamp: u64,
+    transactions: Vec<Transaction>,
+    hash_scale: u64,
+    work: Option<u64>,
+    coinbase: String,
+    reward: u64,
+}
+
+impl BlockBuilder {
+    /// Modelo para o bloco `index` sobre `prev_hash`, sem precisar do bloco anterior completo.
+    pub fn new(index: u64, prev_hash: impl Into<String>) -> Self {
+        BlockBuilder {
+            index,
+            prev_hash: prev_hash.into(),
+            witness: (1, 1, 1, 1),
+            prime: None,
+            hash: None,
+            rules_version: 1,
+            timestamp: 0,
+            transactions: Vec::new(),
+            hash_scale: 0,
+            work: None,
+            coinbase: String::new(),
+            reward: 0,
+        }
+    }
+
+    pub fn on(prev: &Block) -> Self {
+        BlockBuilder {
+            index: prev.index + 1,
+            prev_hash: prev.hash.clone(),
+            witness: (1, 1, 1, 1),
+            prime: None,
+            hash: None,
+            rules_version: prev.rules_version,
+            timestamp: prev.timestamp,
+            transactions: Vec::new(),
+            hash_scale: 0,
+            work: None,
+            coinbase: String::new(),
+            reward: 0,
+        }
+    }
+
+    pub fn index(mut self, index: u64) -> Self {
+        self.index = index;
+        self
+    }
+
+    pub fn prev_hash(mut self, prev_hash: impl Into<String>) -> Self {
+        self.prev_hash = prev_hash.into();
+        self
+    }
+
+    pub fn witness(mut self, a: u64, b: u64, c: u64, d: u64) -> Self {
+        self.witness = (a, b, c, d);
+        self
+    }
+
+    pub fn prime(mut self, prime: impl Into<PrimeValue>) -> Self {
+        self.prime = Some(prime.into());
+        self
+    }
+
+    pub fn rules_version(mut self, rules_version: u32) -> Self {
+        self.rules_version = rules_version;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Carimba o horário atual, sem voltar para antes do timestamp já definido (o do bloco anterior).
+    pub fn stamp_now(mut self) -> Self {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64);
+        self.timestamp = self.timestamp.max(now);
+        self
+    }
+
+    /// Versão das regras do bloco que será montado.
+    pub fn version(&self) -> u32 {
+        self.rules_version
+    }
+
+    /// Transações do bloco, na ordem em que entram na raiz.
+    pub fn transactions(mut self, transactions: Vec<Transaction>) -> Self {
+        self.transactions = transactions;
+        self
+    }
+
+    /// Divisor do alvo de hash das regras v4; 0 nas versões anteriores.
+    pub fn hash_scale(mut self, hash_scale: u64) -> Self {
+        self.hash_scale = hash_scale;
+        self
+    }
+
+    /// Trabalho declarado; sem ele vale o do primo a partir das regras v5 e 0 antes delas.
+    pub fn work(mut self, work: u64) -> Self {
+        self.work = Some(work);
+        self
+    }
+
+    /// Endereço que recebe a recompensa; só aceito a partir das regras v6.
+    pub fn coinbase(mut self, coinbase: impl Into<String>) -> Self {
+        self.coinbase = coinbase.into();
+        self
+    }
+
+    /// Recompensa declarada; `ChainState::template` preenche a do calendário de emissão.
+    pub fn reward(mut self, reward: u64) -> Self {
+        self.reward = reward;
+        self
+    }
+
+    pub fn hash(mut self, hash: impl Into<String>) -> Self {
+        self.hash = Some(hash.into());
+        self
+    }
+
+    pub fn build(self) -> Block {
+        let (a, b, c, d) = self.witness;
+        let prime = self.prime.or_else(|| witness(a, b, c, d)).unwrap_or(PrimeValue::U64(0));
+        let work = self.work.unwrap_or(if self.rules_version >= 5 { prime_work(prime) } else { 0 });
+        let mut block = Block {
+            index: self.index,
+            prev_hash: self.prev_hash,
+            prime,
+            a, b, c, d,
+            hash: String::new(),
+            rules_version: self.rules_version,
+            timestamp: self.timestamp,
+            tx_root: tx_root(&self.transactions),
+            transactions: self.transactions,
+            hash_scale: self.hash_scale,
+            work,
+            coinbase: self.coinbase,
+            reward: self.reward,
+        };
+        block.hash = self.hash.unwrap_or_else(|| compute_hash(&block));
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signature::SigningKey;
+
+    #[test]
+    fn validation_recomputes_the_tx_root() {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let txs: Vec<_> = (0..3).map(|nonce| Transaction::sign(&key, "bob", 5, nonce)).collect();
+        let block = BlockBuilder::on(&Block::genesis()).witness(1, 1, 2, 1).transactions(txs).build();
+        assert_eq!(block.verify(&Block::genesis()), Ok(()));
+
+        // Uma transação trocada depois de montado o bloco não bate com a raiz do cabeçalho
+        let mut swapped = block.clone();
+        swapped.transactions[1] = Transaction::sign(&key, "mallory", 5, 1);
+        assert!(matches!(swapped.verify(&Block::genesis()), Err(VerifyError::TxRootMismatch { .. })));
+
+        // E a raiz entra no hash: acertá-la sem refazer o hash também falha
+        swapped.tx_root = tx_root(&swapped.transactions);
+        assert!(matches!(swapped.verify(&Block::genesis()), Err(VerifyError::HashMismatch { .. })));
+        swapped.hash = compute_hash(&swapped);
+        assert_eq!(swapped.verify(&Block::genesis()), Ok(()));
+    }
+
+    #[test]
+    fn hash_acceptance_scales_with_s() {
+        let hashes: Vec<String> = (0u32..20_000).map(|i| hex::encode(Sha256::digest(i.to_be_bytes()))).collect();
+        for scale in [1u64, 2, 4, 8, 16] {
+            let accepted = hashes.iter().filter(|hash| meets_hash_target(hash, scale)).count();
+            let fraction = accepted as f64 / hashes.len() as f64;
+            let expected = 1.0 / scale as f64;
+            let message = format!("S={}: {:.4} contra {:.4}", scale, fraction, expected);
+            assert!((fraction - expected).abs() < 0.1 * expected, "{}", message);
+        }
+    }
+
+    #[test]
+    fn hash_target_edges() {
+        let zero = "00".repeat(32);
+        let max = "ff".repeat(32);
+        assert!(meets_hash_target(&zero, u64::MAX));
+        assert!(meets_hash_target(&max, 1));
+        assert!(!meets_hash_target(&max, 2));
+        // Fora do formato nunca passa, nem com S = 1
+        assert!(!meets_hash_target("zz", 1));
+        assert!(!meets_hash_target(&"00".repeat(31), 1));
+    }
+
+    #[test]
+    fn witness_and_index_overflow_are_errors() {
+        assert_eq!(witness(u64::MAX, u64::MAX, u64::MAX, u64::MAX), None);
+        let sum = witness(u64::MAX, 1, u64::MAX, 1).unwrap();
+        assert_eq!(sum, PrimeValue::new(2 * u64::MAX as u128));
+
+        // Antecessor hostil no fim de u64: o índice seguinte não existe
+        let block = BlockBuilder::on(&Block::genesis()).witness(1, 1, 2, 1).build();
+        let prev = Block { index: u64::MAX, ..Block::genesis() };
+        assert_eq!(block.verify(&prev), Err(VerifyError::Arithmetic(MathError::Overflow("index"))));
+    }
+}
@@ -0,0 +1,155 @@
+// src/calibration.rs
+#[cfg(feature = "mining")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "mining")]
+use crate::block::BlockBuilder;
+use crate::mining::{Adjustment, Difficulty, DifficultyDecision, DifficultyDelta, MAX_HASH_SCALE, MAX_MIN_DIGITS};
+#[cfg(feature = "mining")]
+use crate::mining::mine_with;
+#[cfg(feature = "mining")]
+use crate::throttle::Throttle;
+
+// Menor n_limit proposto, o mesmo piso dos ajustes para baixo
+const MIN_N_LIMIT: u64 = 100;
+
+/// Mede quantos candidatos por segundo uma thread testa minerando `template` por `duration`,
+/// emendando blocos (que são descartados) até o tempo acabar.
+#[cfg(feature = "mining")]
+pub fn measure_throughput(template: &BlockBuilder, difficulty: &Difficulty, duration: Duration) -> f64 {
+    let started = Instant::now();
+    let mut rng = rand::thread_rng();
+    let mut candidates = 0;
+    while started.elapsed() < duration {
+        let (_, stats) = mine_with(template, difficulty, None, &mut Throttle::unlimited(), &mut rng, None, |_| {
+            started.elapsed() < duration
+        });
+        candidates += stats.candidates;
+    }
+    candidates as f64 / started.elapsed().as_secs_f64()
+}
+
+// Maior n_limit com que `n = a*d + b*c` ainda cabe em u64 para `a`, `c` de `digits` dígitos, com folga
+// para o próximo aumento do ajuste por janela (um dígito a mais e n_limit * 1.5) também caber
+fn max_n_limit(digits: u32) -> u64 {
+    u64::MAX / 2 / 10u64.pow((digits + 1).min(MAX_MIN_DIGITS)) / 3 * 2
+}
+
+/// Dificuldade inicial cujo tempo esperado (`expected_candidates / candidates_per_sec`) fica mais perto
+/// de `target`. Nas regras v4 só o `hash_scale` muda; antes delas procura `min_digits` a partir do de
+/// `base` e o `n_limit` que completa o trabalho, no empate ficando com menos dígitos. Sem v4 o trabalho
+/// por dígitos satura em ~41 candidatos (o witness precisa caber em u64): em máquinas rápidas a
+/// calibração para no máximo viável e o reajuste normal segue dali.
+pub fn calibrate(base: &Difficulty, rules_version: u32, candidates_per_sec: f64, target: f64) -> DifficultyDecision {
+    let candidates_per_sec = candidates_per_sec.max(f64::MIN_POSITIVE);
+    let wanted = target * candidates_per_sec;
+    let mut clamps = Vec::new();
+    let mut after = base.clone();
+
+    if rules_version >= 4 {
+        let unit = Difficulty { hash_scale: 1, ..base.clone() }.expected_candidates();
+        let scaled = (wanted / unit).round();
+        if scaled < 1.0 {
+            clamps.push("hash_scale_min");
+        }
+        if scaled > MAX_HASH_SCALE as f64 {
+            clamps.push("hash_scale_max");
+        }
+        after.hash_scale = scaled.clamp(1.0, MAX_HASH_SCALE as f64) as u64;
+    } else {
+        let error = |d: &Difficulty| (d.expected_candidates() - wanted).abs();
+        let mut best: Option<Difficulty> = None;
+        for digits in base.min_digits.max(1)..=MAX_MIN_DIGITS {
+            let ceiling = max_n_limit(digits);
+            if ceiling < MIN_N_LIMIT {
+                break;
+            }
+            let ideal = (wanted - digits as f64 * std::f64::consts::LN_10).exp();
+            let n_limit = ideal.round().clamp(MIN_N_LIMIT as f64, ceiling as f64) as u64;
+            let candidate = Difficulty { min_digits: digits, n_limit, ..base.clone() };
+            if best.as_ref().is_none_or(|b| error(&candidate) < error(b)) {
+                best = Some(candidate);
+            }
+        }
+        if let Some(best) = best {
+            if best.n_limit == max_n_limit(best.min_digits) {
+                clamps.push("witness_overflow_bound");
+            } else if best.n_limit == MIN_N_LIMIT && best.min_digits == base.min_digits.max(1) {
+                clamps.push("n_limit_min");
+            }
+            after.min_digits = best.min_digits;
+            after.n_limit = best.n_limit;
+        }
+    }
+
+    let before_work = base.expected_candidates();
+    let after_work = after.expected_candidates();
+    let action = Adjustment::between(base, &after);
+    if action != Adjustment::Hold {
+        after.generation += 1;
+    }
+    DifficultyDecision {
+        algorithm: "calibration",
+        reason: "bootstrap",
+        window: Vec::new(),
+        // Tempos esperados pela vazão medida, antes e depois da calibração
+        average: before_work / candidates_per_sec,
+        effective_average: after_work / candidates_per_sec,
+        target,
+        tolerance: 0.0,
+        action,
+        deltas: DifficultyDelta::between(base, &after),
+        before: base.clone(),
+        after,
+        clamps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tempo esperado de `difficulty` a `candidates_per_sec`
+    fn expected_time(difficulty: &Difficulty, candidates_per_sec: f64) -> f64 {
+        difficulty.expected_candidates() / candidates_per_sec
+    }
+
+    #[test]
+    fn digits_and_n_limit_hit_the_target() {
+        let base = Difficulty::default();
+        // 3 candidatos/s por 10 s: ln(n) = 30 - digits * ln(10)
+        let decision = calibrate(&base, 1, 3.0, 10.0);
+        let after = &decision.after;
+        assert!(after.min_digits >= base.min_digits);
+        let ideal = (30.0 - after.min_digits as f64 * std::f64::consts::LN_10).exp().round() as u64;
+        assert_eq!(after.n_limit, ideal);
+        assert!((expected_time(after, 3.0) - 10.0).abs() < 1e-3, "{}", expected_time(after, 3.0));
+        assert!((decision.effective_average - 10.0).abs() < 1e-3 && decision.clamps.is_empty());
+        assert_eq!((decision.reason, decision.algorithm), ("bootstrap", "calibration"));
+    }
+
+    #[test]
+    fn bounds_are_recorded_as_clamps() {
+        let base = Difficulty::default();
+        // Máquina lenta: nem o n_limit mínimo com os dígitos de partida fica abaixo do alvo
+        let slow = calibrate(&base, 1, 0.01, 10.0);
+        assert_eq!((slow.after.n_limit, slow.after.min_digits), (MIN_N_LIMIT, base.min_digits));
+        assert_eq!(slow.clamps, ["n_limit_min"]);
+        // Máquina rápida: o witness em u64 limita o trabalho
+        let fast = calibrate(&base, 1, 1e9, 10.0);
+        assert_eq!(fast.after.n_limit, max_n_limit(fast.after.min_digits));
+        assert_eq!(fast.clamps, ["witness_overflow_bound"]);
+    }
+
+    #[test]
+    fn v4_scales_the_hash_target() {
+        let base = Difficulty { hash_scale: 1, ..Difficulty::default() };
+        let decision = calibrate(&base, 4, 50_000.0, 10.0);
+        let after = &decision.after;
+        assert_eq!((after.n_limit, after.min_digits), (base.n_limit, base.min_digits));
+        assert_eq!(after.hash_scale, (500_000.0 / base.expected_candidates()).round() as u64);
+        // O arredondamento de S erra o alvo em menos de uma unidade de trabalho
+        assert!((expected_time(after, 50_000.0) - 10.0).abs() <= base.expected_candidates() / 50_000.0);
+        assert_eq!(calibrate(&base, 4, 0.001, 10.0).clamps, ["hash_scale_min"]);
+    }
+}
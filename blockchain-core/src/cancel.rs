@@ -0,0 +1,32 @@
+// src/cancel.rs
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Sinal de parada para trabalhos longos, com o progresso feito até agora.
+/// Quem executa consulta `is_cancelled` em intervalos e chama `advance` a cada unidade concluída.
+#[derive(Debug, Default)]
+pub struct CancelToken {
+    stop: AtomicBool,
+    progress: AtomicU64,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    pub fn advance(&self, units: u64) {
+        self.progress.fetch_add(units, Ordering::Relaxed);
+    }
+
+    pub fn progress(&self) -> u64 {
+        self.progress.load(Ordering::Relaxed)
+    }
+}
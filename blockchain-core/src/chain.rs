@@ -0,0 +1,729 @@
+// src/chain.rs
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+
+use crate::block::{Block, BlockBuilder, VerifyError};
+use crate::calibration::calibrate;
+use crate::cancel::CancelToken;
+use crate::emission::{EmissionError, EmissionSchedule};
+use crate::epoch::{EpochSummary, Epochs, DEFAULT_EPOCH_SIZE};
+use crate::fork::ChainSummary;
+use crate::math::expected_twin_probability;
+use crate::mining::{
+    Adjustment, Difficulty, DifficultyDecision, DifficultyDelta, DifficultyOverride, OverrideError, TARGET_TIME,
+};
+use crate::retarget::{AlgorithmConfig, DifficultyAlgorithm, WindowAlgorithm};
+use crate::rules::{validate_block, RuleSchedule, ScheduleError};
+use crate::verifier::ChainError;
+use crate::width::{PrimeWidth, WidthError};
+
+// Decisões de ajuste mantidas no histórico
+const DIFFICULTY_HISTORY: usize = 64;
+// Módulo das classes de resíduo cuja entropia é acompanhada
+pub const ENTROPY_MODULUS: u64 = 30;
+// Blocos recentes com n_limit e candidatos observados na mineração; também a maior janela de estatísticas
+pub const OBSERVED_BLOCKS: usize = 1000;
+// Índices de worker de mineração com vitórias contadas; o pool não sobe mais threads que isso
+pub const MAX_WORKERS: usize = 256;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TwinPrimeDensity {
+    pub twin_blocks: u64,
+    pub total_blocks: u64,
+    pub density: f64,
+    pub expected_density: f64,
+}
+
+/// Entropia de Shannon (em bits) da distribuição de `primo mod 30` nos blocos `0..=index`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EntropyPoint {
+    pub index: u64,
+    pub entropy: f64,
+}
+
+/// Médias da janela dos últimos `window` blocos minerados (a janela desliza de um em um bloco).
+/// Sem observações na janela, as métricas que dependem delas ficam `None`.
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowStats {
+    pub window: usize,
+    pub start_index: u64,
+    pub end_index: u64,
+    pub blocks: usize,
+    // Intervalos entre timestamps, incluindo o do primeiro bloco para o anterior
+    pub mean_mining_duration: Option<f64>,
+    pub mean_candidates: Option<f64>,
+    pub mean_prime_digits: Option<f64>,
+    // Desvio padrão do n_limit vigente ao minerar cada bloco
+    pub difficulty_volatility: Option<f64>,
+}
+
+// Dificuldade e candidatos de um bloco anexado por este nó; não dá para recalcular dos blocos
+#[derive(Debug, Clone)]
+struct BlockObservation {
+    index: u64,
+    hash: String,
+    n_limit: u64,
+    candidates: Option<u64>,
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (count, sum) = values.fold((0usize, 0.0), |(n, s), v| (n + 1, s + v));
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Comparação de uma estrutura derivada mantida incrementalmente com a recalculada dos blocos.
+#[derive(Debug, Clone, Serialize)]
+pub struct DerivedCheck {
+    pub structure: &'static str,
+    pub matched: bool,
+    pub diff: Option<String>,
+}
+
+/// Estruturas derivadas dos blocos, atualizadas a cada bloco anexado.
+#[derive(Debug, Clone)]
+pub struct DerivedState {
+    height: usize,
+    // SHA-256 incremental de p_0.to_le_bytes() || p_1.to_le_bytes() || ...
+    primorial_hasher: Sha256,
+    // Blocos minerados cujo primo tem um gêmeo, e a soma das probabilidades esperadas
+    twin_blocks: u64,
+    twin_expected_sum: f64,
+    epochs: Epochs,
+    residue_counts: [u64; ENTROPY_MODULUS as usize],
+    entropy_series: Vec<EntropyPoint>,
+    // Primo minerado -> primeiro bloco que o minerou, sem o gênesis
+    prime_blocks: HashMap<u128, u64>,
+}
+
+impl DerivedState {
+    fn new(genesis: &Block, epoch_size: u64) -> Self {
+        let mut primorial_hasher = Sha256::new();
+        primorial_hasher.update(genesis.prime.to_le_bytes());
+        let mut derived = DerivedState {
+            height: 1,
+            primorial_hasher,
+            twin_blocks: 0,
+            twin_expected_sum: 0.0,
+            epochs: Epochs::rebuild(epoch_size, std::slice::from_ref(genesis)),
+            residue_counts: [0; ENTROPY_MODULUS as usize],
+            entropy_series: Vec::new(),
+            prime_blocks: HashMap::new(),
+        };
+        derived.record_residue(genesis);
+        derived
+    }
+
+    /// Recalcula tudo a partir de blocos já validados.
+    pub fn from_blocks(blocks: &[Block], epoch_size: u64) -> Self {
+        let mut derived = DerivedState::new(&blocks[0], epoch_size);
+        derived.catch_up(blocks);
+        derived
+    }
+
+    // Soma os blocos além de `height`; a dificuldade de quando foram minerados é desconhecida
+    fn catch_up(&mut self, blocks: &[Block]) {
+        for i in self.height..blocks.len() {
+            self.push(&blocks[i], &blocks[i - 1], None);
+        }
+    }
+
+    fn push(&mut self, block: &Block, prev: &Block, difficulty: Option<&Difficulty>) {
+        self.primorial_hasher.update(block.prime.to_le_bytes());
+        if block.prime.is_twin_prime() {
+            self.twin_blocks += 1;
+        }
+        self.twin_expected_sum += expected_twin_probability(block.prime.get());
+        self.epochs.push(block, Some(prev), difficulty);
+        self.record_residue(block);
+        self.prime_blocks.entry(block.prime.get()).or_insert(block.index);
+        self.height += 1;
+    }
+
+    fn record_residue(&mut self, block: &Block) {
+        self.residue_counts[block.prime.residue(ENTROPY_MODULUS) as usize] += 1;
+        let total = self.residue_counts.iter().sum::<u64>() as f64;
+        let entropy = self
+            .residue_counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| count as f64 / total * (total / count as f64).log2())
+            .sum();
+        self.entropy_series.push(EntropyPoint { index: block.index, entropy });
+    }
+
+    /// Blocos cobertos, do gênesis em diante.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Impressão digital dos primos cobertos, em hex (64 caracteres).
+    pub fn primorial_hash(&self) -> String {
+        format!("{:x}", self.primorial_hasher.clone().finalize())
+    }
+
+    /// Fração dos blocos minerados (sem o gênesis) cujo primo pertence a um par gêmeo.
+    pub fn twin_prime_density(&self) -> TwinPrimeDensity {
+        let total_blocks = self.height as u64 - 1;
+        let ratio = |x: f64| if total_blocks == 0 { 0.0 } else { x / total_blocks as f64 };
+        TwinPrimeDensity {
+            twin_blocks: self.twin_blocks,
+            total_blocks,
+            density: ratio(self.twin_blocks as f64),
+            expected_density: ratio(self.twin_expected_sum),
+        }
+    }
+
+    /// Um ponto por bloco coberto.
+    pub fn entropy_series(&self) -> &[EntropyPoint] {
+        &self.entropy_series
+    }
+
+    /// Primeiro bloco minerado com o primo `prime`; o gênesis não conta.
+    pub fn block_of_prime(&self, prime: u128) -> Option<u64> {
+        self.prime_blocks.get(&prime).copied()
+    }
+
+    pub fn epoch(&self, epoch: u64) -> Option<&EpochSummary> {
+        self.epochs.get(epoch)
+    }
+
+    pub fn epoch_count(&self) -> usize {
+        self.epochs.len()
+    }
+
+    /// Blocos minerados por este nó com candidatos registrados, e quantos candidatos somaram.
+    pub fn recorded_candidates(&self) -> (u64, u64) {
+        self.epochs.recorded_candidates()
+    }
+
+    fn checks(&self, rebuilt: &DerivedState) -> Vec<DerivedCheck> {
+        let check = |structure, diff: Option<String>| DerivedCheck { structure, matched: diff.is_none(), diff };
+        let (hash, rebuilt_hash) = (self.primorial_hash(), rebuilt.primorial_hash());
+        let twins = (self.twin_blocks, self.twin_expected_sum);
+        let rebuilt_twins = (rebuilt.twin_blocks, rebuilt.twin_expected_sum);
+        let epochs = self.epochs.differing(&rebuilt.epochs);
+        let entropy_diverged = self.entropy_series.iter().zip(&rebuilt.entropy_series).position(|(a, b)| a != b);
+        let stale_primes = self.prime_blocks.iter().filter(|&(p, b)| rebuilt.prime_blocks.get(p) != Some(b)).count();
+        vec![
+            check("primorial_hash", (hash != rebuilt_hash).then(|| format!("{} -> {}", hash, rebuilt_hash))),
+            check(
+                "twin_prime_density",
+                // A soma em ponto flutuante é refeita na mesma ordem, então deve bater exatamente
+                (twins != rebuilt_twins).then(|| {
+                    let (before, after) = (twins, rebuilt_twins);
+                    format!("twin_blocks {} -> {}, expected_sum {} -> {}", before.0, after.0, before.1, after.1)
+                }),
+            ),
+            check(
+                "epochs",
+                (!epochs.is_empty() || self.epochs.len() != rebuilt.epochs.len()).then(|| {
+                    format!("{} -> {} epochs, differing: {:?}", self.epochs.len(), rebuilt.epochs.len(), epochs)
+                }),
+            ),
+            check(
+                "residue_entropy",
+                (entropy_diverged.is_some() || self.entropy_series.len() != rebuilt.entropy_series.len()).then(|| {
+                    let (before, after) = (self.entropy_series.len(), rebuilt.entropy_series.len());
+                    format!("{} -> {} points, first differing at {:?}", before, after, entropy_diverged)
+                }),
+            ),
+            check(
+                "prime_index",
+                (stale_primes > 0 || self.prime_blocks.len() != rebuilt.prime_blocks.len()).then(|| {
+                    let (before, after) = (self.prime_blocks.len(), rebuilt.prime_blocks.len());
+                    format!("{} -> {} primes, {} pointing elsewhere", before, after, stale_primes)
+                }),
+            ),
+        ]
+    }
+}
+
+/// Estado da cadeia independente de qualquer frontend HTTP.
+#[derive(Debug, Clone)]
+pub struct ChainState {
+    blocks: Vec<Block>,
+    pub difficulty: Difficulty,
+    rules: RuleSchedule,
+    derived: DerivedState,
+    algorithm: Box<dyn DifficultyAlgorithm>,
+    difficulty_history: VecDeque<DifficultyDecision>,
+    observed: VecDeque<BlockObservation>,
+    worker_wins: Vec<u64>,
+}
+
+impl Default for ChainState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChainState {
+    pub fn new() -> Self {
+        Self::with_rules(RuleSchedule::default())
+    }
+
+    pub fn with_rules(rules: RuleSchedule) -> Self {
+        let genesis = Block::genesis();
+        ChainState {
+            derived: DerivedState::new(&genesis, DEFAULT_EPOCH_SIZE),
+            blocks: vec![genesis],
+            difficulty: Difficulty { width: rules.prime_width(), ..Difficulty::default() },
+            rules,
+            algorithm: Box::new(WindowAlgorithm::default()),
+            difficulty_history: VecDeque::new(),
+            observed: VecDeque::new(),
+            worker_wins: vec![0; MAX_WORKERS],
+        }
+    }
+
+    /// Reconstrói o estado a partir de uma cadeia completa, validando cada bloco.
+    pub fn from_blocks(blocks: Vec<Block>) -> Result<Self, ChainError> {
+        Self::from_blocks_with_rules(blocks, RuleSchedule::default())
+    }
+
+    pub fn from_blocks_with_rules(blocks: Vec<Block>, rules: RuleSchedule) -> Result<Self, ChainError> {
+        Self::from_blocks_cancellable(blocks, rules, &CancelToken::new())
+    }
+
+    /// Como `from_blocks_with_rules`, parando com `ChainError::Cancelled` quando `token` é cancelado.
+    /// O progresso do token conta os blocos validados.
+    pub fn from_blocks_cancellable(
+        blocks: Vec<Block>,
+        rules: RuleSchedule,
+        token: &CancelToken,
+    ) -> Result<Self, ChainError> {
+        let mut blocks = blocks.into_iter();
+        let genesis = blocks.next().ok_or(ChainError::Empty)?;
+        if genesis.hash != Block::genesis().hash {
+            return Err(ChainError::GenesisMismatch);
+        }
+        let mut chain = ChainState::with_rules(rules);
+        for (index, block) in blocks.enumerate() {
+            if token.is_cancelled() {
+                return Err(ChainError::Cancelled { index: index + 1 });
+            }
+            chain
+                .append_block(block, false)
+                .map_err(|error| ChainError::Block { index: index + 1, error })?;
+            token.advance(1);
+        }
+        Ok(chain)
+    }
+
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    /// Trabalho, altura e ponta, para `fork::fork_choice`.
+    pub fn summary(&self) -> ChainSummary {
+        ChainSummary::of(&self.blocks)
+    }
+
+    pub fn tip(&self) -> &Block {
+        self.blocks.last().expect("a cadeia sempre contém o gênesis")
+    }
+
+    pub fn height(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Blocos com `from <= timestamp <= to`, por busca binária (os timestamps não decrescem).
+    pub fn blocks_in_time_range(&self, from: u64, to: u64) -> &[Block] {
+        let start = self.blocks.partition_point(|b| b.timestamp < from);
+        let end = self.blocks.partition_point(|b| b.timestamp <= to);
+        &self.blocks[start..end.max(start)]
+    }
+
+    pub fn rules(&self) -> &RuleSchedule {
+        &self.rules
+    }
+
+    /// Versão das regras que o próximo bloco deve declarar.
+    pub fn next_rules_version(&self) -> u32 {
+        self.rules.version_at(self.blocks.len() as u64)
+    }
+
+    pub fn schedule_rules(&mut self, version: u32, height: u64) -> Result<(), ScheduleError> {
+        self.rules.schedule(version, height, self.blocks.len() as u64)
+    }
+
+    /// `hash_scale` que o próximo bloco deve declarar: o da dificuldade (pelo menos 1) a partir das
+    /// regras v4, zero antes.
+    pub fn next_hash_scale(&self) -> u64 {
+        if self.next_rules_version() >= 4 { self.difficulty.hash_scale.max(1) } else { 0 }
+    }
+
+    /// Recompensa que o próximo bloco deve declarar; 0 antes das regras v6.
+    pub fn next_reward(&self) -> u64 {
+        self.rules.reward_at(self.blocks.len() as u64)
+    }
+
+    /// Troca o calendário de emissão; só enquanto a cadeia tem apenas o gênesis.
+    pub fn set_emission(&mut self, emission: EmissionSchedule) -> Result<(), EmissionError> {
+        if self.blocks.len() > 1 {
+            return Err(EmissionError::ChainNotEmpty { height: self.blocks.len() });
+        }
+        self.rules.set_emission(emission)
+    }
+
+    /// Troca a largura dos primos e os limites da dificuldade com ela; só enquanto a cadeia tem apenas o
+    /// gênesis.
+    pub fn set_prime_width(&mut self, width: PrimeWidth) -> Result<(), WidthError> {
+        if self.blocks.len() > 1 {
+            return Err(WidthError::ChainNotEmpty { height: self.blocks.len() });
+        }
+        self.rules.set_prime_width(width);
+        self.difficulty.width = width;
+        self.difficulty = self.difficulty.clamped();
+        Ok(())
+    }
+
+    /// Soma das recompensas declaradas pelos blocos da cadeia.
+    pub fn total_emitted(&self) -> u128 {
+        self.blocks.iter().map(|b| b.reward as u128).sum()
+    }
+
+    /// Modelo do próximo bloco, já com índice, prev_hash, versão das regras, alvo de hash e recompensa.
+    pub fn template(&self) -> BlockBuilder {
+        BlockBuilder::on(self.tip())
+            .rules_version(self.next_rules_version())
+            .hash_scale(self.next_hash_scale())
+            .reward(self.next_reward())
+    }
+
+    /// Valida `block` contra a ponta atual e o anexa.
+    pub fn append(&mut self, block: Block) -> Result<(), VerifyError> {
+        self.append_block(block, true)
+    }
+
+    // Na reconstrução a dificuldade atual não é a de quando o bloco foi minerado
+    fn append_block(&mut self, block: Block, live: bool) -> Result<(), VerifyError> {
+        validate_block(&block, self.tip(), &self.rules)?;
+        let tip = self.blocks.last().expect("a cadeia sempre contém o gênesis");
+        self.derived.push(&block, tip, live.then_some(&self.difficulty));
+        if live {
+            if self.observed.len() == OBSERVED_BLOCKS {
+                self.observed.pop_front();
+            }
+            let (index, hash, n_limit) = (block.index, block.hash.clone(), self.difficulty.n_limit);
+            self.observed.push_back(BlockObservation { index, hash, n_limit, candidates: None });
+        }
+        self.blocks.push(block);
+        Ok(())
+    }
+
+    /// Registra o tempo do último bloco e reajusta a dificuldade pelo algoritmo ativo.
+    pub fn adjust_difficulty(&mut self, duration: f64) -> DifficultyDecision {
+        // Na v4 a dificuldade passa ao alvo de hash, partindo de 1 (sem restrição extra)
+        if self.next_rules_version() >= 4 && self.difficulty.hash_scale == 0 {
+            self.difficulty.hash_scale = 1;
+            self.difficulty.generation += 1;
+        }
+        let decision = self.algorithm.observe(&self.difficulty, duration);
+        self.difficulty.apply(&decision);
+        if self.difficulty_history.len() == DIFFICULTY_HISTORY {
+            self.difficulty_history.pop_front();
+        }
+        self.difficulty_history.push_back(decision.clone());
+        decision
+    }
+
+    /// Calibra a dificuldade de uma cadeia nova (só o gênesis e nenhum ajuste ainda) pela vazão do nó e
+    /// registra a decisão no histórico; `None`, sem mexer em nada, se a cadeia já tem história.
+    pub fn bootstrap_difficulty(&mut self, candidates_per_sec: f64) -> Option<DifficultyDecision> {
+        if self.blocks.len() > 1 || !self.difficulty_history.is_empty() {
+            return None;
+        }
+        let decision = calibrate(&self.difficulty, self.next_rules_version(), candidates_per_sec, TARGET_TIME);
+        self.difficulty.apply(&decision);
+        self.difficulty_history.push_back(decision.clone());
+        Some(decision)
+    }
+
+    /// Aplica uma alteração manual de uma vez, registrada no histórico como as demais decisões. Nada muda
+    /// se `if_version` não for a versão atual ou se o resultado for inviável; mudando algo, a versão sobe.
+    pub fn override_difficulty(&mut self, change: &DifficultyOverride) -> Result<DifficultyDecision, OverrideError> {
+        let current = self.difficulty.generation;
+        if let Some(expected) = change.if_version.filter(|&v| v != current) {
+            return Err(OverrideError::VersionConflict { expected, current });
+        }
+        let mut after = change.resolve(&self.difficulty, self.next_rules_version() >= 4)?;
+        let deltas = DifficultyDelta::between(&self.difficulty, &after);
+        if deltas != DifficultyDelta::default() {
+            after.generation += 1;
+        }
+        let decision = DifficultyDecision {
+            algorithm: "override",
+            reason: "admin",
+            window: Vec::new(),
+            average: 0.0,
+            effective_average: 0.0,
+            target: TARGET_TIME,
+            tolerance: 0.0,
+            action: Adjustment::between(&self.difficulty, &after),
+            before: self.difficulty.clone(),
+            after,
+            deltas,
+            clamps: Vec::new(),
+        };
+        self.difficulty.apply(&decision);
+        if self.difficulty_history.len() == DIFFICULTY_HISTORY {
+            self.difficulty_history.pop_front();
+        }
+        self.difficulty_history.push_back(decision.clone());
+        Ok(decision)
+    }
+
+    pub fn difficulty_history(&self) -> impl DoubleEndedIterator<Item = &DifficultyDecision> {
+        self.difficulty_history.iter()
+    }
+
+    pub fn difficulty_algorithm(&self) -> AlgorithmConfig {
+        self.algorithm.config()
+    }
+
+    /// Troca o algoritmo de reajuste; o novo começa sem tempos observados.
+    pub fn set_difficulty_algorithm(&mut self, algorithm: Box<dyn DifficultyAlgorithm>) {
+        self.algorithm = algorithm;
+    }
+
+    /// Herda dificuldade, algoritmo, histórico e vitórias dos workers de outro estado (ex.: ao adotar a
+    /// cadeia de um peer).
+    pub fn inherit_difficulty(&mut self, other: &ChainState) {
+        // A largura é a das regras deste estado, mesmo que o outro tenha sido montado com outra
+        self.difficulty = Difficulty { width: self.rules.prime_width(), ..other.difficulty.clone() };
+        self.worker_wins = other.worker_wins.clone();
+        self.algorithm = other.algorithm.clone();
+        self.difficulty_history = other.difficulty_history.clone();
+        let blocks = &self.blocks;
+        self.observed = other
+            .observed
+            .iter()
+            .filter(|o| blocks.get(o.index as usize).is_some_and(|b| b.hash == o.hash))
+            .cloned()
+            .collect();
+        self.derived.epochs.inherit_observed(&other.derived.epochs);
+    }
+
+    pub fn epoch_size(&self) -> u64 {
+        self.derived.epochs.size()
+    }
+
+    /// Troca o tamanho das épocas e recalcula os resumos a partir dos blocos.
+    pub fn set_epoch_size(&mut self, size: u64) {
+        if size != self.derived.epochs.size() {
+            self.derived.epochs = Epochs::rebuild(size, &self.blocks);
+        }
+    }
+
+    pub fn epoch(&self, epoch: u64) -> Option<&EpochSummary> {
+        self.derived.epoch(epoch)
+    }
+
+    pub fn epoch_count(&self) -> usize {
+        self.derived.epoch_count()
+    }
+
+    /// Blocos minerados por este nó com candidatos registrados, e quantos candidatos somaram.
+    pub fn recorded_candidates(&self) -> (u64, u64) {
+        self.derived.recorded_candidates()
+    }
+
+    /// Candidatos testados para minerar o bloco da ponta, para a média da época.
+    pub fn record_candidates(&mut self, candidates: u64) {
+        self.derived.epochs.record_candidates(candidates);
+        let tip = self.tip().index;
+        if let Some(observation) = self.observed.back_mut().filter(|o| o.index == tip) {
+            observation.candidates = Some(candidates);
+        }
+    }
+
+    /// Vitórias de cada worker (`0..MAX_WORKERS`) nas corridas de mineração deste nó.
+    pub fn worker_wins(&self) -> &[u64] {
+        &self.worker_wins
+    }
+
+    /// Conta uma corrida vencida pelo worker `worker`; índices a partir de MAX_WORKERS são ignorados.
+    pub fn record_worker_win(&mut self, worker: usize) {
+        if let Some(wins) = self.worker_wins.get_mut(worker) {
+            *wins += 1;
+        }
+    }
+
+    /// Estatísticas da janela mais recente de `window` blocos minerados; com menos blocos, usa todos.
+    pub fn window_stats(&self, window: usize) -> WindowStats {
+        self.window_stats_at(window, self.tip().index)
+    }
+
+    /// Como `window_stats`, com a janela terminando no bloco `index` (limitado à ponta).
+    pub fn window_stats_at(&self, window: usize, index: u64) -> WindowStats {
+        let window = window.clamp(1, OBSERVED_BLOCKS);
+        let end = (index as usize + 1).min(self.blocks.len());
+        let start = end.saturating_sub(window).max(1);
+        let blocks = &self.blocks[start..end];
+        let durations = blocks
+            .iter()
+            .zip(&self.blocks[start - 1..])
+            .filter(|(block, prev)| block.timestamp > 0 && prev.timestamp > 0)
+            .map(|(block, prev)| block.timestamp.saturating_sub(prev.timestamp) as f64 / 1000.0);
+        let observed: Vec<&BlockObservation> =
+            self.observed.iter().filter(|o| (start as u64..end as u64).contains(&o.index)).collect();
+        let mean_n_limit = mean(observed.iter().map(|o| o.n_limit as f64));
+        WindowStats {
+            window,
+            start_index: start as u64,
+            end_index: end as u64 - 1,
+            blocks: blocks.len(),
+            mean_mining_duration: mean(durations),
+            mean_candidates: mean(observed.iter().filter_map(|o| o.candidates).map(|c| c as f64)),
+            mean_prime_digits: mean(blocks.iter().map(|b| b.prime.to_string().len() as f64)),
+            difficulty_volatility: mean_n_limit.and_then(|m| {
+                mean(observed.iter().map(|o| (o.n_limit as f64 - m).powi(2))).map(f64::sqrt)
+            }),
+        }
+    }
+
+    /// Troca as estruturas derivadas por `rebuilt`, recalculadas de um snapshot destes blocos, e relata
+    /// quais divergiam. Blocos anexados depois do snapshot são somados antes da comparação.
+    /// Devolve `None` se a cadeia foi substituída e o snapshot não é mais um prefixo dela.
+    pub fn repair_derived(&mut self, mut rebuilt: DerivedState, snapshot_tip: &str) -> Option<Vec<DerivedCheck>> {
+        if self.blocks.get(rebuilt.height.checked_sub(1)?).is_none_or(|b| b.hash != snapshot_tip) {
+            return None;
+        }
+        rebuilt.catch_up(&self.blocks);
+        // Dificuldade e candidatos por época não saem dos blocos: ficam os observados
+        rebuilt.epochs.inherit_observed(&self.derived.epochs);
+        let checks = self.derived.checks(&rebuilt);
+        self.derived = rebuilt;
+        Some(checks)
+    }
+
+    /// Estruturas derivadas que divergem das recalculadas dos blocos, sem trocá-las; vazio se íntegras.
+    pub fn derived_drift(&self) -> Vec<DerivedCheck> {
+        let mut rebuilt = DerivedState::from_blocks(&self.blocks, self.epoch_size());
+        rebuilt.epochs.inherit_observed(&self.derived.epochs);
+        self.derived.checks(&rebuilt).into_iter().filter(|c| !c.matched).collect()
+    }
+
+    /// Impressão digital de todos os primos da cadeia, em hex (64 caracteres).
+    pub fn primorial_hash(&self) -> String {
+        self.derived.primorial_hash()
+    }
+
+    /// Fração dos blocos minerados (sem o gênesis) cujo primo pertence a um par gêmeo.
+    pub fn twin_prime_density(&self) -> TwinPrimeDensity {
+        self.derived.twin_prime_density()
+    }
+
+    /// Um ponto por bloco, do gênesis à ponta, recalculado junto com os demais dados derivados.
+    pub fn entropy_series(&self) -> &[EntropyPoint] {
+        &self.derived.entropy_series
+    }
+
+    /// Desvia as estruturas derivadas dos blocos (impressão digital e contagem de gêmeos), para testar
+    /// a detecção e o reparo.
+    #[cfg(any(test, feature = "testkit"))]
+    pub fn corrupt_derived(&mut self) {
+        self.derived.primorial_hasher.update(b"drift");
+        self.derived.twin_blocks += 1;
+    }
+
+    /// Estruturas derivadas mantidas para a cadeia inteira.
+    pub fn derived(&self) -> &DerivedState {
+        &self.derived
+    }
+
+    /// Estruturas derivadas da cadeia como era com a ponta no bloco `index`, recalculadas do prefixo.
+    /// Dificuldade e candidatos observados vêm das épocas atuais. `None` além da ponta.
+    pub fn derived_at(&self, index: u64) -> Option<DerivedState> {
+        let prefix = self.blocks.get(..=usize::try_from(index).ok()?)?;
+        let mut derived = DerivedState::from_blocks(prefix, self.epoch_size());
+        derived.epochs.inherit_observed_prefix(&self.derived.epochs);
+        Some(derived)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockBuilder;
+
+    fn chain_of(blocks: usize) -> ChainState {
+        let mut chain = ChainState::new();
+        for _ in 1..blocks {
+            let block = BlockBuilder::on(chain.tip()).witness(1, 1, 2, 1).build();
+            chain.append(block).unwrap();
+        }
+        chain
+    }
+
+    #[test]
+    fn rebuild_reports_and_repairs_drift() {
+        let mut chain = chain_of(4);
+        assert!(chain.derived_drift().is_empty());
+        let (hash, twins) = (chain.primorial_hash(), chain.twin_prime_density().twin_blocks);
+
+        chain.corrupt_derived();
+        let drift: Vec<_> = chain.derived_drift().iter().map(|c| c.structure).collect();
+        assert_eq!(drift, ["primorial_hash", "twin_prime_density"]);
+
+        let tip = chain.tip().hash.clone();
+        let checks = chain.repair_derived(DerivedState::from_blocks(chain.blocks(), chain.epoch_size()), &tip).unwrap();
+        let repaired: Vec<_> = checks.iter().filter(|c| !c.matched).collect();
+        assert_eq!(repaired.len(), 2);
+        assert!(repaired[0].diff.as_deref().unwrap().ends_with(&format!("-> {}", hash)));
+        assert!(repaired[1].diff.as_deref().unwrap().starts_with(&format!("twin_blocks {} -> {}", twins + 1, twins)));
+        assert!(checks.iter().filter(|c| c.matched).all(|c| c.diff.is_none()));
+        assert!(chain.derived_drift().is_empty());
+        assert_eq!((chain.primorial_hash(), chain.twin_prime_density().twin_blocks), (hash, twins));
+    }
+
+    #[test]
+    fn rebuild_catches_up_and_detects_replacement() {
+        let mut chain = chain_of(3);
+        let snapshot = chain.blocks().to_vec();
+        let rebuilt = DerivedState::from_blocks(&snapshot, chain.epoch_size());
+        // Um bloco anexado durante o recálculo entra antes da comparação
+        chain.append(BlockBuilder::on(chain.tip()).witness(1, 1, 2, 1).build()).unwrap();
+        let checks = chain.repair_derived(rebuilt.clone(), &snapshot[2].hash).unwrap();
+        assert!(checks.iter().all(|c| c.matched), "{:?}", checks);
+        assert_eq!(chain.derived().height(), 4);
+
+        // Snapshot que não é mais prefixo da cadeia
+        assert!(chain.repair_derived(rebuilt, "other").is_none());
+    }
+
+    #[test]
+    fn prime_index_keeps_the_first_block_and_is_repaired() {
+        let mut chain = chain_of(3);
+        chain.append(BlockBuilder::on(chain.tip()).witness(4, 1, 1, 1).build()).unwrap();
+        let repeated = chain.blocks()[1].prime.get();
+        // O primo dos blocos 1 e 2 aponta para o primeiro; o gênesis não entra no índice
+        assert_eq!((chain.derived().block_of_prime(repeated), chain.derived().block_of_prime(5)), (Some(1), Some(3)));
+        assert_eq!(chain.derived().block_of_prime(chain.blocks()[0].prime.get()), None);
+
+        chain.derived.prime_blocks.insert(5, 2);
+        let drift = chain.derived_drift();
+        assert_eq!(drift.iter().map(|c| c.structure).collect::<Vec<_>>(), ["prime_index"]);
+        assert_eq!(drift[0].diff.as_deref(), Some("2 -> 2 primes, 1 pointing elsewhere"));
+        let tip = chain.tip().hash.clone();
+        chain.repair_derived(DerivedState::from_blocks(chain.blocks(), chain.epoch_size()), &tip).unwrap();
+        assert_eq!(chain.derived().block_of_prime(5), Some(3));
+    }
+
+    #[test]
+    fn bootstrap_only_on_a_fresh_chain() {
+        let mut chain = ChainState::new();
+        let decision = chain.bootstrap_difficulty(3.0).unwrap();
+        assert_eq!(decision.reason, "bootstrap");
+        assert_eq!(chain.difficulty_history.back().map(|d| d.reason), Some("bootstrap"));
+        assert_eq!(chain.difficulty.expected_candidates(), decision.after.expected_candidates());
+        // Com ajuste já registrado, ou com blocos além do gênesis, não calibra de novo
+        assert!(chain.bootstrap_difficulty(3_000.0).is_none());
+        let mut grown = chain_of(2);
+        let before = grown.difficulty.clone();
+        assert!(grown.bootstrap_difficulty(3.0).is_none());
+        assert_eq!((grown.difficulty.n_limit, grown.difficulty.min_digits), (before.n_limit, before.min_digits));
+    }
+}
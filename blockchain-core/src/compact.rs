@@ -0,0 +1,393 @@
+// src/compact.rs
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use std::fmt;
+
+use crate::block::Block;
+use crate::transaction::{Transaction, EMPTY_TX_ROOT};
+use crate::width::PrimeValue;
+
+// Versão do layout binário; qualquer mudança de campos exige uma nova.
+// v2 acrescenta a raiz e as transações; blocos sem transações continuam saindo em v1.
+// v3 acrescenta o hash_scale das regras v4 depois da seção de transações.
+// v4 acrescenta o work das regras v5 depois do hash_scale.
+// v5 acrescenta o coinbase e o reward das regras v6 depois do work.
+// v6 é o layout da v5 com o primo em 16 bytes, só para primos acima de u64.
+const COMPACT_VERSION: u16 = 1;
+const COMPACT_VERSION_TX: u16 = 2;
+const COMPACT_VERSION_TARGET: u16 = 3;
+const COMPACT_VERSION_WORK: u16 = 4;
+const COMPACT_VERSION_REWARD: u16 = 5;
+const COMPACT_VERSION_WIDE: u16 = 6;
+
+// Marcadores de string: hash hex de 32 bytes empacotado, ou bytes UTF-8 com tamanho
+const TAG_HEX32: u8 = 0;
+const TAG_RAW: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompactError {
+    Base64,
+    TooShort,
+    Crc { expected: u32, found: u32 },
+    UnknownVersion(u16),
+    Malformed(&'static str),
+}
+
+impl fmt::Display for CompactError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompactError::Base64 => write!(f, "not valid base64url"),
+            CompactError::TooShort => write!(f, "too short to hold a version and checksum"),
+            CompactError::Crc { expected, found } => {
+                write!(f, "checksum mismatch: expected {:08x}, found {:08x}", expected, found)
+            }
+            CompactError::UnknownVersion(version) => write!(f, "unknown encoding version {}", version),
+            CompactError::Malformed(reason) => write!(f, "malformed block payload: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for CompactError {}
+
+fn put_string(out: &mut Vec<u8>, value: &str) {
+    let packed = (value.len() == 64)
+        .then(|| hex::decode(value).ok())
+        .flatten()
+        .filter(|bytes| hex::encode(bytes) == value);
+    match packed {
+        Some(bytes) => {
+            out.push(TAG_HEX32);
+            out.extend_from_slice(&bytes);
+        }
+        None => {
+            let bytes = &value.as_bytes()[..value.len().min(u16::MAX as usize)];
+            out.push(TAG_RAW);
+            out.extend_from_slice(&(bytes.len() as u16).to_le_bytes());
+            out.extend_from_slice(bytes);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], CompactError> {
+        if self.bytes.len() < n {
+            return Err(CompactError::Malformed("truncated"));
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, CompactError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, CompactError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, CompactError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, CompactError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn u128(&mut self) -> Result<u128, CompactError> {
+        Ok(u128::from_le_bytes(self.take(16)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String, CompactError> {
+        match self.u8()? {
+            TAG_HEX32 => Ok(hex::encode(self.take(32)?)),
+            TAG_RAW => {
+                let len = self.u16()? as usize;
+                String::from_utf8(self.take(len)?.to_vec()).map_err(|_| CompactError::Malformed("invalid utf-8"))
+            }
+            _ => Err(CompactError::Malformed("unknown string tag")),
+        }
+    }
+}
+
+impl Block {
+    /// Codificação canônica e curta para compartilhar um bloco:
+    /// base64url(versão u16 || campos || CRC32 de tudo o que vem antes).
+    pub fn to_compact_string(&self) -> String {
+        let wide = self.prime.as_u64().is_none();
+        let with_reward = wide || self.reward != 0 || !self.coinbase.is_empty();
+        let with_work = with_reward || self.work != 0;
+        let with_target = with_work || self.hash_scale != 0;
+        let with_tx = with_target || self.tx_root != EMPTY_TX_ROOT || !self.transactions.is_empty();
+        let version = match (with_reward, with_work, with_target, with_tx) {
+            _ if wide => COMPACT_VERSION_WIDE,
+            (true, _, _, _) => COMPACT_VERSION_REWARD,
+            (false, true, _, _) => COMPACT_VERSION_WORK,
+            (false, false, true, _) => COMPACT_VERSION_TARGET,
+            (false, false, false, true) => COMPACT_VERSION_TX,
+            (false, false, false, false) => COMPACT_VERSION,
+        };
+        let mut out = version.to_le_bytes().to_vec();
+        out.extend_from_slice(&self.index.to_le_bytes());
+        put_string(&mut out, &self.prev_hash);
+        out.extend_from_slice(&self.prime.to_le_bytes());
+        for x in [self.a, self.b, self.c, self.d] {
+            out.extend_from_slice(&x.to_le_bytes());
+        }
+        put_string(&mut out, &self.hash);
+        out.extend_from_slice(&self.rules_version.to_le_bytes());
+        out.extend_from_slice(&self.timestamp.to_le_bytes());
+        if with_tx {
+            put_string(&mut out, &self.tx_root);
+            out.extend_from_slice(&(self.transactions.len() as u32).to_le_bytes());
+            for tx in &self.transactions {
+                put_string(&mut out, &tx.from);
+                put_string(&mut out, &tx.to);
+                out.extend_from_slice(&tx.amount.to_le_bytes());
+                out.extend_from_slice(&tx.nonce.to_le_bytes());
+                put_string(&mut out, &tx.signature);
+            }
+        }
+        if with_target {
+            out.extend_from_slice(&self.hash_scale.to_le_bytes());
+        }
+        if with_work {
+            out.extend_from_slice(&self.work.to_le_bytes());
+        }
+        if with_reward {
+            put_string(&mut out, &self.coinbase);
+            out.extend_from_slice(&self.reward.to_le_bytes());
+        }
+        let crc = crc32fast::hash(&out);
+        out.extend_from_slice(&crc.to_le_bytes());
+        URL_SAFE_NO_PAD.encode(out)
+    }
+
+    /// Decodifica `to_compact_string`. Não valida o bloco, apenas a codificação.
+    pub fn from_compact_string(encoded: &str) -> Result<Block, CompactError> {
+        let bytes = URL_SAFE_NO_PAD.decode(encoded.trim()).map_err(|_| CompactError::Base64)?;
+        if bytes.len() < 6 {
+            return Err(CompactError::TooShort);
+        }
+        let (payload, crc) = bytes.split_at(bytes.len() - 4);
+        let found = u32::from_le_bytes(crc.try_into().unwrap());
+        let expected = crc32fast::hash(payload);
+        if expected != found {
+            return Err(CompactError::Crc { expected, found });
+        }
+        let version = u16::from_le_bytes([payload[0], payload[1]]);
+        if !(COMPACT_VERSION..=COMPACT_VERSION_WIDE).contains(&version) {
+            return Err(CompactError::UnknownVersion(version));
+        }
+
+        let mut reader = Reader { bytes: &payload[2..] };
+        let mut block = Block {
+            index: reader.u64()?,
+            prev_hash: reader.string()?,
+            prime: match version {
+                COMPACT_VERSION_WIDE => PrimeValue::new(reader.u128()?),
+                _ => reader.u64()?.into(),
+            },
+            a: reader.u64()?,
+            b: reader.u64()?,
+            c: reader.u64()?,
+            d: reader.u64()?,
+            hash: reader.string()?,
+            rules_version: reader.u32()?,
+            timestamp: reader.u64()?,
+            tx_root: EMPTY_TX_ROOT.to_string(),
+            transactions: Vec::new(),
+            hash_scale: 0,
+            work: 0,
+            coinbase: String::new(),
+            reward: 0,
+        };
+        if version >= COMPACT_VERSION_TX {
+            block.tx_root = reader.string()?;
+            let count = reader.u32()?;
+            for _ in 0..count {
+                block.transactions.push(Transaction {
+                    from: reader.string()?,
+                    to: reader.string()?,
+                    amount: reader.u64()?,
+                    nonce: reader.u64()?,
+                    signature: reader.string()?,
+                });
+            }
+        }
+        if version >= COMPACT_VERSION_TARGET {
+            block.hash_scale = reader.u64()?;
+        }
+        if version >= COMPACT_VERSION_WORK {
+            block.work = reader.u64()?;
+        }
+        if version >= COMPACT_VERSION_REWARD {
+            block.coinbase = reader.string()?;
+            block.reward = reader.u64()?;
+        }
+        // Uma só codificação por bloco: primo que cabe em u64 não sai em 16 bytes
+        if version == COMPACT_VERSION_WIDE && block.prime.as_u64().is_some() {
+            return Err(CompactError::Malformed("wide prime that fits in u64"));
+        }
+        if !reader.bytes.is_empty() {
+            return Err(CompactError::Malformed("trailing bytes"));
+        }
+        Ok(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockBuilder;
+    use crate::signature::SigningKey;
+
+    // Bloco v6 com uma transação, na codificação v5; se este teste quebrar, o layout mudou e precisa de
+    // uma versão nova
+    const FIXTURE: &str = concat!(
+        "BQADAAAAAAAAAACfn5-fn5-fn5-fn5-fn5-fn5-fn5-fn5-fn5-fn5-fn6cbAAAAAAAA8QMAAAAAAAAEAAAAAAAAAPUDAAAA",
+        "AAAAAwAAAAAAAAAAS7eo0ZJEquJugbqEQa58hEqpa1liyr8u4H7uDxOnN64GAAAAMN3lz4sBAAAAqNCYrdmt7abWVrpVktXo",
+        "tg9GTNhkKh2UZTyNPDI0REgBAAAAAIqI4910CfGV_VLbLTy6XXLKZwm_HZQSG_N0iAG0D29cAQMAYm9iGQAAAAAAAAAAAAAA",
+        "AAAAAAGAADE1NTJjZGVmYTFiNDk5YmVmYjJhMDkxZWExNTAxZjUyMDI5NDNjYzYwZjA5ZmRmNjQ4NGVhOTlhNzk1YjllMTM5",
+        "ZmUwNDBkN2Y1YzQwZGExMzQzMzBmNGUwNzUwNzAwODY4NTBkN2M4NWRkNmY5YzE2ZjRjOTIyOWVlNGUxYTAwEAAAAAAAAAAE",
+        "AAAAAAAAAAEJAG1pbmVyLW9uZQDyBSoBAAAAU5p-jg",
+    );
+
+    fn fixture_block() -> Block {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        BlockBuilder::new(3, "9f".repeat(32))
+            .witness(1009, 4, 1013, 3)
+            .rules_version(6)
+            .timestamp(1_700_000_030_000)
+            .transactions(vec![Transaction::sign(&key, "bob", 25, 0)])
+            .hash_scale(16)
+            .coinbase("miner-one")
+            .reward(5_000_000_000)
+            .build()
+    }
+
+    // SplitMix64: sequência reprodutível sem depender do `rand` da feature `mining`
+    struct Gen(u64);
+
+    impl Gen {
+        fn next(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+
+        // Componente da testemunha com 1 a 20 dígitos; os grandes dão primos acima de u64
+        fn component(&mut self) -> u64 {
+            (self.next() >> self.below(64)).max(1)
+        }
+    }
+
+    // Testemunha coprima com soma prima
+    fn random_witness(gen: &mut Gen) -> (u64, u64, u64, u64) {
+        use num::Integer;
+        loop {
+            let (a, b, c, d) = (gen.component(), gen.component(), gen.component(), gen.component());
+            let prime = crate::block::witness(a, b, c, d);
+            if a.gcd(&b) == 1 && c.gcd(&d) == 1 && prime.is_some_and(PrimeValue::is_prime) {
+                return (a, b, c, d);
+            }
+        }
+    }
+
+    // Bloco que passa em `verify_contents`, com os campos de uma versão de regras sorteada
+    fn random_block(gen: &mut Gen) -> Block {
+        let rules_version = gen.below(6) as u32 + 1;
+        let (a, b, c, d) = random_witness(gen);
+        let transactions = (0..gen.below(3))
+            .map(|nonce| {
+                let key = SigningKey::from_bytes(&[gen.below(250) as u8 + 1; 32]);
+                Transaction::sign(&key, format!("to-{}", gen.below(1000)), gen.next(), nonce)
+            })
+            .collect();
+        let mut builder = BlockBuilder::new(gen.below(1 << 40), hex::encode(gen.next().to_le_bytes().repeat(4)))
+            .witness(a, b, c, d)
+            .rules_version(rules_version)
+            .timestamp(gen.next() >> gen.below(64))
+            .transactions(transactions);
+        if rules_version >= 4 {
+            builder = builder.hash_scale(gen.below(1 << 20) + 1);
+        }
+        if rules_version >= 6 {
+            builder = builder.coinbase(format!("miner-{}", gen.below(100))).reward(gen.next());
+        }
+        let block = builder.build();
+        assert!(block.verify_contents().is_ok(), "{:?}", block);
+        block
+    }
+
+    fn same(a: &Block, b: &Block) -> bool {
+        serde_json::to_value(a).unwrap() == serde_json::to_value(b).unwrap()
+    }
+
+    // Reembala o payload de `encoded` depois de `edit`, com o CRC recalculado
+    fn repack(encoded: &str, edit: impl FnOnce(&mut Vec<u8>)) -> String {
+        let mut payload = URL_SAFE_NO_PAD.decode(encoded).unwrap();
+        payload.truncate(payload.len() - 4);
+        edit(&mut payload);
+        let crc = crc32fast::hash(&payload);
+        payload.extend_from_slice(&crc.to_le_bytes());
+        URL_SAFE_NO_PAD.encode(payload)
+    }
+
+    #[test]
+    fn fixture_decodes_to_the_known_block() {
+        let block = fixture_block();
+        assert!(block.verify_contents().is_ok());
+        assert!(same(&Block::from_compact_string(FIXTURE).unwrap(), &block));
+        assert_eq!(block.to_compact_string(), FIXTURE);
+    }
+
+    #[test]
+    fn random_valid_blocks_round_trip() {
+        let mut gen = Gen(2024);
+        let mut versions = std::collections::BTreeSet::new();
+        for _ in 0..200 {
+            let block = random_block(&mut gen);
+            let encoded = block.to_compact_string();
+            versions.insert(u16::from_le_bytes(URL_SAFE_NO_PAD.decode(&encoded).unwrap()[..2].try_into().unwrap()));
+            let decoded = Block::from_compact_string(&encoded).unwrap();
+            assert!(same(&decoded, &block), "{:?}", block);
+            assert_eq!(decoded.to_compact_string(), encoded);
+        }
+        // Todas as versões do layout saíram ao menos uma vez
+        let all: Vec<u16> = (COMPACT_VERSION..=COMPACT_VERSION_WIDE).collect();
+        assert_eq!(versions.into_iter().collect::<Vec<_>>(), all);
+    }
+
+    #[test]
+    fn corruptions_have_distinct_errors() {
+        let bad_base64 = Block::from_compact_string("not base64!").unwrap_err();
+        assert_eq!(bad_base64, CompactError::Base64);
+
+        // Um bit trocado no meio, CRC original
+        let mut bytes = URL_SAFE_NO_PAD.decode(FIXTURE).unwrap();
+        bytes[10] ^= 1;
+        let bad_crc = Block::from_compact_string(&URL_SAFE_NO_PAD.encode(&bytes)).unwrap_err();
+        assert!(matches!(bad_crc, CompactError::Crc { expected, found } if expected != found), "{:?}", bad_crc);
+
+        let future = repack(FIXTURE, |payload| payload[..2].copy_from_slice(&99u16.to_le_bytes()));
+        let unknown = Block::from_compact_string(&future).unwrap_err();
+        assert_eq!(unknown, CompactError::UnknownVersion(99));
+        let zero = repack(FIXTURE, |payload| payload[..2].copy_from_slice(&0u16.to_le_bytes()));
+        assert_eq!(Block::from_compact_string(&zero).unwrap_err(), CompactError::UnknownVersion(0));
+
+        let messages = [bad_base64.to_string(), bad_crc.to_string(), unknown.to_string()];
+        assert!(messages[0] != messages[1] && messages[1] != messages[2] && messages[0] != messages[2]);
+        assert_eq!(Block::from_compact_string("AAAA").unwrap_err(), CompactError::TooShort);
+    }
+}
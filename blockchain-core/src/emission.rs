@@ -0,0 +1,186 @@
+// src/emission.rs
+//! Recompensa de bloco: um valor inicial que cai pela metade a cada `halving_interval` blocos, sem descer
+//! abaixo de um piso. Só os blocos das regras v6 declaram (e recebem) a recompensa.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Calendário de emissão da cadeia; faz parte de `RuleSchedule`, então valida blocos importados também.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmissionSchedule {
+    pub initial_reward: u64,
+    pub halving_interval: u64,
+    // Recompensa mínima depois dos halvings; acima de zero a oferta não tem teto
+    pub min_reward: u64,
+}
+
+/// Nos moldes do Bitcoin: 50 moedas de 10^8 unidades, halving a cada 210 000 blocos, sem piso.
+pub const DEFAULT_EMISSION: EmissionSchedule =
+    EmissionSchedule { initial_reward: 5_000_000_000, halving_interval: 210_000, min_reward: 0 };
+
+impl Default for EmissionSchedule {
+    fn default() -> Self {
+        DEFAULT_EMISSION
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmissionError {
+    ZeroInterval,
+    FloorAboveInitial { min_reward: u64, initial_reward: u64 },
+    ChainNotEmpty { height: usize },
+}
+
+impl fmt::Display for EmissionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmissionError::ZeroInterval => write!(f, "halving_interval must be at least 1"),
+            EmissionError::FloorAboveInitial { min_reward, initial_reward } => {
+                write!(f, "min_reward {} is above initial_reward {}", min_reward, initial_reward)
+            }
+            EmissionError::ChainNotEmpty { height } => {
+                write!(f, "emission schedule is fixed once blocks are mined (height {})", height)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EmissionError {}
+
+impl EmissionSchedule {
+    pub fn new(initial_reward: u64, halving_interval: u64, min_reward: u64) -> Result<Self, EmissionError> {
+        let schedule = EmissionSchedule { initial_reward, halving_interval, min_reward };
+        schedule.check()?;
+        Ok(schedule)
+    }
+
+    /// Confere um calendário recebido já montado (p. ex. desserializado).
+    pub fn check(&self) -> Result<(), EmissionError> {
+        if self.halving_interval == 0 {
+            return Err(EmissionError::ZeroInterval);
+        }
+        if self.min_reward > self.initial_reward {
+            return Err(EmissionError::FloorAboveInitial {
+                min_reward: self.min_reward,
+                initial_reward: self.initial_reward,
+            });
+        }
+        Ok(())
+    }
+
+    /// Emissão somada, pelo calendário, dos blocos de índice `1..end`.
+    pub fn emitted_before(&self, end: u64) -> u128 {
+        let mut total = 0u128;
+        let mut era_start = 0u64;
+        while era_start < end {
+            let era_end = era_start.saturating_add(self.halving_interval).min(end);
+            let first = era_start.max(1);
+            let reward = reward_at_height(first, self);
+            total += era_end.saturating_sub(first) as u128 * reward as u128;
+            // Chegou ao piso (ou a zero): o resto da faixa paga o mesmo
+            if reward == self.min_reward {
+                total += (end - era_end) as u128 * reward as u128;
+                break;
+            }
+            era_start = era_end;
+        }
+        total
+    }
+
+    /// Oferta total se todo bloco depois do gênesis pagar a recompensa; `None` com piso acima de zero.
+    pub fn max_supply(&self) -> Option<u128> {
+        (self.min_reward == 0).then(|| self.emitted_before(u64::MAX))
+    }
+
+    /// Altura do próximo bloco, depois de `height`, que paga menos que o de `height`; `None` no piso.
+    pub fn next_halving(&self, height: u64) -> Option<u64> {
+        // O gênesis não paga nada; a comparação parte do primeiro bloco que paga
+        let height = height.max(1);
+        let interval = self.halving_interval.max(1);
+        let boundary = (height / interval).checked_add(1)?.checked_mul(interval)?;
+        (reward_at_height(boundary, self) < reward_at_height(height, self)).then_some(boundary)
+    }
+}
+
+/// Recompensa do bloco de índice `height`: `initial_reward >> (height / halving_interval)`, no mínimo
+/// `min_reward`. O gênesis não paga nada.
+pub fn reward_at_height(height: u64, schedule: &EmissionSchedule) -> u64 {
+    if height == 0 {
+        return 0;
+    }
+    let halvings = height / schedule.halving_interval.max(1);
+    let reward = if halvings >= 64 { 0 } else { schedule.initial_reward >> halvings };
+    reward.max(schedule.min_reward)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockBuilder;
+    use crate::chain::ChainState;
+
+    #[test]
+    fn rewards_straddle_halving_boundaries() {
+        let schedule = EmissionSchedule::new(100, 10, 0).unwrap();
+        let rewards: Vec<u64> = [0, 1, 9, 10, 19, 20, 29, 30].iter().map(|&h| reward_at_height(h, &schedule)).collect();
+        assert_eq!(rewards, [0, 100, 100, 50, 50, 25, 25, 12]);
+        // Depois de 64 halvings o deslocamento zeraria tudo; sem piso a recompensa é zero
+        assert_eq!(reward_at_height(64 * 10, &schedule), 0);
+        let halvings: Vec<Option<u64>> = [0, 9, 10].iter().map(|&h| schedule.next_halving(h)).collect();
+        assert_eq!(halvings, [Some(10), Some(10), Some(20)]);
+
+        // Com piso, a recompensa para nele e não há mais halving
+        let floored = EmissionSchedule::new(100, 10, 30).unwrap();
+        let rewards: Vec<u64> = [19, 20, 1_000].iter().map(|&h| reward_at_height(h, &floored)).collect();
+        assert_eq!(rewards, [50, 30, 30]);
+        assert_eq!((floored.next_halving(15), floored.next_halving(20)), (Some(20), None));
+        assert_eq!(reward_at_height(u64::MAX, &DEFAULT_EMISSION), 0);
+    }
+
+    #[test]
+    fn total_emission_matches_brute_force() {
+        for schedule in [
+            EmissionSchedule::new(100, 10, 0).unwrap(),
+            EmissionSchedule::new(100, 10, 30).unwrap(),
+            EmissionSchedule::new(7, 3, 1).unwrap(),
+            EmissionSchedule::new(1, 1, 0).unwrap(),
+        ] {
+            let mut total = 0u128;
+            for end in 0..200 {
+                assert_eq!(schedule.emitted_before(end), total, "{:?} até {}", schedule, end);
+                total += reward_at_height(end, &schedule) as u128;
+            }
+        }
+        // 100 * (9 + 5 * 10 + 2.5 * 10 + ...) com as casas truncadas: 900 + 500 + 250 + 120 + 60 + 30 + 10
+        assert_eq!(EmissionSchedule::new(100, 10, 0).unwrap().max_supply(), Some(1_870));
+        assert_eq!(EmissionSchedule::new(100, 10, 1).unwrap().max_supply(), None);
+
+        // Os 21 milhões do Bitcoin em satoshis, menos o gênesis, que aqui não paga
+        let bitcoin: u128 = (0..33).map(|k| 210_000 * (5_000_000_000u128 >> k)).sum();
+        assert_eq!(DEFAULT_EMISSION.max_supply(), Some(bitcoin - 5_000_000_000));
+        assert_eq!(bitcoin, 2_099_999_997_690_000);
+    }
+
+    #[test]
+    fn invalid_schedules_are_rejected() {
+        assert_eq!(EmissionSchedule::new(100, 0, 0), Err(EmissionError::ZeroInterval));
+        let error = EmissionSchedule::new(10, 5, 11).unwrap_err();
+        assert_eq!(error, EmissionError::FloorAboveInitial { min_reward: 11, initial_reward: 10 });
+        let deserialized = EmissionSchedule { halving_interval: 0, ..DEFAULT_EMISSION };
+        assert_eq!(deserialized.check(), Err(EmissionError::ZeroInterval));
+    }
+
+    #[test]
+    fn schedule_is_fixed_once_blocks_are_mined() {
+        let mut chain = ChainState::new();
+        let other = EmissionSchedule::new(100, 10, 0).unwrap();
+        assert_eq!(chain.set_emission(other), Ok(()));
+        assert_eq!(*chain.rules().emission(), other);
+        let invalid = EmissionSchedule { halving_interval: 0, ..other };
+        assert_eq!(chain.set_emission(invalid), Err(EmissionError::ZeroInterval));
+
+        let block = BlockBuilder::on(chain.tip()).timestamp(1).witness(1, 1, 2, 1).build();
+        chain.append(block).unwrap();
+        assert_eq!(chain.set_emission(DEFAULT_EMISSION), Err(EmissionError::ChainNotEmpty { height: 2 }));
+        assert_eq!(*chain.rules().emission(), other);
+    }
+}
@@ -0,0 +1,194 @@
+// src/epoch.rs
+use serde::Serialize;
+
+use crate::block::Block;
+use crate::mining::Difficulty;
+
+pub const DEFAULT_EPOCH_SIZE: u64 = 10;
+
+/// Agregado dos blocos `[epoch * tamanho, (epoch + 1) * tamanho)`; a última época pode estar incompleta.
+#[derive(Debug, Clone, Serialize)]
+pub struct EpochSummary {
+    pub epoch: u64,
+    pub first_index: u64,
+    pub last_index: u64,
+    pub blocks: u64,
+    pub complete: bool,
+    pub min_digits: u32,
+    pub max_digits: u32,
+    pub mean_digits: f64,
+    // Soma dos intervalos entre timestamps; blocos sem timestamp não contam
+    pub mining_secs: f64,
+    // Só blocos minerados por este nó registram candidatos
+    pub avg_candidates: Option<f64>,
+    pub prime_sum: u128,
+    // Dificuldade vigente ao anexar o primeiro bloco minerado da época; desconhecida em cadeias reconstruídas
+    pub difficulty: Option<Difficulty>,
+    #[serde(skip)]
+    first_hash: String,
+    #[serde(skip)]
+    digit_total: u64,
+    #[serde(skip)]
+    candidates: u64,
+    #[serde(skip)]
+    mined_blocks: u64,
+}
+
+impl EpochSummary {
+    fn start(epoch: u64, block: &Block) -> Self {
+        EpochSummary {
+            epoch,
+            first_index: block.index,
+            last_index: block.index,
+            blocks: 0,
+            complete: false,
+            min_digits: u32::MAX,
+            max_digits: 0,
+            mean_digits: 0.0,
+            mining_secs: 0.0,
+            avg_candidates: None,
+            prime_sum: 0,
+            difficulty: None,
+            first_hash: block.hash.clone(),
+            digit_total: 0,
+            candidates: 0,
+            mined_blocks: 0,
+        }
+    }
+
+    fn add(&mut self, block: &Block, prev: Option<&Block>, size: u64) {
+        let digits = block.prime.digits();
+        self.last_index = block.index;
+        self.blocks += 1;
+        self.complete = self.blocks == size;
+        self.min_digits = self.min_digits.min(digits);
+        self.max_digits = self.max_digits.max(digits);
+        self.digit_total += digits as u64;
+        self.mean_digits = self.digit_total as f64 / self.blocks as f64;
+        // Na u128 a soma satura; nas duas pontas da comparação do mesmo jeito
+        self.prime_sum = self.prime_sum.saturating_add(block.prime.get());
+        if let Some(prev) = prev.filter(|p| p.timestamp > 0 && block.timestamp > 0) {
+            self.mining_secs += block.timestamp.saturating_sub(prev.timestamp) as f64 / 1000.0;
+        }
+    }
+
+    fn same_totals(&self, other: &EpochSummary) -> bool {
+        (self.epoch, self.first_index, self.last_index, self.blocks, &self.first_hash)
+            == (other.epoch, other.first_index, other.last_index, other.blocks, &other.first_hash)
+            && (self.min_digits, self.max_digits, self.digit_total, self.prime_sum)
+                == (other.min_digits, other.max_digits, other.digit_total, other.prime_sum)
+            && self.mining_secs == other.mining_secs
+    }
+
+    fn record_candidates(&mut self, candidates: u64) {
+        // Satura: só alimenta uma média informativa
+        self.candidates = self.candidates.saturating_add(candidates);
+        self.mined_blocks += 1;
+        self.avg_candidates = Some(self.candidates as f64 / self.mined_blocks as f64);
+    }
+}
+
+/// Resumos de todas as épocas, atualizados a cada bloco anexado.
+#[derive(Debug, Clone)]
+pub struct Epochs {
+    size: u64,
+    summaries: Vec<EpochSummary>,
+}
+
+impl Epochs {
+    pub fn new(size: u64) -> Self {
+        Epochs { size: size.max(1), summaries: Vec::new() }
+    }
+
+    /// Recalcula do zero; dificuldade e candidatos das épocas se perdem.
+    pub fn rebuild(size: u64, blocks: &[Block]) -> Self {
+        let mut epochs = Epochs::new(size);
+        for (i, block) in blocks.iter().enumerate() {
+            epochs.push(block, i.checked_sub(1).map(|p| &blocks[p]), None);
+        }
+        epochs
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Soma `block` à sua época; `difficulty` é a vigente quando ele foi minerado, se conhecida.
+    pub fn push(&mut self, block: &Block, prev: Option<&Block>, difficulty: Option<&Difficulty>) {
+        let epoch = block.index / self.size;
+        if self.summaries.last().is_none_or(|s| s.epoch != epoch) {
+            self.summaries.push(EpochSummary::start(epoch, block));
+        }
+        let summary = self.summaries.last_mut().expect("época recém-criada");
+        summary.add(block, prev, self.size);
+        // O gênesis não é minerado: a época 0 fica com a dificuldade do bloco 1
+        if block.index == summary.first_index.max(1) {
+            summary.difficulty = difficulty.cloned();
+        }
+    }
+
+    /// Candidatos testados para o bloco da ponta, minerado por este nó.
+    pub fn record_candidates(&mut self, candidates: u64) {
+        if let Some(summary) = self.summaries.last_mut() {
+            summary.record_candidates(candidates);
+        }
+    }
+
+    /// Blocos com candidatos registrados e o total de candidatos deles, em todas as épocas.
+    pub fn recorded_candidates(&self) -> (u64, u64) {
+        self.summaries.iter().fold((0, 0), |(blocks, total), s| (blocks + s.mined_blocks, total + s.candidates))
+    }
+
+    pub fn get(&self, epoch: u64) -> Option<&EpochSummary> {
+        self.summaries.get(epoch as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.summaries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.summaries.is_empty()
+    }
+
+    /// Copia dificuldade e candidatos das épocas que começam no mesmo bloco em `other`; não dá para
+    /// recalculá-los a partir dos blocos.
+    pub fn inherit_observed(&mut self, other: &Epochs) {
+        self.inherit(other, false);
+    }
+
+    /// Como `inherit_observed` para um prefixo de `other`: os candidatos só vêm das épocas com os mesmos
+    /// blocos, já que a época cortada não sabe quantos foram dos blocos que ficaram.
+    pub fn inherit_observed_prefix(&mut self, other: &Epochs) {
+        self.inherit(other, true);
+    }
+
+    fn inherit(&mut self, other: &Epochs, prefix: bool) {
+        if self.size != other.size {
+            return;
+        }
+        for (mine, theirs) in self.summaries.iter_mut().zip(&other.summaries) {
+            if mine.first_hash != theirs.first_hash {
+                continue;
+            }
+            if mine.difficulty.is_none() {
+                mine.difficulty = theirs.difficulty.clone();
+            }
+            if mine.mined_blocks == 0 && (!prefix || mine.blocks == theirs.blocks) {
+                mine.candidates = theirs.candidates;
+                mine.mined_blocks = theirs.mined_blocks;
+                mine.avg_candidates = theirs.avg_candidates;
+            }
+        }
+    }
+
+    /// Épocas cujos totais derivados dos blocos diferem entre `self` e `other`.
+    pub fn differing(&self, other: &Epochs) -> Vec<u64> {
+        self.summaries
+            .iter()
+            .zip(&other.summaries)
+            .filter(|(a, b)| !a.same_totals(b))
+            .map(|(a, _)| a.epoch)
+            .collect()
+    }
+}
@@ -0,0 +1,113 @@
+// src/fork.rs
+//! Escolha entre cadeias concorrentes: a regra única usada por /chain/resolve, importação de arquivos e
+//! qualquer outra troca de cadeia.
+use serde::Serialize;
+use std::cmp::Ordering;
+
+use crate::block::Block;
+use crate::width::PrimeValue;
+
+/// Trabalho de um primo: a quantidade de dígitos decimais. Inteiro, para que duas cadeias comparem igual
+/// em qualquer máquina; `snapshot::cumulative_work` (soma de `ln p`) segue como estimativa de candidatos.
+pub fn prime_work(prime: PrimeValue) -> u64 {
+    prime.digits() as u64
+}
+
+/// Trabalho do bloco, sempre derivado do primo; nas regras v5 o cabeçalho grava o mesmo valor.
+pub fn block_work(block: &Block) -> u64 {
+    prime_work(block.prime)
+}
+
+/// O que a escolha de fork precisa saber de uma cadeia.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChainSummary {
+    // Soma de `block_work` de todos os blocos, sem o gênesis
+    pub work: u64,
+    pub height: usize,
+    pub tip_hash: String,
+}
+
+impl ChainSummary {
+    /// Resumo de `blocks` como recebidos; não valida nada, então só serve para decidir se vale validar
+    /// ou, depois de validada, se a cadeia deve ser adotada.
+    pub fn of(blocks: &[Block]) -> Self {
+        ChainSummary {
+            work: blocks.iter().skip(1).map(block_work).sum(),
+            height: blocks.len(),
+            tip_hash: blocks.last().map_or_else(String::new, |tip| tip.hash.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preference {
+    First,
+    Second,
+    // Mesma ponta: a mesma cadeia
+    Same,
+}
+
+/// Regra de escolha de fork. Vence, nesta ordem:
+/// 1. o maior trabalho acumulado, para que muitos blocos de poucos dígitos não superem menos blocos
+///    de primos maiores quando a dificuldade diverge entre nós;
+/// 2. a maior altura;
+/// 3. o menor hash da ponta, comparado como texto hex (igual à ordem numérica, já que os hashes têm
+///    o mesmo tamanho), o que torna a escolha determinística entre cadeias de mesmo trabalho e altura.
+pub fn fork_choice(a: &ChainSummary, b: &ChainSummary) -> Preference {
+    let ordering = a
+        .work
+        .cmp(&b.work)
+        .then(a.height.cmp(&b.height))
+        .then_with(|| b.tip_hash.cmp(&a.tip_hash));
+    match ordering {
+        Ordering::Greater => Preference::First,
+        Ordering::Less => Preference::Second,
+        Ordering::Equal => Preference::Same,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockBuilder;
+
+    // `n` blocos sobre o gênesis, todos com o primo `1 + b` da testemunha (1, b, 1, 1)
+    fn chain(n: usize, b: u64, timestamp: u64) -> Vec<Block> {
+        let mut blocks = vec![Block::genesis()];
+        for _ in 0..n {
+            let block = BlockBuilder::on(blocks.last().unwrap()).timestamp(timestamp).witness(1, b, 1, 1).build();
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    #[test]
+    fn heavier_short_chain_beats_light_long_one() {
+        // Dois primos de 10 dígitos contra seis de um dígito
+        let heavy = ChainSummary::of(&chain(2, 1_000_000_006, 1));
+        let light = ChainSummary::of(&chain(6, 2, 1));
+        assert_eq!((heavy.work, light.work), (20, 6));
+        assert!(heavy.height < light.height);
+        assert_eq!(fork_choice(&heavy, &light), Preference::First);
+        assert_eq!(fork_choice(&light, &heavy), Preference::Second);
+    }
+
+    #[test]
+    fn equal_work_breaks_ties_deterministically() {
+        // Mesmo trabalho: a mais alta vence
+        let (short, tall) = (ChainSummary::of(&chain(2, 10, 1)), ChainSummary::of(&chain(4, 2, 1)));
+        assert_eq!((short.work, tall.work), (4, 4));
+        assert_eq!(fork_choice(&short, &tall), Preference::Second);
+
+        // Mesmo trabalho e altura: o menor hash da ponta, nos dois sentidos
+        let a = ChainSummary::of(&chain(3, 2, 1));
+        let b = ChainSummary::of(&chain(3, 2, 2));
+        assert_eq!((a.work, a.height), (b.work, b.height));
+        assert_ne!(a.tip_hash, b.tip_hash);
+        let (low, high) = if a.tip_hash < b.tip_hash { (&a, &b) } else { (&b, &a) };
+        assert_eq!(fork_choice(low, high), Preference::First);
+        assert_eq!(fork_choice(high, low), Preference::Second);
+        assert_eq!(fork_choice(&a, &a.clone()), Preference::Same);
+    }
+}
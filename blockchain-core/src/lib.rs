@@ -0,0 +1,64 @@
+// src/lib.rs
+pub mod archive;
+pub mod block;
+pub mod calibration;
+pub mod cancel;
+pub mod chain;
+pub mod compact;
+pub mod emission;
+pub mod epoch;
+pub mod fork;
+pub mod math;
+pub mod merkle;
+pub mod mining;
+#[cfg(feature = "mining")]
+pub mod pool;
+pub mod receipt;
+pub mod retarget;
+pub mod rules;
+#[cfg(feature = "mining")]
+pub mod seeded;
+pub mod signature;
+#[cfg(feature = "mining")]
+pub mod simulation;
+pub mod snapshot;
+#[cfg(all(feature = "mining", any(test, feature = "testkit")))]
+pub mod testkit;
+pub mod throttle;
+pub mod transaction;
+pub mod verifier;
+pub mod width;
+
+pub use archive::CompressedChain;
+pub use block::{compute_hash, meets_hash_target, Block, BlockBuilder, VerifyError};
+pub use calibration::calibrate;
+#[cfg(feature = "mining")]
+pub use calibration::measure_throughput;
+pub use cancel::CancelToken;
+pub use chain::{ChainState, DerivedCheck, DerivedState, WindowStats, MAX_WORKERS};
+pub use emission::{reward_at_height, EmissionError, EmissionSchedule, DEFAULT_EMISSION};
+pub use epoch::{EpochSummary, DEFAULT_EPOCH_SIZE};
+pub use fork::{block_work, fork_choice, ChainSummary, Preference};
+pub use math::{bpsw, digit_range, miller_rabin_deterministic, MathError};
+#[cfg(feature = "mining")]
+pub use math::{miller_rabin, miller_rabin_rounds};
+pub use merkle::{compute_merkle_root, verify_merkle_proof, MerkleTree, ProofStep};
+pub use mining::{
+    decide_difficulty, farey_tuple, is_farey_pair, Difficulty, DifficultyDecision, DifficultyOverride, MiningStats,
+    OverrideError, MAX_MIN_DIGITS, TARGET_TIME,
+};
+#[cfg(feature = "mining")]
+pub use mining::{mine_template, mine_worker};
+#[cfg(feature = "mining")]
+pub use pool::CandidatePool;
+pub use receipt::{sign_receipt, verify_receipt, MiningReceipt, ReceiptBody, ReceiptStats};
+pub use retarget::{AlgorithmConfig, DifficultyAlgorithm, EmaAlgorithm, WindowAlgorithm};
+#[cfg(feature = "mining")]
+pub use seeded::{worker_rng, RaceResult, SeededRace, WorkerRun};
+#[cfg(feature = "mining")]
+pub use simulation::{simulate, Scenario, Simulation};
+pub use rules::{validate_block, RuleSchedule, LATEST_RULES_VERSION, RULES_ACTIVATION};
+pub use throttle::{Clock, DutyCycle, DutyMeter, Intensity, MiningSchedule, SystemClock, Throttle};
+pub use transaction::{balances, tx_proof, tx_root, Balance, Transaction, EMPTY_TX_ROOT};
+pub use verifier::{ChainError, PoWVerifier};
+pub use width::{PrimeValue, PrimeWidth, WidthError};
@@ -0,0 +1,960 @@
+// src/math.rs
+use num::{BigUint, Integer, One};
+#[cfg(feature = "mining")]
+use rand::Rng;
+use std::fmt;
+
+use crate::cancel::CancelToken;
+
+// Bases suficientes para um Miller-Rabin determinístico em todo o intervalo u64
+const SMALL_PRIMES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+// Bases acima de u64: até 41 o teste é exato abaixo de 3.3 * 10^24; as demais só reforçam
+const WIDE_BASES: [u64; 20] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71];
+
+/// Conta que estouraria o tipo. Caminhos com entrada externa (validação, importação, submissão)
+/// devolvem isso em vez de entrar em panic ou dar a volta em silêncio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathError {
+    Overflow(&'static str),
+    Underflow(&'static str),
+    EmptyRange(&'static str),
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MathError::Overflow(site) => write!(f, "arithmetic overflow in {}", site),
+            MathError::Underflow(site) => write!(f, "arithmetic underflow in {}", site),
+            MathError::EmptyRange(site) => write!(f, "empty range in {}", site),
+        }
+    }
+}
+
+impl std::error::Error for MathError {}
+
+/// Menor e maior número com `digits` dígitos, `[10^(digits-1), 10^digits - 1]`. Erro para 0 dígitos
+/// e a partir de 20, quando `10^digits` não cabe em u64.
+pub fn digit_range(digits: u32) -> Result<(u64, u64), MathError> {
+    let exponent = digits.checked_sub(1).ok_or(MathError::Underflow("digit_range"))?;
+    let high = 10_u64.checked_pow(digits).ok_or(MathError::Overflow("digit_range"))?;
+    Ok((10_u64.pow(exponent), high - 1))
+}
+
+pub fn mod_mul(a: u64, b: u64, modu: u64) -> u64 {
+    ((a as u128 * b as u128) % modu as u128) as u64
+}
+
+pub fn mod_pow(mut base: u64, mut exp: u64, modu: u64) -> u64 {
+    if modu == 1 { return 0; }
+    let mut result = 1;
+    base %= modu;
+    while exp > 0 {
+        if exp % 2 == 1 { result = mod_mul(result, base, modu); }
+        base = mod_mul(base, base, modu);
+        exp /= 2;
+    }
+    result
+}
+
+// n - 1 = d * 2^r com d ímpar
+fn decompose(n_minus_one: u64) -> (u64, u32) {
+    let r = n_minus_one.trailing_zeros();
+    (n_minus_one >> r, r)
+}
+
+fn is_strong_probable_prime(n: u64, d: u64, r: u32, a: u64) -> bool {
+    let mut x = mod_pow(a, d, n);
+    if x == 1 || x == n - 1 { return true; }
+    for _ in 1..r {
+        x = mod_mul(x, x, n);
+        if x == n - 1 { return true; }
+    }
+    false
+}
+
+/// Miller-Rabin probabilístico com `k` bases aleatórias.
+#[cfg(feature = "mining")]
+pub fn miller_rabin(n: u64, k: u32) -> bool {
+    miller_rabin_rounds(n, k).0
+}
+
+/// Como `miller_rabin`, contando as rodadas executadas: `k` para primos, menos quando uma base
+/// prova que `n` é composto (e 0 nos casos triviais).
+#[cfg(feature = "mining")]
+pub fn miller_rabin_rounds(n: u64, k: u32) -> (bool, u32) {
+    if n <= 1 { return (false, 0); }
+    if n <= 3 { return (true, 0); }
+    if n.is_multiple_of(2) { return (false, 0); }
+
+    // n >= 5 aqui, então n - 1 não estoura e o intervalo das bases não é vazio
+    let (d, r) = decompose(n - 1);
+    let mut rng = rand::thread_rng();
+    let mut rounds = 0;
+    let prime = (0..k).all(|_| {
+        rounds += 1;
+        is_strong_probable_prime(n, d, r, rng.gen_range(2..n - 1))
+    });
+    (prime, rounds)
+}
+
+/// Miller-Rabin determinístico para qualquer `u64` (bases primas até 37).
+pub fn miller_rabin_deterministic(n: u64) -> bool {
+    if n < 2 { return false; }
+    for &p in &SMALL_PRIMES {
+        if n == p { return true; }
+        if n.is_multiple_of(p) { return false; }
+    }
+    let (d, r) = decompose(n - 1);
+    SMALL_PRIMES.iter().all(|&a| is_strong_probable_prime(n, d, r, a))
+}
+
+/// Miller-Rabin para `u128` com as 20 primeiras bases primas: exato abaixo de 3.3 * 10^24 e, acima,
+/// sem contraexemplo conhecido. Valores que cabem em u64 vão para `miller_rabin_deterministic`.
+pub fn miller_rabin_u128(n: u128) -> bool {
+    if let Ok(n) = u64::try_from(n) { return miller_rabin_deterministic(n); }
+    if WIDE_BASES.iter().any(|&p| n.is_multiple_of(p as u128)) { return false; }
+    let r = (n - 1).trailing_zeros();
+    let (modulus, minus_one, d) = (BigUint::from(n), BigUint::from(n - 1), BigUint::from((n - 1) >> r));
+    WIDE_BASES.iter().all(|&a| {
+        let mut x = BigUint::from(a).modpow(&d, &modulus);
+        if x.is_one() || x == minus_one { return true; }
+        for _ in 1..r {
+            x = &x * &x % &modulus;
+            if x == minus_one { return true; }
+        }
+        false
+    })
+}
+
+/// Símbolo de Jacobi `(a/n)`; `n` deve ser ímpar.
+pub fn jacobi(mut a: u64, mut n: u64) -> i32 {
+    debug_assert!(n % 2 == 1, "jacobi requer n ímpar");
+    a %= n;
+    let mut result = 1;
+    while a != 0 {
+        while a.is_multiple_of(2) {
+            a /= 2;
+            if n % 8 == 3 || n % 8 == 5 { result = -result; }
+        }
+        std::mem::swap(&mut a, &mut n);
+        if a % 4 == 3 && n % 4 == 3 { result = -result; }
+        a %= n;
+    }
+    if n == 1 { result } else { 0 }
+}
+
+// x / 2 (mod n), com n ímpar
+fn half_mod(x: u64, n: u64) -> u64 {
+    if x.is_multiple_of(2) { x / 2 } else { ((x as u128 + n as u128) / 2) as u64 }
+}
+
+fn sub_mod(a: u64, b: u64, n: u64) -> u64 {
+    if a >= b { a - b } else { n - (b - a) }
+}
+
+fn add_mod(a: u64, b: u64, n: u64) -> u64 {
+    ((a as u128 + b as u128) % n as u128) as u64
+}
+
+// Teste forte de Lucas com parâmetros de Selfridge (P = 1, Q = (1 - D) / 4)
+fn is_strong_lucas_probable_prime(n: u64) -> bool {
+    let root = n.isqrt();
+    if root * root == n { return false; }
+
+    let mut d_abs: u64 = 5;
+    let mut negative = false;
+    let d_mod = loop {
+        let d_mod = if negative { (n - d_abs % n) % n } else { d_abs % n };
+        match jacobi(d_mod, n) {
+            -1 => break d_mod,
+            0 if n != d_abs => return false,
+            0 => return miller_rabin_deterministic(n),
+            _ => {}
+        }
+        d_abs += 2;
+        negative = !negative;
+    };
+
+    let d_signed = if negative { -(d_abs as i128) } else { d_abs as i128 };
+    let q = (1 - d_signed) / 4;
+    let q_mod = q.rem_euclid(n as i128) as u64;
+
+    let n_plus_one = n as u128 + 1;
+    let s = n_plus_one.trailing_zeros();
+    let d = n_plus_one >> s;
+
+    // U_1 = 1, V_1 = P = 1, Q^1
+    let (mut u, mut v, mut qk) = (1u64, 1u64, q_mod);
+    for bit in (0..(127 - d.leading_zeros())).rev() {
+        u = mod_mul(u, v, n);
+        v = sub_mod(mod_mul(v, v, n), add_mod(qk, qk, n), n);
+        qk = mod_mul(qk, qk, n);
+        if (d >> bit) & 1 == 1 {
+            let u_next = half_mod(add_mod(u, v, n), n);
+            v = half_mod(add_mod(mod_mul(d_mod, u, n), v, n), n);
+            u = u_next;
+            qk = mod_mul(qk, q_mod, n);
+        }
+    }
+
+    if u == 0 || v == 0 { return true; }
+    for _ in 1..s {
+        v = sub_mod(mod_mul(v, v, n), add_mod(qk, qk, n), n);
+        qk = mod_mul(qk, qk, n);
+        if v == 0 { return true; }
+    }
+    false
+}
+
+/// Baillie-PSW: Miller-Rabin na base 2 seguido do teste forte de Lucas.
+/// Não há pseudoprimos BPSW conhecidos abaixo de 2^64.
+pub fn bpsw(n: u64) -> bool {
+    if n < 2 { return false; }
+    for &p in &SMALL_PRIMES {
+        if n == p { return true; }
+        if n.is_multiple_of(p) { return false; }
+    }
+    let (d, r) = decompose(n - 1);
+    is_strong_probable_prime(n, d, r, 2) && is_strong_lucas_probable_prime(n)
+}
+
+pub fn prime_heuristic(n: u128, min_prob: f64) -> bool {
+    if n < 2 { return false; }
+    let ln_n = (n as f64).ln();
+    1.0 / ln_n >= min_prob
+}
+
+// Constante dos primos gêmeos de Hardy-Littlewood
+pub const TWIN_PRIME_CONSTANT: f64 = 0.660_161_815_846_869_6;
+
+/// `p` pertence a um par de primos gêmeos (p - 2 ou p + 2 também é primo).
+pub fn is_twin_prime(p: u64) -> bool {
+    bpsw(p) && ((p >= 2 && bpsw(p - 2)) || p.checked_add(2).is_some_and(bpsw))
+}
+
+/// `p` e `p + 6` são ambos primos.
+pub fn is_sexy_prime(p: u64) -> bool {
+    p.checked_add(6).is_some_and(|q| bpsw(p) && bpsw(q))
+}
+
+/// `p` e `p + d` são primos consecutivos (nenhum primo entre eles), com `d` par.
+pub fn is_prime_gap(p: u64, d: u64) -> bool {
+    let Some(q) = p.checked_add(d) else { return false };
+    d > 0 && d.is_multiple_of(2) && bpsw(p) && bpsw(q) && !(p + 1..q).any(bpsw)
+}
+
+/// Probabilidade, segundo Hardy-Littlewood, de um primo próximo de `p` ter um gêmeo.
+pub fn expected_twin_probability(p: u128) -> f64 {
+    if p < 5 { return 0.0; }
+    (4.0 * TWIN_PRIME_CONSTANT / (p as f64).ln()).min(1.0)
+}
+
+/// Quantidade de dígitos decimais de `n`.
+pub fn digits(n: u64) -> u32 {
+    n.checked_ilog10().map_or(1, |d| d + 1)
+}
+
+/// Teorema de Wilson: `(n - 1)! ≡ -1 (mod n)` se e somente se `n` é primo. Custo O(n).
+pub fn wilson_check(n: u64) -> bool {
+    if n < 2 { return false; }
+    let factorial = (2..n).fold(1 % n, |acc, k| mod_mul(acc, k, n));
+    factorial == n - 1
+}
+
+/// Quociente de Wilson `W(p) = ((p - 1)! + 1) / p` módulo `p`, para o primo `p < 2^32`: o fatorial é
+/// reduzido módulo `p²`, onde ainda cabe a divisão exata. `p` é primo de Wilson quando o resultado é 0.
+pub fn wilson_quotient_mod(p: u64) -> u64 {
+    let square = p * p;
+    let factorial = (2..p).fold(1 % square, |acc, k| mod_mul(acc, k, square));
+    ((factorial + 1) % square / p) % p
+}
+
+/// Crivo de Eratóstenes: todos os primos `<= n`.
+pub fn sieve(n: u64) -> Vec<u64> {
+    sieve_cancellable(n, &CancelToken::new()).expect("token nunca cancelado")
+}
+
+// Marcações entre consultas ao token
+const SIEVE_CHUNK: usize = 1 << 16;
+
+/// Crivo que devolve `None` se `token` for cancelado; o progresso conta as marcações feitas.
+pub fn sieve_cancellable(n: u64, token: &CancelToken) -> Option<Vec<u64>> {
+    let n = n as usize;
+    if n < 2 { return Some(Vec::new()); }
+    let mut composite = vec![false; n + 1];
+    let mut primes = Vec::new();
+    for i in 2..=n {
+        if composite[i] { continue; }
+        primes.push(i as u64);
+        let mut multiple = i.saturating_mul(i);
+        while multiple <= n {
+            let end = multiple.saturating_add(i * SIEVE_CHUNK).min(n + 1);
+            while multiple < end {
+                composite[multiple] = true;
+                multiple += i;
+            }
+            if token.is_cancelled() { return None; }
+            token.advance(SIEVE_CHUNK as u64);
+        }
+    }
+    Some(primes)
+}
+
+/// π(x), a quantidade de primos até `x`, pelo método combinatório de Legendre/Meissel na forma
+/// de Lucy: `S(v)` começa em `v - 1` e cada primo `p <= √x` tira os múltiplos `S(v/p) - S(p-1)`
+/// de todo `v >= p²` entre os valores `x / i`. Custo O(x^(3/4)), memória O(√x). Para x pequeno
+/// o crivo é mais barato. `None` se `token` for cancelado; o progresso conta os primos processados.
+pub fn prime_pi_cancellable(x: u64, token: &CancelToken) -> Option<u64> {
+    if x < SIEVE_CHUNK as u64 {
+        return sieve_cancellable(x, token).map(|primes| primes.len() as u64);
+    }
+    let root = x.isqrt();
+    let r = root as usize;
+    // small[v] = S(v) para v <= √x; large[i] = S(x / i) para i <= √x
+    let mut small: Vec<u64> = (0..=root).map(|v| v.saturating_sub(1)).collect();
+    let mut large: Vec<u64> = (0..=root).map(|i| x.checked_div(i).map_or(0, |v| v - 1)).collect();
+    for p in 2..=root {
+        if small[p as usize] == small[p as usize - 1] { continue; }
+        let below = small[p as usize - 1];
+        let square = p * p;
+        let last = r.min((x / square) as usize);
+        for i in 1..=last {
+            // x / i / p = x / (i * p); abaixo de √x cai na tabela pequena
+            let d = i as u64 * p;
+            let s = if d <= root { large[d as usize] } else { small[(x / d) as usize] };
+            large[i] -= s - below;
+        }
+        for v in (square..=root).rev() {
+            small[v as usize] -= small[(v / p) as usize] - below;
+        }
+        if token.is_cancelled() { return None; }
+        token.advance(1);
+    }
+    Some(large[1])
+}
+
+// Constante de Euler-Mascheroni
+pub const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+
+/// Produto de Euler `∏_{p <= n} (1 - 1/p)^(-1)` sobre os primos dados.
+/// Pelo terceiro teorema de Mertens ele cresce como `e^γ · ln n`.
+pub fn euler_product(primes: &[u64]) -> f64 {
+    primes.iter().map(|&p| 1.0 / (1.0 - 1.0 / p as f64)).product()
+}
+
+/// Produto de Euler da zeta, `∏ (1 - p^(-s))^(-1)`, para `s > 1`.
+/// Primos com `p^(-s)` abaixo do épsilon de `f64` não mudam o produto e são ignorados;
+/// devolve o produto e quantos primos entraram nele.
+pub fn zeta_euler_product(primes: &[u64], s: f64) -> (f64, usize) {
+    primes
+        .iter()
+        .map(|&p| (p as f64).powf(-s))
+        .filter(|&term| term >= f64::EPSILON)
+        .fold((1.0, 0), |(product, used), term| (product / (1.0 - term), used + 1))
+}
+
+/// Função erro complementar, pela aproximação de Chebyshev do Numerical Recipes (erro relativo < 1,2e-7).
+pub fn erfc(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 10] = [
+        -1.26551223, 1.00002368, 0.37409196, 0.09678418, -0.18628806,
+        0.27886807, -1.13520398, 1.48851587, -0.82215223, 0.17087277,
+    ];
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = COEFFICIENTS.iter().rev().fold(0.0, |acc, &c| c + t * acc);
+    let tail = t * (-z * z + poly).exp();
+    if x >= 0.0 { tail } else { 2.0 - tail }
+}
+
+// Precisão relativa e menor valor não nulo da fração contínua de Lentz
+const GAMMA_EPSILON: f64 = 1e-12;
+const GAMMA_TINY: f64 = 1e-300;
+// Termos da série ou da fração contínua antes de desistir da convergência
+const GAMMA_MAX_TERMS: u32 = 1000;
+
+// ln Γ(x) para x > 0, pela aproximação de Lanczos do Numerical Recipes (erro < 2e-10)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146, -86.50532032941677, 24.01409824083091,
+        -1.231739572450155, 0.1208650973866179e-2, -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let series =
+        COEFFICIENTS.iter().enumerate().fold(1.000000000190015, |acc, (i, &c)| acc + c / (x + 1.0 + i as f64));
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+// Gama incompleta regularizada superior Q(a, x): série para x < a + 1, fração contínua no resto
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let prefactor = (-x + a * x.ln() - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        let (mut term, mut sum) = (1.0 / a, 1.0 / a);
+        for n in 1..GAMMA_MAX_TERMS {
+            term *= x / (a + n as f64);
+            sum += term;
+            if term.abs() < sum.abs() * GAMMA_EPSILON {
+                break;
+            }
+        }
+        return (1.0 - sum * prefactor).max(0.0);
+    }
+    let mut b = x + 1.0 - a;
+    let (mut c, mut d) = (1.0 / GAMMA_TINY, 1.0 / b);
+    let mut h = d;
+    for i in 1..GAMMA_MAX_TERMS {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < GAMMA_TINY {
+            d = GAMMA_TINY;
+        }
+        c = b + an / c;
+        if c.abs() < GAMMA_TINY {
+            c = GAMMA_TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < GAMMA_EPSILON {
+            break;
+        }
+    }
+    (prefactor * h).min(1.0)
+}
+
+/// P(X ≥ `statistic`) para X qui-quadrado com `degrees` graus de liberdade: `Q(k/2, x/2)`.
+/// Sem graus de liberdade não há o que rejeitar e o resultado é 1.
+pub fn chi_squared_p_value(statistic: f64, degrees: u64) -> f64 {
+    if degrees == 0 {
+        return 1.0;
+    }
+    gamma_q(degrees as f64 / 2.0, statistic / 2.0)
+}
+
+/// Critério de Fermat: `base^(n-1) ≡ 1 (mod n)`.
+pub fn fermat_test(n: u64, base: u64) -> bool {
+    n >= 2 && mod_pow(base, n - 1, n) == 1
+}
+
+/// Critério de Korselt: `n` é composto, livre de quadrados e `p - 1 | n - 1` para todo primo `p | n`.
+/// Fatora por divisão por tentativa, então o custo é O(√n).
+pub fn is_carmichael(n: u64) -> bool {
+    if n < 3 || n.is_multiple_of(2) || miller_rabin_deterministic(n) {
+        return false;
+    }
+    let mut rest = n;
+    let mut p = 3;
+    while p * p <= rest {
+        if rest.is_multiple_of(p) {
+            rest /= p;
+            if rest.is_multiple_of(p) || !(n - 1).is_multiple_of(p - 1) {
+                return false;
+            }
+        }
+        p += 2;
+    }
+    rest == n || (n - 1).is_multiple_of(rest - 1)
+}
+
+/// Um fator não trivial de `n` pelo rho de Pollard; `None` para primos, 0 e 1.
+pub fn pollard_rho(n: u64) -> Option<u64> {
+    pollard_rho_iterations(n).map(|(factor, _)| factor)
+}
+
+/// Como `pollard_rho`, com os passos de Floyd (`x = f(x)`, `y = f(f(y))`) somados sobre todas as constantes
+/// `c` de `f(x) = x² + c` tentadas. Pares saem com o fator 2 sem iterar.
+pub fn pollard_rho_iterations(n: u64) -> Option<(u64, u64)> {
+    if n < 4 || miller_rabin_deterministic(n) {
+        return None;
+    }
+    if n.is_multiple_of(2) {
+        return Some((2, 0));
+    }
+    let mut iterations = 0;
+    for c in 1.. {
+        let f = |x: u64| add_mod(mod_mul(x, x, n), c, n);
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = x.abs_diff(y).gcd(&n);
+            iterations += 1;
+        }
+        // d = n: o ciclo fechou sem separar os fatores; tenta outra constante
+        if d != n {
+            return Some((d, iterations));
+        }
+    }
+    unreachable!("todo composto tem um fator achado por algum c")
+}
+
+// Resíduos módulo 30 coprimos com 2, 3 e 5, e o salto de cada um até o próximo
+const WHEEL_30: [(u64, u64); 8] = [(1, 6), (7, 4), (11, 2), (13, 4), (17, 2), (19, 4), (23, 6), (29, 2)];
+
+/// Menor primo em `(after, after + span]`, pelo BPSW nos candidatos da roda módulo 30.
+pub fn next_prime(after: u64, span: u64) -> Option<u64> {
+    let limit = after.saturating_add(span);
+    if let Some(&p) = [2, 3, 5].iter().find(|&&p| p > after) {
+        return (p <= limit).then_some(p);
+    }
+    let base = after / 30 * 30;
+    let (mut n, mut slot) = match WHEEL_30.iter().position(|&(r, _)| base.checked_add(r).is_some_and(|n| n > after)) {
+        Some(slot) => (base + WHEEL_30[slot].0, slot),
+        None => (base.checked_add(31)?, 0),
+    };
+    while n <= limit {
+        if bpsw(n) {
+            return Some(n);
+        }
+        n = n.checked_add(WHEEL_30[slot].1)?;
+        slot = (slot + 1) % WHEEL_30.len();
+    }
+    None
+}
+
+/// Fatores pequenos achados por divisão por tentativa com no máximo `budget` divisões.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrialFactors {
+    pub factors: Vec<(u64, u32)>,
+    // O que sobrou de n após remover os fatores achados
+    pub cofactor: u64,
+    pub divisions: u64,
+    // Verdadeiro quando o cofator é 1 ou comprovadamente primo (o divisor passou de sua raiz)
+    pub complete: bool,
+}
+
+/// Divide `n` por 2, 3 e pelos ímpares seguintes até a raiz do cofator ou até o orçamento acabar.
+pub fn trial_factor(n: u64, budget: u64) -> TrialFactors {
+    let mut result = TrialFactors { factors: Vec::new(), cofactor: n, divisions: 0, complete: n < 2 };
+    if n < 2 {
+        return result;
+    }
+    let mut divisor = 2;
+    while result.divisions < budget {
+        if divisor > result.cofactor / divisor {
+            result.complete = true;
+            break;
+        }
+        result.divisions += 1;
+        let mut exponent = 0;
+        while result.cofactor.is_multiple_of(divisor) {
+            result.cofactor /= divisor;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            result.factors.push((divisor, exponent));
+        }
+        divisor += if divisor == 2 { 1 } else { 2 };
+    }
+    if result.cofactor == 1 || divisor > result.cofactor / divisor {
+        result.complete = true;
+    }
+    if result.complete && result.cofactor > 1 {
+        result.factors.push((result.cofactor, 1));
+        result.cofactor = 1;
+    }
+    result
+}
+
+/// Fatoração em primos `[(p, expoente)]` em ordem crescente; vazia para 0 e 1.
+pub fn factorize(n: u64) -> Vec<(u64, u32)> {
+    let mut primes = Vec::new();
+    let mut rest = n;
+    if rest < 2 {
+        return Vec::new();
+    }
+    for &p in &SMALL_PRIMES {
+        while rest.is_multiple_of(p) {
+            primes.push(p);
+            rest /= p;
+        }
+    }
+    let mut pending = vec![rest];
+    while let Some(m) = pending.pop() {
+        if m == 1 {
+            continue;
+        }
+        if miller_rabin_deterministic(m) {
+            primes.push(m);
+        } else {
+            let d = pollard_rho(m).expect("m é composto");
+            pending.extend([d, m / d]);
+        }
+    }
+    primes.sort_unstable();
+    primes.chunk_by(|a, b| a == b).map(|run| (run[0], run.len() as u32)).collect()
+}
+
+/// Fração contínua de √n pelo algoritmo clássico (m, d, a): `(a0, período)`, com período vazio se `n` é
+/// quadrado perfeito. Para em `max_terms` termos; o `bool` diz se o período veio completo.
+pub fn sqrt_continued_fraction(n: u64, max_terms: usize) -> (u64, Vec<u64>, bool) {
+    let a0 = n.isqrt();
+    if a0 * a0 == n {
+        return (a0, Vec::new(), true);
+    }
+    // m < √n e d <= 2√n, mas n - m² e d·a passam de u64 perto do topo
+    let (n, root) = (n as u128, a0 as u128);
+    let (mut m, mut d, mut a) = (0u128, 1u128, root);
+    let mut period = Vec::new();
+    while period.len() < max_terms {
+        m = d * a - m;
+        d = (n - m * m) / d;
+        a = (root + m) / d;
+        period.push(a as u64);
+        if a == 2 * root {
+            return (a0, period, true);
+        }
+    }
+    (a0, period, false)
+}
+
+/// Menor raiz primitiva módulo o primo `p` e quantos candidatos foram testados: o primeiro `g` com
+/// `g^((p-1)/q) ≢ 1 (mod p)` para todo primo `q` que divide `p - 1`. Para `p = 2` a raiz é 1.
+pub fn primitive_root(p: u64) -> (u64, u64) {
+    if p == 2 {
+        return (1, 1);
+    }
+    let exponents: Vec<u64> = factorize(p - 1).iter().map(|&(q, _)| (p - 1) / q).collect();
+    let mut checked = 0;
+    for g in 2..p {
+        checked += 1;
+        if exponents.iter().all(|&e| mod_pow(g, e, p) != 1) {
+            return (g, checked);
+        }
+    }
+    unreachable!("todo primo tem raiz primitiva")
+}
+
+/// Os `count` menores resíduos quadráticos não nulos módulo o primo `p`, em ordem crescente, pelo
+/// símbolo de Jacobi. São `(p - 1) / 2` ao todo, ou só o 1 para `p = 2`.
+pub fn quadratic_residues(p: u64, count: usize) -> Vec<u64> {
+    if p == 2 {
+        return [1].into_iter().take(count).collect();
+    }
+    (1..p).filter(|&a| jacobi(a, p) == 1).take(count).collect()
+}
+
+// Bits do dígito simulado por `lehmer_gcd`; com até 62, x + p e quotient * r não estouram i64
+const LEHMER_DIGIT_BITS: u32 = 62;
+
+/// MDC pelo algoritmo de Euclides, uma divisão por passo.
+pub fn euclid_gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// MDC pelo algoritmo de Lehmer (Knuth, TAOCP vol. 2, algoritmo L): enquanto `b` passa de 64 bits,
+/// simula os passos de Euclides nos 62 bits mais altos, em i64, e aplica vários de uma vez com uma
+/// única combinação linear em 128 bits. Com `b` em 64 bits, o resto sai por Euclides em u64.
+pub fn lehmer_gcd(mut a: u128, mut b: u128) -> u128 {
+    if a < b {
+        (a, b) = (b, a);
+    }
+    while b > u64::MAX as u128 {
+        // Os bits mais altos de `a` e os de `b` na mesma posição
+        let shift = 128 - LEHMER_DIGIT_BITS - a.leading_zeros();
+        let (mut x, mut y) = ((a >> shift) as i64, (b >> shift) as i64);
+        let (mut p, mut q, mut r, mut s) = (1i64, 0i64, 0i64, 1i64);
+        // O quociente só vale se os dois extremos do intervalo em que o verdadeiro está concordam
+        while y + r != 0 && y + s != 0 {
+            let quotient = (x + p) / (y + r);
+            if quotient != (x + q) / (y + s) {
+                break;
+            }
+            (p, r) = (r, p - quotient * r);
+            (q, s) = (s, q - quotient * s);
+            (x, y) = (y, x - quotient * y);
+        }
+        if q == 0 {
+            // Nenhum passo simulado: uma divisão em precisão total
+            (a, b) = (b, a % b);
+        } else {
+            // Os resultados cabem em u128 (são restos da sequência), então as parcelas podem dar a volta
+            let combine = |m: i64, n: i64| (m as u128).wrapping_mul(a).wrapping_add((n as u128).wrapping_mul(b));
+            (a, b) = (combine(p, q), combine(r, s));
+        }
+    }
+    if b == 0 {
+        return a;
+    }
+    let (mut a, mut b) = (b as u64, (a % b) as u64);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a as u128
+}
+
+/// Função totiente de Euler φ(n) pela fatoração: `n * Π (1 - 1/p)`.
+pub fn euler_totient(n: u64) -> u64 {
+    factorize(n).iter().fold(n, |phi, &(p, _)| phi / p * (p - 1))
+}
+
+/// Radical de `n`: o produto dos primos distintos que o dividem; 1 para 0 e 1.
+pub fn radical(n: u64) -> u64 {
+    factorize(n).iter().map(|&(p, _)| p).product()
+}
+
+/// Números até `limit` sem fator primo fora de `primes`, com o radical de cada um, em ordem crescente.
+/// Inclui o 1.
+pub fn smooth_numbers(primes: &[u64], limit: u64) -> Vec<(u64, u64)> {
+    let mut numbers: Vec<(u64, u64)> = vec![(1, 1)];
+    for &p in primes {
+        let mut more = Vec::new();
+        for &(n, rad) in &numbers {
+            let mut m = n;
+            while let Some(next) = m.checked_mul(p).filter(|&next| next <= limit) {
+                m = next;
+                more.push((m, rad * p));
+            }
+        }
+        numbers.extend(more);
+    }
+    numbers.sort_unstable();
+    numbers
+}
+
+/// Função de Möbius μ(n): 0 se `n` tem fator quadrado, senão `(-1)^(número de primos)`.
+pub fn mobius(n: u64) -> i8 {
+    let factors = factorize(n);
+    if factors.iter().any(|&(_, e)| e > 1) {
+        return 0;
+    }
+    if factors.len().is_multiple_of(2) { 1 } else { -1 }
+}
+
+/// Soma de Ramanujan `c_q(n) = μ(q/g) φ(q) / φ(q/g)`, com `g = mdc(n, q)`; `q` deve ser positivo.
+/// O valor absoluto não passa de φ(q).
+pub fn ramanujan_sum(q: u64, n: u64) -> i128 {
+    let m = q / n.gcd(&q);
+    mobius(m) as i128 * (euler_totient(q) / euler_totient(m)) as i128
+}
+
+/// Resultado do AKS e quantas multiplicações de polinômios ele fez.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AksResult {
+    pub is_prime: bool,
+    pub steps: u64,
+}
+
+/// Teste AKS (Agrawal–Kayal–Saxena): determinístico e polinomial, mas lento na prática.
+pub fn aks(n: u64) -> AksResult {
+    aks_cancellable(n, &CancelToken::new()).expect("token nunca cancelado")
+}
+
+// n = m^k para algum k >= 2
+fn is_perfect_power(n: u64) -> bool {
+    (2..=n.ilog2()).any(|k| {
+        let root = (n as f64).powf(1.0 / k as f64).round() as u64;
+        (root.saturating_sub(1)..=root + 1).any(|m| m >= 2 && m.checked_pow(k) == Some(n))
+    })
+}
+
+fn euler_phi(mut r: u64) -> u64 {
+    let mut phi = r;
+    let mut p = 2;
+    while p * p <= r {
+        if r.is_multiple_of(p) {
+            while r.is_multiple_of(p) { r /= p; }
+            phi -= phi / p;
+        }
+        p += 1;
+    }
+    if r > 1 { phi -= phi / r; }
+    phi
+}
+
+// Produto em Z_n[X]/(X^r - 1). Com n < 2^32 a soma de r produtos cabe em u128 sem reduzir a cada termo.
+fn poly_mul(p: &[u64], q: &[u64], n: u64) -> Vec<u64> {
+    let r = p.len();
+    let mut acc = vec![0u128; r];
+    for (i, &pi) in p.iter().enumerate().filter(|(_, &c)| c != 0) {
+        let (wrapped, direct) = acc.split_at_mut(i);
+        for (slot, &qj) in direct.iter_mut().chain(wrapped.iter_mut()).zip(q) {
+            *slot += pi as u128 * qj as u128;
+        }
+    }
+    acc.into_iter().map(|c| (c % n as u128) as u64).collect()
+}
+
+// (X + a)^n mod (X^r - 1, n) por quadrados sucessivos; multiplicar por X + a é deslocar e somar
+fn binomial_pow(a: u64, n: u64, r: usize, steps: &mut u64, token: &CancelToken) -> Vec<u64> {
+    let mut result = vec![0u64; r];
+    result[0] = a % n;
+    result[1 % r] = (result[1 % r] + 1) % n;
+    for bit in (0..n.ilog2()).rev() {
+        result = poly_mul(&result, &result, n);
+        *steps += 1;
+        token.advance(1);
+        if (n >> bit) & 1 == 1 {
+            let shifted: Vec<u64> = (0..r).map(|i| result[(i + r - 1) % r]).collect();
+            result = shifted.iter().zip(&result).map(|(&x, &c)| (x + mod_mul(a, c, n)) % n).collect();
+            *steps += 1;
+            token.advance(1);
+        }
+    }
+    result
+}
+
+/// AKS que devolve `None` se `token` for cancelado; o progresso conta as multiplicações.
+/// Pensado para `n` pequeno: exige `n < 2^32`.
+pub fn aks_cancellable(n: u64, token: &CancelToken) -> Option<AksResult> {
+    assert!(n < 1 << 32, "aks: n precisa ser menor que 2^32");
+    let mut steps = 0;
+    let result = |is_prime, steps| Some(AksResult { is_prime, steps });
+    if n < 2 || is_perfect_power(n) {
+        return result(false, steps);
+    }
+    // Menor r com ord_r(n) > log2(n)^2
+    let log2 = (n as f64).log2();
+    let max_order = (log2 * log2).floor() as u64;
+    let mut r = 2;
+    while n.gcd(&r) != 1 || (1..=max_order).any(|k| mod_pow(n, k, r) == 1) {
+        r += 1;
+    }
+    if (2..=r.min(n - 1)).any(|a| n.gcd(&a) > 1) {
+        return result(false, steps);
+    }
+    if n <= r {
+        return result(true, steps);
+    }
+    // (X + a)^n ≡ X^n + a para todo a <= √φ(r)·log2(n)
+    let limit = ((euler_phi(r) as f64).sqrt() * log2).floor() as u64;
+    let r = r as usize;
+    for a in 1..=limit {
+        if token.is_cancelled() { return None; }
+        let lhs = binomial_pow(a, n, r, &mut steps, token);
+        let mut rhs = vec![0u64; r];
+        rhs[0] = a % n;
+        let shift = (n % r as u64) as usize;
+        rhs[shift] = (rhs[shift] + 1) % n;
+        if lhs != rhs {
+            return result(false, steps);
+        }
+    }
+    result(true, steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wilson_agrees_on_the_first_100_primes() {
+        let primes: Vec<u64> = sieve(600).into_iter().take(100).collect();
+        assert_eq!((primes.len(), primes[99]), (100, 541));
+        for &p in &primes {
+            assert!(wilson_check(p) && miller_rabin_deterministic(p), "{} é primo", p);
+        }
+        // E também nos compostos entre eles, incluindo 1 e o caso especial 4
+        for n in (0..=541).filter(|n| !primes.contains(n)) {
+            assert!(!wilson_check(n) && !miller_rabin_deterministic(n), "{} não é primo", n);
+        }
+    }
+
+    #[test]
+    fn digit_range_at_the_u64_edges() {
+        assert_eq!(digit_range(0), Err(MathError::Underflow("digit_range")));
+        assert_eq!(digit_range(1), Ok((1, 9)));
+        assert_eq!(digit_range(19), Ok((10_u64.pow(18), 10_u64.pow(19) - 1)));
+        // 10^20 já não cabe em u64
+        assert_eq!(digit_range(20), Err(MathError::Overflow("digit_range")));
+        assert_eq!(digit_range(u32::MAX), Err(MathError::Overflow("digit_range")));
+    }
+
+    #[test]
+    fn primality_at_the_u64_edges() {
+        for n in 0..=3 {
+            assert_eq!(miller_rabin_deterministic(n), n >= 2, "{}", n);
+        }
+        // Maior primo de u64, e u64::MAX = 3 * 5 * 17 * 257 * 641 * 65537 * 6700417
+        assert!(miller_rabin_deterministic(u64::MAX - 58));
+        assert!(!miller_rabin_deterministic(u64::MAX));
+        let big = |v: u64| BigUint::from(v);
+        let (base, exp, modu) = (u64::MAX, u64::MAX, u64::MAX - 1);
+        assert_eq!(big(mod_pow(base, exp, modu)), big(base).modpow(&big(exp), &big(modu)));
+        assert_eq!(mod_pow(u64::MAX, 2, 1), 0);
+    }
+
+    #[cfg(feature = "mining")]
+    #[test]
+    fn probabilistic_test_handles_tiny_and_huge_inputs() {
+        assert_eq!((0..=4).map(|n| miller_rabin(n, 8)).collect::<Vec<_>>(), [false, false, true, true, false]);
+        assert!(miller_rabin(u64::MAX - 58, 8));
+        assert!(!miller_rabin(u64::MAX, 8));
+    }
+
+    #[test]
+    fn next_prime_across_known_gaps() {
+        // Lacunas máximas conhecidas: 1132 depois de 1693182318746371 e 1550 depois de 18361375334787046697
+        assert_eq!(next_prime(1_693_182_318_746_371, 100_000), Some(1_693_182_318_746_371 + 1132));
+        assert_eq!(next_prime(18_361_375_334_787_046_697, 100_000), Some(18_361_375_334_787_046_697 + 1550));
+        // Span menor que a lacuna: nada achado
+        assert_eq!(next_prime(1_693_182_318_746_371, 1131), None);
+
+        let primes = sieve(20_100);
+        for after in 0..20_000 {
+            let expected = primes.iter().copied().find(|&p| p > after);
+            assert_eq!(next_prime(after, 1_000), expected, "depois de {}", after);
+        }
+        // Topo de u64: depois de u64::MAX - 82 vem o maior primo, u64::MAX - 58, e mais nada
+        assert_eq!(next_prime(u64::MAX - 82, 100), Some(u64::MAX - 58));
+        assert_eq!(next_prime(u64::MAX - 58, u64::MAX), None);
+    }
+
+    #[test]
+    fn trial_factor_stops_at_the_budget() {
+        let full = trial_factor(2u64.pow(3) * 3 * 7 * 7 * 101, 1_000);
+        assert_eq!(full.factors, [(2, 3), (3, 1), (7, 2), (101, 1)]);
+        assert!(full.complete && full.cofactor == 1);
+
+        // Semiprimo com fatores de 7 dígitos: mil divisões não chegam lá
+        let n = 1_000_003 * 1_000_033;
+        let partial = trial_factor(n, 1_000);
+        assert_eq!((partial.factors.len(), partial.cofactor, partial.divisions), (0, n, 1_000));
+        assert!(!partial.complete);
+        let done = trial_factor(n, 1_000_000);
+        assert_eq!(done.factors, [(1_000_003, 1), (1_000_033, 1)]);
+        assert!(trial_factor(1, 0).complete && trial_factor(0, 0).factors.is_empty());
+    }
+
+    // splitmix64: pares reprodutíveis sem depender da feature `mining`
+    fn next(state: &mut u64) -> u64 {
+        *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    #[test]
+    fn lehmer_gcd_matches_euclid() {
+        let big = u64::MAX as u128;
+        let mut pairs = vec![(0, 0), (12_345, 0), (0, 12_345), (u128::MAX, 0), (0, u128::MAX)];
+        pairs.extend([(u128::MAX, u128::MAX), (u128::MAX, u128::MAX - 1), (u128::MAX, 1), (u128::MAX, big)]);
+        // `b` logo acima e logo abaixo de u64::MAX, onde `lehmer_gcd` troca de laço
+        for b in [big - 1, big, big + 1, big + 2, big * 2] {
+            pairs.extend([(u128::MAX, b), (b, u128::MAX - 12_345), (big * big, b)]);
+        }
+        // Fator comum grande: o MDC tem que sair inteiro
+        let factor = 18_446_744_073_709_551_557u128;
+        pairs.extend([(factor * 3, factor * 5), (factor * (big - 1), factor * 7), (factor << 60, factor << 61)]);
+        let mut state = 42;
+        for _ in 0..100_000 {
+            let wide = |state: &mut u64| (next(state) as u128) << 64 | next(state) as u128;
+            let (a, b) = (wide(&mut state), wide(&mut state));
+            // Deslocamentos variados cobrem as duas partes ao misturar tamanhos
+            let shift = (next(&mut state) % 128) as u32;
+            let g = (next(&mut state) as u128) >> (next(&mut state) % 64);
+            pairs.extend([(a, b), (a, b >> shift), (a >> shift, b), ((a >> 64) * g, (b >> 66) * g)]);
+        }
+        for (a, b) in pairs {
+            assert_eq!(lehmer_gcd(a, b), euclid_gcd(a, b), "mdc({}, {})", a, b);
+        }
+        assert_eq!(lehmer_gcd(factor * 3, factor * 5), factor);
+    }
+}
@@ -0,0 +1,196 @@
+// src/merkle.rs
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Um passo da prova de inclusão: o hash irmão e de que lado ele fica.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub sibling: String,
+    pub left: bool,
+}
+
+fn hash_pair(left: &str, right: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn next_level(level: &[String]) -> Vec<String> {
+    // Nível ímpar: o último nó é pareado consigo mesmo
+    level
+        .chunks(2)
+        .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+        .collect()
+}
+
+/// Raiz de Merkle sobre hashes hex; vazio para nenhuma folha.
+pub fn compute_merkle_root(leaves: &[String]) -> String {
+    if leaves.is_empty() {
+        return String::new();
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.remove(0)
+}
+
+/// Prova de inclusão da folha `index`, da base até a raiz.
+pub fn merkle_proof(leaves: &[String], mut index: usize) -> Option<Vec<ProofStep>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        let sibling = if index.is_multiple_of(2) {
+            ProofStep { sibling: level.get(index + 1).unwrap_or(&level[index]).clone(), left: false }
+        } else {
+            ProofStep { sibling: level[index - 1].clone(), left: true }
+        };
+        proof.push(sibling);
+        level = next_level(&level);
+        index /= 2;
+    }
+    Some(proof)
+}
+
+/// Refaz o caminho de `leaf` até a raiz com os irmãos de `proof` e compara com `root`.
+pub fn verify_merkle_proof(root: &str, leaf: &str, proof: &[ProofStep]) -> bool {
+    let computed = proof.iter().fold(leaf.to_string(), |acc, step| {
+        if step.left { hash_pair(&step.sibling, &acc) } else { hash_pair(&acc, &step.sibling) }
+    });
+    computed == root
+}
+
+/// Árvore de Merkle completa num vetor plano, no layout de segment tree com base 0:
+/// a raiz fica em 0 e os filhos de `i` em `2i + 1` e `2i + 2`. As folhas são completadas
+/// até a próxima potência de dois repetindo a última, o que dá a mesma raiz de `compute_merkle_root`.
+#[derive(Debug, Clone, Serialize)]
+pub struct MerkleTree {
+    leaves: usize,
+    nodes: Vec<String>,
+}
+
+impl MerkleTree {
+    pub fn build(leaves: &[String]) -> Self {
+        let Some(last) = leaves.last() else {
+            return MerkleTree { leaves: 0, nodes: Vec::new() };
+        };
+        let width = leaves.len().next_power_of_two();
+        let mut nodes = vec![String::new(); width - 1];
+        nodes.extend(leaves.iter().cloned());
+        nodes.resize(2 * width - 1, last.clone());
+        for i in (0..width - 1).rev() {
+            nodes[i] = hash_pair(&nodes[2 * i + 1], &nodes[2 * i + 2]);
+        }
+        MerkleTree { leaves: leaves.len(), nodes }
+    }
+
+    pub fn root(&self) -> &str {
+        self.nodes.first().map_or("", String::as_str)
+    }
+
+    pub fn nodes(&self) -> &[String] {
+        &self.nodes
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaves
+    }
+
+    /// Folhas depois do preenchimento (potência de dois).
+    pub fn width(&self) -> usize {
+        self.nodes.len().div_ceil(2)
+    }
+
+    /// Irmãos do caminho da folha `index` até a raiz; verificável com `verify_merkle_proof`.
+    pub fn proof(&self, index: usize) -> Option<Vec<ProofStep>> {
+        if index >= self.leaves {
+            return None;
+        }
+        let mut node = self.width() - 1 + index;
+        let mut proof = Vec::new();
+        while node > 0 {
+            proof.push(if !node.is_multiple_of(2) {
+                ProofStep { sibling: self.nodes[node + 1].clone(), left: false }
+            } else {
+                ProofStep { sibling: self.nodes[node - 1].clone(), left: true }
+            });
+            node = (node - 1) / 2;
+        }
+        Some(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signature::SigningKey;
+    use crate::transaction::{tx_proof, tx_root, Transaction, EMPTY_TX_ROOT};
+
+    fn transactions(n: u64) -> Vec<Transaction> {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        (0..n).map(|nonce| Transaction::sign(&key, "bob", 10 + nonce, nonce)).collect()
+    }
+
+    #[test]
+    fn proofs_verify_at_every_position() {
+        let txs = transactions(5);
+        let root = tx_root(&txs);
+        let ids: Vec<String> = txs.iter().map(Transaction::id).collect();
+        let tree = MerkleTree::build(&ids);
+        assert_eq!(tree.root(), root);
+        // Primeira, do meio e última; a última (ímpar) é pareada consigo mesma
+        for position in [0, 2, 4] {
+            let (found, proof) = tx_proof(&txs, &ids[position]).unwrap();
+            assert_eq!(found, position);
+            assert_eq!(proof.len(), 3);
+            assert!(verify_merkle_proof(&root, &ids[position], &proof), "posição {}", position);
+            assert_eq!(tree.proof(position).unwrap(), proof);
+        }
+        assert!(tx_proof(&txs, "unknown").is_none());
+        assert!(merkle_proof(&ids, 5).is_none() && tree.proof(5).is_none());
+    }
+
+    // Uma transação só: a raiz é o próprio id e a prova é vazia
+    #[test]
+    fn sole_transaction_proof_is_empty() {
+        let txs = transactions(1);
+        let id = txs[0].id();
+        assert_eq!(tx_root(&txs), id);
+        let (position, proof) = tx_proof(&txs, &id).unwrap();
+        assert_eq!((position, proof.len()), (0, 0));
+        assert!(verify_merkle_proof(&id, &id, &proof));
+        assert!(!verify_merkle_proof(&id, &transactions(2)[1].id(), &proof));
+    }
+
+    #[test]
+    fn tampered_proofs_and_wrong_ids_fail() {
+        let txs = transactions(4);
+        let root = tx_root(&txs);
+        let (id, other) = (txs[1].id(), txs[2].id());
+        let (_, proof) = tx_proof(&txs, &id).unwrap();
+        assert!(verify_merkle_proof(&root, &id, &proof));
+
+        assert!(!verify_merkle_proof(&root, &other, &proof));
+        assert!(!verify_merkle_proof(&tx_root(&txs[..3]), &id, &proof));
+        let mut sibling = proof.clone();
+        let flipped = if sibling[1].sibling.starts_with('0') { "1" } else { "0" };
+        sibling[1].sibling.replace_range(0..1, flipped);
+        assert!(!verify_merkle_proof(&root, &id, &sibling));
+        let mut side = proof.clone();
+        side[0].left = !side[0].left;
+        assert!(!verify_merkle_proof(&root, &id, &side));
+        assert!(!verify_merkle_proof(&root, &id, &proof[..1]));
+    }
+
+    #[test]
+    fn empty_block_has_a_defined_root() {
+        assert_eq!(tx_root(&[]), EMPTY_TX_ROOT);
+        assert_eq!(EMPTY_TX_ROOT, format!("{:x}", Sha256::digest(b"")));
+        assert_eq!(compute_merkle_root(&[]), "");
+        assert_eq!(MerkleTree::build(&[]).root(), "");
+    }
+}
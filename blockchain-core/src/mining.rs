@@ -0,0 +1,743 @@
+// src/mining.rs
+use log::{error, info};
+use num::{BigUint, Integer, One};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+#[cfg(feature = "mining")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "mining")]
+use std::time::Instant;
+
+use crate::block::witness;
+#[cfg(feature = "mining")]
+use crate::block::{meets_hash_target, Block, BlockBuilder};
+use crate::math::{digit_range, prime_heuristic, MathError};
+#[cfg(feature = "mining")]
+use crate::pool::CandidatePool;
+#[cfg(feature = "mining")]
+use crate::throttle::Throttle;
+use crate::width::{PrimeValue, PrimeWidth};
+
+pub const TARGET_TIME: f64 = 10.0;
+// Teto de min_digits na largura u64 (ver `PrimeWidth::max_min_digits`), que vale para toda cadeia
+pub const MAX_MIN_DIGITS: u32 = 18;
+
+// Primos pequenos usados na divisão por tentativa antes do Miller-Rabin
+const TRIAL_PRIMES: [u64; 14] = [3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Difficulty {
+    pub n_limit: u64,
+    pub min_digits: u32,
+    pub min_prob: u64, // em décimos de milésimo: 100 = 0.01
+    // Incrementado a cada ajuste; identifica candidatos pré-computados obsoletos
+    pub generation: u64,
+    // Divisor do alvo de hash (regras v4); 0 mantém o ajuste por dígitos
+    pub hash_scale: u64,
+    // Largura dos primos da cadeia, copiada das regras: limita min_digits, n_limit e os candidatos
+    pub width: PrimeWidth,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty { n_limit: 1000, min_digits: 7, min_prob: 100, generation: 0, hash_scale: 0, width: PrimeWidth::U64 }
+    }
+}
+
+impl Difficulty {
+    pub fn min_prob_f64(&self) -> f64 {
+        self.min_prob as f64 / 10000.0
+    }
+
+    /// Candidatos esperados por bloco: o inverso da densidade de primos perto da testemunha
+    /// (`ln n`, com `n ~ 10^min_digits * n_limit`) vezes o `hash_scale`. Só uma estimativa, sem os filtros.
+    pub fn expected_candidates(&self) -> f64 {
+        let ln_witness = self.min_digits as f64 * std::f64::consts::LN_10 + (self.n_limit.max(1) as f64).ln();
+        ln_witness * self.hash_scale.max(1) as f64
+    }
+
+    /// Maior hash aceito pelo alvo `2^256 / hash_scale`, em hex de 64 caracteres; `None` fora das regras v4.
+    pub fn hash_target(&self) -> Option<String> {
+        let max_hash = |scale: u64| ((BigUint::one() << 256u32) - 1u32) / scale;
+        (self.hash_scale != 0).then(|| format!("{:0>64}", max_hash(self.hash_scale).to_str_radix(16)))
+    }
+
+    /// Cópia com `min_digits` em `1..=width.max_min_digits()` e `n_limit` de pelo menos 1, para a
+    /// mineração nunca girar sem fim nem sortear de um intervalo vazio.
+    pub fn clamped(&self) -> Difficulty {
+        let mut difficulty = self.clone();
+        let max_digits = self.width.max_min_digits();
+        if !(1..=max_digits).contains(&difficulty.min_digits) {
+            difficulty.min_digits = difficulty.min_digits.clamp(1, max_digits);
+            error!("min_digits {} fora do intervalo; minerando com {}", self.min_digits, difficulty.min_digits);
+        }
+        difficulty.n_limit = difficulty.n_limit.max(1);
+        difficulty
+    }
+
+    /// O que torna a dificuldade inviável de minerar, em inglês para as respostas HTTP; vazio se nada.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let max_digits = self.width.max_min_digits();
+        if !(1..=max_digits).contains(&self.min_digits) {
+            problems.push(format!("min_digits must be between 1 and {} for {} primes", max_digits, self.width));
+        } else {
+            let max_n_limit = self.width.witness_n_limit(self.min_digits);
+            if !(1..=max_n_limit).contains(&self.n_limit) {
+                problems.push(format!(
+                    "n_limit must be between 1 and {} with min_digits {} for the witness to fit in {}",
+                    max_n_limit, self.min_digits, self.width
+                ));
+            }
+            // A heurística recusa n com 1/ln n < min_prob; nem a menor testemunha pode ser recusada
+            let smallest = 10f64.powi(self.min_digits as i32 - 1);
+            if self.min_digits > 1 && 1.0 / smallest.ln() < self.min_prob_f64() {
+                let max_prob = (10_000.0 / smallest.ln()).floor() as u64;
+                problems.push(format!(
+                    "min_prob must be at most {} with min_digits {}, or every witness is rejected",
+                    max_prob, self.min_digits
+                ));
+            }
+        }
+        if self.min_prob > 10_000 {
+            problems.push("min_prob must be at most 10000 (probability 1)".to_string());
+        }
+        if self.hash_scale > MAX_HASH_SCALE {
+            problems.push(format!("hash_scale must be at most {}", MAX_HASH_SCALE));
+        }
+        problems
+    }
+
+    /// Ajusta com base em um único tempo de bloco.
+    pub fn adjust(&mut self, duration: f64) -> DifficultyDecision {
+        let decision = decide_difficulty(self, &[duration]);
+        self.apply(&decision);
+        decision
+    }
+
+    pub fn apply(&mut self, decision: &DifficultyDecision) {
+        *self = decision.after.clone();
+        match decision.action {
+            Adjustment::Raise if self.hash_scale != 0 => {
+                info!("Dificuldade aumentada! hash_scale: {}", self.hash_scale)
+            }
+            Adjustment::Lower if self.hash_scale != 0 => {
+                info!("Dificuldade reduzida! hash_scale: {}", self.hash_scale)
+            }
+            Adjustment::Raise => info!("Dificuldade aumentada! n_limit: {}", self.n_limit),
+            Adjustment::Lower => info!("Dificuldade reduzida! n_limit: {}", self.n_limit),
+            Adjustment::Hold => {}
+        }
+    }
+}
+
+/// Maior `min_prob` que a heurística (`1/ln n >= min_prob`) deixa passar para a testemunha típica, com
+/// `a` e `c` no meio da faixa de dígitos e `b` e `d` em `n_limit / 2`; acima disso quase todo candidato
+/// é recusado e a mineração não termina.
+pub fn typical_min_prob(min_digits: u32, n_limit: u64) -> u64 {
+    let typical = 5.5 * 10f64.powi(min_digits as i32 - 1) * n_limit.max(1) as f64;
+    (10_000.0 / typical.ln()).floor() as u64
+}
+
+// Faixa em torno de TARGET_TIME em que a dificuldade é mantida
+pub const TOLERANCE: f64 = 0.4;
+// A média da janela é limitada a [alvo/4, alvo*4] antes da decisão
+pub const MAX_TIMESPAN_FACTOR: f64 = 4.0;
+// Teto do hash_scale: 2^48 primos por bloco, em média, já está muito além do minerável
+pub const MAX_HASH_SCALE: u64 = 1 << 48;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Adjustment {
+    Raise,
+    Lower,
+    Hold,
+}
+
+impl Adjustment {
+    /// Sentido da mudança pelo trabalho esperado antes e depois.
+    pub fn between(before: &Difficulty, after: &Difficulty) -> Self {
+        match after.expected_candidates().partial_cmp(&before.expected_candidates()) {
+            Some(std::cmp::Ordering::Greater) => Adjustment::Raise,
+            Some(std::cmp::Ordering::Less) => Adjustment::Lower,
+            _ => Adjustment::Hold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DifficultyDelta {
+    pub n_limit: i64,
+    pub min_digits: i64,
+    pub min_prob: i64,
+    pub hash_scale: i64,
+}
+
+impl DifficultyDelta {
+    pub fn between(before: &Difficulty, after: &Difficulty) -> Self {
+        DifficultyDelta {
+            n_limit: after.n_limit as i64 - before.n_limit as i64,
+            min_digits: after.min_digits as i64 - before.min_digits as i64,
+            min_prob: after.min_prob as i64 - before.min_prob as i64,
+            hash_scale: after.hash_scale as i64 - before.hash_scale as i64,
+        }
+    }
+}
+
+/// Entradas, decisão e resultado de um ajuste de dificuldade.
+#[derive(Debug, Clone, Serialize)]
+pub struct DifficultyDecision {
+    // Algoritmo que tomou a decisão
+    pub algorithm: &'static str,
+    // retarget nos reajustes de cada bloco, bootstrap na calibração de uma cadeia nova,
+    // admin nas alterações manuais
+    pub reason: &'static str,
+    pub window: Vec<f64>,
+    pub average: f64,
+    // Média depois do limite de MAX_TIMESPAN_FACTOR
+    pub effective_average: f64,
+    pub target: f64,
+    pub tolerance: f64,
+    pub action: Adjustment,
+    pub before: Difficulty,
+    pub after: Difficulty,
+    pub deltas: DifficultyDelta,
+    pub clamps: Vec<&'static str>,
+}
+
+/// Alteração manual de campos da dificuldade: ou vale inteira, ou nada muda.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DifficultyOverride {
+    pub n_limit: Option<u64>,
+    pub min_digits: Option<u32>,
+    // Em décimos de milésimo, como em `Difficulty`
+    pub min_prob: Option<u64>,
+    pub hash_scale: Option<u64>,
+    // Versão (o `generation`) lida pelo cliente; se a atual for outra, a alteração é recusada
+    pub if_version: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverrideError {
+    VersionConflict { expected: u64, current: u64 },
+    Infeasible(Vec<String>),
+}
+
+impl fmt::Display for OverrideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverrideError::VersionConflict { expected, current } => {
+                write!(f, "difficulty version is {}, not {}", current, expected)
+            }
+            OverrideError::Infeasible(problems) => write!(f, "infeasible difficulty: {}", problems.join("; ")),
+        }
+    }
+}
+
+impl std::error::Error for OverrideError {}
+
+impl DifficultyOverride {
+    /// Dificuldade resultante sobre `current`, validada como um todo: cada problema é listado e nenhum
+    /// campo é aplicado se houver algum. `hash_target` diz se a próxima altura segue as regras v4.
+    pub fn resolve(&self, current: &Difficulty, hash_target: bool) -> Result<Difficulty, OverrideError> {
+        let after = Difficulty {
+            n_limit: self.n_limit.unwrap_or(current.n_limit),
+            min_digits: self.min_digits.unwrap_or(current.min_digits),
+            min_prob: self.min_prob.unwrap_or(current.min_prob),
+            // Como no reajuste, a v4 parte de hash_scale 1
+            hash_scale: self
+                .hash_scale
+                .unwrap_or(if hash_target { current.hash_scale.max(1) } else { current.hash_scale }),
+            generation: current.generation,
+            width: current.width,
+        };
+        let mut problems = after.problems();
+        match (hash_target, self.hash_scale) {
+            (true, _) if after.hash_scale == 0 => {
+                problems.push(format!("hash_scale must be between 1 and {} under rules v4", MAX_HASH_SCALE));
+            }
+            (false, Some(scale)) if scale != 0 => {
+                problems.push("hash_scale only applies from rules v4 and must be 0".to_string());
+            }
+            _ => {}
+        }
+        if problems.is_empty() { Ok(after) } else { Err(OverrideError::Infeasible(problems)) }
+    }
+}
+
+// Sem isto o reajuste sobe dígitos e min_prob até nenhuma testemunha caber na largura ou passar na heurística
+pub(crate) fn clamp_to_witness(difficulty: &mut Difficulty, clamps: &mut Vec<&'static str>) {
+    let ceiling = difficulty.width.witness_n_limit(difficulty.min_digits);
+    if difficulty.n_limit > ceiling {
+        clamps.push("witness_overflow_bound");
+        difficulty.n_limit = ceiling;
+    }
+    let max_prob = typical_min_prob(difficulty.min_digits, difficulty.n_limit);
+    if difficulty.min_prob > max_prob {
+        clamps.push("min_prob_feasible");
+        difficulty.min_prob = max_prob;
+    }
+}
+
+/// Decide o próximo ajuste a partir dos tempos de bloco da janela. Não altera nada.
+pub fn decide_difficulty(difficulty: &Difficulty, window: &[f64]) -> DifficultyDecision {
+    let target = TARGET_TIME;
+    let mut clamps = Vec::new();
+    let average = if window.is_empty() { target } else { window.iter().sum::<f64>() / window.len() as f64 };
+    let effective_average = average.clamp(target / MAX_TIMESPAN_FACTOR, target * MAX_TIMESPAN_FACTOR);
+    if effective_average != average {
+        clamps.push("average_4x_bound");
+    }
+
+    let mut after = difficulty.clone();
+    let too_fast = effective_average < target * (1.0 - TOLERANCE);
+    let too_slow = effective_average > target * (1.0 + TOLERANCE);
+    let action = if difficulty.hash_scale != 0 {
+        // Alvo de hash: o trabalho esperado é proporcional ao hash_scale, então ele escala pela razão
+        // entre o tempo alvo e o observado (já limitada a 4x) e os dígitos ficam parados
+        if too_fast || too_slow {
+            let scaled = (difficulty.hash_scale as f64 * target / effective_average).round();
+            if scaled < 1.0 {
+                clamps.push("hash_scale_min");
+            }
+            if scaled > MAX_HASH_SCALE as f64 {
+                clamps.push("hash_scale_max");
+            }
+            after.hash_scale = scaled.clamp(1.0, MAX_HASH_SCALE as f64) as u64;
+        }
+        match after.hash_scale.cmp(&difficulty.hash_scale) {
+            std::cmp::Ordering::Greater => Adjustment::Raise,
+            std::cmp::Ordering::Less => Adjustment::Lower,
+            std::cmp::Ordering::Equal => Adjustment::Hold,
+        }
+    } else if too_fast {
+        after.n_limit = (difficulty.n_limit as f64 * 1.5) as u64;
+        let max_digits = difficulty.width.max_min_digits();
+        if difficulty.min_digits >= max_digits {
+            clamps.push("min_digits_max");
+            error!("min_digits chegou ao máximo de {}; só n_limit e min_prob continuam subindo", max_digits);
+        }
+        after.min_digits = (difficulty.min_digits + 1).min(max_digits);
+        let min_prob = difficulty.min_prob as f64 * 1.2;
+        if min_prob > 1000.0 {
+            clamps.push("min_prob_max");
+        }
+        after.min_prob = min_prob.min(1000.0) as u64;
+        clamp_to_witness(&mut after, &mut clamps);
+        Adjustment::Raise
+    } else if too_slow {
+        let n_limit = difficulty.n_limit as f64 * 0.7;
+        if n_limit < 100.0 {
+            clamps.push("n_limit_min");
+        }
+        after.n_limit = n_limit.max(100.0) as u64;
+        let min_prob = difficulty.min_prob as f64 * 0.8;
+        if min_prob < 50.0 {
+            clamps.push("min_prob_min");
+        }
+        after.min_prob = min_prob.max(50.0) as u64;
+        clamp_to_witness(&mut after, &mut clamps);
+        Adjustment::Lower
+    } else {
+        Adjustment::Hold
+    };
+    if action != Adjustment::Hold {
+        after.generation += 1;
+    }
+
+    DifficultyDecision {
+        algorithm: "window",
+        reason: "retarget",
+        window: window.to_vec(),
+        average,
+        effective_average,
+        target,
+        tolerance: TOLERANCE,
+        action,
+        deltas: DifficultyDelta::between(difficulty, &after),
+        before: difficulty.clone(),
+        after,
+        clamps,
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MiningStats {
+    pub candidates: u64,
+    pub gcd_rejected: u64,
+    pub parity_rejected: u64,
+    pub trial_division_rejected: u64,
+    pub heuristic_rejected: u64,
+    pub miller_rabin_rejected: u64,
+    // Primos cujo bloco não ficou abaixo do alvo de hash (regras v4)
+    pub hash_target_rejected: u64,
+    pub pool_hits: u64,
+    pub probability: f64,
+    // Gerador de tuplas usado e seu custo, para a vazão em tuplas por segundo
+    pub generator: &'static str,
+    pub generated: u64,
+    pub generator_secs: f64,
+}
+
+impl MiningStats {
+    pub fn generator_throughput(&self) -> f64 {
+        if self.generator_secs == 0.0 { 0.0 } else { self.generated as f64 / self.generator_secs }
+    }
+
+    pub fn pool_hit_rate(&self) -> f64 {
+        if self.candidates == 0 { 0.0 } else { self.pool_hits as f64 / self.candidates as f64 }
+    }
+}
+
+/// Motivo pelo qual uma tupla foi descartada antes do Miller-Rabin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    Gcd,
+    Overflow,
+    Parity,
+    TrialDivision,
+    Heuristic,
+}
+
+/// Tupla com `a` e `c` de `min_digits` dígitos e `b`, `d` em `1..=n_limit`. Erro quando a dificuldade
+/// não permite nenhuma (0 ou mais de 19 dígitos, `n_limit` zero); `clamped` evita os dois casos.
+pub fn random_tuple<R: Rng>(rng: &mut R, difficulty: &Difficulty) -> Result<(u64, u64, u64, u64), MathError> {
+    let (low, high) = digit_range(difficulty.min_digits)?;
+    if difficulty.n_limit == 0 {
+        return Err(MathError::EmptyRange("n_limit"));
+    }
+    let a = rng.gen_range(low..=high);
+    let b = rng.gen_range(1..=difficulty.n_limit);
+    let c = rng.gen_range(low..=high);
+    let d = rng.gen_range(1..=difficulty.n_limit);
+    Ok((a, b, c, d))
+}
+
+// Profundidade máxima da descida na árvore de Stern–Brocot
+const FAREY_MAX_DEPTH: u32 = 32;
+
+/// Vizinhos de Farey `a/b < c/d` (`b*c - a*d = 1`) para as regras v3. Desce a árvore de
+/// Stern–Brocot a partir de `0/1 < 1/1` até uma profundidade aleatória, com denominadores até
+/// `n_limit`, e soma um inteiro `k` às duas frações (o que preserva o determinante) para que
+/// `a` e `c` tenham `min_digits` dígitos. `None` quando não cabe nenhum `k`.
+pub fn farey_tuple<R: Rng>(rng: &mut R, difficulty: &Difficulty) -> Option<(u64, u64, u64, u64)> {
+    let (mut p, mut q, mut r, mut s) = (0u64, 1u64, 1u64, 1u64);
+    for _ in 0..rng.gen_range(1..=FAREY_MAX_DEPTH) {
+        if q + s > difficulty.n_limit {
+            break;
+        }
+        // Mediante (p + r)/(q + s): vira a nova fronteira esquerda ou direita
+        if rng.gen_bool(0.5) {
+            (p, q) = (p + r, q + s);
+        } else {
+            (r, s) = (p + r, q + s);
+        }
+    }
+    let (low, high) = digit_range(difficulty.min_digits).ok()?;
+    let k_min = low.saturating_sub(p).div_ceil(q).max(low.saturating_sub(r).div_ceil(s));
+    let k_max = high.checked_sub(p)? / q;
+    let k_max = k_max.min(high.checked_sub(r)? / s);
+    if k_min > k_max {
+        return None;
+    }
+    let k = rng.gen_range(k_min..=k_max);
+    Some((p + k * q, q, r + k * s, s))
+}
+
+/// `|a*d - b*c| == 1`: `a/b` e `c/d` são vizinhas de Farey.
+pub fn is_farey_pair(a: u64, b: u64, c: u64, d: u64) -> bool {
+    (a as i128 * d as i128 - b as i128 * c as i128).abs() == 1
+}
+
+/// Aplica todos os filtros baratos; devolve `n = a*d + b*c` se a tupla sobreviver e `n` couber em `width`.
+pub fn screen_candidate(
+    a: u64,
+    b: u64,
+    c: u64,
+    d: u64,
+    min_prob: f64,
+    width: PrimeWidth,
+) -> Result<PrimeValue, Rejection> {
+    if a.gcd(&b) != 1 || c.gcd(&d) != 1 {
+        return Err(Rejection::Gcd);
+    }
+    let n = witness(a, b, c, d).filter(|n| n.width() <= width).ok_or(Rejection::Overflow)?;
+    let value = n.get();
+    if value.is_multiple_of(2) {
+        return Err(Rejection::Parity);
+    }
+    if TRIAL_PRIMES.iter().any(|&p| value != p as u128 && value.is_multiple_of(p as u128)) {
+        return Err(Rejection::TrialDivision);
+    }
+    if !prime_heuristic(value, min_prob) {
+        return Err(Rejection::Heuristic);
+    }
+    Ok(n)
+}
+
+/// Procura um sucessor de `prev` até encontrar um primo ou até `stop` ser sinalizado.
+#[cfg(feature = "mining")]
+pub fn mine_worker(prev: &Block, difficulty: &Difficulty, stop: &AtomicBool) -> Option<(Block, MiningStats)> {
+    mine_template(&BlockBuilder::on(prev), difficulty, stop, None, &mut Throttle::unlimited())
+}
+
+// Candidatos por fatia entre chamadas a `Throttle::pace`
+#[cfg(feature = "mining")]
+const THROTTLE_BATCH: u64 = 256;
+
+/// Destino de cada candidato testado; `MiningStats::count` soma nos contadores.
+#[cfg(feature = "mining")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    NoTuple,
+    Rejected(Rejection),
+    NotPrime,
+    AboveTarget,
+    Found,
+}
+
+#[cfg(feature = "mining")]
+impl MiningStats {
+    pub(crate) fn count(&mut self, outcome: Outcome) {
+        self.candidates += 1;
+        match outcome {
+            Outcome::NoTuple | Outcome::Found | Outcome::Rejected(Rejection::Overflow) => {}
+            Outcome::Rejected(Rejection::Gcd) => self.gcd_rejected += 1,
+            Outcome::Rejected(Rejection::Parity) => self.parity_rejected += 1,
+            Outcome::Rejected(Rejection::TrialDivision) => self.trial_division_rejected += 1,
+            Outcome::Rejected(Rejection::Heuristic) => self.heuristic_rejected += 1,
+            Outcome::NotPrime => self.miller_rabin_rejected += 1,
+            Outcome::AboveTarget => self.hash_target_rejected += 1,
+        }
+    }
+}
+
+/// Minera a partir de um modelo de bloco (índice, prev_hash, versão das regras já definidos),
+/// consumindo primeiro os candidatos já filtrados de `pool`. `throttle` limita o uso de CPU.
+/// A partir das regras v3 as tuplas vêm de `farey_tuple` e o pool, que guarda tuplas aleatórias, é ignorado.
+/// Com `hash_scale` no modelo (v4), o primo só vale se o hash do bloco ficar abaixo do alvo.
+#[cfg(feature = "mining")]
+pub fn mine_template(
+    template: &BlockBuilder,
+    difficulty: &Difficulty,
+    stop: &AtomicBool,
+    pool: Option<&CandidatePool>,
+    throttle: &mut Throttle,
+) -> Option<(Block, MiningStats)> {
+    let mut rng = rand::thread_rng();
+    let proceed = |_: &MiningStats| !stop.load(Ordering::Relaxed);
+    let (block, stats) = mine_with(template, difficulty, pool, throttle, &mut rng, None, proceed);
+    block.map(|block| (block, stats))
+}
+
+/// Laço de `mine_template` com o gerador `rng`. `proceed` é consultado antes de cada candidato e
+/// devolve `false` para parar. Numa corrida reprodutível `outcomes` recebe o destino de cada
+/// candidato em ordem e o bloco fica com o timestamp do modelo em vez do relógio.
+#[cfg(feature = "mining")]
+pub(crate) fn mine_with<R: Rng>(
+    template: &BlockBuilder,
+    difficulty: &Difficulty,
+    pool: Option<&CandidatePool>,
+    throttle: &mut Throttle,
+    rng: &mut R,
+    mut outcomes: Option<&mut Vec<Outcome>>,
+    mut proceed: impl FnMut(&MiningStats) -> bool,
+) -> (Option<Block>, MiningStats) {
+    let difficulty = &difficulty.clamped();
+    let farey = template.version() >= 3;
+    let pool = pool.filter(|_| !farey);
+    let mut stats = MiningStats {
+        generator: if farey { "stern_brocot" } else { "random" },
+        ..MiningStats::default()
+    };
+    let min_prob = difficulty.min_prob_f64();
+
+    while proceed(&stats) {
+        if (stats.candidates + 1).is_multiple_of(THROTTLE_BATCH) {
+            throttle.pace();
+        }
+        let candidate = match pool.and_then(|p| p.take(difficulty.generation)) {
+            Some(candidate) => {
+                stats.pool_hits += 1;
+                Ok((candidate.a, candidate.b, candidate.c, candidate.d, candidate.n))
+            }
+            None => {
+                let started = Instant::now();
+                let tuple =
+                    if farey { farey_tuple(rng, difficulty) } else { random_tuple(rng, difficulty).ok() };
+                stats.generator_secs += started.elapsed().as_secs_f64();
+                stats.generated += 1;
+                match tuple {
+                    Some((a, b, c, d)) => screen_candidate(a, b, c, d, min_prob, difficulty.width)
+                        .map(|n| (a, b, c, d, n))
+                        .map_err(Outcome::Rejected),
+                    None => Err(Outcome::NoTuple),
+                }
+            }
+        };
+        let mut found = None;
+        let outcome = match candidate {
+            Err(outcome) => outcome,
+            Ok((_, _, _, _, n)) if !n.is_prime() => Outcome::NotPrime,
+            Ok((a, b, c, d, n)) => {
+                let builder = template.clone().witness(a, b, c, d).prime(n);
+                let block = if outcomes.is_some() { builder.build() } else { builder.stamp_now().build() };
+                if block.hash_scale != 0 && !meets_hash_target(&block.hash, block.hash_scale) {
+                    Outcome::AboveTarget
+                } else {
+                    stats.probability = 1.0 / n.to_f64().ln();
+                    found = Some(block);
+                    Outcome::Found
+                }
+            }
+        };
+        stats.count(outcome);
+        if let Some(outcomes) = outcomes.as_deref_mut() {
+            outcomes.push(outcome);
+        }
+        if let Some(block) = found {
+            info!("Bloco minerado! Primo: {} ({} dígitos)", block.prime, block.prime.digits());
+            return (Some(block), stats);
+        }
+    }
+    (None, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(n_limit: i64, min_digits: i64, min_prob: i64, hash_scale: i64) -> DifficultyDelta {
+        DifficultyDelta { n_limit, min_digits, min_prob, hash_scale }
+    }
+
+    #[test]
+    fn fast_window_raises_by_fixed_steps() {
+        let decision = decide_difficulty(&Difficulty::default(), &[4.0, 6.0]);
+        assert_eq!((decision.average, decision.effective_average), (5.0, 5.0));
+        assert_eq!(decision.action, Adjustment::Raise);
+        assert_eq!(decision.deltas, delta(500, 1, 20, 0));
+        assert_eq!(decision.after.generation, 1);
+        assert!(decision.clamps.is_empty());
+    }
+
+    #[test]
+    fn slow_window_lowers_down_to_the_floors() {
+        let decision = decide_difficulty(&Difficulty::default(), &[20.0]);
+        assert_eq!((decision.action, decision.deltas), (Adjustment::Lower, delta(-300, 0, -20, 0)));
+
+        let low = Difficulty { n_limit: 120, min_prob: 55, ..Difficulty::default() };
+        let decision = decide_difficulty(&low, &[20.0]);
+        assert_eq!((decision.after.n_limit, decision.after.min_prob), (100, 50));
+        assert_eq!(decision.clamps, ["n_limit_min", "min_prob_min"]);
+    }
+
+    #[test]
+    fn hold_band_is_inclusive() {
+        let start = Difficulty::default();
+        for window in [&[6.0][..], &[14.0], &[10.0, 10.0], &[]] {
+            let decision = decide_difficulty(&start, window);
+            assert_eq!((decision.action, decision.deltas), (Adjustment::Hold, delta(0, 0, 0, 0)), "{:?}", window);
+            assert_eq!(decision.after.generation, start.generation);
+        }
+        assert_eq!(decide_difficulty(&start, &[5.99]).action, Adjustment::Raise);
+        assert_eq!(decide_difficulty(&start, &[14.01]).action, Adjustment::Lower);
+    }
+
+    #[test]
+    fn average_is_clamped_at_4x() {
+        let scaled = Difficulty { hash_scale: 1000, ..Difficulty::default() };
+        let slow = decide_difficulty(&scaled, &[1000.0]);
+        assert_eq!((slow.average, slow.effective_average), (1000.0, 40.0));
+        assert_eq!(slow.clamps, ["average_4x_bound"]);
+        assert_eq!((slow.action, slow.deltas), (Adjustment::Lower, delta(0, 0, 0, -750)));
+
+        let fast = decide_difficulty(&scaled, &[0.01]);
+        assert_eq!(fast.effective_average, 2.5);
+        assert_eq!(fast.deltas, delta(0, 0, 0, 3000));
+        // Exatamente no limite nada é cortado
+        assert!(decide_difficulty(&scaled, &[40.0]).clamps.is_empty());
+    }
+
+    #[test]
+    fn min_digits_guard_fires_at_18() {
+        let below = Difficulty { min_digits: 17, ..Difficulty::default() };
+        let decision = decide_difficulty(&below, &[1.0]);
+        assert_eq!(decision.after.min_digits, 18);
+        assert!(!decision.clamps.contains(&"min_digits_max"));
+
+        let at_max = Difficulty { min_digits: MAX_MIN_DIGITS, ..Difficulty::default() };
+        let decision = decide_difficulty(&at_max, &[1.0]);
+        assert_eq!(decision.after.min_digits, 18);
+        assert!(decision.clamps.contains(&"min_digits_max"));
+        assert!(decision.after.min_prob > at_max.min_prob, "min_prob continua subindo");
+
+        // Primos de 128 bits comportam um dígito a mais
+        let wide = Difficulty { width: PrimeWidth::U128, ..at_max.clone() };
+        assert_eq!(decide_difficulty(&wide, &[1.0]).after.min_digits, 19);
+    }
+
+    #[test]
+    fn mining_clamps_out_of_range_digits() {
+        let too_many = Difficulty { min_digits: 19, ..Difficulty::default() };
+        assert_eq!(too_many.clamped().min_digits, MAX_MIN_DIGITS);
+        assert!(!too_many.problems().is_empty());
+        let zero = Difficulty { min_digits: 0, n_limit: 0, ..Difficulty::default() };
+        assert_eq!((zero.clamped().min_digits, zero.clamped().n_limit), (1, 1));
+        let fine = Difficulty { min_digits: MAX_MIN_DIGITS, ..Difficulty::default() };
+        assert_eq!(fine.clamped().min_digits, MAX_MIN_DIGITS);
+    }
+
+    #[cfg(feature = "mining")]
+    #[test]
+    fn generated_tuples_are_farey_neighbours() {
+        use rand::{rngs::StdRng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(169);
+        for (n_limit, min_digits) in [(2, 1), (10, 2), (1000, 6), (100_000, 12)] {
+            let difficulty = Difficulty { n_limit, min_digits, ..Difficulty::default() };
+            let (low, high) = digit_range(min_digits).unwrap();
+            let mut generated = 0;
+            for _ in 0..2000 {
+                let Some((a, b, c, d)) = farey_tuple(&mut rng, &difficulty) else { continue };
+                generated += 1;
+                assert!(is_farey_pair(a, b, c, d), "{}/{} e {}/{}", a, b, c, d);
+                assert_eq!(b as i128 * c as i128 - a as i128 * d as i128, 1, "a/b < c/d");
+                assert!(b <= n_limit && d <= n_limit);
+                assert!((low..=high).contains(&a) && (low..=high).contains(&c));
+            }
+            assert!(generated > 1000, "só {} tuplas com n_limit {}", generated, n_limit);
+        }
+        assert!(is_farey_pair(1, 2, 1, 1) && is_farey_pair(2, 1, 1, 1) && !is_farey_pair(1, 3, 2, 3));
+    }
+
+    #[cfg(feature = "mining")]
+    #[test]
+    fn stern_brocot_generator_throughput_is_reported() {
+        use std::sync::atomic::AtomicBool;
+        let difficulty = Difficulty { n_limit: 1000, min_digits: 4, min_prob: 0, ..Difficulty::default() };
+        let template = BlockBuilder::on(&Block::genesis()).rules_version(3);
+        let (stop, mut throttle) = (AtomicBool::new(false), Throttle::unlimited());
+        let (block, stats) = mine_template(&template, &difficulty, &stop, None, &mut throttle).unwrap();
+        assert!(is_farey_pair(block.a, block.b, block.c, block.d));
+        assert_eq!(stats.generator, "stern_brocot");
+        assert!(stats.generated > 0 && stats.generator_throughput() > 0.0);
+    }
+
+    #[cfg(feature = "mining")]
+    #[test]
+    fn impossible_difficulties_are_errors() {
+        use rand::{rngs::StdRng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(176);
+        let tuple = |rng: &mut StdRng, n_limit, min_digits| {
+            random_tuple(rng, &Difficulty { n_limit, min_digits, ..Difficulty::default() })
+        };
+        assert_eq!(tuple(&mut rng, 10, 20), Err(MathError::Overflow("digit_range")));
+        assert_eq!(tuple(&mut rng, 10, 0), Err(MathError::Underflow("digit_range")));
+        assert_eq!(tuple(&mut rng, 0, 5), Err(MathError::EmptyRange("n_limit")));
+        // Nos extremos que ainda cabem, a tupla sai dentro dos limites
+        let (a, b, c, d) = tuple(&mut rng, u64::MAX, 19).unwrap();
+        assert!(a >= 10_u64.pow(18) && c >= 10_u64.pow(18) && b >= 1 && d >= 1);
+    }
+}
@@ -0,0 +1,166 @@
+// src/pool.rs
+use crossbeam_queue::ArrayQueue;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::mining::{random_tuple, screen_candidate, Difficulty};
+use crate::width::PrimeValue;
+
+/// Tupla que já passou por gcd, paridade, divisão por tentativa e heurística.
+#[derive(Debug, Clone, Copy)]
+pub struct ScreenedCandidate {
+    pub a: u64,
+    pub b: u64,
+    pub c: u64,
+    pub d: u64,
+    pub n: PrimeValue,
+    pub generation: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStats {
+    pub len: usize,
+    pub capacity: usize,
+    pub generation: u64,
+    pub produced: u64,
+    pub consumed: u64,
+    pub discarded: u64,
+}
+
+/// Fila lock-free e limitada de candidatos pré-filtrados para a dificuldade atual.
+#[derive(Debug)]
+pub struct CandidatePool {
+    queue: ArrayQueue<ScreenedCandidate>,
+    generation: AtomicU64,
+    produced: AtomicU64,
+    consumed: AtomicU64,
+    discarded: AtomicU64,
+}
+
+impl CandidatePool {
+    pub fn new(capacity: usize) -> Self {
+        CandidatePool {
+            queue: ArrayQueue::new(capacity.max(1)),
+            generation: AtomicU64::new(0),
+            produced: AtomicU64::new(0),
+            consumed: AtomicU64::new(0),
+            discarded: AtomicU64::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.queue.is_full()
+    }
+
+    /// Descarta tudo o que foi filtrado sob outra geração de dificuldade.
+    pub fn invalidate(&self, generation: u64) {
+        if self.generation.swap(generation, Ordering::AcqRel) != generation {
+            while self.queue.pop().is_some() {
+                self.discarded.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Retira um candidato da geração pedida, jogando fora os obsoletos.
+    pub fn take(&self, generation: u64) -> Option<ScreenedCandidate> {
+        while let Some(candidate) = self.queue.pop() {
+            if candidate.generation == generation {
+                self.consumed.fetch_add(1, Ordering::Relaxed);
+                return Some(candidate);
+            }
+            self.discarded.fetch_add(1, Ordering::Relaxed);
+        }
+        None
+    }
+
+    /// Gera até `attempts` tuplas aleatórias e enfileira as que passarem nos filtros.
+    /// Devolve quantas entraram na fila.
+    pub fn fill(&self, difficulty: &Difficulty, attempts: usize) -> usize {
+        if self.generation.load(Ordering::Acquire) != difficulty.generation {
+            return 0;
+        }
+        let mut rng = rand::thread_rng();
+        let min_prob = difficulty.min_prob_f64();
+        let mut pushed = 0;
+        for _ in 0..attempts {
+            // Dificuldade sem tuplas possíveis: nenhuma tentativa daria certo
+            let Ok((a, b, c, d)) = random_tuple(&mut rng, difficulty) else { break };
+            let Ok(n) = screen_candidate(a, b, c, d, min_prob, difficulty.width) else { continue };
+            let candidate = ScreenedCandidate { a, b, c, d, n, generation: difficulty.generation };
+            if self.queue.push(candidate).is_err() {
+                break;
+            }
+            pushed += 1;
+        }
+        self.produced.fetch_add(pushed as u64, Ordering::Relaxed);
+        pushed
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            len: self.queue.len(),
+            capacity: self.queue.capacity(),
+            generation: self.generation.load(Ordering::Relaxed),
+            produced: self.produced.load(Ordering::Relaxed),
+            consumed: self.consumed.load(Ordering::Relaxed),
+            discarded: self.discarded.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{Block, BlockBuilder};
+    use crate::mining::mine_template;
+    use crate::testkit::trivial_difficulty;
+    use crate::throttle::Throttle;
+    use std::sync::atomic::AtomicBool;
+
+    fn filled(capacity: usize) -> (CandidatePool, Difficulty) {
+        let difficulty = Difficulty { generation: 3, ..trivial_difficulty() };
+        let pool = CandidatePool::new(capacity);
+        pool.invalidate(difficulty.generation);
+        while !pool.is_full() {
+            pool.fill(&difficulty, capacity);
+        }
+        (pool, difficulty)
+    }
+
+    #[test]
+    fn mining_drains_the_pool_first() {
+        let (pool, difficulty) = filled(64);
+        let template = BlockBuilder::on(&Block::genesis()).rules_version(2);
+        let stop = AtomicBool::new(false);
+        let (block, stats) = mine_template(&template, &difficulty, &stop, Some(&pool), &mut Throttle::unlimited())
+            .expect("dificuldade trivial sempre acha um primo");
+        assert!(block.prime.is_prime());
+        assert!(stats.pool_hits > 0 && stats.pool_hit_rate() > 0.0);
+        assert_eq!(pool.stats().consumed, stats.pool_hits);
+        assert_eq!(pool.len() as u64, 64 - stats.pool_hits);
+    }
+
+    #[test]
+    fn difficulty_change_empties_the_pool() {
+        let (pool, difficulty) = filled(16);
+        pool.invalidate(difficulty.generation);
+        assert!(pool.is_full(), "a mesma geração não descarta nada");
+
+        let next = Difficulty { generation: difficulty.generation + 1, ..difficulty.clone() };
+        pool.invalidate(next.generation);
+        assert!(pool.is_empty());
+        assert_eq!(pool.stats().discarded, 16);
+        // Quem ainda filtra sob a geração antiga não enche o pool de novo
+        assert_eq!(pool.fill(&difficulty, 100), 0);
+        assert!(pool.take(difficulty.generation).is_none());
+        assert!(pool.fill(&next, 100) > 0);
+    }
+}
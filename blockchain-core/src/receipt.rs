@@ -0,0 +1,137 @@
+// src/receipt.rs
+use ed25519_dalek::Signer;
+use serde::{Deserialize, Serialize};
+
+use crate::mining::MiningStats;
+use crate::signature::{verify_signature, SigningKey};
+
+/// Números da mineração de um bloco, como o nó os registrou.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReceiptStats {
+    pub duration_ms: u64,
+    pub candidates: u64,
+    pub gcd_rejected: u64,
+    pub parity_rejected: u64,
+    pub trial_division_rejected: u64,
+    pub heuristic_rejected: u64,
+    pub miller_rabin_rejected: u64,
+    pub hash_target_rejected: u64,
+    pub pool_hits: u64,
+}
+
+impl ReceiptStats {
+    pub fn new(stats: &MiningStats, duration_ms: u64) -> Self {
+        ReceiptStats {
+            duration_ms,
+            candidates: stats.candidates,
+            gcd_rejected: stats.gcd_rejected,
+            parity_rejected: stats.parity_rejected,
+            trial_division_rejected: stats.trial_division_rejected,
+            heuristic_rejected: stats.heuristic_rejected,
+            miller_rabin_rejected: stats.miller_rabin_rejected,
+            hash_target_rejected: stats.hash_target_rejected,
+            pool_hits: stats.pool_hits,
+        }
+    }
+}
+
+/// Conteúdo assinado do recibo. Só inteiros e strings, na ordem dos campos, para a serialização
+/// ser canônica.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReceiptBody {
+    pub chain_id: String,
+    pub hash: String,
+    pub height: u64,
+    // Do bloco, em Unix milissegundos
+    pub timestamp: u64,
+    pub mined_locally: bool,
+    // Ausente quando o bloco veio de fora (sincronizado ou submetido)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<ReceiptStats>,
+    pub node_pubkey: String,
+    pub issued_at: u64,
+}
+
+/// Recibo de mineração assinado (ed25519) pela chave do nó.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MiningReceipt {
+    #[serde(flatten)]
+    pub body: ReceiptBody,
+    pub signature: String,
+}
+
+/// Mensagem assinada: o corpo em JSON compacto com separação de domínio.
+pub fn receipt_message(body: &ReceiptBody) -> Vec<u8> {
+    let json = serde_json::to_string(body).expect("corpo do recibo serializável");
+    format!("proof-of-prime:receipt:{}", json).into_bytes()
+}
+
+/// Assina o corpo com `key`; `node_pubkey` é preenchido com a chave pública correspondente.
+pub fn sign_receipt(key: &SigningKey, mut body: ReceiptBody) -> MiningReceipt {
+    body.node_pubkey = hex::encode(key.verifying_key().to_bytes());
+    let signature = hex::encode(key.sign(&receipt_message(&body)).to_bytes());
+    MiningReceipt { body, signature }
+}
+
+/// Confere a assinatura contra `pubkey_hex`, que também precisa ser a chave declarada no recibo.
+pub fn verify_receipt(receipt: &MiningReceipt, pubkey_hex: &str) -> bool {
+    receipt.body.node_pubkey.eq_ignore_ascii_case(pubkey_hex)
+        && verify_signature(pubkey_hex, &receipt.signature, &receipt_message(&receipt.body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt(stats: Option<ReceiptStats>) -> MiningReceipt {
+        let body = ReceiptBody {
+            chain_id: "default".to_string(),
+            hash: "ab".repeat(32),
+            height: 4,
+            timestamp: 1_760_000_000_000,
+            mined_locally: stats.is_some(),
+            stats,
+            node_pubkey: String::new(),
+            issued_at: 1_760_000_001,
+        };
+        sign_receipt(&SigningKey::from_bytes(&[5; 32]), body)
+    }
+
+    fn stats() -> ReceiptStats {
+        ReceiptStats::new(&MiningStats { candidates: 40, miller_rabin_rejected: 12, ..MiningStats::default() }, 250)
+    }
+
+    #[test]
+    fn issued_receipts_verify_and_tampering_fails() {
+        let issued = receipt(Some(stats()));
+        let pubkey = issued.body.node_pubkey.clone();
+        assert_eq!(pubkey, hex::encode(SigningKey::from_bytes(&[5; 32]).verifying_key().to_bytes()));
+        assert!(verify_receipt(&issued, &pubkey));
+        assert!(verify_receipt(&issued, &pubkey.to_uppercase()));
+        // Volta igual de um JSON
+        let decoded: MiningReceipt = serde_json::from_str(&serde_json::to_string(&issued).unwrap()).unwrap();
+        assert!(verify_receipt(&decoded, &pubkey));
+
+        let tamper = |change: fn(&mut ReceiptBody)| {
+            let mut forged = issued.clone();
+            change(&mut forged.body);
+            verify_receipt(&forged, &pubkey)
+        };
+        assert!(!tamper(|b| b.height = 5));
+        assert!(!tamper(|b| b.stats.as_mut().unwrap().candidates = 1));
+        assert!(!tamper(|b| b.mined_locally = false));
+        // Outra chave não serve, nem declarada no recibo
+        let other = hex::encode(SigningKey::from_bytes(&[6; 32]).verifying_key().to_bytes());
+        assert!(!verify_receipt(&issued, &other));
+        assert!(!tamper(|b| b.node_pubkey = "00".repeat(32)));
+    }
+
+    #[test]
+    fn external_blocks_carry_no_stats() {
+        let external = receipt(None);
+        assert!(!external.body.mined_locally);
+        let json = serde_json::to_value(&external).unwrap();
+        assert!(json.get("stats").is_none());
+        assert!(verify_receipt(&external, &external.body.node_pubkey));
+    }
+}
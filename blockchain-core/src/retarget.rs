@@ -0,0 +1,189 @@
+// src/retarget.rs
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::mining::{clamp_to_witness, decide_difficulty, Adjustment, Difficulty, DifficultyDecision, DifficultyDelta};
+use crate::mining::{MAX_HASH_SCALE, TARGET_TIME};
+
+// Tempos de bloco considerados no ajuste por janela
+pub const ADJUSTMENT_WINDOW: usize = 5;
+// Razões alvo/EMA mais próximas de 1 que isso não mexem na dificuldade
+const EMA_DEADBAND: f64 = 0.05;
+
+/// Algoritmo de reajuste: recebe o tempo de cada bloco e decide a próxima dificuldade.
+pub trait DifficultyAlgorithm: fmt::Debug + Send + Sync {
+    fn config(&self) -> AlgorithmConfig;
+
+    /// Registra o tempo do último bloco e decide o ajuste, sem aplicá-lo.
+    fn observe(&mut self, difficulty: &Difficulty, duration: f64) -> DifficultyDecision;
+
+    fn clone_box(&self) -> Box<dyn DifficultyAlgorithm>;
+}
+
+impl Clone for Box<dyn DifficultyAlgorithm> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Escolha do algoritmo e seus parâmetros, como vem da configuração ou de /admin/difficulty.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case", deny_unknown_fields)]
+pub enum AlgorithmConfig {
+    #[default]
+    Window,
+    Ema {
+        #[serde(default = "default_alpha")]
+        alpha: f64,
+        #[serde(default = "default_max_step")]
+        max_step: f64,
+    },
+}
+
+fn default_alpha() -> f64 {
+    0.2
+}
+
+fn default_max_step() -> f64 {
+    1.25
+}
+
+impl AlgorithmConfig {
+    pub fn ema() -> Self {
+        AlgorithmConfig::Ema { alpha: default_alpha(), max_step: default_max_step() }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AlgorithmConfig::Window => "window",
+            AlgorithmConfig::Ema { .. } => "ema",
+        }
+    }
+
+    pub fn build(self) -> Result<Box<dyn DifficultyAlgorithm>, String> {
+        match self {
+            AlgorithmConfig::Window => Ok(Box::new(WindowAlgorithm::default())),
+            AlgorithmConfig::Ema { alpha, max_step } => {
+                if !(alpha > 0.0 && alpha <= 1.0) {
+                    return Err(format!("ema alpha must be in (0, 1], got {}", alpha));
+                }
+                if !(max_step > 1.0 && max_step <= 4.0) {
+                    return Err(format!("ema max_step must be in (1, 4], got {}", max_step));
+                }
+                Ok(Box::new(EmaAlgorithm { alpha, max_step, ema: TARGET_TIME }))
+            }
+        }
+    }
+}
+
+/// Média dos últimos `ADJUSTMENT_WINDOW` tempos, com degraus fixos (o algoritmo original).
+#[derive(Debug, Clone, Default)]
+pub struct WindowAlgorithm {
+    times: VecDeque<f64>,
+}
+
+impl DifficultyAlgorithm for WindowAlgorithm {
+    fn config(&self) -> AlgorithmConfig {
+        AlgorithmConfig::Window
+    }
+
+    fn observe(&mut self, difficulty: &Difficulty, duration: f64) -> DifficultyDecision {
+        if self.times.len() == ADJUSTMENT_WINDOW {
+            self.times.pop_front();
+        }
+        self.times.push_back(duration);
+        let window: Vec<f64> = self.times.iter().copied().collect();
+        decide_difficulty(difficulty, &window)
+    }
+
+    fn clone_box(&self) -> Box<dyn DifficultyAlgorithm> {
+        Box::new(self.clone())
+    }
+}
+
+/// Média móvel exponencial dos tempos; a dificuldade contínua (hash_scale na v4, senão n_limit e
+/// min_prob) escala pela razão alvo/EMA, limitada a `max_step` por bloco. Parte do tempo alvo.
+#[derive(Debug, Clone)]
+pub struct EmaAlgorithm {
+    alpha: f64,
+    max_step: f64,
+    ema: f64,
+}
+
+impl DifficultyAlgorithm for EmaAlgorithm {
+    fn config(&self) -> AlgorithmConfig {
+        AlgorithmConfig::Ema { alpha: self.alpha, max_step: self.max_step }
+    }
+
+    fn observe(&mut self, difficulty: &Difficulty, duration: f64) -> DifficultyDecision {
+        let target = TARGET_TIME;
+        self.ema = self.alpha * duration + (1.0 - self.alpha) * self.ema;
+        let mut clamps = Vec::new();
+        let raw_ratio = if self.ema > 0.0 { target / self.ema } else { self.max_step };
+        let ratio = raw_ratio.clamp(1.0 / self.max_step, self.max_step);
+        if ratio != raw_ratio {
+            clamps.push("ema_step_bound");
+        }
+
+        let mut after = difficulty.clone();
+        if (ratio - 1.0).abs() >= EMA_DEADBAND {
+            // Arredonda para o lado do ajuste, senão valores pequenos nunca saem do lugar
+            let scale = |value: u64| {
+                let scaled = value as f64 * ratio;
+                if ratio > 1.0 { scaled.ceil() } else { scaled.floor() }
+            };
+            if difficulty.hash_scale != 0 {
+                let hash_scale = scale(difficulty.hash_scale);
+                if hash_scale < 1.0 {
+                    clamps.push("hash_scale_min");
+                }
+                if hash_scale > MAX_HASH_SCALE as f64 {
+                    clamps.push("hash_scale_max");
+                }
+                after.hash_scale = hash_scale.clamp(1.0, MAX_HASH_SCALE as f64) as u64;
+            } else {
+                let n_limit = scale(difficulty.n_limit);
+                if n_limit < 100.0 {
+                    clamps.push("n_limit_min");
+                }
+                after.n_limit = n_limit.max(100.0) as u64;
+                let min_prob = scale(difficulty.min_prob);
+                if !(50.0..=1000.0).contains(&min_prob) {
+                    clamps.push(if min_prob < 50.0 { "min_prob_min" } else { "min_prob_max" });
+                }
+                after.min_prob = min_prob.clamp(50.0, 1000.0) as u64;
+                clamp_to_witness(&mut after, &mut clamps);
+            }
+        }
+        let changed = (after.hash_scale, after.n_limit, after.min_prob)
+            != (difficulty.hash_scale, difficulty.n_limit, difficulty.min_prob);
+        let action = match (changed, ratio > 1.0) {
+            (false, _) => Adjustment::Hold,
+            (true, true) => Adjustment::Raise,
+            (true, false) => Adjustment::Lower,
+        };
+        if changed {
+            after.generation += 1;
+        }
+
+        DifficultyDecision {
+            algorithm: "ema",
+            reason: "retarget",
+            window: vec![duration],
+            average: self.ema,
+            effective_average: target / ratio,
+            target,
+            tolerance: EMA_DEADBAND,
+            action,
+            deltas: DifficultyDelta::between(difficulty, &after),
+            before: difficulty.clone(),
+            after,
+            clamps,
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn DifficultyAlgorithm> {
+        Box::new(self.clone())
+    }
+}
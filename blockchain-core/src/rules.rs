@@ -0,0 +1,339 @@
+// src/rules.rs
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::block::{meets_hash_target, Block, VerifyError};
+use crate::emission::{reward_at_height, EmissionError, EmissionSchedule};
+use crate::fork::prime_work;
+use crate::math::digits;
+use crate::mining::is_farey_pair;
+use crate::width::PrimeWidth;
+
+/// Versões de regras e a altura a partir da qual cada uma vale.
+/// v1: regras originais (gcd, soma da testemunha, primalidade, hash).
+/// v2: v1 + `a` e `c` com a mesma quantidade de dígitos e primo com pelo menos 7 dígitos;
+///     a versão passa a fazer parte do hash.
+/// v3: v2 + `a/b` e `c/d` vizinhas de Farey (`|a*d - b*c| = 1`). Sem ativação padrão; agende por
+///     RULES_ACTIVATION ou /admin/rules.
+/// v4: v3 + `hash_scale >= 1` declarado no bloco e hash abaixo de `2^256 / hash_scale`; antes da v4
+///     o campo precisa ser zero. Também sem ativação padrão.
+/// v5: v4 + `work` no cabeçalho igual ao trabalho do primo (`fork::prime_work`), entrando no hash;
+///     antes da v5 o campo precisa ser zero. Também sem ativação padrão.
+/// v6: v5 + `reward` igual a `emission::reward_at_height` do calendário da cadeia, creditada a `coinbase`;
+///     antes da v6 os dois campos ficam vazios. Também sem ativação padrão.
+pub const RULES_ACTIVATION: &[(u32, u64)] = &[(1, 0), (2, 1000)];
+
+pub const LATEST_RULES_VERSION: u32 = 6;
+
+const MIN_PRIME_DIGITS_V2: u32 = 7;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    UnknownVersion(u32),
+    AlreadyActive(u32),
+    NotInFuture { height: u64, current_height: u64 },
+    OutOfOrder { version: u32, height: u64 },
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::UnknownVersion(v) => write!(f, "unknown rules version {}", v),
+            ScheduleError::AlreadyActive(v) => write!(f, "rules version {} is already active", v),
+            ScheduleError::NotInFuture { height, current_height } => {
+                write!(f, "activation height {} is not above current height {}", height, current_height)
+            }
+            ScheduleError::OutOfOrder { version, height } => {
+                write!(f, "activation of version {} at height {} breaks version ordering", version, height)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+/// Tabela de ativação em vigor no nó; parte de `RULES_ACTIVATION` e aceita agendamentos futuros.
+/// Leva também o calendário de emissão, que as regras v6 conferem, e a largura dos primos, que vale em
+/// todas as versões.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleSchedule {
+    activations: Vec<(u32, u64)>,
+    #[serde(default)]
+    emission: EmissionSchedule,
+    // Cadeias anteriores ao campo são u64
+    #[serde(default)]
+    prime_width: PrimeWidth,
+}
+
+impl Default for RuleSchedule {
+    fn default() -> Self {
+        RuleSchedule {
+            activations: RULES_ACTIVATION.to_vec(),
+            emission: EmissionSchedule::default(),
+            prime_width: PrimeWidth::default(),
+        }
+    }
+}
+
+impl RuleSchedule {
+    pub fn activations(&self) -> &[(u32, u64)] {
+        &self.activations
+    }
+
+    pub fn emission(&self) -> &EmissionSchedule {
+        &self.emission
+    }
+
+    /// Troca o calendário; quem chama garante que nenhum bloco minerado depende do anterior.
+    pub fn set_emission(&mut self, emission: EmissionSchedule) -> Result<(), EmissionError> {
+        emission.check()?;
+        self.emission = emission;
+        Ok(())
+    }
+
+    pub fn prime_width(&self) -> PrimeWidth {
+        self.prime_width
+    }
+
+    /// Troca a largura; como em `set_emission`, quem chama garante que não há blocos minerados.
+    pub fn set_prime_width(&mut self, width: PrimeWidth) {
+        self.prime_width = width;
+    }
+
+    /// Recompensa que o bloco de índice `height` deve declarar: a do calendário nas regras v6, 0 antes.
+    pub fn reward_at(&self, height: u64) -> u64 {
+        if self.version_at(height) >= 6 { reward_at_height(height, &self.emission) } else { 0 }
+    }
+
+    /// Versão exigida para o bloco de índice `height`.
+    pub fn version_at(&self, height: u64) -> u32 {
+        self.activations
+            .iter()
+            .take_while(|(_, activation)| *activation <= height)
+            .last()
+            .map_or(1, |(version, _)| *version)
+    }
+
+    /// Agenda (ou reagenda) a ativação de `version`; `next_height` é o índice do próximo bloco.
+    pub fn schedule(&mut self, version: u32, height: u64, next_height: u64) -> Result<(), ScheduleError> {
+        if !(2..=LATEST_RULES_VERSION).contains(&version) {
+            return Err(ScheduleError::UnknownVersion(version));
+        }
+        if self.version_at(next_height.saturating_sub(1)) >= version {
+            return Err(ScheduleError::AlreadyActive(version));
+        }
+        if height < next_height {
+            return Err(ScheduleError::NotInFuture { height, current_height: next_height });
+        }
+        let activation_of = |v: u32| self.activations.iter().find(|(x, _)| *x == v).map(|(_, h)| *h);
+        let after_previous = activation_of(version - 1).is_some_and(|h| h < height);
+        let before_next = activation_of(version + 1).is_none_or(|h| height < h);
+        if !after_previous || !before_next {
+            return Err(ScheduleError::OutOfOrder { version, height });
+        }
+        match self.activations.iter_mut().find(|(v, _)| *v == version) {
+            Some(entry) => entry.1 = height,
+            None => self.activations.push((version, height)),
+        }
+        self.activations.sort();
+        Ok(())
+    }
+}
+
+/// Valida `block` sobre `prev` com o conjunto de regras vigente na altura do bloco e a largura da cadeia.
+pub fn validate_block(block: &Block, prev: &Block, schedule: &RuleSchedule) -> Result<(), VerifyError> {
+    let expected = schedule.version_at(block.index);
+    if block.rules_version != expected {
+        return Err(VerifyError::RulesVersion { expected, found: block.rules_version });
+    }
+    let width = schedule.prime_width();
+    if block.prime.width() > width {
+        return Err(VerifyError::PrimeWidth { width, found: block.prime });
+    }
+    block.verify(prev)?;
+    if block.rules_version >= 2 {
+        validate_v2(block)?;
+    }
+    if block.rules_version >= 3 {
+        validate_v3(block)?;
+    }
+    validate_v4(block)?;
+    validate_v5(block)?;
+    validate_v6(block, schedule)
+}
+
+fn validate_v6(block: &Block, schedule: &RuleSchedule) -> Result<(), VerifyError> {
+    if block.rules_version < 6 && !block.coinbase.is_empty() {
+        return Err(VerifyError::CoinbaseBeforeV6 { rules_version: block.rules_version });
+    }
+    let expected = schedule.reward_at(block.index);
+    if block.reward != expected {
+        return Err(VerifyError::BlockReward { rules_version: block.rules_version, expected, found: block.reward });
+    }
+    Ok(())
+}
+
+fn validate_v5(block: &Block) -> Result<(), VerifyError> {
+    let expected = if block.rules_version >= 5 { prime_work(block.prime) } else { 0 };
+    if block.work != expected {
+        return Err(VerifyError::BlockWork { rules_version: block.rules_version, expected, found: block.work });
+    }
+    Ok(())
+}
+
+fn validate_v4(block: &Block) -> Result<(), VerifyError> {
+    let declared = block.hash_scale != 0;
+    if declared != (block.rules_version >= 4) {
+        return Err(VerifyError::HashScale { rules_version: block.rules_version, found: block.hash_scale });
+    }
+    if declared && !meets_hash_target(&block.hash, block.hash_scale) {
+        return Err(VerifyError::HashAboveTarget { hash_scale: block.hash_scale });
+    }
+    Ok(())
+}
+
+fn validate_v3(block: &Block) -> Result<(), VerifyError> {
+    if !is_farey_pair(block.a, block.b, block.c, block.d) {
+        let determinant = block.a as i128 * block.d as i128 - block.b as i128 * block.c as i128;
+        return Err(VerifyError::NotFareyNeighbors { determinant });
+    }
+    Ok(())
+}
+
+fn validate_v2(block: &Block) -> Result<(), VerifyError> {
+    if digits(block.a) != digits(block.c) {
+        return Err(VerifyError::DigitStructure(format!(
+            "a has {} digits but c has {}",
+            digits(block.a),
+            digits(block.c)
+        )));
+    }
+    if block.prime.digits() < MIN_PRIME_DIGITS_V2 {
+        return Err(VerifyError::DigitStructure(format!(
+            "prime has {} digits, minimum is {}",
+            block.prime.digits(),
+            MIN_PRIME_DIGITS_V2
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockBuilder;
+    use crate::chain::ChainState;
+
+    // v2 a partir do bloco 3
+    fn v2_at_3() -> RuleSchedule {
+        let mut schedule = RuleSchedule::default();
+        schedule.schedule(2, 3, 1).unwrap();
+        schedule
+    }
+
+    // Primo de 1 dígito: vale na v1 e quebra `digit_structure` na v2
+    fn small(prev: &Block, rules_version: u32) -> Block {
+        BlockBuilder::on(prev).rules_version(rules_version).witness(1, 1, 2, 1).build()
+    }
+
+    // `a` e `c` de 7 dígitos e soma prima, válido nas duas versões
+    fn large(prev: &Block, rules_version: u32) -> Block {
+        let template = BlockBuilder::on(prev).rules_version(rules_version);
+        (0..)
+            .map(|i| template.clone().witness(1_000_000 + i, 1, 1_000_001, 1).build())
+            .find(|block| block.prime.is_prime())
+            .unwrap()
+    }
+
+    #[test]
+    fn chain_spans_the_activation() {
+        let schedule = v2_at_3();
+        let mut blocks = vec![Block::genesis()];
+        for index in 1..=4 {
+            let prev = blocks.last().unwrap();
+            let block = if index < 3 { small(prev, 1) } else { large(prev, 2) };
+            assert_eq!(validate_block(&block, prev, &schedule), Ok(()), "bloco {}", index);
+            blocks.push(block);
+        }
+        let chain = ChainState::from_blocks_with_rules(blocks.clone(), schedule.clone()).unwrap();
+        assert_eq!(chain.height(), 5);
+
+        // O primo pequeno que passou na v1 não passa depois da ativação
+        let late = small(&blocks[2], 2);
+        assert_eq!(validate_block(&late, &blocks[2], &schedule).unwrap_err().invariant(), "digit_structure");
+    }
+
+    #[test]
+    fn version_must_match_the_height() {
+        let schedule = v2_at_3();
+        let genesis = Block::genesis();
+        let early = large(&genesis, 2);
+        assert_eq!(
+            validate_block(&early, &genesis, &schedule),
+            Err(VerifyError::RulesVersion { expected: 1, found: 2 })
+        );
+
+        let prev = BlockBuilder::on(&small(&genesis, 1)).witness(1, 1, 2, 1).build();
+        let stale = large(&prev, 1);
+        assert_eq!(stale.index, 3);
+        assert_eq!(validate_block(&stale, &prev, &schedule), Err(VerifyError::RulesVersion { expected: 2, found: 1 }));
+    }
+
+    // `a/1` e `(a+1)/1` são vizinhas de Farey, com primo 2a + 1 de 7 dígitos
+    fn farey(prev: &Block, rules_version: u32) -> Block {
+        let template = BlockBuilder::on(prev).rules_version(rules_version);
+        (1_000_000..)
+            .map(|a| template.clone().witness(a, 1, a + 1, 1).build())
+            .find(|block| block.prime.is_prime())
+            .unwrap()
+    }
+
+    // a*1 - 2*c fica longe de ±1
+    fn non_farey(prev: &Block, rules_version: u32) -> Block {
+        let template = BlockBuilder::on(prev).rules_version(rules_version);
+        (1_000_001..)
+            .step_by(2)
+            .map(|a| template.clone().witness(a, 2, 1_000_001, 1).build())
+            .find(|block| block.prime.is_prime())
+            .unwrap()
+    }
+
+    #[test]
+    fn farey_condition_applies_from_v3() {
+        let mut schedule = RuleSchedule::default();
+        schedule.schedule(2, 2, 1).unwrap();
+        schedule.schedule(3, 3, 1).unwrap();
+        let genesis = Block::genesis();
+        let first = small(&genesis, 1);
+        // Antes da v3 a testemunha não precisa ser de Farey
+        let second = non_farey(&first, 2);
+        assert!(!is_farey_pair(second.a, second.b, second.c, second.d));
+        assert_eq!(validate_block(&second, &first, &schedule), Ok(()));
+
+        let third = farey(&second, 3);
+        assert_eq!(validate_block(&third, &second, &schedule), Ok(()));
+        let chain = ChainState::from_blocks_with_rules(vec![genesis, first, second.clone(), third], schedule.clone());
+        assert_eq!(chain.unwrap().height(), 4);
+
+        // A mesma testemunha de antes, agora na v3
+        let violating = non_farey(&second, 3);
+        let determinant = violating.a as i128 * violating.d as i128 - violating.b as i128 * violating.c as i128;
+        let error = validate_block(&violating, &second, &schedule).unwrap_err();
+        assert_eq!(error, VerifyError::NotFareyNeighbors { determinant });
+        assert_eq!(error.invariant(), "farey_determinant");
+    }
+
+    #[test]
+    fn schedule_errors() {
+        let mut schedule = RuleSchedule::default();
+        assert_eq!(schedule.schedule(7, 50, 10), Err(ScheduleError::UnknownVersion(7)));
+        assert_eq!(schedule.schedule(2, 5, 10), Err(ScheduleError::NotInFuture { height: 5, current_height: 10 }));
+        assert_eq!(schedule.schedule(3, 500, 10), Err(ScheduleError::OutOfOrder { version: 3, height: 500 }));
+        schedule.schedule(2, 20, 10).unwrap();
+        assert_eq!(schedule.activations(), &[(1, 0), (2, 20)]);
+        schedule.schedule(3, 30, 10).unwrap();
+        assert_eq!(schedule.schedule(3, 15, 10), Err(ScheduleError::OutOfOrder { version: 3, height: 15 }));
+        assert_eq!(schedule.schedule(2, 40, 25), Err(ScheduleError::AlreadyActive(2)));
+        assert_eq!((schedule.version_at(19), schedule.version_at(20), schedule.version_at(30)), (1, 2, 3));
+    }
+}
@@ -0,0 +1,128 @@
+// src/seeded.rs
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::block::{Block, BlockBuilder};
+use crate::mining::{mine_with, Difficulty, MiningStats, Outcome};
+use crate::throttle::Throttle;
+
+/// Fluxo ChaCha20 independente do worker `worker` num job com semente `seed`: SHA-256(seed || worker).
+pub fn worker_rng(seed: u64, worker: usize) -> ChaCha20Rng {
+    let digest = Sha256::new().chain_update(seed.to_le_bytes()).chain_update((worker as u64).to_le_bytes()).finalize();
+    ChaCha20Rng::from_seed(digest.into())
+}
+
+/// Mineração paralela reprodutível: cada worker tira suas tuplas de `worker_rng(seed, i)` e vence quem
+/// acha um primo com menos candidatos (no empate, o menor índice), não quem termina primeiro. Os
+/// workers só param ao chegar no menor número de candidatos já vencedor, então a mesma semente, com
+/// o mesmo modelo (timestamp incluso), dificuldade e número de workers, dá o mesmo bloco.
+/// O pool de candidatos é ignorado, já que a ordem em que ele é consumido depende do escalonamento.
+#[derive(Debug)]
+pub struct SeededRace {
+    seed: u64,
+    cutoff: AtomicU64,
+}
+
+/// Resultado de um worker: o destino de cada candidato testado, para recontar até o vencedor.
+#[derive(Debug)]
+pub struct WorkerRun {
+    worker: usize,
+    block: Option<Block>,
+    stats: MiningStats,
+    outcomes: Vec<Outcome>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RaceResult {
+    pub seed: u64,
+    pub worker: usize,
+    pub block: Block,
+    pub stats: MiningStats,
+    // Contadores de cada worker nos primeiros `stats.candidates` candidatos, sem os tempos
+    pub workers: Vec<MiningStats>,
+}
+
+impl SeededRace {
+    pub fn new(seed: u64) -> Self {
+        SeededRace { seed, cutoff: AtomicU64::new(u64::MAX) }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Minera como o worker `worker`, até achar, passar do corte ou `stop` ser sinalizado.
+    pub fn run(
+        &self,
+        worker: usize,
+        template: &BlockBuilder,
+        difficulty: &Difficulty,
+        stop: &AtomicBool,
+        throttle: &mut Throttle,
+    ) -> WorkerRun {
+        let mut rng = worker_rng(self.seed, worker);
+        let mut outcomes = Vec::new();
+        let (block, stats) = mine_with(template, difficulty, None, throttle, &mut rng, Some(&mut outcomes), |stats| {
+            !stop.load(Ordering::Relaxed) && stats.candidates < self.cutoff.load(Ordering::Acquire)
+        });
+        if block.is_some() {
+            self.cutoff.fetch_min(stats.candidates, Ordering::AcqRel);
+        }
+        WorkerRun { worker, block, stats, outcomes }
+    }
+
+    /// Escolhe o vencedor entre os resultados de todos os workers; `None` se nenhum achou (cancelado).
+    pub fn settle(&self, mut runs: Vec<WorkerRun>) -> Option<RaceResult> {
+        runs.sort_by_key(|run| run.worker);
+        let winner = runs
+            .iter()
+            .filter(|run| run.block.is_some())
+            .min_by_key(|run| (run.stats.candidates, run.worker))?;
+        let candidates = winner.stats.candidates as usize;
+        let workers = runs
+            .iter()
+            .map(|run| {
+                let mut stats = MiningStats { generator: run.stats.generator, ..MiningStats::default() };
+                for &outcome in run.outcomes.iter().take(candidates) {
+                    stats.count(outcome);
+                }
+                stats.generated = stats.candidates;
+                if run.worker == winner.worker {
+                    stats.probability = winner.stats.probability;
+                }
+                stats
+            })
+            .collect();
+        Some(RaceResult {
+            seed: self.seed,
+            worker: winner.worker,
+            block: winner.block.clone()?,
+            stats: winner.stats.clone(),
+            workers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    fn stream(seed: u64, worker: usize) -> Vec<u64> {
+        let mut rng = worker_rng(seed, worker);
+        (0..16).map(|_| rng.next_u64()).collect()
+    }
+
+    #[test]
+    fn each_worker_gets_its_own_stream() {
+        assert_eq!(stream(7, 0), stream(7, 0));
+        let streams: Vec<_> = (0..4).map(|worker| stream(7, worker)).collect();
+        for (i, a) in streams.iter().enumerate() {
+            assert!(streams[i + 1..].iter().all(|b| a != b), "worker {} repete outro fluxo", i);
+            // Nem o mesmo worker com outra semente
+            assert_ne!(*a, stream(8, i));
+        }
+    }
+}
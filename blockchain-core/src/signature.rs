@@ -0,0 +1,33 @@
+// src/signature.rs
+use ed25519_dalek::{Signature, Signer, Verifier};
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+use crate::block::Block;
+
+/// Mensagem assinada pelo minerador: o hash do bloco com separação de domínio.
+pub fn signing_message(block: &Block) -> Vec<u8> {
+    format!("proof-of-prime:block:{}", block.hash).into_bytes()
+}
+
+/// Assina o bloco e devolve a assinatura em hex.
+pub fn sign_block(key: &SigningKey, block: &Block) -> String {
+    hex::encode(key.sign(&signing_message(block)).to_bytes())
+}
+
+pub fn parse_public_key(pubkey_hex: &str) -> Option<VerifyingKey> {
+    let bytes: [u8; 32] = hex::decode(pubkey_hex).ok()?.try_into().ok()?;
+    VerifyingKey::from_bytes(&bytes).ok()
+}
+
+pub fn verify_block_signature(pubkey_hex: &str, signature_hex: &str, block: &Block) -> bool {
+    verify_signature(pubkey_hex, signature_hex, &signing_message(block))
+}
+
+/// Confere uma assinatura ed25519 em hex sobre `message`.
+pub fn verify_signature(pubkey_hex: &str, signature_hex: &str, message: &[u8]) -> bool {
+    let Some(key) = parse_public_key(pubkey_hex) else { return false };
+    let Some(bytes) = hex::decode(signature_hex).ok().and_then(|b| <[u8; 64]>::try_from(b).ok()) else {
+        return false;
+    };
+    key.verify(message, &Signature::from_bytes(&bytes)).is_ok()
+}
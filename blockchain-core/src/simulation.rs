@@ -0,0 +1,338 @@
+// src/simulation.rs
+//! Reajuste de dificuldade simulado, sem minerar: o tempo de cada bloco sai do modelo estatístico
+//! (`expected_candidates` candidatos por primo, chegadas de Poisson à vazão do momento) e alimenta o
+//! algoritmo de verdade, numa dificuldade própria que nunca toca uma cadeia.
+use rand::Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::calibration::calibrate;
+use crate::mining::{Adjustment, Difficulty, TARGET_TIME};
+use crate::retarget::AlgorithmConfig;
+
+// Blocos por simulação; 10 mil saem em bem menos de um segundo
+pub const MAX_SIMULATED_BLOCKS: usize = 100_000;
+
+/// Vazão (candidatos testados por segundo) num instante do tempo simulado.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HashratePoint {
+    pub at_secs: f64,
+    pub candidates_per_sec: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub blocks: usize,
+    // Interpolada linearmente entre os pontos, em ordem de `at_secs`; constante antes do primeiro e
+    // depois do último
+    pub hashrate: Vec<HashratePoint>,
+    #[serde(default)]
+    pub algorithm: AlgorithmConfig,
+    pub seed: u64,
+    // Parte da dificuldade calibrada para a vazão inicial em vez da recebida
+    #[serde(default)]
+    pub calibrate: bool,
+}
+
+impl Scenario {
+    fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_SIMULATED_BLOCKS).contains(&self.blocks) {
+            return Err(format!("blocks must be between 1 and {}", MAX_SIMULATED_BLOCKS));
+        }
+        if self.hashrate.is_empty() {
+            return Err("hashrate needs at least one point".to_string());
+        }
+        for point in &self.hashrate {
+            if !(point.at_secs.is_finite() && point.at_secs >= 0.0) {
+                return Err(format!("hashrate at_secs must be finite and non-negative, got {}", point.at_secs));
+            }
+            if !(point.candidates_per_sec.is_finite() && point.candidates_per_sec > 0.0) {
+                return Err(format!(
+                    "hashrate candidates_per_sec must be finite and positive, got {}",
+                    point.candidates_per_sec
+                ));
+            }
+        }
+        if self.hashrate.windows(2).any(|pair| pair[1].at_secs < pair[0].at_secs) {
+            return Err("hashrate points must be ordered by at_secs".to_string());
+        }
+        Ok(())
+    }
+
+    fn hashrate_at(&self, secs: f64) -> f64 {
+        let after = self.hashrate.partition_point(|point| point.at_secs <= secs);
+        match (after.checked_sub(1).map(|i| self.hashrate[i]), self.hashrate.get(after).copied()) {
+            (Some(from), Some(to)) => {
+                let t = (secs - from.at_secs) / (to.at_secs - from.at_secs);
+                from.candidates_per_sec + t * (to.candidates_per_sec - from.candidates_per_sec)
+            }
+            (Some(point), None) | (None, Some(point)) => point.candidates_per_sec,
+            (None, None) => unreachable!("validate garante ao menos um ponto"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedBlock {
+    pub index: usize,
+    // Fim do bloco no tempo simulado
+    pub at_secs: f64,
+    pub duration: f64,
+    // Tempo médio pela dificuldade e vazão do bloco; a distância dele ao alvo mede o algoritmo sem o
+    // ruído do sorteio
+    pub expected_time: f64,
+    pub candidates_per_sec: f64,
+    pub expected_candidates: f64,
+    pub n_limit: u64,
+    pub min_digits: u32,
+    pub min_prob: u64,
+    pub hash_scale: u64,
+    // Decisão tomada ao fim do bloco, que vale para o seguinte
+    pub action: Adjustment,
+}
+
+/// Erros contra o tempo alvo, em segundos; o percentual é relativo ao alvo.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TimeErrors {
+    pub mean: f64,
+    pub mean_absolute_error: f64,
+    pub root_mean_square_error: f64,
+    pub mean_absolute_percentage_error: f64,
+}
+
+impl TimeErrors {
+    fn of(times: impl Iterator<Item = f64> + Clone, target: f64) -> Self {
+        let count = times.clone().count().max(1) as f64;
+        let mean = times.clone().sum::<f64>() / count;
+        let mean_absolute_error = times.clone().map(|t| (t - target).abs()).sum::<f64>() / count;
+        let root_mean_square_error = (times.map(|t| (t - target).powi(2)).sum::<f64>() / count).sqrt();
+        TimeErrors {
+            mean,
+            mean_absolute_error,
+            root_mean_square_error,
+            mean_absolute_percentage_error: mean_absolute_error / target * 100.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationSummary {
+    pub target: f64,
+    pub total_secs: f64,
+    pub block_times: TimeErrors,
+    pub expected_times: TimeErrors,
+    // Tempos esperados do último quarto dos blocos, para ver onde o algoritmo assentou
+    pub last_quarter_expected_times: TimeErrors,
+    pub raises: usize,
+    pub lowers: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Simulation {
+    pub algorithm: AlgorithmConfig,
+    pub seed: u64,
+    pub start: Difficulty,
+    pub summary: SimulationSummary,
+    pub blocks: Vec<SimulatedBlock>,
+}
+
+/// Roda `scenario` a partir de `start`, como se fosse a próxima dificuldade de uma cadeia nas regras
+/// `rules_version`. O tempo de cada bloco é exponencial com média `expected_candidates / vazão`, a
+/// vazão lida no início dele; a mesma semente dá a mesma trajetória.
+pub fn simulate(scenario: &Scenario, start: &Difficulty, rules_version: u32) -> Result<Simulation, String> {
+    scenario.validate()?;
+    let mut algorithm = scenario.algorithm.build()?;
+    let mut difficulty = start.clone();
+    // Como em `ChainState::adjust_difficulty`: na v4 o alvo de hash parte de 1
+    if rules_version >= 4 && difficulty.hash_scale == 0 {
+        difficulty.hash_scale = 1;
+    }
+    if scenario.calibrate {
+        difficulty = calibrate(&difficulty, rules_version, scenario.hashrate_at(0.0), TARGET_TIME).after;
+    }
+    let start = difficulty.clone();
+
+    let mut rng = ChaCha20Rng::seed_from_u64(scenario.seed);
+    let mut at_secs = 0.0;
+    let mut blocks = Vec::with_capacity(scenario.blocks);
+    for index in 1..=scenario.blocks {
+        let candidates_per_sec = scenario.hashrate_at(at_secs);
+        let expected_candidates = difficulty.expected_candidates();
+        let expected_time = expected_candidates / candidates_per_sec;
+        // Amostra por inversão; `1 - u` fica em (0, 1], então o logaritmo é finito
+        let duration = -(1.0 - rng.gen::<f64>()).ln() * expected_time;
+        at_secs += duration;
+        // `decision.after` direto: `Difficulty::apply` registraria cada ajuste no log
+        let decision = algorithm.observe(&difficulty, duration);
+        blocks.push(SimulatedBlock {
+            index,
+            at_secs,
+            duration,
+            expected_time,
+            candidates_per_sec,
+            expected_candidates,
+            n_limit: difficulty.n_limit,
+            min_digits: difficulty.min_digits,
+            min_prob: difficulty.min_prob,
+            hash_scale: difficulty.hash_scale,
+            action: decision.action,
+        });
+        difficulty = decision.after;
+    }
+
+    let last_quarter = &blocks[blocks.len() - blocks.len().div_ceil(4)..];
+    let summary = SimulationSummary {
+        target: TARGET_TIME,
+        total_secs: at_secs,
+        block_times: TimeErrors::of(blocks.iter().map(|b| b.duration), TARGET_TIME),
+        expected_times: TimeErrors::of(blocks.iter().map(|b| b.expected_time), TARGET_TIME),
+        last_quarter_expected_times: TimeErrors::of(last_quarter.iter().map(|b| b.expected_time), TARGET_TIME),
+        raises: blocks.iter().filter(|b| b.action == Adjustment::Raise).count(),
+        lowers: blocks.iter().filter(|b| b.action == Adjustment::Lower).count(),
+    };
+    Ok(Simulation { algorithm: algorithm.config(), seed: scenario.seed, start, summary, blocks })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(rules_version: u32) -> Simulation {
+        let scenario = Scenario {
+            blocks: 400,
+            hashrate: vec![HashratePoint { at_secs: 0.0, candidates_per_sec: 20_000.0 }],
+            algorithm: AlgorithmConfig::ema(),
+            seed: 7,
+            calibrate: false,
+        };
+        simulate(&scenario, &Difficulty::default(), rules_version).unwrap()
+    }
+
+    // Maior salto, em log, do tempo esperado entre blocos seguidos
+    fn largest_step(blocks: &[SimulatedBlock]) -> f64 {
+        blocks.windows(2).map(|w| (w[1].expected_time / w[0].expected_time).ln().abs()).fold(0.0, f64::max)
+    }
+
+    /// Com o ajuste fino do hash a rede chega perto do alvo; presa a degraus de dígitos, não.
+    #[test]
+    fn hash_scale_converges_more_smoothly_than_digits() {
+        let digits = run(3);
+        let scaled = run(4);
+        let mape = |sim: &Simulation| sim.summary.last_quarter_expected_times.mean_absolute_percentage_error;
+        assert!(mape(&scaled) < 60.0, "v4 longe do alvo: {:.1}%", mape(&scaled));
+        assert!(mape(&scaled) < mape(&digits) / 2.0);
+        // Passos perto do teto do EMA (1.25), com folga para o arredondamento inteiro de S
+        let tail = &scaled.blocks[scaled.blocks.len() / 4..];
+        assert!(largest_step(tail) < 1.3f64.ln(), "passo de {:.2}", largest_step(tail));
+    }
+
+    // Vazão quadruplica 100 blocos-alvo adentro, com a dificuldade calibrada para a vazão inicial
+    fn step_change(algorithm: AlgorithmConfig) -> (Simulation, usize) {
+        let at_secs = 100.0 * TARGET_TIME;
+        let scenario = Scenario {
+            blocks: 400,
+            hashrate: vec![
+                HashratePoint { at_secs, candidates_per_sec: 20_000.0 },
+                HashratePoint { at_secs, candidates_per_sec: 80_000.0 },
+            ],
+            algorithm,
+            seed: 7,
+            calibrate: true,
+        };
+        let sim = simulate(&scenario, &Difficulty::default(), 4).unwrap();
+        let step = sim.blocks.iter().position(|b| b.candidates_per_sec > 20_000.0).unwrap();
+        (sim, step)
+    }
+
+    // Distância média, em log, do tempo esperado ao alvo
+    fn mean_deviation(blocks: &[SimulatedBlock]) -> f64 {
+        blocks.iter().map(|b| (b.expected_time / TARGET_TIME).ln().abs()).sum::<f64>() / blocks.len() as f64
+    }
+
+    #[test]
+    fn ema_follows_a_hashrate_step_within_its_clamp() {
+        let (ema, step) = step_change(AlgorithmConfig::ema());
+        let (window, window_step) = step_change(AlgorithmConfig::default());
+
+        // Volta a ±35% do alvo em poucos blocos depois do salto
+        let settled = ema.blocks[step..].iter().position(|b| (b.expected_time / TARGET_TIME).ln().abs() < 0.3);
+        assert!(settled.is_some_and(|blocks| blocks <= 20), "EMA assentou em {:?} blocos", settled);
+        let after = |sim: &Simulation, from: usize| mean_deviation(&sim.blocks[from..from + 50]);
+        assert!(after(&ema, step) < after(&window, window_step));
+
+        // Nenhum bloco mexe nos candidatos esperados além de `max_step`, a menos do arredondamento de S
+        let largest = ema.blocks.windows(2).map(|w| (w[1].expected_candidates / w[0].expected_candidates).ln().abs());
+        let largest = largest.fold(0.0, f64::max);
+        assert!(largest <= 1.25f64.ln() + 1e-3, "passo de {:.4}", largest);
+    }
+
+    fn constant(algorithm: AlgorithmConfig, blocks: usize, seed: u64) -> Simulation {
+        let scenario = Scenario {
+            blocks,
+            hashrate: vec![HashratePoint { at_secs: 0.0, candidates_per_sec: 500.0 }],
+            algorithm,
+            seed,
+            calibrate: false,
+        };
+        simulate(&scenario, &Difficulty::default(), 4).unwrap()
+    }
+
+    fn trajectory(sim: &Simulation) -> serde_json::Value {
+        serde_json::to_value(&sim.blocks).unwrap()
+    }
+
+    #[test]
+    fn window_and_ema_trajectories_are_deterministic_and_differ() {
+        let (window, ema) = (constant(AlgorithmConfig::default(), 500, 3), constant(AlgorithmConfig::ema(), 500, 3));
+        assert_eq!(trajectory(&window), trajectory(&constant(AlgorithmConfig::default(), 500, 3)));
+        assert_eq!(trajectory(&ema), trajectory(&constant(AlgorithmConfig::ema(), 500, 3)));
+        // Mesma semente, mesmo primeiro sorteio; as decisões é que divergem
+        assert_eq!(window.blocks[0].duration, ema.blocks[0].duration);
+        assert_ne!(trajectory(&window), trajectory(&ema));
+        assert_ne!(trajectory(&ema), trajectory(&constant(AlgorithmConfig::ema(), 500, 4)));
+        assert_eq!((window.algorithm.name(), ema.algorithm.name()), ("window", "ema"));
+    }
+
+    #[test]
+    fn constant_hashrate_converges_near_the_target() {
+        // Assenta um pouco acima do alvo (uns 10%), pelo passo discreto dos ajustes
+        for seed in [9, 19, 33] {
+            let sim = constant(AlgorithmConfig::ema(), 2_000, seed);
+            let settled = sim.summary.last_quarter_expected_times;
+            assert!((settled.mean - TARGET_TIME).abs() < 0.25 * TARGET_TIME, "média assentada {:.2}s", settled.mean);
+            // Os tempos sorteados acompanham os esperados
+            assert!((sim.summary.block_times.mean - TARGET_TIME).abs() < 0.25 * TARGET_TIME);
+            assert!(sim.summary.raises > 0 && sim.summary.lowers > 0);
+        }
+    }
+
+    #[test]
+    fn ten_thousand_blocks_run_quickly() {
+        let started = std::time::Instant::now();
+        let sim = constant(AlgorithmConfig::ema(), 10_000, 1);
+        assert_eq!(sim.blocks.len(), 10_000);
+        assert!(started.elapsed() < std::time::Duration::from_secs(1), "levou {:?}", started.elapsed());
+    }
+
+    #[test]
+    fn invalid_scenarios_are_refused() {
+        let scenario = |blocks, hashrate: Vec<(f64, f64)>| Scenario {
+            blocks,
+            hashrate: hashrate
+                .into_iter()
+                .map(|(at_secs, candidates_per_sec)| HashratePoint { at_secs, candidates_per_sec })
+                .collect(),
+            algorithm: AlgorithmConfig::default(),
+            seed: 0,
+            calibrate: false,
+        };
+        let error = |scenario: Scenario| simulate(&scenario, &Difficulty::default(), 4).unwrap_err();
+        assert_eq!(error(scenario(0, vec![(0.0, 1.0)])), "blocks must be between 1 and 100000");
+        assert_eq!(error(scenario(10, vec![])), "hashrate needs at least one point");
+        assert!(error(scenario(10, vec![(0.0, 0.0)])).contains("candidates_per_sec must be finite and positive"));
+        assert_eq!(error(scenario(10, vec![(5.0, 1.0), (1.0, 1.0)])), "hashrate points must be ordered by at_secs");
+    }
+}
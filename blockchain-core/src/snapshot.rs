@@ -0,0 +1,168 @@
+// src/snapshot.rs
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+use crate::block::Block;
+
+// Versão do formato do arquivo de exportação
+pub const SNAPSHOT_VERSION: u32 = 1;
+// Nível do deflate: os segmentos são gravados uma vez e lidos raramente
+const DEFLATE_LEVEL: u8 = 9;
+
+/// Resumo de um segmento: intervalo de blocos e SHA-256 dos bytes comprimidos.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SegmentDigest {
+    pub index: usize,
+    pub from_index: u64,
+    pub to_index: u64,
+    pub bytes: usize,
+    pub sha256: String,
+}
+
+/// Cabeçalho do arquivo; a importação confere tudo aqui antes de aplicar qualquer bloco.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub chain_id: String,
+    pub genesis_hash: String,
+    pub height: usize,
+    pub tip_hash: String,
+    pub cumulative_work: f64,
+    pub segments: Vec<SegmentDigest>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotError {
+    MissingManifest,
+    Manifest(String),
+    UnsupportedVersion(u32),
+    Truncated { expected: usize, found: usize },
+    TrailingData,
+    Segment { index: usize, reason: String },
+    Mismatch(&'static str),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::MissingManifest => write!(f, "archive has no manifest line"),
+            SnapshotError::Manifest(e) => write!(f, "invalid manifest: {}", e),
+            SnapshotError::UnsupportedVersion(v) => write!(f, "unsupported archive version {}", v),
+            SnapshotError::Truncated { expected, found } => {
+                write!(f, "archive truncated: manifest lists {} segments, found {}", expected, found)
+            }
+            SnapshotError::TrailingData => write!(f, "unexpected data after the last segment"),
+            SnapshotError::Segment { index, reason } => write!(f, "segment {}: {}", index, reason),
+            SnapshotError::Mismatch(field) => write!(f, "blocks do not match the manifest's {}", field),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// Trabalho esperado da cadeia: pelo teorema dos números primos, achar um primo perto de `p`
+/// custa cerca de `ln p` candidatos. O gênesis não conta.
+pub fn cumulative_work(blocks: &[Block]) -> f64 {
+    blocks.iter().skip(1).map(|b| b.prime.to_f64().ln()).sum()
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Arquivo de duas partes: o manifesto em JSON na primeira linha e, em seguida, uma linha
+/// base64url por segmento com os blocos no formato compacto, comprimidos com deflate.
+pub fn write_snapshot(blocks: &[Block], chain_id: &str, segment_size: usize) -> String {
+    let mut segments = Vec::new();
+    let mut lines = Vec::new();
+    for (index, chunk) in blocks.chunks(segment_size.max(1)).enumerate() {
+        let payload = chunk.iter().map(Block::to_compact_string).collect::<Vec<_>>().join("\n");
+        let compressed = miniz_oxide::deflate::compress_to_vec(payload.as_bytes(), DEFLATE_LEVEL);
+        segments.push(SegmentDigest {
+            index,
+            from_index: chunk[0].index,
+            to_index: chunk[chunk.len() - 1].index,
+            bytes: compressed.len(),
+            sha256: sha256_hex(&compressed),
+        });
+        lines.push(URL_SAFE_NO_PAD.encode(&compressed));
+    }
+    let manifest = Manifest {
+        version: SNAPSHOT_VERSION,
+        chain_id: chain_id.to_string(),
+        genesis_hash: blocks.first().map(|b| b.hash.clone()).unwrap_or_default(),
+        height: blocks.len(),
+        tip_hash: blocks.last().map(|b| b.hash.clone()).unwrap_or_default(),
+        cumulative_work: cumulative_work(blocks),
+        segments,
+    };
+    let mut out = serde_json::to_string(&manifest).expect("manifesto serializável");
+    for line in lines {
+        out.push('\n');
+        out.push_str(&line);
+    }
+    out.push('\n');
+    out
+}
+
+fn read_segment(digest: &SegmentDigest, line: &str) -> Result<Vec<Block>, SnapshotError> {
+    let invalid = |reason: String| SnapshotError::Segment { index: digest.index, reason };
+    let compressed = URL_SAFE_NO_PAD
+        .decode(line.trim())
+        .map_err(|_| invalid("not valid base64url".to_string()))?;
+    let found = sha256_hex(&compressed);
+    if found != digest.sha256 {
+        return Err(invalid(format!("sha256 mismatch: expected {}, found {}", digest.sha256, found)));
+    }
+    let payload = miniz_oxide::inflate::decompress_to_vec(&compressed)
+        .map_err(|e| invalid(format!("deflate error: {:?}", e.status)))?;
+    let payload = String::from_utf8(payload).map_err(|_| invalid("payload is not UTF-8".to_string()))?;
+    let blocks = payload
+        .lines()
+        .map(|s| Block::from_compact_string(s).map_err(|e| invalid(e.to_string())))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (first, last) = (blocks.first().map(|b| b.index), blocks.last().map(|b| b.index));
+    if first != Some(digest.from_index) || last != Some(digest.to_index) {
+        return Err(invalid(format!("does not cover blocks {}..={}", digest.from_index, digest.to_index)));
+    }
+    Ok(blocks)
+}
+
+/// Confere o manifesto e o SHA-256 de cada segmento antes de decodificar os blocos.
+/// A validação das regras de consenso fica com quem aplica os blocos.
+pub fn read_snapshot(archive: &str) -> Result<(Manifest, Vec<Block>), SnapshotError> {
+    let mut lines = archive.lines().filter(|l| !l.trim().is_empty());
+    let manifest: Manifest = serde_json::from_str(lines.next().ok_or(SnapshotError::MissingManifest)?)
+        .map_err(|e| SnapshotError::Manifest(e.to_string()))?;
+    if manifest.version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(manifest.version));
+    }
+    let segment_lines: Vec<&str> = lines.collect();
+    if segment_lines.len() < manifest.segments.len() {
+        return Err(SnapshotError::Truncated { expected: manifest.segments.len(), found: segment_lines.len() });
+    }
+    if segment_lines.len() > manifest.segments.len() {
+        return Err(SnapshotError::TrailingData);
+    }
+
+    let mut blocks = Vec::with_capacity(manifest.height);
+    for (digest, line) in manifest.segments.iter().zip(segment_lines) {
+        blocks.extend(read_segment(digest, line)?);
+    }
+    if blocks.len() != manifest.height {
+        return Err(SnapshotError::Mismatch("height"));
+    }
+    if blocks.first().map(|b| &b.hash) != Some(&manifest.genesis_hash) {
+        return Err(SnapshotError::Mismatch("genesis_hash"));
+    }
+    if blocks.last().map(|b| &b.hash) != Some(&manifest.tip_hash) {
+        return Err(SnapshotError::Mismatch("tip_hash"));
+    }
+    if (cumulative_work(&blocks) - manifest.cumulative_work).abs() > 1e-6 {
+        return Err(SnapshotError::Mismatch("cumulative_work"));
+    }
+    Ok((manifest, blocks))
+}
@@ -0,0 +1,277 @@
+// src/testkit.rs
+//! Construtores determinísticos para testes, deste crate e de quem embute o núcleo (feature `testkit`).
+use std::sync::atomic::AtomicBool;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::block::{compute_hash, witness, Block};
+use crate::chain::ChainState;
+use crate::epoch::DEFAULT_EPOCH_SIZE;
+use crate::mining::Difficulty;
+use crate::rules::RuleSchedule;
+use crate::seeded::SeededRace;
+use crate::signature::SigningKey;
+use crate::throttle::{Clock, Throttle};
+use crate::transaction::Transaction;
+use crate::width::PrimeWidth;
+
+// Sementes tentadas por bloco antes de desistir; só as regras v2+ recusam algum bloco minerado
+const MAX_ATTEMPTS: u64 = 64;
+
+/// Dificuldade em que um bloco sai em poucos candidatos: `a` e `c` de 4 dígitos e `b`, `d` até 1000,
+/// o que ainda costuma dar os 7 dígitos exigidos pelas regras v2. Com regras v4 ativas, `hash_scale`
+/// vira 1 no modelo.
+pub fn trivial_difficulty() -> Difficulty {
+    Difficulty { n_limit: 1000, min_digits: 4, min_prob: 0, generation: 0, hash_scale: 0, width: PrimeWidth::U64 }
+}
+
+/// Chave ed25519 fixa número `n`, para remetentes reprodutíveis.
+pub fn test_key(n: u8) -> SigningKey {
+    SigningKey::from_bytes(&[n; 32])
+}
+
+/// Ponto de partida de uma cadeia de teste: regras (com a largura dos primos), épocas, dificuldade da
+/// mineração e relógio dos blocos.
+#[derive(Debug, Clone)]
+pub struct GenesisConfig {
+    pub rules: RuleSchedule,
+    pub epoch_size: u64,
+    pub difficulty: Difficulty,
+    // Timestamp do primeiro bloco minerado e o passo entre blocos, em ms
+    pub start_ms: u64,
+    pub block_interval_ms: u64,
+}
+
+impl Default for GenesisConfig {
+    fn default() -> Self {
+        GenesisConfig {
+            rules: RuleSchedule::default(),
+            epoch_size: DEFAULT_EPOCH_SIZE,
+            difficulty: trivial_difficulty(),
+            start_ms: 1_700_000_000_000,
+            block_interval_ms: 10_000,
+        }
+    }
+}
+
+/// Cadeia válida minerada de verdade, sem relógio nem entropia do sistema: a mesma configuração e as
+/// mesmas sementes dão os mesmos blocos, hashes inclusos.
+#[derive(Debug, Clone)]
+pub struct ChainBuilder {
+    chain: ChainState,
+    config: GenesisConfig,
+}
+
+impl ChainBuilder {
+    pub fn new(config: GenesisConfig) -> Self {
+        let mut chain = ChainState::with_rules(config.rules.clone());
+        chain.set_epoch_size(config.epoch_size);
+        chain.difficulty = Difficulty { width: config.rules.prime_width(), ..config.difficulty.clone() };
+        ChainBuilder { chain, config }
+    }
+
+    /// Minera `n` blocos vazios, cada um com uma semente derivada de `seed` e da altura.
+    pub fn mine_n(mut self, n: usize, seed: u64) -> Self {
+        for _ in 0..n {
+            self = self.mine_with(Vec::new(), seed);
+        }
+        self
+    }
+
+    /// Minera um bloco com `transactions`, na ordem dada.
+    pub fn mine_with(mut self, transactions: Vec<Transaction>, seed: u64) -> Self {
+        let block = self.next_block(transactions, seed);
+        self.chain.append(block).expect("bloco minerado pelo testkit é válido");
+        self
+    }
+
+    /// O próximo bloco válido, sem anexá-lo.
+    pub fn next_block(&self, transactions: Vec<Transaction>, seed: u64) -> Block {
+        let prev = self.chain.tip();
+        let index = prev.index + 1;
+        // O gênesis tem timestamp 0; os seguintes andam `block_interval_ms` a partir do anterior
+        let timestamp =
+            if prev.index == 0 { self.config.start_ms } else { prev.timestamp + self.config.block_interval_ms };
+        let template = self.chain.template().timestamp(timestamp).transactions(transactions);
+        let stop = AtomicBool::new(false);
+        for attempt in 0..MAX_ATTEMPTS {
+            let race = SeededRace::new(seed ^ index.rotate_left(32) ^ attempt);
+            let run = race.run(0, &template, &self.chain.difficulty, &stop, &mut Throttle::unlimited());
+            let block = race.settle(vec![run]).expect("mineração sem parada sempre acha um bloco").block;
+            if crate::rules::validate_block(&block, prev, self.chain.rules()).is_ok() {
+                return block;
+            }
+        }
+        panic!("nenhum bloco válido em {} tentativas; dificuldade incompatível com as regras", MAX_ATTEMPTS)
+    }
+
+    pub fn chain(&self) -> &ChainState {
+        &self.chain
+    }
+
+    pub fn build(self) -> ChainState {
+        self.chain
+    }
+
+    pub fn blocks(self) -> Vec<Block> {
+        self.chain.blocks().to_vec()
+    }
+}
+
+/// Bloco válido sobre a ponta de `chain`, estragado de um jeito só (o hash é recalculado, salvo em
+/// `bad_hash`), para conferir que cada regra recusa o que deve. Cada preset cita o `invariant()` esperado.
+#[derive(Debug, Clone)]
+pub struct InvalidBlock {
+    block: Block,
+    keep_hash: bool,
+}
+
+impl InvalidBlock {
+    pub fn on(chain: &ChainState, seed: u64) -> Self {
+        let builder = ChainBuilder { chain: chain.clone(), config: GenesisConfig::default() };
+        InvalidBlock { block: builder.next_block(Vec::new(), seed), keep_hash: false }
+    }
+
+    /// `prev_hash` que não é o da ponta: `prev_hash_link`.
+    pub fn bad_link(mut self) -> Self {
+        self.block.prev_hash = "0".repeat(64);
+        self
+    }
+
+    /// Testemunha coprima cuja soma é composta: `primality`.
+    pub fn composite_prime(mut self) -> Self {
+        // 1*1 + 2*4 = 9
+        self.set_witness(1, 2, 4, 1);
+        self
+    }
+
+    /// `gcd(a, b) > 1`: `coprime_witness`.
+    pub fn gcd_violation(mut self) -> Self {
+        self.set_witness(2, 4, 3, 1);
+        self
+    }
+
+    /// `a` e `c` com quantidades diferentes de dígitos e primo de 2 dígitos: `digit_structure` (regras v2+).
+    pub fn bad_digits(mut self) -> Self {
+        // 10*1 + 1*3 = 13
+        self.set_witness(10, 1, 3, 1);
+        self
+    }
+
+    /// Transação com assinatura forjada (e raiz das transações recalculada): `tx_signature`.
+    pub fn bad_signature(mut self) -> Self {
+        let mut tx = Transaction::sign(&test_key(1), "bob", 1, 0);
+        tx.signature = "00".repeat(64);
+        self.block.transactions = vec![tx];
+        self.block.tx_root = crate::transaction::tx_root(&self.block.transactions);
+        self
+    }
+
+    /// Recompensa uma unidade acima da do calendário: `block_reward`.
+    pub fn bad_reward(mut self) -> Self {
+        self.block.reward += 1;
+        self
+    }
+
+    /// Mantém o hash do bloco válido, que deixa de bater com o conteúdo: `block_hash`.
+    pub fn bad_hash(mut self) -> Self {
+        self.keep_hash = true;
+        self
+    }
+
+    fn set_witness(&mut self, a: u64, b: u64, c: u64, d: u64) {
+        let block = &mut self.block;
+        (block.a, block.b, block.c, block.d) = (a, b, c, d);
+        block.prime = witness(a, b, c, d).expect("testemunha pequena");
+    }
+
+    pub fn build(self) -> Block {
+        let mut block = self.block;
+        if self.keep_hash {
+            block.hash = format!("{}00", &block.hash[2..]);
+        } else {
+            block.hash = compute_hash(&block);
+        }
+        block
+    }
+}
+
+/// Relógio que só anda quando mandado; `sleep` avança na hora em vez de esperar.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: Mutex<Duration>,
+}
+
+impl ManualClock {
+    pub fn at(now: Duration) -> Self {
+        ManualClock { now: Mutex::new(now) }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Regras v2 desde o primeiro bloco minerado, para que `bad_digits` tenha o que violar
+    fn v2_config() -> GenesisConfig {
+        let mut rules = RuleSchedule::default();
+        rules.schedule(2, 1, 1).unwrap();
+        GenesisConfig { rules, ..GenesisConfig::default() }
+    }
+
+    fn hashes(config: GenesisConfig, seed: u64) -> Vec<String> {
+        ChainBuilder::new(config).mine_n(5, seed).blocks().into_iter().map(|b| b.hash).collect()
+    }
+
+    #[test]
+    fn mine_n_is_reproducible() {
+        assert_eq!(hashes(GenesisConfig::default(), 42), hashes(GenesisConfig::default(), 42));
+        assert_eq!(hashes(v2_config(), 42), hashes(v2_config(), 42));
+        assert_ne!(hashes(GenesisConfig::default(), 42), hashes(GenesisConfig::default(), 43));
+    }
+
+    #[test]
+    fn mine_n_builds_a_valid_chain() {
+        let chain = ChainBuilder::new(v2_config()).mine_n(4, 1).build();
+        assert_eq!(chain.height(), 5);
+        let rebuilt = ChainState::from_blocks_with_rules(chain.blocks().to_vec(), chain.rules().clone()).unwrap();
+        assert_eq!(rebuilt.tip().hash, chain.tip().hash);
+    }
+
+    type Preset = fn(InvalidBlock) -> InvalidBlock;
+
+    #[test]
+    fn each_preset_breaks_its_invariant() {
+        let chain = ChainBuilder::new(v2_config()).mine_n(2, 3).build();
+        let presets: [(Preset, &str); 7] = [
+            (InvalidBlock::bad_link, "prev_hash_link"),
+            (InvalidBlock::composite_prime, "primality"),
+            (InvalidBlock::gcd_violation, "coprime_witness"),
+            (InvalidBlock::bad_digits, "digit_structure"),
+            (InvalidBlock::bad_signature, "tx_signature"),
+            (InvalidBlock::bad_reward, "block_reward"),
+            (InvalidBlock::bad_hash, "block_hash"),
+        ];
+        for (preset, invariant) in presets {
+            let block = preset(InvalidBlock::on(&chain, 9)).build();
+            let error = chain.clone().append(block).expect_err(invariant);
+            assert_eq!(error.invariant(), invariant, "{}", error);
+        }
+        // Sem preset, o mesmo bloco entra
+        assert!(chain.clone().append(InvalidBlock::on(&chain, 9).build()).is_ok());
+    }
+}
@@ -0,0 +1,237 @@
+// src/throttle.rs
+use serde::Serialize;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Fonte de tempo da mineração; trocável por um relógio simulado.
+pub trait Clock: Send + Sync {
+    /// Tempo de parede desde a época unix.
+    fn now(&self) -> Duration;
+    fn sleep(&self, duration: Duration);
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Fração do tempo de parede que a mineração pode ocupar, entre 0.1 e 1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Intensity(f64);
+
+impl Intensity {
+    pub const FULL: Intensity = Intensity(1.0);
+
+    pub fn new(value: f64) -> Result<Self, String> {
+        if !(0.1..=1.0).contains(&value) {
+            return Err(format!("mining intensity must be between 0.1 and 1.0, got {}", value));
+        }
+        Ok(Intensity(value))
+    }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    /// Pausa após `busy` de trabalho para que trabalho / (trabalho + pausa) fique na intensidade.
+    pub fn pause_after(self, busy: Duration) -> Duration {
+        busy.mul_f64((1.0 - self.0) / self.0)
+    }
+}
+
+/// Tempo ocupado e tempo de parede somados de todos os laços de mineração.
+#[derive(Debug, Default)]
+pub struct DutyMeter {
+    busy_nanos: AtomicU64,
+    wall_nanos: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DutyCycle {
+    pub busy_secs: f64,
+    pub wall_secs: f64,
+    pub effective: Option<f64>,
+}
+
+impl DutyMeter {
+    pub fn record(&self, busy: Duration, wall: Duration) {
+        self.busy_nanos.fetch_add(busy.as_nanos() as u64, Ordering::Relaxed);
+        self.wall_nanos.fetch_add(wall.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub fn duty_cycle(&self) -> DutyCycle {
+        let busy = self.busy_nanos.load(Ordering::Relaxed);
+        let wall = self.wall_nanos.load(Ordering::Relaxed);
+        DutyCycle {
+            busy_secs: busy as f64 / 1e9,
+            wall_secs: wall as f64 / 1e9,
+            effective: (wall > 0).then(|| busy as f64 / wall as f64),
+        }
+    }
+}
+
+/// Ritmo de um laço de mineração em fatias: `pace` é chamado entre lotes de candidatos,
+/// mede a fatia no relógio e dorme o proporcional à intensidade.
+pub struct Throttle {
+    intensity: Intensity,
+    clock: Arc<dyn Clock>,
+    meter: Arc<DutyMeter>,
+    slice_start: Duration,
+}
+
+impl Throttle {
+    pub fn new(intensity: Intensity, clock: Arc<dyn Clock>, meter: Arc<DutyMeter>) -> Self {
+        let slice_start = clock.now();
+        Throttle { intensity, clock, meter, slice_start }
+    }
+
+    /// Sem limite e sem medição compartilhada.
+    pub fn unlimited() -> Self {
+        Throttle::new(Intensity::FULL, Arc::new(SystemClock), Arc::new(DutyMeter::default()))
+    }
+
+    pub fn pace(&mut self) {
+        let busy = self.clock.now().saturating_sub(self.slice_start);
+        let pause = self.intensity.pause_after(busy);
+        if !pause.is_zero() {
+            self.clock.sleep(pause);
+        }
+        let end = self.clock.now();
+        self.meter.record(busy, end.saturating_sub(self.slice_start));
+        self.slice_start = end;
+    }
+}
+
+/// Horas (UTC) em que a mineração em segundo plano pode rodar, no formato `inicio-fim,...`.
+/// O fim é exclusivo e a janela pode virar a meia-noite (`22-6`); vazio libera o dia todo.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MiningSchedule {
+    windows: Vec<(u32, u32)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleParseError(String);
+
+impl fmt::Display for ScheduleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid mining hours {:?}: expected start-end with hours 0-24", self.0)
+    }
+}
+
+impl std::error::Error for ScheduleParseError {}
+
+impl MiningSchedule {
+    pub fn parse(spec: &str) -> Result<Self, ScheduleParseError> {
+        let windows = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|w| !w.is_empty())
+            .map(|window| {
+                let invalid = || ScheduleParseError(window.to_string());
+                let (start, end) = window.split_once('-').ok_or_else(invalid)?;
+                let start: u32 = start.trim().parse().map_err(|_| invalid())?;
+                let end: u32 = end.trim().parse().map_err(|_| invalid())?;
+                if start > 23 || end > 24 || start == end {
+                    return Err(invalid());
+                }
+                Ok((start, end))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(MiningSchedule { windows })
+    }
+
+    pub fn is_open(&self, now: Duration) -> bool {
+        let hour = (now.as_secs() / 3600 % 24) as u32;
+        self.windows.is_empty()
+            || self.windows.iter().any(|&(start, end)| {
+                if start < end { (start..end).contains(&hour) } else { hour >= start || hour < end }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // Relógio simulado: o trabalho e as pausas só andam com ele
+    #[derive(Default)]
+    struct SimClock(Mutex<Duration>);
+
+    impl SimClock {
+        fn at(now: Duration) -> Arc<Self> {
+            Arc::new(SimClock(Mutex::new(now)))
+        }
+
+        fn work(&self, cost: Duration) {
+            *self.0.lock().unwrap() += cost;
+        }
+    }
+
+    impl Clock for SimClock {
+        fn now(&self) -> Duration {
+            *self.0.lock().unwrap()
+        }
+
+        fn sleep(&self, duration: Duration) {
+            self.work(duration);
+        }
+    }
+
+    #[test]
+    fn quarter_intensity_duty_cycle() {
+        let clock = SimClock::at(Duration::ZERO);
+        let meter = Arc::new(DutyMeter::default());
+        let mut throttle = Throttle::new(Intensity::new(0.25).unwrap(), clock.clone(), meter.clone());
+        // Lote de candidatos com custo fixo de 4 ms
+        for _ in 0..200 {
+            clock.work(Duration::from_millis(4));
+            throttle.pace();
+        }
+        let cycle = meter.duty_cycle();
+        let effective = cycle.effective.unwrap();
+        assert!((effective - 0.25).abs() < 0.01, "ciclo efetivo {}", effective);
+        assert!((cycle.busy_secs - 0.8).abs() < 1e-6);
+        assert!((cycle.wall_secs - 3.2).abs() < 1e-3, "parede {}", cycle.wall_secs);
+        assert_eq!(clock.now(), Duration::from_nanos((cycle.wall_secs * 1e9).round() as u64));
+    }
+
+    #[test]
+    fn full_intensity_never_pauses() {
+        let clock = SimClock::at(Duration::ZERO);
+        let meter = Arc::new(DutyMeter::default());
+        let mut throttle = Throttle::new(Intensity::FULL, clock.clone(), meter.clone());
+        clock.work(Duration::from_millis(7));
+        throttle.pace();
+        assert_eq!((clock.now(), meter.duty_cycle().effective), (Duration::from_millis(7), Some(1.0)));
+        assert!(Intensity::new(0.05).is_err() && Intensity::new(1.5).is_err());
+    }
+
+    #[test]
+    fn schedule_gate_follows_the_clock() {
+        let hour = |h: u64| Duration::from_secs(19_000 * 86_400 + h * 3600);
+        let clock = SimClock::at(hour(8));
+        let office = MiningSchedule::parse("9-17").unwrap();
+        assert!(!office.is_open(clock.now()));
+        clock.work(Duration::from_secs(3600));
+        assert!(office.is_open(clock.now()));
+        clock.work(Duration::from_secs(8 * 3600));
+        assert!(!office.is_open(clock.now()), "o fim da janela é exclusivo");
+
+        let night = MiningSchedule::parse("22-6").unwrap();
+        assert!(night.is_open(hour(23)) && night.is_open(hour(5)) && !night.is_open(hour(6)));
+        assert!(MiningSchedule::parse("").unwrap().is_open(hour(12)));
+        assert!(MiningSchedule::parse("9").is_err() && MiningSchedule::parse("5-5").is_err());
+        assert!(MiningSchedule::parse("20-25").is_err());
+    }
+}
@@ -0,0 +1,132 @@
+// src/transaction.rs
+use ed25519_dalek::Signer;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use crate::block::Block;
+use crate::merkle::{compute_merkle_root, merkle_proof, ProofStep};
+use crate::signature::{verify_signature, SigningKey};
+
+/// Raiz de um bloco sem transações: SHA-256 da entrada vazia.
+pub const EMPTY_TX_ROOT: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// Transferência assinada por `from` (chave pública ed25519 em hex).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    pub from: String,
+    pub to: String,
+    pub amount: u64,
+    pub nonce: u64,
+    pub signature: String,
+}
+
+impl Transaction {
+    pub fn sign(key: &SigningKey, to: impl Into<String>, amount: u64, nonce: u64) -> Self {
+        let mut tx = Transaction {
+            from: hex::encode(key.verifying_key().to_bytes()),
+            to: to.into(),
+            amount,
+            nonce,
+            signature: String::new(),
+        };
+        tx.signature = hex::encode(key.sign(&tx.signing_message()).to_bytes());
+        tx
+    }
+
+    pub fn signing_message(&self) -> Vec<u8> {
+        format!("proof-of-prime:tx:{}:{}:{}:{}", self.from, self.to, self.amount, self.nonce).into_bytes()
+    }
+
+    pub fn verify_signature(&self) -> bool {
+        verify_signature(&self.from, &self.signature, &self.signing_message())
+    }
+
+    /// Identificador: SHA-256 da mensagem assinada e da assinatura.
+    pub fn id(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.signing_message());
+        hasher.update(self.signature.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+fn ids(transactions: &[Transaction]) -> Vec<String> {
+    transactions.iter().map(Transaction::id).collect()
+}
+
+/// Raiz de Merkle dos ids na ordem em que as transações aparecem no bloco (a ordem de nonce
+/// de cada remetente precisa ser preservada, então não há ordenação).
+pub fn tx_root(transactions: &[Transaction]) -> String {
+    if transactions.is_empty() {
+        return EMPTY_TX_ROOT.to_string();
+    }
+    compute_merkle_root(&ids(transactions))
+}
+
+/// Posição da transação `txid` e sua prova de inclusão, verificável com
+/// `verify_merkle_proof(tx_root, txid, proof)`.
+pub fn tx_proof(transactions: &[Transaction], txid: &str) -> Option<(usize, Vec<ProofStep>)> {
+    let ids = ids(transactions);
+    let position = ids.iter().position(|id| id == txid)?;
+    Some((position, merkle_proof(&ids, position)?))
+}
+
+/// Movimento de um endereço nas transações incluídas e nas recompensas de bloco (regras v6) que recebeu.
+/// Transferências não são conferidas contra o saldo, então ele pode ser negativo.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Balance {
+    pub received: u128,
+    pub sent: u128,
+    pub mined: u128,
+    pub transactions: u64,
+}
+
+impl Balance {
+    pub fn net(&self) -> i128 {
+        self.received as i128 + self.mined as i128 - self.sent as i128
+    }
+}
+
+/// Saldos de todos os endereços que aparecem nas transações ou no coinbase de `blocks`.
+pub fn balances(blocks: &[Block]) -> HashMap<String, Balance> {
+    let mut balances: HashMap<String, Balance> = HashMap::new();
+    for block in blocks.iter().filter(|b| b.reward > 0 && !b.coinbase.is_empty()) {
+        balances.entry(block.coinbase.clone()).or_default().mined += block.reward as u128;
+    }
+    for tx in blocks.iter().flat_map(|b| &b.transactions) {
+        let sender = balances.entry(tx.from.clone()).or_default();
+        sender.sent += tx.amount as u128;
+        sender.transactions += 1;
+        let receiver = balances.entry(tx.to.clone()).or_default();
+        receiver.received += tx.amount as u128;
+        // Transferência para si mesmo conta uma vez
+        if tx.to != tx.from {
+            receiver.transactions += 1;
+        }
+    }
+    balances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockBuilder;
+
+    #[test]
+    fn enormous_amounts_sum_without_wrapping() {
+        let key = SigningKey::from_bytes(&[3; 32]);
+        let mut blocks = vec![Block::genesis()];
+        for nonce in 0..3 {
+            let tx = Transaction::sign(&key, "bob", u64::MAX, nonce);
+            let builder = BlockBuilder::on(blocks.last().unwrap()).witness(1, 1, 2, 1).transactions(vec![tx]);
+            blocks.push(builder.reward(u64::MAX).coinbase("miner").build());
+        }
+        let balances = balances(&blocks);
+        let three = 3 * u64::MAX as u128;
+        assert_eq!(balances["miner"].mined, three);
+        assert_eq!((balances["bob"].received, balances["bob"].transactions), (three, 3));
+        let sender = &balances[&blocks[1].transactions[0].from];
+        assert_eq!((sender.sent, sender.net()), (three, -(three as i128)));
+    }
+}
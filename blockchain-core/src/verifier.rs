@@ -0,0 +1,127 @@
+// src/verifier.rs
+use crate::block::{Block, VerifyError};
+use crate::rules::{validate_block, RuleSchedule};
+
+/// Verificador de cadeias completas, utilizável sem o servidor.
+#[derive(Debug, Clone, Default)]
+pub struct PoWVerifier {
+    min_digits: Option<u32>,
+    rules: RuleSchedule,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainError {
+    Empty,
+    GenesisMismatch,
+    Block { index: usize, error: VerifyError },
+    TooFewDigits { index: usize, digits: u32, min_digits: u32 },
+    // Validação interrompida antes do bloco `index`; não diz nada sobre a validade da cadeia
+    Cancelled { index: usize },
+}
+
+impl std::fmt::Display for ChainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChainError::Empty => write!(f, "chain is empty"),
+            ChainError::GenesisMismatch => write!(f, "genesis block does not match"),
+            ChainError::Block { index, error } => write!(f, "block {}: {}", index, error),
+            ChainError::TooFewDigits { index, digits, min_digits } => {
+                write!(f, "block {}: prime has {} digits, minimum is {}", index, digits, min_digits)
+            }
+            ChainError::Cancelled { index } => write!(f, "validation cancelled at block {}", index),
+        }
+    }
+}
+
+impl std::error::Error for ChainError {}
+
+impl ChainError {
+    pub fn invariant(&self) -> &'static str {
+        match self {
+            ChainError::Empty => "non_empty",
+            ChainError::GenesisMismatch => "genesis",
+            ChainError::Block { error, .. } => error.invariant(),
+            ChainError::TooFewDigits { .. } => "min_digits",
+            ChainError::Cancelled { .. } => "cancelled",
+        }
+    }
+
+    /// Índice do primeiro bloco inválido.
+    pub fn index(&self) -> usize {
+        match self {
+            ChainError::Empty | ChainError::GenesisMismatch => 0,
+            ChainError::Block { index, .. }
+            | ChainError::TooFewDigits { index, .. }
+            | ChainError::Cancelled { index } => *index,
+        }
+    }
+}
+
+impl PoWVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exige que todo primo (exceto o gênesis) tenha pelo menos `min_digits` dígitos.
+    pub fn with_min_digits(mut self, min_digits: u32) -> Self {
+        self.min_digits = Some(min_digits);
+        self
+    }
+
+    /// Usa outra tabela de ativação de regras no lugar de `RULES_ACTIVATION`.
+    pub fn with_rules(mut self, rules: RuleSchedule) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn verify_block(&self, block: &Block, prev: &Block) -> Result<(), VerifyError> {
+        validate_block(block, prev, &self.rules)
+    }
+
+    pub fn verify_chain(&self, blocks: &[Block]) -> Result<(), ChainError> {
+        if blocks.is_empty() {
+            return Err(ChainError::Empty);
+        }
+        for (index, pair) in blocks.windows(2).enumerate() {
+            let index = index + 1;
+            self.verify_block(&pair[1], &pair[0])
+                .map_err(|error| ChainError::Block { index, error })?;
+            if let Some(min_digits) = self.min_digits {
+                let digits = pair[1].prime.digits();
+                if digits < min_digits {
+                    return Err(ChainError::TooFewDigits { index, digits, min_digits });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockBuilder;
+
+    // Cadeia montada à mão, sem minerar: compila e roda sem a feature `mining`
+    fn chain(witnesses: &[(u64, u64, u64, u64)]) -> Vec<Block> {
+        let mut blocks = vec![Block::genesis()];
+        for &(a, b, c, d) in witnesses {
+            let block = BlockBuilder::on(blocks.last().unwrap()).witness(a, b, c, d).build();
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    #[test]
+    fn hand_built_chain_verifies_without_mining() {
+        let verifier = PoWVerifier::new();
+        assert_eq!(verifier.verify_chain(&chain(&[(1, 1, 2, 1), (2, 1, 3, 1)])), Ok(()));
+        assert_eq!(verifier.verify_chain(&[]), Err(ChainError::Empty));
+
+        // 1*1 + 1*3 = 4 não é primo
+        let composite = verifier.verify_chain(&chain(&[(1, 1, 2, 1), (1, 1, 3, 1)]));
+        assert!(matches!(composite, Err(ChainError::Block { index: 2, .. })), "{:?}", composite);
+        let short = PoWVerifier::new().with_min_digits(2).verify_chain(&chain(&[(1, 1, 2, 1)]));
+        assert_eq!(short, Err(ChainError::TooFewDigits { index: 1, digits: 1, min_digits: 2 }));
+    }
+}
@@ -0,0 +1,289 @@
+// src/width.rs
+//! Largura inteira dos primos de uma cadeia: u64 (o padrão e o de toda cadeia anterior ao campo) ou u128.
+//! A largura é declarada no gênesis, junto das regras, e um bloco com primo mais largo é recusado.
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+use crate::math::{digits, is_twin_prime, miller_rabin_deterministic, miller_rabin_u128};
+
+// Maior inteiro exato num double (2^53); acima dele o primo sai como string decimal no JSON
+pub const MAX_JSON_SAFE_INTEGER: u64 = 1 << 53;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrimeWidth {
+    #[default]
+    U64,
+    U128,
+}
+
+impl PrimeWidth {
+    pub fn name(self) -> &'static str {
+        match self {
+            PrimeWidth::U64 => "u64",
+            PrimeWidth::U128 => "u128",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<PrimeWidth> {
+        [PrimeWidth::U64, PrimeWidth::U128].into_iter().find(|width| width.name() == name)
+    }
+
+    /// Maior primo que cabe na largura.
+    pub fn max_value(self) -> u128 {
+        match self {
+            PrimeWidth::U64 => u64::MAX as u128,
+            PrimeWidth::U128 => u128::MAX,
+        }
+    }
+
+    /// Teto de `min_digits`. `a` e `c` são sempre u64 (19 dígitos no máximo); na u64, com 19 dígitos
+    /// o testemunho `a*d + b*c` estoura e nenhum candidato sobrevive.
+    pub fn max_min_digits(self) -> u32 {
+        match self {
+            PrimeWidth::U64 => 18,
+            PrimeWidth::U128 => 19,
+        }
+    }
+
+    /// Maior `n_limit` com que `n = a*d + b*c` cabe na largura para `a`, `c` de `min_digits` dígitos.
+    pub fn witness_n_limit(self, min_digits: u32) -> u64 {
+        (self.max_value() / 2 / 10u128.pow(min_digits)).min(u64::MAX as u128) as u64
+    }
+}
+
+impl fmt::Display for PrimeWidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Primo de um bloco. Na forma canônica `U128` só guarda valores acima de `u64::MAX`, o que mantém
+/// igualdade, ordem e hash coerentes entre as variantes: construa por `new` ou `From<u64>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PrimeValue {
+    U64(u64),
+    U128(u128),
+}
+
+impl PrimeValue {
+    pub fn new(value: u128) -> Self {
+        u64::try_from(value).map_or(PrimeValue::U128(value), PrimeValue::U64)
+    }
+
+    pub fn get(self) -> u128 {
+        match self {
+            PrimeValue::U64(n) => n as u128,
+            PrimeValue::U128(n) => n,
+        }
+    }
+
+    pub fn as_u64(self) -> Option<u64> {
+        match self {
+            PrimeValue::U64(n) => Some(n),
+            PrimeValue::U128(_) => None,
+        }
+    }
+
+    /// Menor largura em que o valor cabe.
+    pub fn width(self) -> PrimeWidth {
+        match self {
+            PrimeValue::U64(_) => PrimeWidth::U64,
+            PrimeValue::U128(_) => PrimeWidth::U128,
+        }
+    }
+
+    /// Quantidade de dígitos decimais.
+    pub fn digits(self) -> u32 {
+        match self {
+            PrimeValue::U64(n) => digits(n),
+            PrimeValue::U128(n) => n.ilog10() + 1,
+        }
+    }
+
+    /// Bytes que entram no hash do bloco: os 8 de sempre na u64, 16 acima dela.
+    pub fn to_le_bytes(self) -> Vec<u8> {
+        match self {
+            PrimeValue::U64(n) => n.to_le_bytes().to_vec(),
+            PrimeValue::U128(n) => n.to_le_bytes().to_vec(),
+        }
+    }
+
+    /// Miller-Rabin determinístico na u64; acima dela, `miller_rabin_u128`.
+    pub fn is_prime(self) -> bool {
+        match self {
+            PrimeValue::U64(n) => miller_rabin_deterministic(n),
+            PrimeValue::U128(n) => miller_rabin_u128(n),
+        }
+    }
+
+    /// O valor é primo e `p - 2` ou `p + 2` também é.
+    pub fn is_twin_prime(self) -> bool {
+        match self {
+            PrimeValue::U64(n) => is_twin_prime(n),
+            PrimeValue::U128(n) => {
+                miller_rabin_u128(n) && (miller_rabin_u128(n - 2) || n.checked_add(2).is_some_and(miller_rabin_u128))
+            }
+        }
+    }
+
+    /// Resto da divisão por `modulus`.
+    pub fn residue(self, modulus: u64) -> u64 {
+        (self.get() % modulus as u128) as u64
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.get() as f64
+    }
+}
+
+impl From<u64> for PrimeValue {
+    fn from(n: u64) -> Self {
+        PrimeValue::U64(n)
+    }
+}
+
+impl fmt::Display for PrimeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrimeValue::U64(n) => write!(f, "{}", n),
+            PrimeValue::U128(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+impl Serialize for PrimeValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.as_u64() {
+            Some(n) if n <= MAX_JSON_SAFE_INTEGER => serializer.serialize_u64(n),
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+struct PrimeValueVisitor;
+
+impl Visitor<'_> for PrimeValueVisitor {
+    type Value = PrimeValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a non-negative integer or a decimal string")
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<PrimeValue, E> {
+        Ok(PrimeValue::U64(n))
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<PrimeValue, E> {
+        u64::try_from(n).map(PrimeValue::U64).map_err(|_| E::invalid_value(de::Unexpected::Signed(n), &self))
+    }
+
+    fn visit_u128<E: de::Error>(self, n: u128) -> Result<PrimeValue, E> {
+        Ok(PrimeValue::new(n))
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<PrimeValue, E> {
+        // Só dígitos: `parse` aceitaria um `+` na frente
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(E::invalid_value(de::Unexpected::Str(s), &self));
+        }
+        s.parse().map(PrimeValue::new).map_err(|_| E::custom(format!("{} does not fit in u128", s)))
+    }
+}
+
+impl<'de> Deserialize<'de> for PrimeValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<PrimeValue, D::Error> {
+        deserializer.deserialize_any(PrimeValueVisitor)
+    }
+}
+
+/// Troca de largura recusada.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WidthError {
+    ChainNotEmpty { height: usize },
+}
+
+impl fmt::Display for WidthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WidthError::ChainNotEmpty { height } => {
+                write!(f, "prime width is fixed once blocks are mined (height {})", height)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WidthError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{Block, BlockBuilder, VerifyError};
+    use crate::chain::ChainState;
+    use crate::rules::RuleSchedule;
+
+    fn rules(width: PrimeWidth) -> RuleSchedule {
+        let mut rules = RuleSchedule::default();
+        rules.set_prime_width(width);
+        rules
+    }
+
+    // 10^24 + 7, primo de 25 dígitos: a = 10^18, d = 10^6, b = 7, c = 1
+    fn wide_block() -> Block {
+        BlockBuilder::on(&Block::genesis()).timestamp(1).witness(1_000_000_000_000_000_000, 7, 1, 1_000_000).build()
+    }
+
+    #[cfg(feature = "mining")]
+    #[test]
+    fn mine_and_validate_on_both_widths() {
+        use crate::testkit::{trivial_difficulty, ChainBuilder, GenesisConfig};
+
+        // Na u128, `a` e `c` de 19 dígitos já levam o primo além de u64::MAX
+        let wide = crate::Difficulty { n_limit: 1_000, min_digits: 19, ..trivial_difficulty() };
+        for (width, difficulty) in [(PrimeWidth::U64, trivial_difficulty()), (PrimeWidth::U128, wide)] {
+            let config = GenesisConfig { rules: rules(width), difficulty, ..GenesisConfig::default() };
+            let blocks = ChainBuilder::new(config).mine_n(3, 11).blocks();
+            assert!(blocks[1..].iter().all(|block| block.prime.width() == width && block.prime.is_prime()));
+
+            let json = serde_json::to_string(&blocks).unwrap();
+            let decoded: Vec<Block> = serde_json::from_str(&json).unwrap();
+            assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+            let replayed = ChainState::from_blocks_with_rules(decoded, rules(width)).unwrap();
+            assert_eq!(replayed.tip().hash, blocks[3].hash);
+            // Acima de 2^53 o primo sai como string decimal
+            let prime = &serde_json::to_value(&blocks[1]).unwrap()["prime"];
+            assert_eq!(prime.is_string(), blocks[1].prime.get() > MAX_JSON_SAFE_INTEGER as u128);
+        }
+    }
+
+    #[test]
+    fn wide_prime_is_refused_on_a_u64_chain() {
+        let block = wide_block();
+        assert_eq!((block.prime.get(), block.prime.digits()), (10u128.pow(24) + 7, 25));
+
+        let mut narrow = ChainState::new();
+        let error = narrow.append(block.clone()).unwrap_err();
+        assert!(matches!(error, VerifyError::PrimeWidth { width: PrimeWidth::U64, .. }));
+        assert_eq!(error.invariant(), "prime_width");
+        assert_eq!(
+            error.to_string(),
+            format!("prime 1000000000000000000000007 (25 digits) exceeds the chain's u64 width (at most {})", u64::MAX)
+        );
+        assert_eq!(narrow.height(), 1);
+
+        let mut wide = ChainState::with_rules(rules(PrimeWidth::U128));
+        wide.append(block).unwrap();
+        assert_eq!(wide.tip().prime.width(), PrimeWidth::U128);
+    }
+
+    #[test]
+    fn prime_values_are_canonical_and_parse_from_either_form() {
+        assert_eq!(PrimeValue::new(u64::MAX as u128), PrimeValue::U64(u64::MAX));
+        assert_eq!(PrimeValue::new(u64::MAX as u128 + 1).width(), PrimeWidth::U128);
+        let parsed: Vec<PrimeValue> = serde_json::from_str(r#"[101, "101", "1000000000000000000000007"]"#).unwrap();
+        assert_eq!(parsed, [PrimeValue::U64(101), PrimeValue::U64(101), PrimeValue::new(10u128.pow(24) + 7)]);
+        assert_eq!(serde_json::to_string(&PrimeValue::U64(MAX_JSON_SAFE_INTEGER)).unwrap(), "9007199254740992");
+        assert_eq!(serde_json::to_string(&PrimeValue::U64(MAX_JSON_SAFE_INTEGER + 1)).unwrap(), "\"9007199254740993\"");
+    }
+}
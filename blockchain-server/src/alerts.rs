@@ -0,0 +1,332 @@
+// src/alerts.rs
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+use crate::config::{AlertConfig, AlertConfigPatch};
+use crate::errors::ApiError;
+use crate::state::AppState;
+use crate::webhooks;
+
+// Alertas (ativos e resolvidos) mantidos para /alerts
+const KEPT_ALERTS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    NoBlockMined,
+    ValidationFailure,
+    NoPeers,
+    MempoolFull,
+    DifficultyClamped,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertState {
+    Active,
+    Resolved,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub id: u64,
+    pub kind: AlertKind,
+    pub state: AlertState,
+    pub message: String,
+    pub fired_at: u64,
+    pub resolved_at: Option<u64>,
+}
+
+/// Fotografia do nó usada numa avaliação; `now` vem de fora para a avaliação ser determinística.
+#[derive(Debug, Clone, Default)]
+pub struct AlertInputs {
+    pub now: u64,
+    pub last_block_at: u64,
+    pub last_validation_failure_at: Option<u64>,
+    pub peers_registered: usize,
+    pub peers_healthy: usize,
+    pub peers_ever_registered: bool,
+    pub mempool_size: usize,
+    pub clamp_streak: usize,
+}
+
+/// Condições disparadas (com mensagem) e as que estão normais (`None`). Sem efeitos colaterais.
+pub fn evaluate(inputs: &AlertInputs, config: &AlertConfig) -> Vec<(AlertKind, Option<String>)> {
+    let idle = inputs.now.saturating_sub(inputs.last_block_at);
+    let no_block = (config.no_block_minutes > 0 && idle >= config.no_block_minutes * 60)
+        .then(|| format!("no block appended for {} minutes", idle / 60));
+
+    let validation = inputs
+        .last_validation_failure_at
+        .filter(|at| inputs.now.saturating_sub(*at) <= config.validation_failure_window_secs)
+        .map(|at| format!("chain validation failure detected at {}", at));
+
+    let no_peers = (config.no_peers && inputs.peers_ever_registered && inputs.peers_healthy == 0)
+        .then(|| format!("no healthy peers ({} registered)", inputs.peers_registered));
+
+    let mempool = (config.mempool_threshold > 0 && inputs.mempool_size > config.mempool_threshold)
+        .then(|| format!("mempool holds {} transactions (threshold {})", inputs.mempool_size, config.mempool_threshold));
+
+    let clamped = (config.difficulty_clamp_streak > 0 && inputs.clamp_streak >= config.difficulty_clamp_streak)
+        .then(|| format!("difficulty adjustment clamped {} times in a row", inputs.clamp_streak));
+
+    vec![
+        (AlertKind::NoBlockMined, no_block),
+        (AlertKind::ValidationFailure, validation),
+        (AlertKind::NoPeers, no_peers),
+        (AlertKind::MempoolFull, mempool),
+        (AlertKind::DifficultyClamped, clamped),
+    ]
+}
+
+#[derive(Debug)]
+pub struct Alerts {
+    config: AlertConfig,
+    next_id: u64,
+    alerts: VecDeque<Alert>,
+    peers_ever_registered: bool,
+}
+
+impl Alerts {
+    pub fn new(config: AlertConfig) -> Self {
+        Alerts { config, next_id: 0, alerts: VecDeque::new(), peers_ever_registered: false }
+    }
+
+    pub fn config(&self) -> &AlertConfig {
+        &self.config
+    }
+
+    pub fn configure(&mut self, patch: AlertConfigPatch) -> Result<AlertConfig, String> {
+        self.config.apply(patch)?;
+        Ok(self.config.clone())
+    }
+
+    pub fn list(&self) -> Vec<Alert> {
+        self.alerts.iter().rev().cloned().collect()
+    }
+
+    /// Aplica uma avaliação e devolve os alertas que mudaram de estado (disparados ou resolvidos).
+    pub fn record(&mut self, results: Vec<(AlertKind, Option<String>)>, now: u64) -> Vec<Alert> {
+        let mut changed = Vec::new();
+        for (kind, firing) in results {
+            let active = self.alerts.iter_mut().find(|a| a.kind == kind && a.state == AlertState::Active);
+            match (active, firing) {
+                (Some(alert), None) => {
+                    alert.state = AlertState::Resolved;
+                    alert.resolved_at = Some(now);
+                    changed.push(alert.clone());
+                }
+                (None, Some(message)) => {
+                    let alert = Alert {
+                        id: self.next_id,
+                        kind,
+                        state: AlertState::Active,
+                        message,
+                        fired_at: now,
+                        resolved_at: None,
+                    };
+                    self.next_id += 1;
+                    if self.alerts.len() == KEPT_ALERTS {
+                        // Prefere descartar um resolvido; os ativos ficam
+                        let oldest = self.alerts.iter().position(|a| a.state == AlertState::Resolved).unwrap_or(0);
+                        self.alerts.remove(oldest);
+                    }
+                    self.alerts.push_back(alert.clone());
+                    changed.push(alert);
+                }
+                _ => {}
+            }
+        }
+        changed
+    }
+}
+
+fn gather_inputs(state: &AppState, started_at: u64, alerts: &mut Alerts) -> AlertInputs {
+    // Mesmo relógio da janela de mineração, que os testes controlam
+    let now = state.miner.clock().now().as_secs();
+    let (last_block_at, clamp_streak) = {
+        let guard = state.chain.lock().unwrap();
+        let clamp_streak = guard.difficulty_history().rev().take_while(|d| !d.clamps.is_empty()).count();
+        (guard.tip().timestamp / 1000, clamp_streak)
+    };
+    let last_quarantine = state.quarantine.lock().unwrap().entries().iter().map(|e| e.detected_at).max();
+    let deep_check_failed = state.health_tasks.lock().unwrap().last_check_failed();
+    let peers = state.peers.lock().unwrap().list();
+    alerts.peers_ever_registered |= !peers.is_empty();
+    AlertInputs {
+        now,
+        last_block_at: last_block_at.max(started_at),
+        // Uma revalidação profunda falha conta como falha no instante da avaliação
+        last_validation_failure_at: if deep_check_failed { Some(now) } else { last_quarantine },
+        peers_registered: peers.len(),
+        peers_healthy: peers.iter().filter(|p| p.healthy).count(),
+        peers_ever_registered: alerts.peers_ever_registered,
+        mempool_size: state.mempool.lock().unwrap().size(),
+        clamp_streak,
+    }
+}
+
+// Uma avaliação: registra as mudanças de estado e as entrega aos webhooks do tipo `alert`
+fn evaluate_once(state: &AppState, started_at: u64) {
+    let changed = {
+        let mut alerts = state.alerts.lock().unwrap();
+        let inputs = gather_inputs(state, started_at, &mut alerts);
+        let results = evaluate(&inputs, alerts.config());
+        alerts.record(results, inputs.now)
+    };
+    for alert in changed {
+        match alert.state {
+            AlertState::Active => warn!("Alerta {:?}: {}", alert.kind, alert.message),
+            AlertState::Resolved => info!("Alerta {:?} resolvido", alert.kind),
+        }
+        webhooks::dispatch(state, "alert", serde_json::to_value(&alert).unwrap_or_default());
+    }
+}
+
+/// Avalia as condições periodicamente e entrega as mudanças aos webhooks do tipo `alert`.
+pub async fn alert_loop(state: AppState) {
+    let started_at = state.miner.clock().now().as_secs();
+    loop {
+        let eval_secs = state.alerts.lock().unwrap().config().eval_secs;
+        tokio::time::sleep(Duration::from_secs(eval_secs)).await;
+        evaluate_once(&state, started_at);
+    }
+}
+
+pub async fn list_alerts_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let alerts = state.alerts.lock().unwrap().list();
+    let active = alerts.iter().filter(|a| a.state == AlertState::Active).count();
+    Json(serde_json::json!({ "active": active, "alerts": alerts }))
+}
+
+fn runtime_config(state: &AppState) -> serde_json::Value {
+    serde_json::json!({
+        "alerts": state.alerts.lock().unwrap().config(),
+        "route_roles": *state.route_roles.lock().unwrap(),
+    })
+}
+
+pub async fn config_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(runtime_config(&state))
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigPatch {
+    alerts: Option<AlertConfigPatch>,
+    route_roles: Option<BTreeMap<String, String>>,
+}
+
+/// Altera em tempo de execução a parte da configuração que admite isso: alertas e papéis das rotas.
+pub async fn patch_config_handler(
+    State(state): State<AppState>,
+    Json(patch): Json<ConfigPatch>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let unprocessable = |e: String| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, e).into_response();
+    // Valida os papéis antes de mexer nos alertas, para a alteração ser tudo ou nada
+    let roles = match &patch.route_roles {
+        Some(patch) => {
+            let mut updated = state.route_roles.lock().unwrap().clone();
+            updated.apply(patch).map_err(unprocessable)?;
+            Some(updated)
+        }
+        None => None,
+    };
+    if let Some(alerts) = patch.alerts {
+        state.alerts.lock().unwrap().configure(alerts).map_err(unprocessable)?;
+    }
+    if let Some(roles) = roles {
+        *state.route_roles.lock().unwrap() = roles;
+    }
+    Ok(Json(runtime_config(&state)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{test_clock, test_config, test_router, test_state, READ_KEY};
+    use axum::{body::Body, http::Request, routing::post, Router};
+    use blockchain_core::{Block, BlockBuilder, ChainState, Clock};
+    use tokio::sync::mpsc;
+    use tower::ServiceExt;
+
+    // Webhook falso que repassa cada corpo recebido
+    async fn webhook_receiver() -> (String, mpsc::UnboundedReceiver<serde_json::Value>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let router = Router::new().route(
+            "/hook",
+            post(move |Json(body): Json<serde_json::Value>| async move {
+                let _ = tx.send(body);
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        (url, rx)
+    }
+
+    async fn delivered(rx: &mut mpsc::UnboundedReceiver<serde_json::Value>) -> serde_json::Value {
+        tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.expect("webhook não recebeu o alerta").unwrap()
+    }
+
+    async fn get_alerts(state: &AppState) -> serde_json::Value {
+        let request = Request::get("/alerts").header("x-api-key", READ_KEY).body(Body::empty()).unwrap();
+        let response = test_router(state.clone()).oneshot(request).await.unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn no_block_alert_fires_and_resolves() {
+        let clock = test_clock();
+        let state = test_state(ChainState::new(), &test_config(), clock.clone());
+        let patch = AlertConfigPatch { no_block_minutes: Some(5), ..AlertConfigPatch::default() };
+        state.alerts.lock().unwrap().configure(patch).unwrap();
+        let (url, mut hook) = webhook_receiver().await;
+        state.webhooks.lock().unwrap().add(url, vec!["alert".to_string()], None);
+        let started_at = clock.now().as_secs();
+
+        evaluate_once(&state, started_at);
+        assert_eq!(get_alerts(&state).await, serde_json::json!({ "active": 0, "alerts": [] }));
+
+        // Cinco minutos sem bloco: dispara
+        clock.advance(Duration::from_secs(5 * 60));
+        evaluate_once(&state, started_at);
+        let fired = delivered(&mut hook).await;
+        assert_eq!(fired["event"], "alert");
+        let payload = &fired["payload"];
+        assert_eq!((payload["kind"].as_str(), payload["state"].as_str()), (Some("no_block_mined"), Some("active")));
+        assert_eq!(payload["message"], "no block appended for 5 minutes");
+        assert_eq!(payload["fired_at"].as_u64(), Some(started_at + 300));
+        assert!(payload["resolved_at"].is_null());
+        let listed = get_alerts(&state).await;
+        assert_eq!(listed["active"], 1);
+        assert_eq!(listed["alerts"][0], *payload);
+
+        // Ainda ativo: a reavaliação não entrega de novo
+        clock.advance(Duration::from_secs(60));
+        evaluate_once(&state, started_at);
+
+        // Um bloco novo resolve o alerta
+        clock.advance(Duration::from_secs(60));
+        let now_ms = clock.now().as_millis() as u64;
+        let block = BlockBuilder::on(&Block::genesis()).timestamp(now_ms).witness(1, 1, 2, 1).build();
+        state.chain.lock().unwrap().append(block).unwrap();
+        evaluate_once(&state, started_at);
+        let resolved = delivered(&mut hook).await;
+        let payload = &resolved["payload"];
+        assert_eq!((payload["id"].as_u64(), payload["state"].as_str()), (Some(0), Some("resolved")));
+        assert_eq!(payload["resolved_at"].as_u64(), Some(started_at + 420));
+        assert!(hook.try_recv().is_err(), "só duas mudanças de estado");
+        let listed = get_alerts(&state).await;
+        assert_eq!((listed["active"].as_u64(), listed["alerts"][0]["state"].as_str()), (Some(0), Some("resolved")));
+    }
+}
@@ -0,0 +1,278 @@
+// src/archive.rs
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use blockchain_core::snapshot::{read_snapshot, write_snapshot};
+use blockchain_core::{fork_choice, Block, ChainError, ChainState, CompressedChain, Preference};
+use log::{info, warn};
+use rusqlite::types::Value;
+use rusqlite::{params, Connection};
+use serde::Deserialize;
+use std::fs;
+use tokio::task;
+
+use crate::deadline::Deadline;
+use crate::errors::ApiError;
+use crate::invariants::debug_check;
+use crate::state::AppState;
+
+const DEFAULT_INTERVAL: u64 = 100;
+const ARCHIVE_FILE: &str = "chain-archive.json";
+// Blocos por segmento da exportação
+const DEFAULT_SEGMENT_SIZE: usize = 500;
+const MAX_SEGMENT_SIZE: usize = 10_000;
+pub const SNAPSHOT_CONTENT_TYPE: &str = "application/x-proof-of-prime-archive";
+const SQLITE_CONTENT_TYPE: &str = "application/x-sqlite3";
+// Uma coluna por campo de `Block`; as transações vão para a tabela filha, na ordem do bloco. As colunas
+// u64 (e o primo) ficam sem tipo: com afinidade INTEGER, o texto de um valor acima de i64::MAX viraria
+// REAL e perderia dígitos
+const SQLITE_SCHEMA: &str = "
+    CREATE TABLE blocks (
+        \"index\" INTEGER PRIMARY KEY,
+        prev_hash TEXT NOT NULL,
+        prime NOT NULL,
+        a NOT NULL,
+        b NOT NULL,
+        c NOT NULL,
+        d NOT NULL,
+        hash TEXT NOT NULL UNIQUE,
+        rules_version INTEGER NOT NULL,
+        timestamp INTEGER NOT NULL,
+        tx_root TEXT NOT NULL,
+        hash_scale NOT NULL,
+        work NOT NULL,
+        coinbase TEXT NOT NULL,
+        reward NOT NULL
+    );
+    CREATE TABLE transactions (
+        block_index INTEGER NOT NULL REFERENCES blocks(\"index\"),
+        position INTEGER NOT NULL,
+        \"from\" TEXT NOT NULL,
+        \"to\" TEXT NOT NULL,
+        amount NOT NULL,
+        nonce NOT NULL,
+        signature TEXT NOT NULL,
+        PRIMARY KEY (block_index, position)
+    );
+";
+
+#[derive(Deserialize)]
+pub struct CompressQuery {
+    interval: Option<u64>,
+}
+
+/// Gera o arquivo comprimido da cadeia (checkpoints + raízes de Merkle dos trechos removidos).
+/// A cadeia em memória continua completa: peers e a validação profunda dependem dela.
+pub async fn compress_handler(
+    State(state): State<AppState>,
+    Query(query): Query<CompressQuery>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let interval = query.interval.unwrap_or(DEFAULT_INTERVAL);
+    if interval < 2 {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "interval must be at least 2").into());
+    }
+    let (blocks, rules) = {
+        let guard = state.chain.lock().unwrap();
+        (guard.blocks().to_vec(), guard.rules().clone())
+    };
+
+    let compressed = task::spawn_blocking(move || {
+        let compressed = CompressedChain::compress(&blocks, interval);
+        compressed.verify(&rules).map(|_| compressed)
+    })
+    .await
+    .expect("Falha na compressão")
+    .map_err(|e| {
+        ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Compressed chain failed integrity check: {}", e))
+    })?;
+
+    let archived_to = state.config.data_file(ARCHIVE_FILE).and_then(|path| {
+        let written = serde_json::to_vec(&compressed).map_err(|e| e.to_string())
+            .and_then(|bytes| fs::write(&path, bytes).map_err(|e| e.to_string()));
+        match written {
+            Ok(()) => Some(path.display().to_string()),
+            Err(e) => {
+                warn!("Falha ao gravar o arquivo da cadeia em {}: {}", path.display(), e);
+                None
+            }
+        }
+    });
+    info!("Cadeia comprimida: {} blocos removidos", compressed.pruned());
+
+    Ok(Json(serde_json::json!({
+        "height": compressed.height(),
+        "interval": interval,
+        "stored_blocks": compressed.checkpoints.len(),
+        "pruned": compressed.pruned(),
+        "segments": compressed.segments,
+        "valid": true,
+        "archived_to": archived_to,
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    format: Option<String>,
+    segment_size: Option<usize>,
+}
+
+/// Exporta a cadeia no formato de arquivo com manifesto (ver `blockchain_core::snapshot`).
+pub async fn export_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, Response> {
+    if query.format.as_deref() != Some("archive") {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "unsupported format; use format=archive").into());
+    }
+    let segment_size = query.segment_size.unwrap_or(DEFAULT_SEGMENT_SIZE);
+    if !(1..=MAX_SEGMENT_SIZE).contains(&segment_size) {
+        let message = format!("segment_size must be between 1 and {}", MAX_SEGMENT_SIZE);
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, message).into());
+    }
+    let blocks = state.chain.lock().unwrap().blocks().to_vec();
+    let chain_id = state.namespace.clone();
+    let body = task::spawn_blocking(move || write_snapshot(&blocks, &chain_id, segment_size))
+        .await
+        .expect("Falha na exportação");
+    Ok(([(header::CONTENT_TYPE, SNAPSHOT_CONTENT_TYPE)], body).into_response())
+}
+
+// Inteiro que não cabe em i64 (u64 alto ou primo de cadeia u128) fica como texto decimal na coluna
+fn integer(value: impl Into<u128>) -> Value {
+    let value = value.into();
+    i64::try_from(value).map_or_else(|_| Value::Text(value.to_string()), Value::Integer)
+}
+
+// Monta o banco em memória e devolve a imagem do arquivo
+fn write_sqlite(blocks: &[Block]) -> rusqlite::Result<Vec<u8>> {
+    let mut conn = Connection::open_in_memory()?;
+    conn.execute_batch(SQLITE_SCHEMA)?;
+    let tx = conn.transaction()?;
+    {
+        let mut insert_block = tx.prepare(
+            "INSERT INTO blocks VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        )?;
+        let mut insert_tx = tx.prepare("INSERT INTO transactions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
+        for b in blocks {
+            insert_block.execute(params![
+                b.index, b.prev_hash, integer(b.prime.get()), integer(b.a), integer(b.b), integer(b.c), integer(b.d),
+                b.hash, b.rules_version, b.timestamp, b.tx_root, integer(b.hash_scale), integer(b.work), b.coinbase,
+                integer(b.reward),
+            ])?;
+            for (position, t) in b.transactions.iter().enumerate() {
+                let (amount, nonce) = (integer(t.amount), integer(t.nonce));
+                insert_tx.execute(params![b.index, position, t.from, t.to, amount, nonce, t.signature])?;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(conn.serialize(rusqlite::MAIN_DB)?.to_vec())
+}
+
+/// Exporta a cadeia como um banco SQLite com as tabelas `blocks` e `transactions`.
+pub async fn export_sqlite_handler(State(state): State<AppState>) -> Result<Response, Response> {
+    let blocks = state.chain.lock().unwrap().blocks().to_vec();
+    let body = task::spawn_blocking(move || write_sqlite(&blocks))
+        .await
+        .expect("Falha na exportação")
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("SQLite export failed: {}", e)))?;
+    let disposition = format!("attachment; filename=\"{}.sqlite\"", state.namespace);
+    Ok(([(header::CONTENT_TYPE, SQLITE_CONTENT_TYPE.to_string()), (header::CONTENT_DISPOSITION, disposition)], body)
+        .into_response())
+}
+
+/// Importa um arquivo exportado: confere manifesto e digests, valida os blocos pelas regras locais
+/// e só então substitui a cadeia, se o arquivo vencer a local em `fork_choice`.
+pub async fn import_handler(
+    State(state): State<AppState>,
+    deadline: Deadline,
+    body: String,
+) -> Result<Json<serde_json::Value>, Response> {
+    let (manifest, blocks) = read_snapshot(&body)
+        .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+    let rules = state.chain.lock().unwrap().rules().clone();
+    let mut imported = deadline
+        .run(move |token| match ChainState::from_blocks_cancellable(blocks, rules, token) {
+            Err(ChainError::Cancelled { .. }) => None,
+            result => Some(result),
+        })
+        .await
+        .map_err(IntoResponse::into_response)?
+        .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, format!("Archive chain is invalid: {}", e)))?;
+
+    let mut guard = state.chain.lock().unwrap();
+    let (archive, local) = (imported.summary(), guard.summary());
+    if fork_choice(&archive, &local) != Preference::First {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            format!(
+                "archive chain (work {}, height {}) does not beat the local chain (work {}, height {})",
+                archive.work, archive.height, local.work, local.height
+            ),
+        )
+        .into());
+    }
+    imported.set_epoch_size(guard.epoch_size());
+    imported.inherit_difficulty(&guard);
+    *guard = imported;
+    state.mempool.lock().unwrap().confirm_chain(guard.blocks());
+    let _ = state.events.send(guard.tip().clone());
+    info!("Cadeia importada de {} (altura {})", manifest.chain_id, guard.height());
+    let (height, tip_hash) = (guard.height(), guard.tip().hash.clone());
+    drop(guard);
+    debug_check(&state, "import");
+    Ok(Json(serde_json::json!({
+        "imported": true,
+        "height": height,
+        "tip_hash": tip_hash,
+        "source_chain": manifest.chain_id,
+        "segments": manifest.segments.len(),
+        "cumulative_work": manifest.cumulative_work,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{test_clock, test_config, test_router, test_state, READ_KEY};
+    use axum::{body::Body, http::Request};
+    use blockchain_core::testkit::{trivial_difficulty, ChainBuilder, GenesisConfig};
+    use blockchain_core::{Difficulty, PrimeWidth, RuleSchedule};
+    use tower::ServiceExt;
+
+    // Cadeia u128 de 19 dígitos: `a` e `c` vão até 10^19 - 1, acima de i64::MAX, e o primo passa de u64::MAX
+    fn wide_chain() -> ChainState {
+        let mut rules = RuleSchedule::default();
+        rules.set_prime_width(PrimeWidth::U128);
+        let difficulty = Difficulty { n_limit: 1_000, min_digits: 19, ..trivial_difficulty() };
+        ChainBuilder::new(GenesisConfig { rules, difficulty, ..GenesisConfig::default() }).mine_n(8, 11).build()
+    }
+
+    #[tokio::test]
+    async fn sqlite_export_keeps_wide_witnesses() {
+        let chain = wide_chain();
+        let blocks = chain.blocks().to_vec();
+        let above = |n: u64| n > i64::MAX as u64;
+        assert!(blocks.iter().any(|b| above(b.a)) && blocks.iter().any(|b| above(b.c)));
+
+        let request = Request::get("/chain/export/sqlite").header("x-api-key", READ_KEY).body(Body::empty()).unwrap();
+        let response = test_router(test_state(chain, &test_config(), test_clock())).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.deserialize_read_exact(rusqlite::MAIN_DB, &bytes[..], bytes.len(), true).unwrap();
+        let query = "SELECT CAST(prime AS TEXT), CAST(a AS TEXT), CAST(c AS TEXT), typeof(a) FROM blocks";
+        let mut select = conn.prepare(query).unwrap();
+        let rows = select.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get::<_, String>(3)?))).unwrap();
+        let rows: Vec<(String, String, String, String)> = rows.map(Result::unwrap).collect();
+        assert_eq!(rows.len(), blocks.len());
+        for (block, (prime, a, c, kind)) in blocks.iter().zip(rows) {
+            assert_eq!((prime, a, c), (block.prime.to_string(), block.a.to_string(), block.c.to_string()));
+            assert_eq!(kind, if above(block.a) { "text" } else { "integer" });
+        }
+    }
+}
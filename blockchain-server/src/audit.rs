@@ -0,0 +1,127 @@
+// src/audit.rs
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+    Json,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+
+use crate::consistency::CHAIN_POSITION;
+use crate::errors::RequestId;
+use crate::state::AppState;
+use crate::sync::now_secs;
+
+// Entradas mantidas; as mais antigas são descartadas
+const AUDIT_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditResult {
+    Ok,
+    Err,
+}
+
+/// Registro de uma operação que alterou (ou tentou alterar) o estado do nó.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub timestamp: u64,
+    pub operation: &'static str,
+    pub request_id: String,
+    pub actor_key_hash: String,
+    pub result: AuditResult,
+    pub detail: serde_json::Value,
+}
+
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    entries: VecDeque<AuditEntry>,
+}
+
+impl AuditLog {
+    pub fn record(&mut self, entry: AuditEntry) {
+        if self.entries.len() == AUDIT_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.entries.iter().cloned().collect()
+    }
+}
+
+// Rotas de escrita e o nome da operação registrado para cada uma
+fn operation(method: &Method, path: &str) -> Option<&'static str> {
+    Some(match (method.as_str(), path) {
+        ("GET", "/mine") => "mine",
+        ("POST", "/mine/submit") | ("POST", "/blocks/compact") => "submit",
+        ("POST", "/blocks/announce") => "announce",
+        ("POST", "/chain/resolve") => "resolve",
+        ("POST", "/chain/compress") => "compress",
+        ("POST", "/chain/import") => "import",
+        ("PUT", "/admin/rules") => "rules",
+        ("PATCH", "/admin/config") => "config",
+        ("PUT", "/admin/difficulty") => "difficulty",
+        ("PUT", "/difficulty") => "difficulty_override",
+        ("POST", "/admin/rebuild") => "rebuild",
+        ("POST", "/admin/gc") => "gc",
+        ("POST", "/peers") => "peer_add",
+        ("DELETE", "/admin/quarantine/:id") => "quarantine_release",
+        ("POST", "/admin/webhooks") => "webhook_add",
+        ("DELETE", "/admin/webhooks/:id") => "webhook_remove",
+        ("POST", "/admin/chains") => "chain_create",
+        ("DELETE", "/admin/chains/:name") => "chain_delete",
+        ("POST", "/miners") => "miner_register",
+        ("POST", "/transactions") => "transaction",
+        ("POST", "/transactions/batch") => "transaction_batch",
+        _ => return None,
+    })
+}
+
+// Prefixo do SHA-256 da chave; a chave em si nunca é gravada
+fn key_hash(req: &Request) -> String {
+    match req.headers().get("x-api-key") {
+        Some(key) => format!("{:x}", Sha256::digest(key.as_bytes()))[..16].to_string(),
+        None => "none".to_string(),
+    }
+}
+
+/// Registra as rotas de escrita no log de auditoria, com o id que `errors::render` deu à requisição.
+pub async fn audit(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let path = req.extensions().get::<MatchedPath>().map(|p| p.as_str().to_string());
+    let Some(operation) = path.as_deref().and_then(|p| operation(req.method(), p)) else {
+        return next.run(req).await;
+    };
+    let request_id = match req.extensions().get::<RequestId>() {
+        Some(id) => id.0.clone(),
+        None => uuid::Uuid::new_v4().to_string(),
+    };
+    let actor_key_hash = key_hash(&req);
+    let uri_path = req.uri().path().to_string();
+
+    let response = next.run(req).await;
+    let status = response.status();
+    let position = response.headers().get(CHAIN_POSITION).and_then(|v| v.to_str().ok()).map(String::from);
+    state.audit.lock().unwrap().record(AuditEntry {
+        timestamp: now_secs(),
+        operation,
+        request_id: request_id.clone(),
+        actor_key_hash,
+        result: if status.is_success() { AuditResult::Ok } else { AuditResult::Err },
+        detail: serde_json::json!({
+            "chain": state.namespace,
+            "path": uri_path,
+            "status": status.as_u16(),
+            "position": position,
+        }),
+    });
+    response
+}
+
+pub async fn audit_log_handler(State(state): State<AppState>) -> Json<Vec<AuditEntry>> {
+    Json(state.audit.lock().unwrap().entries())
+}
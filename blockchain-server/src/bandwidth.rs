@@ -0,0 +1,323 @@
+// src/bandwidth.rs
+//! Bytes de corpo servidos por rota, contados quadro a quadro enquanto a resposta sai: respostas em
+//! fluxo (SSE) entram conforme são produzidas, e as comprimidas por `compression::compress`, por dentro
+//! desta, entram já codificadas. Cabeçalhos ficam de fora.
+use axum::{
+    body::{Body, Bytes},
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use blockchain_core::{Block, Clock, SystemClock};
+use http_body::{Frame, SizeHint};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use crate::state::AppState;
+
+// Minutos guardados por rota, para a taxa da última hora
+const KEPT_MINUTES: u64 = 60;
+// Limites superiores (bytes) das faixas do histograma de tamanho; a última faixa pega o que passar do maior
+const SIZE_BOUNDS: [u64; 8] = [256, 1024, 4096, 16_384, 65_536, 262_144, 1_048_576, 4_194_304];
+
+#[derive(Debug, Clone, Copy, Default)]
+struct MinuteBytes {
+    minute: u64,
+    bytes: u64,
+}
+
+#[derive(Debug, Clone)]
+struct RouteBytes {
+    bytes: u64,
+    // Respostas terminadas (ou abandonadas pelo cliente); as em curso já somam em `bytes`
+    responses: u64,
+    sizes: [u64; SIZE_BOUNDS.len() + 1],
+    minutes: [MinuteBytes; KEPT_MINUTES as usize],
+}
+
+impl Default for RouteBytes {
+    fn default() -> Self {
+        RouteBytes {
+            bytes: 0,
+            responses: 0,
+            sizes: [0; SIZE_BOUNDS.len() + 1],
+            minutes: [MinuteBytes::default(); KEPT_MINUTES as usize],
+        }
+    }
+}
+
+impl RouteBytes {
+    fn last_hour(&self, now: u64) -> u64 {
+        let oldest = now.saturating_sub(KEPT_MINUTES - 1);
+        self.minutes.iter().filter(|m| (oldest..=now).contains(&m.minute)).map(|m| m.bytes).sum()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SizeBucket {
+    // Limite superior da faixa; `null` na última
+    pub le: Option<u64>,
+    pub responses: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteBandwidth {
+    pub route: String,
+    pub bytes: u64,
+    pub responses: u64,
+    pub mean_response_bytes: Option<f64>,
+    pub last_hour_bytes: u64,
+    pub sizes: Vec<SizeBucket>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BandwidthReport {
+    pub total_bytes: u64,
+    pub last_hour_bytes: u64,
+    pub last_hour_bytes_per_sec: f64,
+    pub routes: Vec<RouteBandwidth>,
+}
+
+/// Contagem por rota do roteador (`MÉTODO /rota`), do nó todo, como os SLOs.
+pub struct BandwidthTracker {
+    clock: Arc<dyn Clock>,
+    routes: BTreeMap<String, RouteBytes>,
+}
+
+impl BandwidthTracker {
+    pub fn new() -> Self {
+        BandwidthTracker::with_clock(Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        BandwidthTracker { clock, routes: BTreeMap::new() }
+    }
+
+    fn minute(&self) -> u64 {
+        self.clock.now().as_secs() / 60
+    }
+
+    fn add(&mut self, route: &str, bytes: u64) {
+        let minute = self.minute();
+        let entry = self.routes.entry(route.to_string()).or_default();
+        entry.bytes += bytes;
+        let bucket = &mut entry.minutes[(minute % KEPT_MINUTES) as usize];
+        if bucket.minute != minute {
+            *bucket = MinuteBytes { minute, bytes: 0 };
+        }
+        bucket.bytes += bytes;
+    }
+
+    fn finish(&mut self, route: &str, bytes: u64) {
+        let entry = self.routes.entry(route.to_string()).or_default();
+        entry.responses += 1;
+        entry.sizes[SIZE_BOUNDS.iter().position(|&bound| bytes <= bound).unwrap_or(SIZE_BOUNDS.len())] += 1;
+    }
+
+    pub fn report(&self) -> BandwidthReport {
+        let now = self.minute();
+        let routes: Vec<RouteBandwidth> = self
+            .routes
+            .iter()
+            .map(|(route, counts)| RouteBandwidth {
+                route: route.clone(),
+                bytes: counts.bytes,
+                responses: counts.responses,
+                mean_response_bytes: (counts.responses > 0).then(|| counts.bytes as f64 / counts.responses as f64),
+                last_hour_bytes: counts.last_hour(now),
+                sizes: counts
+                    .sizes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &responses)| SizeBucket { le: SIZE_BOUNDS.get(i).copied(), responses })
+                    .collect(),
+            })
+            .collect();
+        let last_hour_bytes = routes.iter().map(|r| r.last_hour_bytes).sum();
+        BandwidthReport {
+            total_bytes: routes.iter().map(|r| r.bytes).sum(),
+            last_hour_bytes,
+            last_hour_bytes_per_sec: last_hour_bytes as f64 / (KEPT_MINUTES * 60) as f64,
+            routes,
+        }
+    }
+
+    /// Contador de bytes e histograma de tamanho das respostas, no formato do Prometheus.
+    pub fn prometheus(&self, body: &mut String) {
+        body.push_str(
+            "# HELP proof_of_prime_response_bytes_total Response body bytes served\n\
+             # TYPE proof_of_prime_response_bytes_total counter\n",
+        );
+        for (route, counts) in &self.routes {
+            body.push_str(&format!("proof_of_prime_response_bytes_total{{route=\"{}\"}} {}\n", route, counts.bytes));
+        }
+        body.push_str(
+            "# HELP proof_of_prime_response_size_bytes Response body size of finished responses\n\
+             # TYPE proof_of_prime_response_size_bytes histogram\n",
+        );
+        for (route, counts) in &self.routes {
+            let mut cumulative = 0;
+            for (i, count) in counts.sizes.iter().enumerate() {
+                cumulative += count;
+                let le = SIZE_BOUNDS.get(i).map_or("+Inf".to_string(), u64::to_string);
+                body.push_str(&format!(
+                    "proof_of_prime_response_size_bytes_bucket{{route=\"{}\",le=\"{}\"}} {}\n",
+                    route, le, cumulative
+                ));
+            }
+            body.push_str(&format!("proof_of_prime_response_size_bytes_count{{route=\"{}\"}} {}\n", route, cumulative));
+        }
+    }
+}
+
+impl Default for BandwidthTracker {
+    fn default() -> Self {
+        BandwidthTracker::new()
+    }
+}
+
+// Bytes de uma resposta; ao ser descartado (fim do corpo ou cliente que desconectou) fecha a contagem
+struct Meter {
+    tracker: Arc<Mutex<BandwidthTracker>>,
+    route: String,
+    bytes: u64,
+}
+
+impl Drop for Meter {
+    fn drop(&mut self) {
+        self.tracker.lock().unwrap().finish(&self.route, self.bytes);
+    }
+}
+
+// Corpo que repassa os quadros e o tamanho declarado do original, para o Content-Length continuar valendo
+struct MeteredBody {
+    inner: Body,
+    meter: Meter,
+}
+
+impl http_body::Body for MeteredBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Bytes>, axum::Error>>> {
+        let this = &mut *self;
+        let polled = Pin::new(&mut this.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &polled {
+            if let Some(data) = frame.data_ref() {
+                this.meter.bytes += data.len() as u64;
+                this.meter.tracker.lock().unwrap().add(&this.meter.route, data.len() as u64);
+            }
+        }
+        polled
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Mede o corpo de cada resposta de rota casada; fica por fora de tudo para medir o que de fato sai.
+pub async fn meter(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let Some(path) = req.extensions().get::<MatchedPath>().map(|p| p.as_str().to_string()) else {
+        return next.run(req).await;
+    };
+    let route = format!("{} {}", req.method(), path);
+    let (parts, body) = next.run(req).await.into_parts();
+    let meter = Meter { tracker: state.bandwidth.clone(), route, bytes: 0 };
+    Response::from_parts(parts, Body::new(MeteredBody { inner: body, meter }))
+}
+
+/// Tamanho em JSON de cada bloco, calculado na primeira consulta e guardado com o hash dele: uma troca
+/// de cadeia recalcula a partir do primeiro hash diferente.
+#[derive(Debug, Default)]
+pub struct BlockSizes {
+    sizes: Vec<(String, u64)>,
+}
+
+impl BlockSizes {
+    /// Soma dos tamanhos de `blocks`, um prefixo da cadeia.
+    pub fn total(&mut self, blocks: &[Block]) -> u64 {
+        let known = self.sizes.iter().zip(blocks).take_while(|((hash, _), block)| *hash == block.hash).count();
+        if known < blocks.len() {
+            self.sizes.truncate(known);
+            self.sizes.extend(blocks[known..].iter().map(|block| (block.hash.clone(), serialized_size(block))));
+        }
+        self.sizes[..blocks.len()].iter().map(|(_, size)| size).sum()
+    }
+}
+
+/// Bytes do bloco em JSON, como sai em /chain.
+pub fn serialized_size(block: &Block) -> u64 {
+    serde_json::to_vec(block).map_or(0, |bytes| bytes.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testkit::{test_node, MINE_KEY, READ_KEY};
+    use axum::{body::Body, extract::Request, http::header, Router};
+    use blockchain_core::testkit::trivial_difficulty;
+    use blockchain_core::ChainState;
+    use tower::ServiceExt;
+
+    use crate::state::AppState;
+
+    async fn fetch(router: &Router, uri: &str, gzip: bool) -> (Option<String>, Vec<u8>) {
+        let mut request = Request::get(uri).header("x-api-key", READ_KEY);
+        if gzip {
+            request = request.header(header::ACCEPT_ENCODING, "br;q=1, gzip;q=0.8");
+        }
+        let response = router.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        let encoding = response.headers().get(header::CONTENT_ENCODING).map(|v| v.to_str().unwrap().to_string());
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (encoding, bytes.to_vec())
+    }
+
+    fn chain_bytes(state: &AppState) -> u64 {
+        let report = state.bandwidth.lock().unwrap().report();
+        report.routes.iter().find(|r| r.route == "GET /chain").map_or(0, |r| r.bytes)
+    }
+
+    // Corpo do gzip: deflate entre o cabeçalho de 10 bytes e o rodapé com CRC-32 e tamanho
+    fn gunzip(data: &[u8]) -> Vec<u8> {
+        assert_eq!(&data[..3], &[0x1f, 0x8b, 8]);
+        let (body, footer) = data[10..].split_at(data.len() - 18);
+        let plain = miniz_oxide::inflate::decompress_to_vec(body).unwrap();
+        assert_eq!(footer[..4], crc32fast::hash(&plain).to_le_bytes());
+        assert_eq!(footer[4..], (plain.len() as u32).to_le_bytes());
+        plain
+    }
+
+    #[tokio::test]
+    async fn chain_sizes_with_and_without_gzip() {
+        let mut chain = ChainState::new();
+        chain.difficulty = trivial_difficulty();
+        let (router, state, _) = test_node(chain);
+        for _ in 0..5 {
+            let mine = Request::get("/mine").header("x-api-key", MINE_KEY).body(Body::empty()).unwrap();
+            assert!(router.clone().oneshot(mine).await.unwrap().status().is_success());
+        }
+
+        let (encoding, plain) = fetch(&router, "/chain", false).await;
+        assert_eq!(encoding, None);
+        assert_eq!(chain_bytes(&state), plain.len() as u64);
+
+        let (encoding, gzipped) = fetch(&router, "/chain", true).await;
+        assert_eq!(encoding.as_deref(), Some("gzip"));
+        assert!(gzipped.len() < plain.len(), "{} bytes comprimidos contra {}", gzipped.len(), plain.len());
+        assert_eq!(chain_bytes(&state), (plain.len() + gzipped.len()) as u64);
+        assert_eq!(gunzip(&gzipped), plain);
+
+        // `q=0` recusa o gzip
+        let request = Request::get("/chain").header("x-api-key", READ_KEY).header(header::ACCEPT_ENCODING, "gzip;q=0");
+        let response = router.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(response.headers()[header::VARY], "accept-encoding");
+    }
+}
@@ -0,0 +1,603 @@
+// src/blocks.rs
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use blockchain_core::chain::{ENTROPY_MODULUS, OBSERVED_BLOCKS};
+use blockchain_core::math::{chi_squared_p_value, erfc, jacobi};
+use blockchain_core::{compute_hash, miller_rabin_rounds, Block, EpochSummary, PrimeValue, WindowStats};
+use chrono::DateTime;
+use num::Integer;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ApiError;
+use crate::history::{self, AtHeight};
+use crate::projection::{FieldsQuery, Projection};
+use crate::state::AppState;
+
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 1000;
+
+/// Envelope de paginação por offset.
+#[derive(Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub next_offset: Option<usize>,
+}
+
+impl<T: Clone> Page<T> {
+    pub fn of(all: &[T], offset: Option<usize>, limit: Option<usize>) -> Self {
+        let offset = offset.unwrap_or(0);
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let items: Vec<T> = all.iter().skip(offset).take(limit).cloned().collect();
+        let next_offset = (offset + items.len() < all.len()).then_some(offset + items.len());
+        Page { items, total: all.len(), offset, limit, next_offset }
+    }
+}
+
+impl<T> Page<T> {
+    /// A mesma página com cada item trocado por `f(item)`.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        let Page { items, total, offset, limit, next_offset } = self;
+        Page { items: items.into_iter().map(f).collect(), total, offset, limit, next_offset }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TimeRangeQuery {
+    from_ts: Option<String>,
+    to_ts: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+/// Aceita milissegundos unix ou data ISO-8601 (RFC 3339, ex.: 2026-10-14T12:00:00Z).
+fn parse_timestamp(name: &str, value: &str) -> Result<u64, String> {
+    if let Ok(millis) = value.parse::<u64>() {
+        return Ok(millis);
+    }
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .and_then(|dt| u64::try_from(dt.timestamp_millis()).ok())
+        .ok_or_else(|| format!("{} must be unix milliseconds or an ISO-8601 datetime, got {:?}", name, value))
+}
+
+/// Blocos de um intervalo de tempo, paginados; aceita `?fields=` como /chain.
+pub async fn blocks_by_time_handler(
+    State(state): State<AppState>,
+    Query(query): Query<TimeRangeQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Response, Response> {
+    let projection = Projection::from_query(&fields).map_err(IntoResponse::into_response)?;
+    let bad_request = |error: String| ApiError::new(StatusCode::BAD_REQUEST, error).into_response();
+    let from = query.from_ts.as_deref().map(|v| parse_timestamp("from_ts", v)).transpose().map_err(bad_request)?;
+    let to = query.to_ts.as_deref().map(|v| parse_timestamp("to_ts", v)).transpose().map_err(bad_request)?;
+    let (from, to) = (from.unwrap_or(0), to.unwrap_or(u64::MAX));
+    if from > to {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "from_ts must not be after to_ts").into());
+    }
+    let page = Page::of(state.chain.lock().unwrap().blocks_in_time_range(from, to), query.offset, query.limit);
+    Ok(Json(page.map(|block| projection.view(block))).into_response())
+}
+
+/// Ancestrais do bloco `index` até `depth` saltos (no máximo 1000), seguindo `prev_hash`, do pai ao mais
+/// distante. Esta árvore guarda só a cadeia principal, então há um ancestral por salto; a caminhada para
+/// no gênesis, e `depth` na resposta é quantos saltos foram dados.
+pub async fn dag_ancestors_handler(
+    State(state): State<AppState>,
+    Path((index, depth)): Path<(usize, usize)>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let guard = state.chain.lock().unwrap();
+    let blocks = guard.blocks();
+    let root = blocks
+        .get(index)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("Block {} not found", index)))?;
+    let mut ancestors: Vec<Block> = Vec::new();
+    let mut current = root;
+    while ancestors.len() < depth.min(MAX_LIMIT) {
+        // O pai de uma cadeia válida está na posição anterior; o hash confirma a ligação
+        let Some(parent) = (current.index as usize).checked_sub(1).and_then(|i| blocks.get(i)) else { break };
+        if parent.hash != current.prev_hash {
+            break;
+        }
+        ancestors.push(parent.clone());
+        current = parent;
+    }
+    Ok(Json(serde_json::json!({
+        "root_index": root.index,
+        "depth": ancestors.len(),
+        "blocks": ancestors,
+    })))
+}
+
+/// Probabilidade de finalidade `1 - 2^(-confirmações)`, com confirmações = altura - índice (a ponta tem uma).
+/// `is_final` segue a heurística das 6 confirmações do Bitcoin, ajustável por FINALITY_THRESHOLD.
+pub async fn finality_score_handler(
+    State(state): State<AppState>,
+    Path(index): Path<usize>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let height = state.chain.lock().unwrap().height();
+    if index >= height {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, format!("Block {} not found", index)).into());
+    }
+    let confirmations = (height - index) as u64;
+    // Acima de ~1075 confirmações 2^(-c) já é 0 em f64
+    let finality_score = 1.0 - 0.5f64.powi(confirmations.min(i32::MAX as u64) as i32);
+    Ok(Json(serde_json::json!({
+        "block_index": index,
+        "confirmations": confirmations,
+        "finality_score": finality_score,
+        "is_final": confirmations >= state.config.finality_threshold,
+    })))
+}
+
+/// XOR acumulado dos hashes do gênesis até `index`, lidos como inteiros de 256 bits. Hash que não é hex
+/// de 32 bytes (o do gênesis é "genesis") conta como zero. `normalized` é o resultado sobre `2^256 - 1`.
+pub async fn genesis_distance_handler(
+    State(state): State<AppState>,
+    Path(index): Path<usize>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let guard = state.chain.lock().unwrap();
+    let Some(blocks) = guard.blocks().get(..=index) else {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, format!("Block {} not found", index)).into());
+    };
+    let mut distance = [0u8; 32];
+    for block in blocks {
+        let Some(bytes) = hex::decode(&block.hash).ok().and_then(|b| <[u8; 32]>::try_from(b).ok()) else { continue };
+        distance.iter_mut().zip(bytes).for_each(|(acc, byte)| *acc ^= byte);
+    }
+    // Horner em f64 do byte mais significativo ao menos; 2^256 - 1 arredonda para 2^256
+    let normalized = distance.iter().fold(0.0f64, |acc, &byte| acc * 256.0 + byte as f64) / 2f64.powi(256);
+    Ok(Json(serde_json::json!({
+        "block_index": index,
+        "genesis_distance": hex::encode(distance),
+        "normalized": normalized,
+    })))
+}
+
+#[derive(Serialize)]
+pub struct NonCoprimePair {
+    index: u64,
+    prime: PrimeValue,
+    gcd: PrimeValue,
+}
+
+/// Confere `gcd(p_i, p_j) = 1` contra todos os outros blocos; só falha se um primo se repetir.
+pub async fn gcd_test_handler(
+    State(state): State<AppState>,
+    Path(index): Path<usize>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let guard = state.chain.lock().unwrap();
+    let block = guard
+        .blocks()
+        .get(index)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("Block {} not found", index)))?;
+    let non_coprime: Vec<NonCoprimePair> = guard
+        .blocks()
+        .iter()
+        .enumerate()
+        .filter(|&(j, _)| j != index)
+        .map(|(_, other)| NonCoprimePair {
+            index: other.index,
+            prime: other.prime,
+            gcd: PrimeValue::new(block.prime.get().gcd(&other.prime.get())),
+        })
+        .filter(|pair| pair.gcd != PrimeValue::U64(1))
+        .collect();
+    Ok(Json(serde_json::json!({
+        "index": block.index,
+        "prime": block.prime,
+        "compared": guard.height() - 1,
+        "all_coprime": non_coprime.is_empty(),
+        "non_coprime_pairs": non_coprime,
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct ResidueQuery {
+    a: i64,
+}
+
+/// Símbolo de Jacobi `(a/p)` para o primo do bloco, que com `p` primo é também o de Legendre:
+/// 1 se `a` é resíduo quadrático módulo `p`, -1 se não é, 0 se `p | a`.
+pub async fn residue_symbol_handler(
+    State(state): State<AppState>,
+    Path(index): Path<usize>,
+    Query(query): Query<ResidueQuery>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let prime = {
+        let guard = state.chain.lock().unwrap();
+        let block = guard.blocks().get(index);
+        let not_found = || ApiError::new(StatusCode::NOT_FOUND, format!("Block {} not found", index)).into_response();
+        block.map(|b| b.prime).ok_or_else(not_found)?
+    };
+    let Some(p) = prime.as_u64() else {
+        let message = format!("the Jacobi symbol is computed for u64 moduli, block {} has prime {}", index, prime);
+        return Err(ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, message).into());
+    };
+    if p.is_multiple_of(2) {
+        let message = format!("the Jacobi symbol needs an odd modulus, block {} has prime {}", index, p);
+        return Err(ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, message).into());
+    }
+    // a negativo entra pelo representante em [0, p)
+    let residue = (query.a as i128).rem_euclid(p as i128) as u64;
+    Ok(Json(serde_json::json!({
+        "a": query.a,
+        "p": p,
+        "jacobi": jacobi(residue, p),
+    })))
+}
+
+/// Resumo pré-computado da época `n`, com `EPOCH_SIZE` blocos cada.
+pub async fn epoch_handler(
+    State(state): State<AppState>,
+    Path(n): Path<u64>,
+    Query(query): Query<AtHeight>,
+) -> Result<Json<EpochSummary>, Response> {
+    let guard = state.chain.lock().unwrap();
+    let derived = history::derived(&state, &guard, query.at_height).map_err(IntoResponse::into_response)?;
+    derived.epoch(n).cloned().map(Json).ok_or_else(|| {
+        let message = format!("Epoch {} not found (chain has {} epochs)", n, derived.epoch_count());
+        ApiError::new(StatusCode::NOT_FOUND, message).into_response()
+    })
+}
+
+// Rodadas do Miller-Rabin probabilístico medidas no relatório de tempo
+const TIMING_MR_ROUNDS: u32 = 20;
+
+// Compara sem sair no primeiro byte diferente; só o tamanho é comparado antes
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[derive(Serialize)]
+pub struct CodePath {
+    pub path: &'static str,
+    pub variable_time: bool,
+    pub note: &'static str,
+}
+
+// Auditoria manual dos caminhos que tocam o hash e o primo de um bloco
+const CODE_PATHS: [CodePath; 5] = [
+    CodePath {
+        path: "Block::verify_contents hash check",
+        variable_time: true,
+        note: "String != returns at the first differing byte",
+    },
+    CodePath {
+        path: "math::mod_pow",
+        variable_time: true,
+        note: "square-and-multiply branches on each exponent bit",
+    },
+    CodePath {
+        path: "math::miller_rabin",
+        variable_time: true,
+        note: "stops at the first base that proves n composite; runs all k rounds only for primes",
+    },
+    CodePath {
+        path: "math::miller_rabin_deterministic",
+        variable_time: true,
+        note: "returns early on small prime divisors and on the first witness of compositeness",
+    },
+    CodePath {
+        path: "timing-attack-resistance hash check",
+        variable_time: false,
+        note: "XOR-folds every byte; only the length is compared up front",
+    },
+];
+
+/// Relatório de tempo constante para o bloco: recompara o hash sem saída antecipada, mede as rodadas
+/// do Miller-Rabin no primo e lista os caminhos de tempo variável.
+pub async fn timing_report_handler(
+    State(state): State<AppState>,
+    Path(index): Path<usize>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let block = state
+        .chain
+        .lock()
+        .unwrap()
+        .blocks()
+        .get(index)
+        .cloned()
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("Block {} not found", index)))?;
+    let recomputed = compute_hash(&block);
+    // As rodadas aleatórias são em u64; primos acima dela (cadeias u128) saem sem a medida
+    let miller_rabin = block.prime.as_u64().map(|prime| {
+        let (probable_prime, rounds) = miller_rabin_rounds(prime, TIMING_MR_ROUNDS);
+        serde_json::json!({
+            "k": TIMING_MR_ROUNDS,
+            "rounds_run": rounds,
+            "probable_prime": probable_prime,
+            "fixed_iterations": rounds == TIMING_MR_ROUNDS,
+        })
+    });
+    Ok(Json(serde_json::json!({
+        "index": block.index,
+        "hash_check": {
+            "matches": constant_time_eq(block.hash.as_bytes(), recomputed.as_bytes()),
+            "comparison": "constant_time",
+        },
+        "miller_rabin": miller_rabin,
+        "code_paths": CODE_PATHS,
+        // Nada disso é segredo: primo, testemunha e hash são públicos em /chain
+        "secret_inputs": false,
+    })))
+}
+
+// Referência de comparação: notebook consumindo 50 W
+const LAPTOP_WATTS: f64 = 50.0;
+const JOULES_PER_KWH: f64 = 3.6e6;
+
+#[derive(Deserialize)]
+pub struct EnergyQuery {
+    joules_per_candidate: Option<f64>,
+}
+
+/// Energia estimada da cadeia: candidatos × joules por candidato. Blocos sem candidatos registrados
+/// (gênesis, recebidos de peers ou de mineradores externos) entram pela média dos registrados.
+pub async fn energy_estimate_handler(
+    State(state): State<AppState>,
+    Query(query): Query<EnergyQuery>,
+    Query(at): Query<AtHeight>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let joules_per_candidate = query.joules_per_candidate.unwrap_or(state.config.joules_per_candidate);
+    if !joules_per_candidate.is_finite() || joules_per_candidate < 0.0 {
+        let message = "joules_per_candidate must be a non-negative number".to_string();
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, message).into());
+    }
+    let (height, (recorded_blocks, recorded_candidates)) = {
+        let guard = state.chain.lock().unwrap();
+        let derived = history::derived(&state, &guard, at.at_height).map_err(IntoResponse::into_response)?;
+        (derived.height() as u64, derived.recorded_candidates())
+    };
+    let mined = height - 1;
+    let estimated_blocks = mined.saturating_sub(recorded_blocks);
+    let average = if recorded_blocks == 0 { 0.0 } else { recorded_candidates as f64 / recorded_blocks as f64 };
+    let total_candidates = recorded_candidates as f64 + average * estimated_blocks as f64;
+    let joules = total_candidates * joules_per_candidate;
+    Ok(Json(serde_json::json!({
+        "height": height,
+        "joules_per_candidate": joules_per_candidate,
+        "calibrated": query.joules_per_candidate.is_none(),
+        "total_candidates": total_candidates.round() as u64,
+        "recorded_blocks": recorded_blocks,
+        "estimated_blocks": estimated_blocks,
+        "joules": joules,
+        "kilowatt_hours": joules / JOULES_PER_KWH,
+        "comparison": {
+            "reference": format!("laptop at {} W", LAPTOP_WATTS),
+            "seconds": joules / LAPTOP_WATTS,
+        },
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct WindowQuery {
+    window: Option<usize>,
+}
+
+/// Médias móveis da janela mais recente de blocos minerados (padrão: 10).
+pub async fn rolling_window_stats_handler(
+    State(state): State<AppState>,
+    Query(query): Query<WindowQuery>,
+    Query(at): Query<AtHeight>,
+) -> Result<Json<WindowStats>, Response> {
+    let window = query.window.unwrap_or(10);
+    if !(1..=OBSERVED_BLOCKS).contains(&window) {
+        let message = format!("window must be between 1 and {}", OBSERVED_BLOCKS);
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, message).into());
+    }
+    let guard = state.chain.lock().unwrap();
+    let index = history::resolve(&guard, at.at_height).map_err(IntoResponse::into_response)?;
+    Ok(Json(guard.window_stats_at(window, index)))
+}
+
+/// Série completa da entropia de `primo mod 30`, um ponto por bloco; deve estabilizar com a altura.
+pub async fn entropy_vs_height_handler(
+    State(state): State<AppState>,
+    Query(query): Query<AtHeight>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let guard = state.chain.lock().unwrap();
+    let derived = history::derived(&state, &guard, query.at_height).map_err(IntoResponse::into_response)?;
+    Ok(Json(serde_json::json!({ "modulus": ENTROPY_MODULUS, "points": derived.entropy_series() })))
+}
+
+// NIST SP 800-22: sequências menores não são conclusivas, e p-valor abaixo de ALPHA reprova
+const MONOBIT_MIN_BITS: usize = 100;
+const MONOBIT_ALPHA: f64 = 0.01;
+
+/// Bits dos primos minerados (sem o gênesis): o bit baixo de cada primo, seguido da paridade de cada
+/// dígito decimal, do mais significativo ao menos.
+fn prime_bits(blocks: &[Block]) -> Vec<bool> {
+    let mut bits = Vec::new();
+    for block in blocks.iter().skip(1) {
+        bits.push(block.prime.get() & 1 == 1);
+        bits.extend(block.prime.to_string().bytes().map(|digit| (digit - b'0') % 2 == 1));
+    }
+    bits
+}
+
+// Oito bits por byte, o primeiro no mais significativo; o último byte é completado com zeros
+fn pack_hex(bits: &[bool]) -> String {
+    let bytes: Vec<u8> = bits
+        .chunks(8)
+        .map(|chunk| chunk.iter().enumerate().fold(0u8, |byte, (i, &bit)| byte | (bit as u8) << (7 - i)))
+        .collect();
+    hex::encode(bytes)
+}
+
+#[derive(Serialize)]
+pub struct MonobitTest {
+    pub ones: usize,
+    pub zeros: usize,
+    // |S_n| / sqrt(n), com S_n a soma de +1 por bit 1 e -1 por bit 0
+    pub s_obs: f64,
+    // Ausentes abaixo de MONOBIT_MIN_BITS bits
+    pub p_value: Option<f64>,
+    pub passed: Option<bool>,
+}
+
+/// Teste de frequência (monobit) do NIST SP 800-22, seção 2.1: `p = erfc(s_obs / √2)`.
+fn monobit(bits: &[bool]) -> MonobitTest {
+    let ones = bits.iter().filter(|&&bit| bit).count();
+    let zeros = bits.len() - ones;
+    let sum = ones as f64 - zeros as f64;
+    let s_obs = if bits.is_empty() { 0.0 } else { sum.abs() / (bits.len() as f64).sqrt() };
+    let p_value = (bits.len() >= MONOBIT_MIN_BITS).then(|| erfc(s_obs / std::f64::consts::SQRT_2));
+    MonobitTest { ones, zeros, s_obs, p_value, passed: p_value.map(|p| p >= MONOBIT_ALPHA) }
+}
+
+/// Bits dos primos minerados até `at_height` (padrão: a ponta) em hex, com o teste monobit. Todo primo
+/// acima de 2 é ímpar, então o bit baixo puxa a frequência de uns para cima.
+pub async fn prime_bits_handler(
+    State(state): State<AppState>,
+    Query(query): Query<AtHeight>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let (index, bits) = {
+        let guard = state.chain.lock().unwrap();
+        let index = history::resolve(&guard, query.at_height).map_err(IntoResponse::into_response)?;
+        (index, prime_bits(&guard.blocks()[..=index as usize]))
+    };
+    Ok(Json(serde_json::json!({
+        "height": index,
+        "bits": bits.len(),
+        "hex": pack_hex(&bits),
+        "monobit": monobit(&bits),
+    })))
+}
+
+// Vitórias esperadas por worker abaixo das quais a aproximação qui-quadrado não vale; p-valor abaixo de
+// ALPHA indica viés
+const FAIRNESS_MIN_EXPECTED: f64 = 5.0;
+const FAIRNESS_ALPHA: f64 = 0.01;
+
+#[derive(Serialize)]
+pub struct MiningFairness {
+    pub workers: usize,
+    pub races: u64,
+    // Vitórias de cada worker do pool, pelo índice
+    pub wins: Vec<u64>,
+    pub expected_per_worker: f64,
+    pub chi_squared: f64,
+    pub degrees_of_freedom: u64,
+    // Ausente com menos de FAIRNESS_MIN_EXPECTED vitórias esperadas por worker
+    pub p_value: Option<f64>,
+    pub alpha: f64,
+    // Sem amostra suficiente, ou com um worker só, não há evidência de viés
+    pub is_fair: bool,
+}
+
+/// Teste qui-quadrado das vitórias de cada worker contra a distribuição uniforme: `Σ (o - e)² / e`,
+/// com `workers - 1` graus de liberdade.
+fn mining_fairness(wins: &[u64]) -> MiningFairness {
+    let races: u64 = wins.iter().sum();
+    let expected = races as f64 / wins.len().max(1) as f64;
+    let chi_squared = if races == 0 {
+        0.0
+    } else {
+        wins.iter().map(|&observed| (observed as f64 - expected).powi(2) / expected).sum()
+    };
+    let degrees_of_freedom = wins.len().saturating_sub(1) as u64;
+    let p_value = (expected >= FAIRNESS_MIN_EXPECTED && degrees_of_freedom > 0)
+        .then(|| chi_squared_p_value(chi_squared, degrees_of_freedom));
+    MiningFairness {
+        workers: wins.len(),
+        races,
+        wins: wins.to_vec(),
+        expected_per_worker: expected,
+        chi_squared,
+        degrees_of_freedom,
+        p_value,
+        alpha: FAIRNESS_ALPHA,
+        is_fair: p_value.is_none_or(|p| p >= FAIRNESS_ALPHA),
+    }
+}
+
+/// Quantas corridas de /mine cada worker do pool venceu, e se a distribuição é compatível com a uniforme.
+pub async fn mining_fairness_handler(State(state): State<AppState>) -> Json<MiningFairness> {
+    let workers = state.miner.threads();
+    let guard = state.chain.lock().unwrap();
+    Json(mining_fairness(&guard.worker_wins()[..workers]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testkit::{test_node, READ_KEY};
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use blockchain_core::{Block, BlockBuilder, ChainState};
+    use tower::ServiceExt;
+
+    // 2025-10-09T08:53:20Z
+    const T: u64 = 1_760_000_000_000;
+    const MINUTE: u64 = 60_000;
+
+    // Blocos 1 a 6 em T, T+1min (três blocos), T+2min e T+3min; o gênesis fica em 0
+    fn fixture_chain() -> ChainState {
+        let mut blocks = vec![Block::genesis()];
+        for offset in [0, MINUTE, MINUTE, MINUTE, 2 * MINUTE, 3 * MINUTE] {
+            let block = BlockBuilder::on(blocks.last().unwrap()).timestamp(T + offset).witness(1, 1, 2, 1).build();
+            blocks.push(block);
+        }
+        ChainState::from_blocks(blocks).unwrap()
+    }
+
+    async fn query(params: &str) -> (StatusCode, serde_json::Value) {
+        let (router, _, _) = test_node(fixture_chain());
+        let request = Request::get(format!("/blocks?{}", params)).header("x-api-key", READ_KEY);
+        let response = router.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    async fn indices(params: &str) -> Vec<u64> {
+        let (status, page) = query(params).await;
+        assert_eq!(status, StatusCode::OK, "{}: {}", params, page);
+        page["items"].as_array().unwrap().iter().map(|b| b["index"].as_u64().unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn interior_ranges_and_boundaries() {
+        assert_eq!(indices(&format!("from_ts={}&to_ts={}", T + 1, T + 2 * MINUTE)).await, [2, 3, 4, 5]);
+        // Os três blocos com o mesmo timestamp entram inteiros pelas duas pontas
+        assert_eq!(indices(&format!("from_ts={0}&to_ts={0}", T + MINUTE)).await, [2, 3, 4]);
+        assert_eq!(indices(&format!("to_ts={}", T)).await, [0, 1]);
+        assert_eq!(indices(&format!("from_ts={}", T + 3 * MINUTE)).await, [6]);
+        // Entre o gênesis e o primeiro bloco, e depois da ponta
+        assert!(indices(&format!("from_ts=1&to_ts={}", T - 1)).await.is_empty());
+        assert!(indices(&format!("from_ts={}", T + 3 * MINUTE + 1)).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn iso_datetimes_and_millis_mix() {
+        let iso = "from_ts=2025-10-09T08:54:20Z&to_ts=2025-10-09T08:55:20%2B00:00";
+        assert_eq!(indices(iso).await, [2, 3, 4, 5]);
+        assert_eq!(indices(&format!("from_ts=2025-10-09T08:56:20Z&to_ts={}", T + 3 * MINUTE)).await, [6]);
+    }
+
+    #[tokio::test]
+    async fn pagination_envelope() {
+        let (_, page) = query(&format!("from_ts={}&limit=2", T + 1)).await;
+        assert_eq!((page["total"].as_u64(), page["next_offset"].as_u64()), (Some(5), Some(2)));
+        assert_eq!(page["items"].as_array().unwrap().len(), 2);
+        let (_, last) = query(&format!("from_ts={}&limit=2&offset=4", T + 1)).await;
+        assert_eq!((last["items"][0]["index"].as_u64(), last["next_offset"].as_u64()), (Some(6), None));
+    }
+
+    #[tokio::test]
+    async fn bad_ranges_are_400() {
+        let (status, body) = query(&format!("from_ts={}&to_ts={}", T + MINUTE, T)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "from_ts must not be after to_ts");
+        let (status, body) = query("from_ts=yesterday").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().starts_with("from_ts must be unix milliseconds or an ISO-8601"));
+        assert_eq!(query("to_ts=2025-13-01T00:00:00Z").await.0, StatusCode::BAD_REQUEST);
+    }
+}
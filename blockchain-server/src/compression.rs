@@ -0,0 +1,59 @@
+// src/compression.rs
+//! Gzip das respostas para quem manda `Accept-Encoding: gzip`, com o mesmo deflate dos snapshots do
+//! núcleo. Fica por dentro de `bandwidth::meter`, que assim conta os bytes já codificados. Corpos em
+//! fluxo (SSE) e sem tamanho conhecido passam como estão.
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http_body::Body as _;
+
+// Abaixo disto o cabeçalho e o rodapé do gzip comem o ganho
+const MIN_BYTES: u64 = 1024;
+// Nível do deflate: as respostas são comprimidas a cada requisição, então o meio-termo
+const DEFLATE_LEVEL: u8 = 6;
+
+// `gzip` (ou `*`) na lista, sem `q=0`
+fn accepts_gzip(req: &Request) -> bool {
+    let values = req.headers().get_all(header::ACCEPT_ENCODING).into_iter().filter_map(|v| v.to_str().ok());
+    values.flat_map(|v| v.split(',')).any(|coding| {
+        let mut params = coding.split(';').map(str::trim);
+        let name = params.next().unwrap_or_default();
+        let refused = params.filter_map(|p| p.strip_prefix("q=")).any(|q| q.parse::<f32>().is_ok_and(|q| q == 0.0));
+        (name.eq_ignore_ascii_case("gzip") || name == "*") && !refused
+    })
+}
+
+/// `data` em gzip (RFC 1952): cabeçalho mínimo sem nome nem horário, deflate, CRC-32 e tamanho.
+pub fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+    out.extend(miniz_oxide::deflate::compress_to_vec(data, DEFLATE_LEVEL));
+    out.extend(crc32fast::hash(data).to_le_bytes());
+    out.extend((data.len() as u32).to_le_bytes());
+    out
+}
+
+/// Comprime o corpo de tamanho conhecido a partir de `MIN_BYTES` quando o cliente aceita gzip.
+pub async fn compress(req: Request, next: Next) -> Response {
+    let accepts = accepts_gzip(&req);
+    let response = next.run(req).await;
+    let size = response.body().size_hint().exact();
+    if size.is_none_or(|size| size < MIN_BYTES) || response.headers().contains_key(header::CONTENT_ENCODING) {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    parts.headers.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    if !accepts {
+        return Response::from_parts(parts, body);
+    }
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let encoded = gzip(&bytes);
+    parts.headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+    parts.headers.insert(header::CONTENT_LENGTH, HeaderValue::from(encoded.len()));
+    Response::from_parts(parts, Body::from(encoded))
+}
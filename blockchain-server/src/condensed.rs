@@ -0,0 +1,97 @@
+// src/condensed.rs
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::errors::ApiError;
+use crate::history::{self, AtHeight};
+use crate::state::AppState;
+
+// Primo de Mersenne 2^61 - 1: o produto dos primos da cadeia é reduzido por ele
+const PRIMORIAL_MODULUS: u64 = (1 << 61) - 1;
+// Tamanho do bloco interno do SHA-256, em bytes (RFC 2104)
+const HMAC_BLOCK: usize = 64;
+
+#[derive(Serialize)]
+pub struct CondensedProof {
+    pub height: usize,
+    pub genesis_hash: String,
+    pub tip_hash: String,
+    // Candidatos registrados nos blocos minerados por este nó
+    pub total_candidates: u64,
+    pub sum_of_primes: u128,
+    pub chain_primorial_mod: u64,
+    pub primorial_modulus: u64,
+    // HMAC-SHA256 em hex de `message()`, com a chave de X-API-Key
+    pub signature: String,
+}
+
+impl CondensedProof {
+    /// Texto autenticado: os campos separados por `:`, na ordem da estrutura.
+    fn message(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{}:{}:{}",
+            self.height,
+            self.genesis_hash,
+            self.tip_hash,
+            self.total_candidates,
+            self.sum_of_primes,
+            self.chain_primorial_mod,
+            self.primorial_modulus
+        )
+    }
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    // Chaves maiores que o bloco entram pelo hash delas
+    let mut padded = [0u8; HMAC_BLOCK];
+    if key.len() > HMAC_BLOCK {
+        padded[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        padded[..key.len()].copy_from_slice(key);
+    }
+    let inner = Sha256::new().chain_update(padded.map(|b| b ^ 0x36)).chain_update(message).finalize();
+    Sha256::new().chain_update(padded.map(|b| b ^ 0x5c)).chain_update(inner).finalize().into()
+}
+
+/// Resumo do trabalho da cadeia até `at_height` (padrão: a ponta), autenticado por HMAC sob a chave de
+/// quem pediu. Um cliente leve confere a assinatura com a própria chave e pede alguns blocos em
+/// /block/:index para conferir por amostragem.
+pub async fn condensed_proof_handler(
+    State(state): State<AppState>,
+    Query(query): Query<AtHeight>,
+    headers: HeaderMap,
+) -> Result<Json<CondensedProof>, Response> {
+    // Com a rota aberta ao público a chave não passa pelo middleware; sem ela não há o que assinar
+    let Some(key) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) else {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Missing X-API-Key header").into());
+    };
+    if !state.config.api_keys.iter().any(|(k, _)| k == key) {
+        return Err(ApiError::new(StatusCode::UNAUTHORIZED, "Invalid API key").into());
+    }
+
+    let guard = state.chain.lock().unwrap();
+    let derived = history::derived(&state, &guard, query.at_height).map_err(IntoResponse::into_response)?;
+    let blocks = &guard.blocks()[..derived.height()];
+    let chain_primorial_mod = blocks
+        .iter()
+        .fold(1u128, |acc, b| acc * b.prime.residue(PRIMORIAL_MODULUS) as u128 % PRIMORIAL_MODULUS as u128);
+    let mut proof = CondensedProof {
+        height: blocks.len(),
+        genesis_hash: blocks[0].hash.clone(),
+        tip_hash: blocks[blocks.len() - 1].hash.clone(),
+        total_candidates: derived.recorded_candidates().1,
+        // Satura nas cadeias u128
+        sum_of_primes: blocks.iter().fold(0u128, |sum, b| sum.saturating_add(b.prime.get())),
+        chain_primorial_mod: chain_primorial_mod as u64,
+        primorial_modulus: PRIMORIAL_MODULUS,
+        signature: String::new(),
+    };
+    proof.signature = hex::encode(hmac_sha256(key.as_bytes(), proof.message().as_bytes()));
+    Ok(Json(proof))
+}
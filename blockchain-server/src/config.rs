@@ -0,0 +1,298 @@
+// src/config.rs
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use blockchain_core::{
+    AlgorithmConfig, EmissionSchedule, Intensity, MiningSchedule, PrimeWidth, DEFAULT_EMISSION, DEFAULT_EPOCH_SIZE,
+};
+
+use crate::errors::ApiErrorConfig;
+use crate::middleware::{Role, RouteRoles};
+use crate::slo::SloTargets;
+
+/// Configuração do nó lida das variáveis de ambiente (secrets do Shuttle).
+#[derive(Debug, Clone)]
+pub struct Config {
+    // 0 desativa a pré-computação de candidatos
+    pub candidate_pool_size: usize,
+    // Diretório dos arquivos persistidos; sem ele tudo fica só em memória
+    pub data_dir: Option<PathBuf>,
+    // Peers registrados e sincronizados na inicialização
+    pub bootstrap_peers: Vec<String>,
+    // X-API-Key enviada ao buscar a cadeia de um peer: a API_KEY do nó, que a rede compartilha
+    pub peer_api_key: String,
+    // NODE_KEY: chave do nó em hex; sem ela vale a de DATA_DIR, ou uma nova
+    pub node_key: Option<String>,
+    // Ativações extras de regras no formato "versao:altura,versao:altura"
+    pub rules_activation: Vec<(u32, u64)>,
+    // Alturas que um modelo de mineração continua válido depois de emitido
+    pub template_window: u64,
+    // Transações pendentes aceitas antes de recusar com mempool_full
+    pub mempool_capacity: usize,
+    // Transações do mempool incluídas em cada bloco minerado pelo nó
+    pub block_max_transactions: usize,
+    // Algoritmo de reajuste inicial (window ou ema); alterável por /admin/difficulty
+    pub difficulty_algorithm: AlgorithmConfig,
+    // Duração do benchmark que calibra a dificuldade de uma cadeia nova; 0 pula a calibração
+    pub bootstrap_calibration_secs: f64,
+    // Blocos por época em /chain/epoch/:n
+    pub epoch_size: u64,
+    // Calibração padrão de /chain/energy-estimate, medida em benchmark
+    pub joules_per_candidate: f64,
+    // Intervalo entre snapshots das métricas; 0 grava só no desligamento
+    pub metrics_snapshot_secs: u64,
+    // Profundidade abaixo da ponta além da qual um órfão não pode mais ser adotado e é coletado
+    pub gc_max_reorg_depth: u64,
+    // Intervalo entre coletas de órfãos; 0 deixa só a coleta manual de POST /admin/gc
+    pub gc_interval_secs: u64,
+    // Confirmações a partir das quais /chain/finality-score/:index considera o bloco final
+    pub finality_threshold: u64,
+    // Calendário de recompensas das cadeias novas (só pago com as regras v6 ativas)
+    pub emission: EmissionSchedule,
+    // Largura dos primos das cadeias novas (u64 ou u128); fica fixa a partir do gênesis
+    pub prime_width: PrimeWidth,
+    // Quem recebe a recompensa dos blocos que este nó minera; padrão: a chave pública do nó
+    pub coinbase_address: Option<String>,
+    // Threads do pool de mineração; padrão: paralelismo disponível
+    pub mining_threads: usize,
+    // Semente dos workers de /mine: desligada (thread_rng), "entropy" (nova a cada job) ou um número fixo
+    pub mining_seed: MiningSeed,
+    // Fração da CPU que cada thread de mineração pode ocupar (0.1 a 1.0)
+    pub mining_intensity: Intensity,
+    // Prazo de cada /mine, em segundos; 0 espera até achar um primo
+    pub mining_timeout_secs: u64,
+    // Horas UTC em que a pré-computação roda, ex.: "22-6"; vazio libera o dia todo
+    pub mining_schedule: MiningSchedule,
+    // Valores iniciais; alteráveis em tempo de execução por /admin/config
+    pub alerts: AlertConfig,
+    // Máximo de cadeias no nó, contando a default
+    pub chain_namespaces_max: usize,
+    // Chaves aceitas em X-API-Key e o papel de cada uma; API_KEY é sempre admin
+    pub api_keys: Vec<(String, Role)>,
+    // Papel exigido por grupo de rotas no formato "grupo=papel,..."; alterável por /admin/config
+    pub route_roles: RouteRoles,
+    // Objetivo padrão "p99_ms:taxa_de_erro" e por rota "MÉTODO /rota=p99_ms:taxa,..." de /admin/slo
+    pub slo_targets: SloTargets,
+    // Prazo das rotas com trabalho pesado; X-Request-Timeout-Ms só pode encurtá-lo
+    pub request_timeout_secs: u64,
+    // Maior distância procurada por /primes/next a partir do valor dado
+    pub prime_search_span: u64,
+    // Divisões por tentativa permitidas por requisição em /primes/check
+    pub prime_check_budget: u64,
+    // Requisições por minuto e por chave nas rotas que gastam CPU; 0 desliga o limite
+    pub cpu_rate_limit: u32,
+    // Atraso do escalonador (ms) a partir do qual as rotas de baixa prioridade recebem 503; 0 desliga
+    pub shed_delay_ms: u64,
+    // Tempo seguido com o atraso abaixo da metade do limite para o descarte acabar
+    pub shed_recovery_secs: u64,
+    // Estruturas de prefixos (?at_height=) mantidas em cache por cadeia; 0 recalcula sempre
+    pub history_cache_entries: usize,
+    // Esperas simultâneas de GET /tip no nó; além disso, 503
+    pub tip_long_polls_max: usize,
+    // Corpo das respostas de erro (src/errors.rs)
+    pub api_errors: ApiErrorConfig,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        let mempool_capacity = env_or("MEMPOOL_CAPACITY", 10_000);
+        let api_keys = api_keys_from_env();
+        Config {
+            candidate_pool_size: env_or("CANDIDATE_POOL_SIZE", 0),
+            data_dir: env::var("DATA_DIR").ok().filter(|v| !v.is_empty()).map(PathBuf::from),
+            bootstrap_peers: env::var("BOOTSTRAP_PEERS")
+                .map(|v| v.split(',').map(str::trim).filter(|p| !p.is_empty()).map(String::from).collect())
+                .unwrap_or_default(),
+            peer_api_key: api_keys[0].0.clone(),
+            node_key: env::var("NODE_KEY").ok().filter(|v| !v.trim().is_empty()),
+            rules_activation: env::var("RULES_ACTIVATION")
+                .map(|v| {
+                    v.split(',')
+                        .filter_map(|pair| pair.trim().split_once(':'))
+                        .filter_map(|(version, height)| Some((version.parse().ok()?, height.parse().ok()?)))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            template_window: env_or("TEMPLATE_WINDOW", 6),
+            mempool_capacity,
+            block_max_transactions: env_or("BLOCK_MAX_TRANSACTIONS", 100),
+            difficulty_algorithm: difficulty_algorithm_from_env(),
+            bootstrap_calibration_secs: match env_or("BOOTSTRAP_CALIBRATION_SECS", 3.0) {
+                secs if (0.0..=60.0).contains(&secs) => secs,
+                secs => panic!("BOOTSTRAP_CALIBRATION_SECS inválido: {} fora de 0 a 60", secs),
+            },
+            epoch_size: match env_or("EPOCH_SIZE", DEFAULT_EPOCH_SIZE) {
+                0 => panic!("EPOCH_SIZE inválido: deve ser maior que zero"),
+                size => size,
+            },
+            joules_per_candidate: env_or("JOULES_PER_CANDIDATE", 5e-5),
+            metrics_snapshot_secs: env_or("METRICS_SNAPSHOT_SECS", 60),
+            gc_max_reorg_depth: match env_or("GC_MAX_REORG_DEPTH", 100) {
+                0 => panic!("GC_MAX_REORG_DEPTH inválido: deve ser maior que zero"),
+                depth => depth,
+            },
+            gc_interval_secs: env_or("GC_INTERVAL_SECS", 300),
+            finality_threshold: env_or("FINALITY_THRESHOLD", 6),
+            emission: emission_from_env(),
+            prime_width: match env::var("PRIME_WIDTH").unwrap_or_default().trim() {
+                "" => PrimeWidth::default(),
+                other => PrimeWidth::parse(other)
+                    .unwrap_or_else(|| panic!("PRIME_WIDTH inválido: {:?} (use u64 ou u128)", other)),
+            },
+            coinbase_address: env::var("COINBASE_ADDRESS").ok().filter(|v| !v.trim().is_empty()),
+            mining_threads: env_or(
+                "MINING_THREADS",
+                std::thread::available_parallelism().map_or(4, |n| n.get()),
+            ),
+            mining_seed: match env::var("MINING_SEED").unwrap_or_default().trim() {
+                "" => MiningSeed::Off,
+                "entropy" => MiningSeed::Entropy,
+                seed => MiningSeed::Fixed(seed.parse().unwrap_or_else(|e| panic!("MINING_SEED inválido: {}", e))),
+            },
+            mining_intensity: Intensity::new(env_or("MINING_INTENSITY", 1.0))
+                .unwrap_or_else(|e| panic!("MINING_INTENSITY inválido: {}", e)),
+            mining_timeout_secs: env_or("MINING_TIMEOUT_SECS", 0),
+            mining_schedule: MiningSchedule::parse(&env::var("MINING_HOURS").unwrap_or_default())
+                .unwrap_or_else(|e| panic!("MINING_HOURS inválido: {}", e)),
+            alerts: AlertConfig::from_env(mempool_capacity),
+            chain_namespaces_max: env_or("CHAIN_NAMESPACES_MAX", 4),
+            request_timeout_secs: env_or("REQUEST_TIMEOUT_SECS", 30),
+            prime_search_span: match env_or("PRIME_SEARCH_SPAN", 100_000) {
+                0 => panic!("PRIME_SEARCH_SPAN inválido: deve ser maior que zero"),
+                span => span,
+            },
+            prime_check_budget: match env_or("PRIME_CHECK_BUDGET", 1_000_000) {
+                0 => panic!("PRIME_CHECK_BUDGET inválido: deve ser maior que zero"),
+                budget => budget,
+            },
+            cpu_rate_limit: env_or("CPU_RATE_LIMIT", 60),
+            shed_delay_ms: env_or("SHED_DELAY_MS", 250),
+            shed_recovery_secs: env_or("SHED_RECOVERY_SECS", 10),
+            history_cache_entries: env_or("HISTORY_CACHE_ENTRIES", 16),
+            tip_long_polls_max: env_or("TIP_LONG_POLLS_MAX", 256),
+            api_errors: ApiErrorConfig::from_env(|key| env::var(key).ok()),
+            slo_targets: SloTargets::parse(
+                &env::var("SLO_DEFAULT_TARGET").unwrap_or_default(),
+                &env::var("SLO_TARGETS").unwrap_or_default(),
+            )
+            .unwrap_or_else(|e| panic!("SLO_TARGETS inválido: {}", e)),
+            api_keys,
+            route_roles: RouteRoles::parse(&env::var("ROUTE_ROLES").unwrap_or_default())
+                .unwrap_or_else(|e| panic!("ROUTE_ROLES inválido: {}", e)),
+        }
+    }
+
+    pub fn data_file(&self, name: &str) -> Option<PathBuf> {
+        self.data_dir.as_ref().map(|dir| dir.join(name))
+    }
+
+    pub fn mining_timeout(&self) -> Option<Duration> {
+        (self.mining_timeout_secs > 0).then(|| Duration::from_secs(self.mining_timeout_secs))
+    }
+}
+
+/// Origem da semente dos jobs de mineração; com semente, o bloco vencedor é reprodutível.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MiningSeed {
+    Off,
+    Entropy,
+    Fixed(u64),
+}
+
+impl MiningSeed {
+    /// Semente do próximo job; `None` mantém os workers com thread_rng.
+    pub fn next(self) -> Option<u64> {
+        match self {
+            MiningSeed::Off => None,
+            MiningSeed::Entropy => Some(uuid::Uuid::new_v4().as_u64_pair().0),
+            MiningSeed::Fixed(seed) => Some(seed),
+        }
+    }
+}
+
+/// Condições de alerta avaliadas periodicamente. Limites em zero desativam a condição.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertConfig {
+    pub eval_secs: u64,
+    pub no_block_minutes: u64,
+    pub validation_failure_window_secs: u64,
+    pub no_peers: bool,
+    pub mempool_threshold: usize,
+    pub difficulty_clamp_streak: usize,
+}
+
+/// Atualização parcial de `AlertConfig` recebida por /admin/config.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertConfigPatch {
+    pub eval_secs: Option<u64>,
+    pub no_block_minutes: Option<u64>,
+    pub validation_failure_window_secs: Option<u64>,
+    pub no_peers: Option<bool>,
+    pub mempool_threshold: Option<usize>,
+    pub difficulty_clamp_streak: Option<usize>,
+}
+
+impl AlertConfig {
+    fn from_env(mempool_capacity: usize) -> Self {
+        AlertConfig {
+            eval_secs: env_or("ALERT_EVAL_SECS", 30),
+            no_block_minutes: env_or("ALERT_NO_BLOCK_MINUTES", 30),
+            validation_failure_window_secs: env_or("ALERT_VALIDATION_WINDOW_SECS", 600),
+            no_peers: env_or("ALERT_NO_PEERS", true),
+            mempool_threshold: env_or("ALERT_MEMPOOL_THRESHOLD", mempool_capacity * 9 / 10),
+            difficulty_clamp_streak: env_or("ALERT_DIFFICULTY_CLAMP_STREAK", 5),
+        }
+    }
+
+    pub fn apply(&mut self, patch: AlertConfigPatch) -> Result<(), String> {
+        if patch.eval_secs == Some(0) {
+            return Err("eval_secs must be positive".to_string());
+        }
+        if let Some(v) = patch.eval_secs { self.eval_secs = v; }
+        if let Some(v) = patch.no_block_minutes { self.no_block_minutes = v; }
+        if let Some(v) = patch.validation_failure_window_secs { self.validation_failure_window_secs = v; }
+        if let Some(v) = patch.no_peers { self.no_peers = v; }
+        if let Some(v) = patch.mempool_threshold { self.mempool_threshold = v; }
+        if let Some(v) = patch.difficulty_clamp_streak { self.difficulty_clamp_streak = v; }
+        Ok(())
+    }
+}
+
+fn api_keys_from_env() -> Vec<(String, Role)> {
+    let admin = env::var("API_KEY").expect("API_KEY must be set in Shuttle secrets");
+    let mut keys = vec![(admin, Role::Admin)];
+    for (var, role) in [("MINE_API_KEY", Role::Mine), ("READ_API_KEY", Role::Read)] {
+        if let Some(key) = env::var(var).ok().filter(|k| !k.is_empty()) {
+            keys.push((key, role));
+        }
+    }
+    keys
+}
+
+fn emission_from_env() -> EmissionSchedule {
+    let schedule = EmissionSchedule::new(
+        env_or("EMISSION_INITIAL_REWARD", DEFAULT_EMISSION.initial_reward),
+        env_or("EMISSION_HALVING_INTERVAL", DEFAULT_EMISSION.halving_interval),
+        env_or("EMISSION_MIN_REWARD", DEFAULT_EMISSION.min_reward),
+    );
+    schedule.unwrap_or_else(|e| panic!("EMISSION_* inválido: {}", e))
+}
+
+fn difficulty_algorithm_from_env() -> AlgorithmConfig {
+    let algorithm = match env::var("DIFFICULTY_ALGORITHM").unwrap_or_default().trim() {
+        "" | "window" => AlgorithmConfig::Window,
+        "ema" => AlgorithmConfig::Ema { alpha: env_or("EMA_ALPHA", 0.2), max_step: env_or("EMA_MAX_STEP", 1.25) },
+        other => panic!("DIFFICULTY_ALGORITHM inválido: {:?} (use window ou ema)", other),
+    };
+    if let Err(e) = algorithm.build() {
+        panic!("DIFFICULTY_ALGORITHM inválido: {}", e);
+    }
+    algorithm
+}
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
@@ -0,0 +1,121 @@
+// src/consistency.rs
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{request::Parts, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use blockchain_core::ChainState;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{timeout_at, Instant};
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+
+pub const CHAIN_POSITION: &str = "x-chain-position";
+pub const REQUIRE_POSITION: &str = "x-require-position";
+
+// Tempo máximo que uma leitura espera a cadeia local alcançar a posição exigida
+const MAX_WAIT: Duration = Duration::from_secs(2);
+
+/// Token `<height>:<tip_hash>` devolvido pelas rotas que alteram a cadeia.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainPosition {
+    pub height: usize,
+    pub tip_hash: String,
+}
+
+impl ChainPosition {
+    pub fn of(chain: &ChainState) -> Self {
+        ChainPosition { height: chain.height(), tip_hash: chain.tip().hash.clone() }
+    }
+
+    pub fn reached_by(&self, chain: &ChainState) -> bool {
+        match self.height.checked_sub(1) {
+            None => true,
+            Some(index) => chain.blocks().get(index).is_some_and(|b| b.hash == self.tip_hash),
+        }
+    }
+}
+
+impl fmt::Display for ChainPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.height, self.tip_hash)
+    }
+}
+
+impl FromStr for ChainPosition {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (height, tip_hash) = s.split_once(':').ok_or(())?;
+        let height = height.trim().parse().map_err(|_| ())?;
+        let tip_hash = tip_hash.trim();
+        if tip_hash.is_empty() {
+            return Err(());
+        }
+        Ok(ChainPosition { height, tip_hash: tip_hash.to_string() })
+    }
+}
+
+/// Espera (no máximo `max_wait`) até a cadeia local conter `required`.
+pub async fn wait_for_position(state: &AppState, required: &ChainPosition, max_wait: Duration) -> bool {
+    // Inscreve antes de checar para não perder um bloco anexado entre as duas etapas
+    let mut events = state.events.subscribe();
+    let deadline = Instant::now() + max_wait;
+    loop {
+        if required.reached_by(&state.chain.lock().unwrap()) {
+            return true;
+        }
+        match timeout_at(deadline, events.recv()).await {
+            Ok(Ok(_)) | Ok(Err(RecvError::Lagged(_))) => continue,
+            Ok(Err(RecvError::Closed)) | Err(_) => return false,
+        }
+    }
+}
+
+/// Extractor das rotas de leitura: honra o header opcional `X-Require-Position`.
+#[derive(Debug)]
+pub struct RequirePosition;
+
+#[async_trait]
+impl FromRequestParts<AppState> for RequirePosition {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(REQUIRE_POSITION) else {
+            return Ok(RequirePosition);
+        };
+        let required: ChainPosition = value
+            .to_str()
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| ApiError::new(StatusCode::BAD_REQUEST, "Invalid X-Require-Position header"))?;
+
+        if wait_for_position(state, &required, MAX_WAIT).await {
+            Ok(RequirePosition)
+        } else {
+            Err(ApiError::new(
+                StatusCode::PRECONDITION_FAILED,
+                format!("Chain has not reached position {}", required),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// Middleware das rotas que alteram a cadeia: anexa `X-Chain-Position` à resposta.
+pub async fn stamp_position(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    if response.status().is_success() {
+        let position = ChainPosition::of(&state.chain.lock().unwrap());
+        if let Ok(value) = HeaderValue::from_str(&position.to_string()) {
+            response.headers_mut().insert(CHAIN_POSITION, value);
+        }
+    }
+    response
+}
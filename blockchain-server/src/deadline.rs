@@ -0,0 +1,291 @@
+// src/deadline.rs
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, MatchedPath},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
+use blockchain_core::CancelToken;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::convert::Infallible;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::{timeout_at, Instant};
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+use crate::store;
+use crate::sync::now_secs;
+
+// O cliente pode encurtar o prazo do servidor, nunca alongar
+pub const REQUEST_TIMEOUT: &str = "x-request-timeout-ms";
+// Trabalhos encerrados mantidos para consulta em /admin/runtime
+const RECENT_JOBS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobOutcome {
+    Running,
+    Completed,
+    TimedOut,
+    ClientGone,
+    // Estava em andamento quando o nó parou
+    Interrupted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobView {
+    pub id: u64,
+    pub operation: String,
+    pub started_at: u64,
+    pub progress: u64,
+    pub outcome: JobOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+struct Job {
+    operation: String,
+    started_at: u64,
+    token: Arc<CancelToken>,
+    // Semente de uma mineração reprodutível
+    seed: Option<u64>,
+}
+
+// Conteúdo de jobs.json: o progresso é o do momento da última gravação
+#[derive(Default, Serialize, Deserialize)]
+struct Snapshot {
+    next_id: u64,
+    jobs: Vec<JobView>,
+}
+
+/// Trabalhos canceláveis em andamento e os últimos encerrados.
+#[derive(Default)]
+pub struct Jobs {
+    next_id: u64,
+    running: BTreeMap<u64, Job>,
+    // O progresso é lido do token na consulta, então dá para ver que parou de avançar
+    recent: VecDeque<(u64, Job, JobOutcome)>,
+    // jobs.json do nó; None em memória
+    path: Option<PathBuf>,
+}
+
+impl Jobs {
+    /// Restaura os trabalhos gravados; os que estavam em andamento voltam como `interrupted`.
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let snapshot: Snapshot = store::load(path.as_deref(), "Registro de trabalhos");
+        let mut jobs = Jobs { next_id: snapshot.next_id, path, ..Jobs::default() };
+        let mut restored = snapshot.jobs;
+        restored.sort_by_key(|job| job.id);
+        for record in restored {
+            let token = Arc::new(CancelToken::new());
+            token.advance(record.progress);
+            let outcome = match record.outcome {
+                JobOutcome::Running => JobOutcome::Interrupted,
+                outcome => outcome,
+            };
+            let job = Job { operation: record.operation, started_at: record.started_at, token, seed: record.seed };
+            jobs.retire(record.id, job, outcome);
+        }
+        jobs
+    }
+
+    fn persist(&self) {
+        let snapshot = Snapshot { next_id: self.next_id, jobs: self.list() };
+        store::save(self.path.as_deref(), &snapshot, "os trabalhos");
+    }
+
+    fn start(&mut self, operation: String, token: Arc<CancelToken>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.running.insert(id, Job { operation, started_at: now_secs(), token, seed: None });
+        self.persist();
+        id
+    }
+
+    /// Registra uma mineração com semente já concluída; o progresso são os candidatos do vencedor.
+    pub fn record_mining(&mut self, seed: u64, started_at: u64, candidates: u64) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let token = Arc::new(CancelToken::new());
+        token.advance(candidates);
+        let job = Job { operation: "/mine".to_string(), started_at, token, seed: Some(seed) };
+        self.retire(id, job, JobOutcome::Completed);
+        self.persist();
+    }
+
+    fn retire(&mut self, id: u64, job: Job, outcome: JobOutcome) {
+        if self.recent.len() == RECENT_JOBS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((id, job, outcome));
+    }
+
+    // Só o primeiro encerramento conta
+    fn finish(&mut self, id: u64, outcome: JobOutcome) {
+        let Some(job) = self.running.remove(&id) else { return };
+        if outcome != JobOutcome::Completed {
+            job.token.cancel();
+        }
+        self.retire(id, job, outcome);
+        self.persist();
+    }
+
+    pub fn list(&self) -> Vec<JobView> {
+        let running = self.running.iter().map(|(&id, job)| view(id, job, JobOutcome::Running));
+        running.chain(self.recent.iter().map(|(id, job, outcome)| view(*id, job, *outcome))).collect()
+    }
+}
+
+fn view(id: u64, job: &Job, outcome: JobOutcome) -> JobView {
+    JobView {
+        id,
+        operation: job.operation.clone(),
+        started_at: job.started_at,
+        progress: job.token.progress(),
+        outcome,
+        seed: job.seed,
+    }
+}
+
+/// Prazo esgotado antes de o trabalho terminar (504). Um cliente que desconectou não recebe resposta;
+/// o trabalho aparece como `client_gone` em /admin/runtime.
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl IntoResponse for Cancelled {
+    fn into_response(self) -> Response {
+        ApiError::new(StatusCode::GATEWAY_TIMEOUT, "Deadline exceeded").into_response()
+    }
+}
+
+/// Prazo da requisição e o token que o trabalho pesado consulta.
+pub struct Deadline {
+    at: Instant,
+    operation: String,
+    token: Arc<CancelToken>,
+    jobs: Arc<Mutex<Jobs>>,
+}
+
+// Encerra o trabalho como ClientGone se o handler for descartado no meio (cliente desconectou)
+struct Running<'a> {
+    jobs: &'a Mutex<Jobs>,
+    id: u64,
+}
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        self.jobs.lock().unwrap().finish(self.id, JobOutcome::ClientGone);
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for Deadline {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let limit = Duration::from_secs(state.config.request_timeout_secs);
+        let requested = parts
+            .headers
+            .get(REQUEST_TIMEOUT)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok())
+            .map(Duration::from_millis);
+        let timeout = requested.map_or(limit, |r| r.min(limit));
+        let operation = parts
+            .extensions
+            .get::<MatchedPath>()
+            .map_or_else(|| parts.uri.path().to_string(), |p| p.as_str().to_string());
+        Ok(Deadline {
+            at: Instant::now() + timeout,
+            operation,
+            token: Arc::new(CancelToken::new()),
+            jobs: state.jobs.clone(),
+        })
+    }
+}
+
+impl Deadline {
+    pub fn token(&self) -> Arc<CancelToken> {
+        self.token.clone()
+    }
+
+    /// Aguarda `future` até o prazo; ao estourar, cancela o token para o trabalho em outras threads parar.
+    pub async fn within<F: Future>(&self, future: F) -> Result<F::Output, Cancelled> {
+        let id = self.jobs.lock().unwrap().start(self.operation.clone(), self.token.clone());
+        let running = Running { jobs: &self.jobs, id };
+        let result = timeout_at(self.at, future).await;
+        let outcome = if result.is_ok() { JobOutcome::Completed } else { JobOutcome::TimedOut };
+        self.jobs.lock().unwrap().finish(running.id, outcome);
+        result.map_err(|_| Cancelled)
+    }
+
+    /// Executa `work` fora do runtime; `work` devolve `None` quando parou pelo token.
+    pub async fn run<T, F>(&self, work: F) -> Result<T, Cancelled>
+    where
+        T: Send + 'static,
+        F: FnOnce(&CancelToken) -> Option<T> + Send + 'static,
+    {
+        let token = self.token();
+        let handle = tokio::task::spawn_blocking(move || work(&token));
+        match self.within(handle).await? {
+            Ok(Some(value)) => Ok(value),
+            Ok(None) => Err(Cancelled),
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{test_clock, test_config, test_state};
+    use axum::http::Request;
+    use blockchain_core::{Block, BlockBuilder, ChainError, ChainState, RuleSchedule};
+
+    // Cadeia válida longa o bastante para a validação passar bem do prazo
+    fn long_chain(blocks: usize) -> Vec<Block> {
+        let mut chain = vec![Block::genesis()];
+        for _ in 1..blocks {
+            let block = BlockBuilder::on(chain.last().unwrap()).timestamp(1).witness(1, 1, 2, 1).build();
+            chain.push(block);
+        }
+        chain
+    }
+
+    #[tokio::test]
+    async fn validation_stops_at_the_deadline() {
+        const BLOCKS: usize = 40_000;
+        let blocks = long_chain(BLOCKS);
+        // Os blocos são v1: sem adiar a v2, a validação rápida pararia no bloco 1000 e não no prazo
+        let mut rules = RuleSchedule::default();
+        rules.schedule(2, BLOCKS as u64, 1).unwrap();
+        let state = test_state(ChainState::new(), &test_config(), test_clock());
+        let request = Request::post("/chain/resolve").header(REQUEST_TIMEOUT, "20").body(()).unwrap();
+        let (mut parts, _) = request.into_parts();
+        let Ok(deadline) = Deadline::from_request_parts(&mut parts, &state).await;
+        let token = deadline.token();
+        let result = deadline
+            .run(move |token| match ChainState::from_blocks_cancellable(blocks, rules, token) {
+                Err(ChainError::Cancelled { .. }) => None,
+                other => Some(other.is_ok()),
+            })
+            .await;
+        assert!(result.is_err(), "a validação deveria ter estourado o prazo");
+
+        // O trabalho para de avançar antes do fim da cadeia
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let stopped = token.progress();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(token.progress(), stopped);
+        assert!(stopped > 0 && stopped < BLOCKS as u64 - 1, "parou em {} blocos", stopped);
+
+        let jobs = state.jobs.lock().unwrap().list();
+        let job = jobs.iter().find(|job| job.operation == "/chain/resolve").unwrap();
+        assert_eq!((job.outcome, job.progress), (JobOutcome::TimedOut, stopped));
+        assert_eq!(Cancelled.into_response().status(), StatusCode::GATEWAY_TIMEOUT);
+    }
+}
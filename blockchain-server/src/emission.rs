@@ -0,0 +1,135 @@
+// src/emission.rs
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use blockchain_core::{reward_at_height, ChainState, EmissionError, EmissionSchedule, TARGET_TIME};
+use chrono::DateTime;
+use serde::Serialize;
+
+use crate::state::AppState;
+use crate::sync::now_secs;
+
+// Blocos recentes cujo intervalo médio estima a data do próximo halving
+const AVERAGE_WINDOW: usize = 100;
+
+#[derive(Serialize)]
+pub struct NextHalving {
+    pub height: u64,
+    pub blocks_remaining: u64,
+    pub reward_after: u64,
+    // Unix em ms e ISO-8601, a partir da ponta e do intervalo médio dos blocos
+    pub estimated_at: u64,
+    pub estimated_at_iso: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct EmissionView {
+    pub schedule: EmissionSchedule,
+    // Só os blocos das regras v6 pagam recompensa; antes delas nada é emitido
+    pub rewards_active: bool,
+    pub height: usize,
+    pub next_reward: u64,
+    pub total_emitted: u128,
+    pub average_block_secs: f64,
+    pub next_halving: Option<NextHalving>,
+    // Limite da soma de todas as recompensas; `null` com `min_reward` acima de zero
+    pub max_supply: Option<u128>,
+}
+
+fn emission_view(chain: &ChainState) -> EmissionView {
+    let schedule = *chain.rules().emission();
+    let tip = chain.tip();
+    let average_block_secs = chain.window_stats(AVERAGE_WINDOW).mean_mining_duration.unwrap_or(TARGET_TIME);
+    let next_halving = schedule.next_halving(tip.index).map(|height| {
+        let blocks_remaining = height - tip.index;
+        // O gênesis tem timestamp 0; sem blocos minerados a estimativa parte de agora
+        let from_ms = if tip.index == 0 { now_secs() * 1000 } else { tip.timestamp };
+        let estimated_at = from_ms.saturating_add((blocks_remaining as f64 * average_block_secs * 1000.0) as u64);
+        NextHalving {
+            height,
+            blocks_remaining,
+            reward_after: reward_at_height(height, &schedule),
+            estimated_at,
+            estimated_at_iso: i64::try_from(estimated_at)
+                .ok()
+                .and_then(DateTime::from_timestamp_millis)
+                .map(|dt| dt.to_rfc3339()),
+        }
+    });
+    EmissionView {
+        schedule,
+        rewards_active: chain.next_rules_version() >= 6,
+        height: chain.height(),
+        next_reward: chain.next_reward(),
+        total_emitted: chain.total_emitted(),
+        average_block_secs,
+        next_halving,
+        max_supply: schedule.max_supply(),
+    }
+}
+
+/// Calendário de emissão, quanto já foi emitido e quando vem o próximo halving.
+pub async fn emission_handler(State(state): State<AppState>) -> Json<EmissionView> {
+    Json(emission_view(&state.chain.lock().unwrap()))
+}
+
+/// Troca o calendário de emissão; só com a cadeia ainda no gênesis.
+pub async fn set_emission_handler(
+    State(state): State<AppState>,
+    Json(schedule): Json<EmissionSchedule>,
+) -> Result<Json<EmissionView>, Response> {
+    let mut guard = state.chain.lock().unwrap();
+    guard.set_emission(schedule).map_err(|e| {
+        let status = match e {
+            EmissionError::ChainNotEmpty { .. } => StatusCode::CONFLICT,
+            _ => StatusCode::UNPROCESSABLE_ENTITY,
+        };
+        (status, Json(serde_json::json!({ "error": e.to_string() }))).into_response()
+    })?;
+    Ok(Json(emission_view(&guard)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{test_node, ADMIN_KEY, MINE_KEY};
+    use axum::{body::Body, extract::Request, Router};
+    use blockchain_core::testkit::trivial_difficulty;
+    use tower::ServiceExt;
+
+    async fn call(router: &Router, request: Request) -> (StatusCode, serde_json::Value) {
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    fn put(schedule: serde_json::Value) -> Request {
+        let request = Request::put("/admin/emission").header("x-api-key", ADMIN_KEY);
+        request.header("content-type", "application/json").body(Body::from(schedule.to_string())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn schedule_changes_only_before_the_first_block() {
+        let mut chain = ChainState::new();
+        chain.difficulty = trivial_difficulty();
+        let (router, _, _) = test_node(chain);
+
+        let bad = serde_json::json!({ "initial_reward": 10, "halving_interval": 5, "min_reward": 11 });
+        assert_eq!(call(&router, put(bad)).await.0, StatusCode::UNPROCESSABLE_ENTITY);
+        let schedule = serde_json::json!({ "initial_reward": 100, "halving_interval": 10, "min_reward": 0 });
+        let (status, view) = call(&router, put(schedule.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((&view["schedule"], &view["max_supply"]), (&schedule, &serde_json::json!(1_870)));
+        assert_eq!((&view["next_halving"]["height"], &view["next_halving"]["reward_after"]), (&10.into(), &50.into()));
+
+        let mine = Request::get("/mine").header("x-api-key", MINE_KEY).body(Body::empty()).unwrap();
+        assert_eq!(call(&router, mine).await.0, StatusCode::OK);
+        let (status, body) = call(&router, put(schedule)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"], "emission schedule is fixed once blocks are mined (height 2)");
+    }
+}
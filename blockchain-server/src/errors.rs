@@ -0,0 +1,259 @@
+// src/errors.rs
+//! Respostas de erro das rotas. Cada handler devolve um `ApiError` com status, código e mensagem; o
+//! middleware `render` completa o corpo pela `ApiErrorConfig` do nó (id da requisição, backtrace em
+//! desenvolvimento, códigos legíveis). A configuração fica em `Config`, no `AppState`, e não no
+//! `ChainState`: é do nó HTTP, não da cadeia, e o núcleo não conhece rotas.
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use log::warn;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use crate::state::AppState;
+
+pub const REQUEST_ID: &str = "x-request-id";
+
+/// Como os erros saem: `include_stack_trace` só vale com `RUST_ENV=development` (e os quadros só
+/// aparecem com `RUST_BACKTRACE=1`, como nos pânicos).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiErrorConfig {
+    pub include_request_id: bool,
+    pub include_stack_trace: bool,
+    pub human_readable_codes: bool,
+}
+
+impl Default for ApiErrorConfig {
+    fn default() -> Self {
+        ApiErrorConfig { include_request_id: true, include_stack_trace: false, human_readable_codes: false }
+    }
+}
+
+impl ApiErrorConfig {
+    /// API_ERROR_REQUEST_ID, API_ERROR_STACK_TRACE e API_ERROR_HUMAN_CODES, com o backtrace recusado
+    /// fora de `RUST_ENV=development`.
+    pub fn from_env(env: impl Fn(&str) -> Option<String>) -> Self {
+        let flag = |key: &str, default: bool| env(key).and_then(|v| v.trim().parse().ok()).unwrap_or(default);
+        let development = env("RUST_ENV").is_some_and(|v| v.trim() == "development");
+        let stack_trace = flag("API_ERROR_STACK_TRACE", false);
+        if stack_trace && !development {
+            warn!("API_ERROR_STACK_TRACE ignorado: só vale com RUST_ENV=development");
+        }
+        ApiErrorConfig {
+            include_request_id: flag("API_ERROR_REQUEST_ID", true),
+            include_stack_trace: stack_trace && development,
+            human_readable_codes: flag("API_ERROR_HUMAN_CODES", false),
+        }
+    }
+}
+
+/// Id da requisição, criado por `render` (ou trazido pelo cliente em X-Request-Id) e visível às camadas
+/// de dentro, como a auditoria.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Erro de uma rota. O código padrão vem do status (`not_found`, `conflict`...); `details` são campos
+/// extras do corpo, ao lado de `error` e `code`.
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    status: StatusCode,
+    code: Cow<'static, str>,
+    message: String,
+    details: serde_json::Map<String, serde_json::Value>,
+    backtrace: Arc<Backtrace>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        ApiError {
+            status,
+            code: Cow::Owned(status_code(status)),
+            message: message.into(),
+            details: serde_json::Map::new(),
+            backtrace: Arc::new(Backtrace::capture()),
+        }
+    }
+
+    /// Erro a partir de um corpo JSON: `error` vira a mensagem e os outros campos, detalhes.
+    pub fn json(status: StatusCode, body: serde_json::Value) -> Self {
+        let serde_json::Value::Object(mut details) = body else {
+            return ApiError::new(status, body.to_string());
+        };
+        let message = match details.remove("error") {
+            Some(serde_json::Value::String(message)) => message,
+            Some(other) => other.to_string(),
+            None => status.canonical_reason().unwrap_or("Error").to_string(),
+        };
+        ApiError { details, ..ApiError::new(status, message) }
+    }
+
+    pub fn with_code(mut self, code: impl Into<Cow<'static, str>>) -> Self {
+        self.code = code.into();
+        self
+    }
+
+    fn body(&self, config: &ApiErrorConfig, request_id: Option<&str>) -> serde_json::Value {
+        let mut body = serde_json::Map::new();
+        body.insert("error".to_string(), self.message.clone().into());
+        let code = if config.human_readable_codes { human_code(&self.code) } else { self.code.to_string() };
+        body.insert("code".to_string(), code.into());
+        for (key, value) in &self.details {
+            body.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        if let Some(id) = request_id.filter(|_| config.include_request_id) {
+            body.insert("request_id".to_string(), id.into());
+        }
+        if config.include_stack_trace {
+            let trace = match self.backtrace.status() {
+                BacktraceStatus::Captured => self.backtrace.to_string(),
+                _ => "backtrace disabled; set RUST_BACKTRACE=1".to_string(),
+            };
+            body.insert("stack_trace".to_string(), trace.into());
+        }
+        serde_json::Value::Object(body)
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}): {}", self.status, self.code, self.message)
+    }
+}
+
+impl From<(StatusCode, String)> for ApiError {
+    fn from((status, message): (StatusCode, String)) -> Self {
+        ApiError::new(status, message)
+    }
+}
+
+// Sem `render` por fora (rotas montadas à mão), o corpo sai com a configuração padrão e sem id
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = self.body(&ApiErrorConfig::default(), None);
+        let mut response = (self.status, Json(body)).into_response();
+        response.extensions_mut().insert(self);
+        response
+    }
+}
+
+// Para `?` e `.into()` nos handlers que devolvem `Result<_, Response>`
+impl From<ApiError> for Response {
+    fn from(error: ApiError) -> Self {
+        error.into_response()
+    }
+}
+
+// `NOT_FOUND` -> `not_found`
+fn status_code(status: StatusCode) -> String {
+    status.canonical_reason().unwrap_or("error").to_ascii_lowercase().replace([' ', '-'], "_").replace('\'', "")
+}
+
+// `not_found` -> `Not found`
+fn human_code(code: &str) -> String {
+    let spaced = code.replace('_', " ");
+    let mut chars = spaced.chars();
+    chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+}
+
+/// Dá um id a cada requisição e refaz o corpo dos `ApiError` pela configuração do nó; o id também volta
+/// em X-Request-Id.
+pub async fn render(State(state): State<AppState>, mut req: Request, next: Next) -> Response {
+    let incoming = req.headers().get(REQUEST_ID).and_then(|v| v.to_str().ok()).filter(|v| v.len() <= 64);
+    // Requisições encaminhadas a outra cadeia já chegam com o id
+    let known = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+    let request_id = known.or(incoming.map(String::from)).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let mut response = next.run(req).await;
+    if let Some(error) = response.extensions_mut().remove::<ApiError>() {
+        let body = error.body(&state.config.api_errors, Some(&request_id));
+        let (mut parts, _) = response.into_parts();
+        parts.headers.remove(header::CONTENT_LENGTH);
+        parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        response = Response::from_parts(parts, Body::from(body.to_string()));
+    }
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{test_clock, test_config, test_router, test_state, READ_KEY};
+    use blockchain_core::ChainState;
+    use tower::ServiceExt;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| vars.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string())
+    }
+
+    async fn get(config: ApiErrorConfig, path: &str, request_id: Option<&str>) -> (Response, serde_json::Value) {
+        let config = crate::Config { api_errors: config, ..test_config() };
+        let router = test_router(test_state(ChainState::new(), &config, test_clock()));
+        let mut request = Request::get(path).header("x-api-key", READ_KEY);
+        if let Some(id) = request_id {
+            request = request.header(REQUEST_ID, id);
+        }
+        let response = router.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        let (parts, body) = response.into_parts();
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        (Response::from_parts(parts, Body::empty()), serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[test]
+    fn stack_trace_needs_development() {
+        assert_eq!(ApiErrorConfig::from_env(env(&[])), ApiErrorConfig::default());
+        let trace_in = |rust_env| {
+            ApiErrorConfig::from_env(env(&[("API_ERROR_STACK_TRACE", "true"), ("RUST_ENV", rust_env)]))
+        };
+        assert!(!trace_in("production").include_stack_trace);
+        assert!(trace_in("development").include_stack_trace);
+        let quiet = [("API_ERROR_REQUEST_ID", "false"), ("API_ERROR_HUMAN_CODES", "true")];
+        let quiet = ApiErrorConfig::from_env(env(&quiet));
+        assert!(!quiet.include_request_id && quiet.human_readable_codes);
+    }
+
+    #[test]
+    fn json_body_keeps_its_fields() {
+        let error = ApiError::json(StatusCode::CONFLICT, serde_json::json!({ "error": "stale", "tip_index": 4 }));
+        let body = error.with_code("stale_template").body(&ApiErrorConfig::default(), Some("abc"));
+        assert_eq!(
+            body,
+            serde_json::json!({ "error": "stale", "code": "stale_template", "tip_index": 4, "request_id": "abc" })
+        );
+        let human = ApiErrorConfig { human_readable_codes: true, ..ApiErrorConfig::default() };
+        assert_eq!(ApiError::new(StatusCode::NOT_FOUND, "gone").body(&human, None)["code"], "Not found");
+    }
+
+    #[tokio::test]
+    async fn render_adds_the_request_id() {
+        let (response, body) = get(ApiErrorConfig::default(), "/block/99", Some("req-1")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[REQUEST_ID], "req-1");
+        let expected = serde_json::json!({ "error": "Block 99 not found", "code": "not_found", "request_id": "req-1" });
+        assert_eq!(body, expected);
+
+        // Sem cabeçalho o id é gerado, e o do corpo é o mesmo da resposta
+        let (response, body) = get(ApiErrorConfig::default(), "/block/99", None).await;
+        assert_eq!(body["request_id"], response.headers()[REQUEST_ID].to_str().unwrap());
+    }
+
+    #[tokio::test]
+    async fn render_follows_the_node_config() {
+        let config =
+            ApiErrorConfig { include_request_id: false, include_stack_trace: true, human_readable_codes: true };
+        let (response, body) = get(config, "/block/99", None).await;
+        assert!(response.headers().contains_key(REQUEST_ID));
+        assert!(body.get("request_id").is_none());
+        assert_eq!(body["code"], "Not found");
+        assert!(body["stack_trace"].is_string());
+    }
+}
@@ -0,0 +1,96 @@
+// src/events.rs
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use blockchain_core::Block;
+use serde::Deserialize;
+use std::convert::Infallible;
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::state::AppState;
+
+// Blocos lidos da cadeia por vez no replay, para não segurar o lock
+const REPLAY_CHUNK: usize = 100;
+// Eventos prontos aguardando o cliente ler
+const STREAM_BUFFER: usize = 16;
+
+#[derive(Deserialize)]
+pub struct EventsQuery {
+    from_height: Option<u64>,
+}
+
+type EventSender = mpsc::Sender<Result<Event, Infallible>>;
+
+fn block_event(block: &Block) -> Event {
+    Event::default().event("block").id(block.index.to_string()).json_data(block).unwrap_or_default()
+}
+
+// Envia da cadeia os blocos a partir de `next` até a ponta atual; Err se o cliente desconectou
+async fn replay(state: &AppState, next: &mut u64, tx: &EventSender) -> Result<(), ()> {
+    loop {
+        let chunk: Vec<Block> = {
+            let guard = state.chain.lock().unwrap();
+            guard.blocks().iter().skip(*next as usize).take(REPLAY_CHUNK).cloned().collect()
+        };
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        for block in chunk {
+            tx.send(Ok(block_event(&block))).await.map_err(|_| ())?;
+            *next = block.index + 1;
+        }
+    }
+}
+
+// Replay desde `from` e depois os blocos ao vivo, cada altura uma única vez e em ordem
+async fn stream_blocks(state: AppState, from: Option<u64>, tx: EventSender) {
+    // Inscreve antes do replay para não perder blocos anexados durante ele
+    let mut live = state.events.subscribe();
+    let mut next = match from {
+        Some(from) => from,
+        None => state.chain.lock().unwrap().tip().index + 1,
+    };
+    if replay(&state, &mut next, &tx).await.is_err() {
+        return;
+    }
+    loop {
+        let received = tokio::select! {
+            received = live.recv() => received,
+            _ = tx.closed() => return,
+        };
+        let sent = match received {
+            // Já enviado no replay
+            Ok(block) if block.index < next => Ok(()),
+            Ok(block) if block.index == next => {
+                next += 1;
+                tx.send(Ok(block_event(&block))).await.map_err(|_| ())
+            }
+            // Lacuna (eventos perdidos ou cadeia substituída): completa pela cadeia
+            Ok(_) | Err(RecvError::Lagged(_)) => replay(&state, &mut next, &tx).await,
+            Err(RecvError::Closed) => return,
+        };
+        if sent.is_err() {
+            return;
+        }
+    }
+}
+
+/// Stream SSE de blocos. Com `from_height` reenvia primeiro o histórico; o `id` de cada evento é a
+/// altura do bloco, e o `Last-Event-ID` de uma reconexão (que prevalece) retoma na altura seguinte.
+pub async fn events_handler(
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+    headers: HeaderMap,
+) -> Sse<ReceiverStream<Result<Event, Infallible>>> {
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok());
+    let from = last_event_id.map(|id| id.saturating_add(1)).or(query.from_height);
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+    tokio::spawn(stream_blocks(state, from, tx));
+    Sse::new(ReceiverStream::new(rx)).keep_alive(KeepAlive::default())
+}
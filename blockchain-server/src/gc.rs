@@ -0,0 +1,151 @@
+// src/gc.rs
+use axum::{extract::State, Json};
+use log::info;
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::invariants::debug_check;
+use crate::orphans::OrphanView;
+use crate::state::AppState;
+use crate::sync::now_secs;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GcTrigger {
+    Background,
+    Manual,
+}
+
+/// Resultado de uma passada de coleta.
+#[derive(Debug, Clone, Serialize)]
+pub struct GcReport {
+    pub ran_at: u64,
+    pub trigger: GcTrigger,
+    pub tip_index: u64,
+    pub max_reorg_depth: u64,
+    pub orphans_removed: Vec<OrphanView>,
+    pub orphans_kept: usize,
+}
+
+/// Remove os órfãos que não podem mais ser adotados: o ponto de fork (o pai, na altura anterior)
+/// ficou mais fundo que `GC_MAX_REORG_DEPTH` abaixo da ponta. Tudo dentro dessa janela fica.
+/// Esta árvore não guarda blocos laterais de forks abandonados; as cadeias de peers que perdem a
+/// disputa nunca são mantidas, então o pool de órfãos é a única coisa a coletar.
+pub fn collect(state: &AppState, trigger: GcTrigger) -> GcReport {
+    let depth = state.config.gc_max_reorg_depth;
+    let tip_index = state.chain.lock().unwrap().tip().index;
+    let (removed, kept) = {
+        let mut orphans = state.orphans.lock().unwrap();
+        let removed = orphans.collect(tip_index, depth);
+        (removed, orphans.len())
+    };
+    if !removed.is_empty() {
+        info!("GC removeu {} órfãos abaixo da profundidade de reorg {}", removed.len(), depth);
+        state.metrics.lock().unwrap().counters_mut().orphans_collected += removed.len() as u64;
+    }
+    let report = GcReport {
+        ran_at: now_secs(),
+        trigger,
+        tip_index,
+        max_reorg_depth: depth,
+        orphans_removed: removed,
+        orphans_kept: kept,
+    };
+    *state.last_gc.lock().unwrap() = Some(report.clone());
+    debug_check(state, "gc");
+    report
+}
+
+/// Coleta a cada `interval`.
+pub async fn gc_loop(state: AppState, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        collect(&state, GcTrigger::Background);
+    }
+}
+
+/// Configuração, total coletado desde o primeiro deploy e a última passada.
+pub async fn gc_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let collected = state.metrics.lock().unwrap().counters().orphans_collected;
+    Json(serde_json::json!({
+        "max_reorg_depth": state.config.gc_max_reorg_depth,
+        "interval_secs": state.config.gc_interval_secs,
+        "orphans_collected_total": collected,
+        "last_run": *state.last_gc.lock().unwrap(),
+    }))
+}
+
+pub async fn run_gc_handler(State(state): State<AppState>) -> Json<GcReport> {
+    Json(collect(&state, GcTrigger::Manual))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testkit::{test_clock, test_config, test_router, test_state, ADMIN_KEY};
+    use axum::body::Body;
+    use axum::http::Request;
+    use blockchain_core::block::BlockBuilder;
+    use blockchain_core::{Block, ChainState};
+    use tower::ServiceExt;
+
+    async fn call(router: &axum::Router, request: axum::http::request::Builder, body: Body) -> serde_json::Value {
+        let request = request.header("x-api-key", ADMIN_KEY).header("content-type", "application/json");
+        let response = router.clone().oneshot(request.body(body).unwrap()).await.unwrap();
+        assert!(response.status().is_success(), "{}", response.status());
+        serde_json::from_slice(&axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+    }
+
+    async fn announce(router: &axum::Router, block: &Block) {
+        let body = Body::from(serde_json::to_string(block).unwrap());
+        assert_eq!(call(router, Request::post("/blocks/announce"), body).await["status"], "orphan");
+    }
+
+    // Filho de `prev` com outro primo, para sair do ramo principal
+    fn side(prev: &Block) -> Block {
+        BlockBuilder::on(prev).witness(1, 2, 3, 1).build()
+    }
+
+    /// Fork com ponto abaixo da profundidade máxima some; órfãos ainda adotáveis ficam.
+    #[tokio::test]
+    async fn deep_fork_orphans_are_collected() {
+        let config = crate::Config { gc_max_reorg_depth: 3, ..test_config() };
+        let state = test_state(ChainState::new(), &config, test_clock());
+        let router = test_router(state.clone());
+
+        // Fork no gênesis: só o segundo bloco é anunciado, então fica órfão com ponto de fork na altura 1
+        let deep = side(&side(&Block::genesis()));
+        announce(&router, &deep).await;
+        let main: Vec<Block> = {
+            let mut chain = state.chain.lock().unwrap();
+            for _ in 0..10 {
+                let block = BlockBuilder::on(chain.tip()).witness(1, 1, 2, 1).build();
+                chain.append(block).unwrap();
+            }
+            chain.blocks().to_vec()
+        };
+        // Lateral a partir do bloco 8, e o filho de um bloco 11 que ainda não chegou
+        let recent_side = side(&side(&main[8]));
+        announce(&router, &recent_side).await;
+        let missing = BlockBuilder::on(&main[10]).witness(1, 1, 2, 1).build();
+        let ahead = BlockBuilder::on(&missing).witness(1, 1, 2, 1).build();
+        announce(&router, &ahead).await;
+
+        let report = call(&router, Request::post("/admin/gc"), Body::empty()).await;
+        assert_eq!(report["trigger"], "manual");
+        assert_eq!(report["tip_index"], 10);
+        let removed: Vec<_> = report["orphans_removed"].as_array().unwrap().iter().map(|o| o["hash"].clone()).collect();
+        assert_eq!(removed, [deep.hash.as_str()]);
+        assert_eq!(report["orphans_kept"], 2);
+        assert_eq!(state.orphans.lock().unwrap().len(), 2);
+
+        // Segunda passada não acha nada; o total e a última passada aparecem no GET
+        let again = call(&router, Request::post("/admin/gc"), Body::empty()).await;
+        assert!(again["orphans_removed"].as_array().unwrap().is_empty());
+        let summary = call(&router, Request::get("/admin/gc"), Body::empty()).await;
+        assert_eq!(summary["orphans_collected_total"], 1);
+        assert_eq!(summary["max_reorg_depth"], 3);
+        assert_eq!(summary["last_run"]["orphans_kept"], 2);
+    }
+}
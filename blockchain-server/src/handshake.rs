@@ -0,0 +1,96 @@
+// src/handshake.rs
+use axum::{extract::State, Json};
+use blockchain_core::signature::SigningKey;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::config::Config;
+use crate::state::AppState;
+
+/// Identidade e regras do nó, trocadas antes de registrar um peer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Handshake {
+    pub chain_id: String,
+    pub genesis_hash: String,
+    pub height: u64,
+    // Versão que o próximo bloco deve declarar
+    pub rules_version: u32,
+    pub node_pubkey: String,
+    // Informativa: não entra na verificação de compatibilidade
+    pub software_version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mismatch {
+    pub field: String,
+    pub local: String,
+    pub remote: String,
+}
+
+pub fn local_handshake(state: &AppState) -> Handshake {
+    let guard = state.chain.lock().unwrap();
+    Handshake {
+        chain_id: state.namespace.clone(),
+        genesis_hash: guard.blocks()[0].hash.clone(),
+        height: guard.height() as u64,
+        rules_version: guard.next_rules_version(),
+        node_pubkey: hex::encode(state.node_key.verifying_key().to_bytes()),
+        software_version: env!("CARGO_PKG_VERSION").to_string(),
+    }
+}
+
+/// Campos em que o peer diverge deste nó. As regras são comparadas na altura do peer: a versão que ele
+/// declara tem que ser a que o nosso cronograma exige ali, senão um dos dois rejeitaria os blocos do outro.
+pub fn mismatches(state: &AppState, remote: &Handshake) -> Vec<Mismatch> {
+    let local = local_handshake(state);
+    let expected_rules = state.chain.lock().unwrap().rules().version_at(remote.height);
+    let mut found = Vec::new();
+    let mut compare = |field: &str, local: String, remote: String| {
+        if local != remote {
+            found.push(Mismatch { field: field.to_string(), local, remote });
+        }
+    };
+    compare("chain_id", local.chain_id, remote.chain_id.clone());
+    compare("genesis_hash", local.genesis_hash, remote.genesis_hash.clone());
+    compare("rules_version", expected_rules.to_string(), remote.rules_version.to_string());
+    found
+}
+
+pub async fn fetch_handshake(client: &reqwest::Client, url: &str) -> Result<Handshake, String> {
+    let response = client.get(format!("{}/handshake", url)).send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("peer answered {}", response.status()));
+    }
+    response.json().await.map_err(|e| e.to_string())
+}
+
+/// Chave do nó: NODE_KEY (hex de 32 bytes), senão a gravada em `<DATA_DIR>/node_key`, senão uma nova,
+/// gravada quando há DATA_DIR para a identidade sobreviver a reinícios.
+pub fn load_node_key(config: &Config) -> SigningKey {
+    let parse = |hex_key: &str| -> Option<SigningKey> {
+        let bytes: [u8; 32] = hex::decode(hex_key.trim()).ok()?.try_into().ok()?;
+        Some(SigningKey::from_bytes(&bytes))
+    };
+    if let Some(value) = &config.node_key {
+        return parse(value).unwrap_or_else(|| panic!("NODE_KEY inválido: esperado hex de 32 bytes"));
+    }
+    let path = config.data_file("node_key");
+    if let Some(key) = path.as_ref().and_then(|p| fs::read_to_string(p).ok()).and_then(|v| parse(&v)) {
+        return key;
+    }
+    let seed: [u8; 32] = rand::random();
+    let key = SigningKey::from_bytes(&seed);
+    if let Some(path) = path {
+        match fs::write(&path, hex::encode(seed)) {
+            Ok(()) => info!("Nova chave do nó gravada em {}", path.display()),
+            Err(e) => warn!("Não foi possível gravar {}: {}", path.display(), e),
+        }
+    }
+    key
+}
+
+/// Público e barato: nada além de ler a ponta da cadeia.
+pub async fn handshake_handler(State(state): State<AppState>) -> Json<Handshake> {
+    Json(local_handshake(&state))
+}
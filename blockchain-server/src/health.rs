@@ -0,0 +1,223 @@
+// src/health.rs
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use blockchain_core::{DerivedState, PoWVerifier};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::Instant;
+use tokio::task;
+
+use crate::errors::ApiError;
+use crate::invariants::debug_check;
+use crate::state::AppState;
+
+// Relatórios de revalidação guardados para consulta por task_id
+const KEPT_REPORTS: usize = 32;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Running,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeepHealthReport {
+    pub task_id: u64,
+    pub status: TaskStatus,
+    pub height: usize,
+    pub valid: Option<bool>,
+    pub invalid_index: Option<usize>,
+    pub error: Option<String>,
+    pub duration_ms: Option<u128>,
+}
+
+#[derive(Debug, Default)]
+pub struct DeepHealthTasks {
+    next_id: u64,
+    running: Option<u64>,
+    reports: VecDeque<DeepHealthReport>,
+}
+
+impl DeepHealthTasks {
+    fn start(&mut self, height: usize) -> u64 {
+        let task_id = self.next_id;
+        self.next_id += 1;
+        self.running = Some(task_id);
+        if self.reports.len() == KEPT_REPORTS {
+            self.reports.pop_front();
+        }
+        self.reports.push_back(DeepHealthReport {
+            task_id,
+            status: TaskStatus::Running,
+            height,
+            valid: None,
+            invalid_index: None,
+            error: None,
+            duration_ms: None,
+        });
+        task_id
+    }
+
+    fn get(&self, task_id: u64) -> Option<DeepHealthReport> {
+        self.reports.iter().find(|r| r.task_id == task_id).cloned()
+    }
+
+    /// A revalidação concluída mais recente encontrou a cadeia inválida.
+    pub fn last_check_failed(&self) -> bool {
+        self.reports.iter().rev().find(|r| r.status == TaskStatus::Done).is_some_and(|r| r.valid == Some(false))
+    }
+
+    fn finish(&mut self, task_id: u64, update: impl FnOnce(&mut DeepHealthReport)) -> Option<DeepHealthReport> {
+        if self.running == Some(task_id) {
+            self.running = None;
+        }
+        let report = self.reports.iter_mut().find(|r| r.task_id == task_id)?;
+        report.status = TaskStatus::Done;
+        update(report);
+        Some(report.clone())
+    }
+}
+
+pub async fn healthz_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let height = state.chain.lock().unwrap().height();
+    let load = state.shedder.lock().unwrap().status();
+    Json(serde_json::json!({ "status": "ok", "height": height, "load_shedding": load }))
+}
+
+/// Revalida a cadeia inteira; se já houver uma revalidação em curso, devolve 202 com o task_id dela.
+pub async fn deep_health_handler(State(state): State<AppState>) -> Response {
+    let (blocks, rules) = {
+        let guard = state.chain.lock().unwrap();
+        (guard.blocks().to_vec(), guard.rules().clone())
+    };
+    let task_id = {
+        let mut tasks = state.health_tasks.lock().unwrap();
+        if let Some(running) = tasks.running.and_then(|id| tasks.get(id)) {
+            return (StatusCode::ACCEPTED, Json(running)).into_response();
+        }
+        tasks.start(blocks.len())
+    };
+
+    // O resultado é gravado dentro da tarefa, mesmo que o cliente desconecte
+    let tasks = state.health_tasks.clone();
+    let handle = task::spawn_blocking(move || {
+        let start = Instant::now();
+        let result = PoWVerifier::new().with_rules(rules).verify_chain(&blocks);
+        tasks.lock().unwrap().finish(task_id, |report| {
+            report.duration_ms = Some(start.elapsed().as_millis());
+            report.valid = Some(result.is_ok());
+            if let Err(e) = result {
+                report.invalid_index = Some(e.index());
+                report.error = Some(e.to_string());
+            }
+        })
+    });
+
+    match handle.await {
+        Ok(Some(report)) => Json(report).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            state.health_tasks.lock().unwrap().finish(task_id, |report| {
+                report.valid = Some(false);
+                report.error = Some(e.to_string());
+            });
+            ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+pub async fn deep_health_task_handler(
+    State(state): State<AppState>,
+    Path(task_id): Path<u64>,
+) -> Result<Json<DeepHealthReport>, ApiError> {
+    let report = state.health_tasks.lock().unwrap().get(task_id);
+    report.map(Json).ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("Health task {} not found", task_id)))
+}
+
+/// Recalcula as estruturas derivadas a partir de um snapshot dos blocos, fora do lock; as leituras seguem
+/// servidas pelas antigas até a troca, feita de uma vez sob o lock da cadeia.
+pub async fn rebuild_handler(State(state): State<AppState>) -> Response {
+    let (blocks, epoch_size) = {
+        let guard = state.chain.lock().unwrap();
+        (guard.blocks().to_vec(), guard.epoch_size())
+    };
+    let snapshot_tip = blocks.last().expect("a cadeia sempre contém o gênesis").hash.clone();
+    let start = Instant::now();
+    let rebuilt = match task::spawn_blocking(move || DerivedState::from_blocks(&blocks, epoch_size)).await {
+        Ok(rebuilt) => rebuilt,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let mut guard = state.chain.lock().unwrap();
+    let Some(checks) = guard.repair_derived(rebuilt, &snapshot_tip) else {
+        return ApiError::new(StatusCode::CONFLICT, "Chain was replaced during the rebuild; try again").into_response();
+    };
+    let height = guard.height();
+    drop(guard);
+    debug_check(&state, "rebuild");
+    let repaired: Vec<&str> = checks.iter().filter(|c| !c.matched).map(|c| c.structure).collect();
+    if repaired.is_empty() {
+        info!("Estado derivado recalculado sem divergências (altura {})", height);
+    } else {
+        warn!("Estado derivado divergia e foi reparado: {:?}", repaired);
+        state.metrics.lock().unwrap().counters_mut().derived_state_drift += repaired.len() as u64;
+    }
+    Json(serde_json::json!({
+        "height": height,
+        "drift": !repaired.is_empty(),
+        "structures": checks,
+        "duration_ms": start.elapsed().as_millis(),
+    }))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testkit::{test_node, ADMIN_KEY};
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use blockchain_core::{BlockBuilder, ChainState};
+    use tower::ServiceExt;
+
+    async fn rebuild(router: &axum::Router) -> serde_json::Value {
+        let request = Request::post("/admin/rebuild").header("x-api-key", ADMIN_KEY).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn rebuild_repairs_a_corrupted_node() {
+        let mut chain = ChainState::new();
+        for _ in 0..3 {
+            chain.append(BlockBuilder::on(chain.tip()).witness(1, 1, 2, 1).build()).unwrap();
+        }
+        let (router, state, _) = test_node(chain);
+        let clean = rebuild(&router).await;
+        assert_eq!((clean["drift"].as_bool(), clean["height"].as_u64()), (Some(false), Some(4)));
+
+        state.chain.lock().unwrap().corrupt_derived();
+        let report = rebuild(&router).await;
+        assert_eq!(report["drift"], true);
+        let repaired: Vec<_> = report["structures"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|s| s["matched"] == false)
+            .map(|s| s["structure"].as_str().unwrap())
+            .collect();
+        assert_eq!(repaired, ["primorial_hash", "twin_prime_density"]);
+        assert_eq!(state.metrics.lock().unwrap().counters().derived_state_drift, 2);
+
+        assert!(state.chain.lock().unwrap().derived_drift().is_empty());
+        assert_eq!(rebuild(&router).await["drift"], false);
+        assert_eq!(state.metrics.lock().unwrap().counters().derived_state_drift, 2);
+    }
+}
@@ -0,0 +1,321 @@
+// src/history.rs
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use blockchain_core::snapshot::cumulative_work;
+use blockchain_core::{balances, Balance, ChainState, DerivedState};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+
+/// `?at_height=H`: a consulta vê a cadeia como era com a ponta no bloco `H`.
+#[derive(Deserialize)]
+pub struct AtHeight {
+    pub at_height: Option<u64>,
+}
+
+#[derive(Clone)]
+enum Structure {
+    Derived(Arc<DerivedState>),
+    Balances(Arc<HashMap<String, Balance>>),
+}
+
+/// Estruturas recalculadas de prefixos da cadeia, pela altura e pelo nome da estrutura, das mais antigas
+/// para as mais recentes. Cada entrada guarda o hash do bloco na altura: depois de uma troca de cadeia
+/// o prefixo é outro e a entrada deixa de valer.
+pub struct HistoryCache {
+    capacity: usize,
+    entries: VecDeque<((u64, &'static str), String, Structure)>,
+}
+
+impl HistoryCache {
+    pub fn new(capacity: usize) -> Self {
+        HistoryCache { capacity, entries: VecDeque::new() }
+    }
+
+    fn get(&self, key: (u64, &'static str), hash: &str) -> Option<Structure> {
+        self.entries.iter().find(|(k, h, _)| *k == key && h == hash).map(|(_, _, value)| value.clone())
+    }
+
+    fn insert(&mut self, key: (u64, &'static str), hash: &str, value: Structure) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.entries.retain(|(k, _, _)| *k != key);
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, hash.to_string(), value));
+    }
+}
+
+/// Altura pedida além da ponta.
+#[derive(Debug)]
+pub struct BeyondTip {
+    pub height: u64,
+    pub tip: u64,
+}
+
+impl IntoResponse for BeyondTip {
+    fn into_response(self) -> Response {
+        let message = format!("Height {} is beyond the tip ({})", self.height, self.tip);
+        ApiError::new(StatusCode::NOT_FOUND, message).into_response()
+    }
+}
+
+/// Índice do bloco em `at_height`, ou da ponta sem altura.
+pub fn resolve(chain: &ChainState, at_height: Option<u64>) -> Result<u64, BeyondTip> {
+    let tip = chain.tip().index;
+    match at_height {
+        Some(height) if height > tip => Err(BeyondTip { height, tip }),
+        height => Ok(height.unwrap_or(tip)),
+    }
+}
+
+/// Estruturas derivadas vistas de uma altura: as mantidas ao vivo na ponta, as de um prefixo antes dela.
+pub enum Derived<'a> {
+    Live(&'a DerivedState),
+    Prefix(Arc<DerivedState>),
+}
+
+impl Deref for Derived<'_> {
+    type Target = DerivedState;
+
+    fn deref(&self) -> &DerivedState {
+        match self {
+            Derived::Live(derived) => derived,
+            Derived::Prefix(derived) => derived,
+        }
+    }
+}
+
+pub fn derived<'a>(
+    state: &AppState,
+    chain: &'a ChainState,
+    at_height: Option<u64>,
+) -> Result<Derived<'a>, BeyondTip> {
+    let index = resolve(chain, at_height)?;
+    if index == chain.tip().index {
+        return Ok(Derived::Live(chain.derived()));
+    }
+    let key = (index, "derived");
+    let hash = &chain.blocks()[index as usize].hash;
+    if let Some(Structure::Derived(derived)) = state.history.lock().unwrap().get(key, hash) {
+        return Ok(Derived::Prefix(derived));
+    }
+    let derived = Arc::new(chain.derived_at(index).expect("altura dentro da cadeia"));
+    state.history.lock().unwrap().insert(key, hash, Structure::Derived(derived.clone()));
+    Ok(Derived::Prefix(derived))
+}
+
+// Saldos não são mantidos ao vivo: até a ponta também saem do prefixo
+fn balances_at(state: &AppState, chain: &ChainState, index: u64) -> Arc<HashMap<String, Balance>> {
+    let key = (index, "balances");
+    let hash = &chain.blocks()[index as usize].hash;
+    if let Some(Structure::Balances(balances)) = state.history.lock().unwrap().get(key, hash) {
+        return balances;
+    }
+    let computed = Arc::new(balances(&chain.blocks()[..=index as usize]));
+    state.history.lock().unwrap().insert(key, hash, Structure::Balances(computed.clone()));
+    computed
+}
+
+/// Resumo da cadeia com a ponta no bloco `index`. A dificuldade é a registrada na época do bloco
+/// (a vigente ao minerar o primeiro bloco dela); desconhecida em cadeias reconstruídas. Os bytes são os
+/// dos blocos em JSON, calculados uma vez por bloco.
+fn summary(state: &AppState, chain: &ChainState, index: u64) -> serde_json::Value {
+    let prefix = &chain.blocks()[..=index as usize];
+    let block = &prefix[index as usize];
+    let epoch = index / chain.epoch_size();
+    let chain_bytes = state.block_sizes.lock().unwrap().total(prefix);
+    serde_json::json!({
+        "height": index,
+        "tip_hash": block.hash,
+        "timestamp": block.timestamp,
+        "rules_version": block.rules_version,
+        "cumulative_work": cumulative_work(prefix),
+        "epoch": epoch,
+        "difficulty": chain.epoch(epoch).and_then(|summary| summary.difficulty.clone()),
+        "chain_bytes": chain_bytes,
+        "average_block_bytes": chain_bytes as f64 / prefix.len() as f64,
+    })
+}
+
+pub async fn summary_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let guard = state.chain.lock().unwrap();
+    Json(summary(&state, &guard, guard.tip().index))
+}
+
+pub async fn as_of_summary_handler(
+    State(state): State<AppState>,
+    Path(height): Path<u64>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let guard = state.chain.lock().unwrap();
+    let index = resolve(&guard, Some(height)).map_err(IntoResponse::into_response)?;
+    Ok(Json(summary(&state, &guard, index)))
+}
+
+#[derive(Serialize)]
+pub struct BalanceView {
+    pub address: String,
+    pub height: u64,
+    pub received: u128,
+    pub sent: u128,
+    // Recompensas dos blocos que pagaram a `address` na coinbase (regras v6)
+    pub mined: u128,
+    // Recebido mais minerado menos enviado; a cadeia não confere saldos, então pode ser negativo
+    pub balance: i128,
+    pub transactions: u64,
+}
+
+/// Saldo de `address` nas transações dos blocos até `at_height` (padrão: a ponta).
+pub async fn balance_handler(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(query): Query<AtHeight>,
+) -> Result<Json<BalanceView>, Response> {
+    let guard = state.chain.lock().unwrap();
+    let index = resolve(&guard, query.at_height).map_err(IntoResponse::into_response)?;
+    let balance = balances_at(&state, &guard, index).get(&address).cloned().unwrap_or_default();
+    Ok(Json(BalanceView {
+        address,
+        height: index,
+        received: balance.received,
+        sent: balance.sent,
+        mined: balance.mined,
+        balance: balance.net(),
+        transactions: balance.transactions,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{test_node, READ_KEY};
+    use axum::{body::Body, extract::Request, Router};
+    use blockchain_core::testkit::{test_key, trivial_difficulty};
+    use blockchain_core::{mine_template, Block, Difficulty, RuleSchedule, Throttle, Transaction};
+    use std::sync::atomic::AtomicBool;
+    use tower::ServiceExt;
+
+    // Regras v2 a v5 nas alturas 1 a 4 e v6 (recompensa e coinbase) a partir do bloco 5
+    fn rewarding_chain() -> ChainState {
+        let mut rules = RuleSchedule::default();
+        for version in 2..=6 {
+            rules.schedule(version, version as u64 - 1, 1).unwrap();
+        }
+        let mut chain = ChainState::with_rules(rules);
+        chain.difficulty = Difficulty { min_digits: 7, ..trivial_difficulty() };
+        chain
+    }
+
+    fn mine(chain: &mut ChainState, miner: &str, transactions: Vec<Transaction>) {
+        let coinbase = if chain.next_rules_version() >= 6 { miner } else { "" };
+        let timestamp = chain.tip().timestamp.max(1_700_000_000_000) + 10_000;
+        let template = chain.template().timestamp(timestamp).coinbase(coinbase).transactions(transactions);
+        let stop = AtomicBool::new(false);
+        for _ in 0..20 {
+            let (block, _) = mine_template(&template, &chain.difficulty, &stop, None, &mut Throttle::unlimited())
+                .expect("mineração sem parada sempre acha um bloco");
+            if chain.append(block).is_ok() {
+                return;
+            }
+        }
+        panic!("nenhum bloco válido em 20 tentativas");
+    }
+
+    // Soma feita à mão sobre os blocos 0..=height, sem passar por `balances`
+    fn prefix_sum(blocks: &[Block], address: &str, height: u64) -> i128 {
+        let mut total = 0i128;
+        for block in &blocks[..=height as usize] {
+            if block.coinbase == address {
+                total += block.reward as i128;
+            }
+            for tx in &block.transactions {
+                if tx.to == address {
+                    total += tx.amount as i128;
+                }
+                if tx.from == address {
+                    total -= tx.amount as i128;
+                }
+            }
+        }
+        total
+    }
+
+    async fn get(router: &Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let request = Request::get(uri).header("x-api-key", READ_KEY).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn balances_at_height_match_prefix_sums() {
+        let mut chain = rewarding_chain();
+        let (payer, nonce) = (test_key(1), std::cell::Cell::new(0));
+        let pay = |to: &str, amount| {
+            nonce.set(nonce.get() + 1);
+            Transaction::sign(&payer, to, amount, nonce.get())
+        };
+        for height in 1..=10u64 {
+            let miner = if height % 3 == 0 { "bob" } else { "alice" };
+            let transactions = match height {
+                6 => vec![pay("alice", 7), pay("bob", 11)],
+                8 => vec![pay("bob", 5)],
+                _ => Vec::new(),
+            };
+            mine(&mut chain, miner, transactions);
+        }
+        let blocks = chain.blocks().to_vec();
+        assert!(blocks[4].reward == 0 && blocks[5].reward > 0, "recompensa só a partir das regras v6");
+        let (router, _, _) = test_node(chain);
+
+        let payer = hex::encode(test_key(1).verifying_key().to_bytes());
+        for address in ["alice", "bob", payer.as_str()] {
+            for height in 0..=10 {
+                let (status, body) = get(&router, &format!("/balance/{}?at_height={}", address, height)).await;
+                assert_eq!(status, StatusCode::OK);
+                assert_eq!(body["height"], height);
+                let expected = prefix_sum(&blocks, address, height);
+                assert_eq!(body["balance"].as_i64().map(i128::from), Some(expected), "{} em {}", address, height);
+            }
+        }
+        // Sem altura é a ponta
+        let (_, tip) = get(&router, "/balance/bob").await;
+        assert_eq!(tip["balance"].as_i64().map(i128::from), Some(prefix_sum(&blocks, "bob", 10)));
+    }
+
+    #[tokio::test]
+    async fn summary_as_of_height_and_beyond_the_tip() {
+        let mut chain = rewarding_chain();
+        for _ in 0..6 {
+            mine(&mut chain, "alice", Vec::new());
+        }
+        let blocks = chain.blocks().to_vec();
+        let (router, _, _) = test_node(chain);
+        for height in [0u64, 3, 6] {
+            let (status, body) = get(&router, &format!("/chain/as-of/{}/summary", height)).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body["height"], height);
+            assert_eq!(body["tip_hash"], blocks[height as usize].hash);
+            // O JSON lido de volta pode diferir no último bit
+            let work = cumulative_work(&blocks[..=height as usize]);
+            assert!((body["cumulative_work"].as_f64().unwrap() - work).abs() <= work * 1e-12);
+        }
+
+        let (status, body) = get(&router, "/chain/as-of/7/summary").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "Height 7 is beyond the tip (6)");
+        assert_eq!(get(&router, "/balance/alice?at_height=7").await.0, StatusCode::NOT_FOUND);
+        assert_eq!(get(&router, "/chain/primorial-hash?at_height=99").await.0, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,284 @@
+// src/invariants.rs
+use axum::{extract::State, Json};
+use blockchain_core::{Block, Transaction};
+use log::error;
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::state::AppState;
+
+/// Invariante quebrada numa cadeia do nó.
+#[derive(Debug, Clone, Serialize)]
+pub struct InvariantViolation {
+    pub chain: String,
+    pub invariant: &'static str,
+    pub detail: String,
+}
+
+// Cada bloco no seu índice, ligado ao anterior e sem voltar no tempo, a partir do gênesis
+fn block_links(blocks: &[Block]) -> Vec<String> {
+    let mut problems = Vec::new();
+    if blocks.first().is_none_or(|genesis| genesis.hash != Block::genesis().hash) {
+        problems.push("chain does not start at the genesis block".to_string());
+    }
+    for (position, block) in blocks.iter().enumerate() {
+        if block.index != position as u64 {
+            problems.push(format!("block {} sits at position {}", block.index, position));
+        }
+    }
+    for pair in blocks.windows(2) {
+        let (prev, block) = (&pair[0], &pair[1]);
+        if block.prev_hash != prev.hash {
+            problems.push(format!("block {} points to {} instead of {}", block.index, block.prev_hash, prev.hash));
+        }
+        if block.timestamp < prev.timestamp {
+            problems.push(format!("block {} is older than block {}", block.index, prev.index));
+        }
+    }
+    problems
+}
+
+/// Confere as invariantes de uma cadeia do nó: blocos ligados e únicos, estruturas derivadas iguais às
+/// recalculadas dos blocos, dificuldade minerável, mempool sem transações já incluídas e consistente, e
+/// pool de órfãos indexado pelo pai. Trava a cadeia durante toda a conferência.
+pub fn check_all(state: &AppState) -> Vec<InvariantViolation> {
+    let mut found: Vec<(&'static str, String)> = Vec::new();
+    let guard = state.chain.lock().unwrap();
+    let blocks = guard.blocks();
+    found.extend(block_links(blocks).into_iter().map(|detail| ("block_links", detail)));
+
+    let mut hashes = HashSet::new();
+    for block in blocks.iter().filter(|b| !hashes.insert(b.hash.as_str())) {
+        found.push(("unique_block_hashes", format!("hash {} appears more than once", block.hash)));
+    }
+    for check in guard.derived_drift() {
+        let detail = format!("{}: {}", check.structure, check.diff.unwrap_or_default());
+        found.push(("derived_state", detail));
+    }
+    found.extend(guard.difficulty.problems().into_iter().map(|detail| ("difficulty_feasible", detail)));
+
+    // Mesma ordem de travas de quem anexa blocos: cadeia, depois mempool
+    let mempool = state.mempool.lock().unwrap();
+    found.extend(mempool.problems().into_iter().map(|detail| ("mempool_consistent", detail)));
+    let included: HashSet<String> = blocks.iter().flat_map(|b| &b.transactions).map(Transaction::id).collect();
+    for id in mempool.pending(usize::MAX).iter().map(Transaction::id).filter(|id| included.contains(id)) {
+        found.push(("mempool_excludes_chain", format!("transaction {} is pending but already in a block", id)));
+    }
+    drop(mempool);
+    found.extend(state.orphans.lock().unwrap().problems().into_iter().map(|detail| ("orphan_pool", detail)));
+    drop(guard);
+
+    found
+        .into_iter()
+        .map(|(invariant, detail)| InvariantViolation { chain: state.namespace.clone(), invariant, detail })
+        .collect()
+}
+
+/// Em builds de depuração, confere as invariantes depois de `operation` e entra em pânico se alguma
+/// quebrou. Deve ser chamada sem travas do estado. Em release não faz nada.
+pub fn debug_check(state: &AppState, operation: &str) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let violations = check_all(state);
+    for v in &violations {
+        error!("Invariante {} quebrada após {} na cadeia {}: {}", v.invariant, operation, v.chain, v.detail);
+    }
+    debug_assert!(violations.is_empty(), "{} invariantes quebradas após {}", violations.len(), operation);
+}
+
+/// Invariantes de todas as cadeias do nó, conferidas agora.
+pub async fn invariants_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let chains = state.chains.lock().unwrap().states();
+    let violations: Vec<InvariantViolation> = chains.iter().flat_map(check_all).collect();
+    Json(serde_json::json!({
+        "ok": violations.is_empty(),
+        "checked_chains": chains.len(),
+        "violations": violations,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{Cluster, ADMIN_KEY};
+    use blockchain_core::testkit::test_key;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashMap;
+
+    const SEEDS: [u64; 3] = [1, 7, 2024];
+    const STEPS: usize = 60;
+    // Remetentes das transações sorteadas
+    const SENDERS: u8 = 3;
+
+    fn assert_intact(cluster: &Cluster, step: &str) {
+        for (i, node) in cluster.nodes.iter().enumerate() {
+            let chains = node.state.chains.lock().unwrap().states();
+            let violations: Vec<_> = chains.iter().flat_map(check_all).collect();
+            assert!(violations.is_empty(), "nó {} após {}: {:?}", i, step, violations);
+        }
+    }
+
+    // Recusas (4xx, 503) fazem parte do jogo; 5xx ou conexão caída indicam pânico ou bug
+    async fn call(request: reqwest::RequestBuilder, step: &str) -> (u16, String) {
+        let response = request.header("x-api-key", ADMIN_KEY).send().await;
+        let response = response.unwrap_or_else(|e| panic!("{}: sem resposta ({})", step, e));
+        let status = response.status().as_u16();
+        let body = response.text().await.unwrap_or_default();
+        assert!(status < 500 || status == 503, "{}: status {} ({})", step, status, body);
+        (status, body)
+    }
+
+    // Assina com o nonce que o harness acha que o nó espera, às vezes repetido ou adiantado, e envia a
+    // cada nó de `targets`; acerta o nonce esperado de cada um pela resposta
+    async fn send_tx(
+        cluster: &Cluster,
+        rng: &mut StdRng,
+        nonces: &mut HashMap<(usize, u8), u64>,
+        targets: &[usize],
+        step: &str,
+    ) {
+        let sender = rng.gen_range(1..=SENDERS);
+        let mut nonce = nonces.get(&(targets[0], sender)).copied().unwrap_or(0);
+        match rng.gen_range(0..5) {
+            0 => nonce = nonce.saturating_sub(1),
+            1 => nonce += 1,
+            _ => {}
+        }
+        let tx = Transaction::sign(&test_key(sender), "bob", rng.gen_range(1..=100), nonce);
+        for &i in targets {
+            let request = cluster.client.post(format!("{}/transactions", cluster.node(i).url)).json(&tx);
+            let (status, body) = call(request, step).await;
+            if status == 202 {
+                nonces.insert((i, sender), nonce + 1);
+            } else if let Some(expected) = body.split("expected ").nth(1).and_then(|r| r.split(',').next()) {
+                nonces.insert((i, sender), expected.parse().unwrap());
+            }
+        }
+    }
+
+    async fn run(seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut cluster = Cluster::start(2).await;
+        let mut nonces = HashMap::new();
+        let mut partitioned = false;
+        assert_intact(&cluster, "start");
+        for n in 1..=STEPS {
+            let node = rng.gen_range(0..2);
+            let other = 1 - node;
+            let url = cluster.node(node).url.clone();
+            let step = match rng.gen_range(0..13) {
+                0..=2 => {
+                    cluster.mine(node).await;
+                    format!("{} mine {}", n, node)
+                }
+                3 => {
+                    let step = format!("{} submit {}", n, node);
+                    send_tx(&cluster, &mut rng, &mut nonces, &[node], &step).await;
+                    step
+                }
+                4 => {
+                    // A mesma transação nos dois: um bloco de um a inclui enquanto segue no mempool do outro
+                    let step = format!("{} submit {}+{}", n, node, other);
+                    send_tx(&cluster, &mut rng, &mut nonces, &[node, other], &step).await;
+                    step
+                }
+                5 => {
+                    let step = format!("{} import {} -> {}", n, other, node);
+                    let export = cluster.client.get(format!("{}/chain/export?format=archive", cluster.node(other).url));
+                    let (_, archive) = call(export, &step).await;
+                    call(cluster.client.post(format!("{}/chain/import", url)).body(archive), &step).await;
+                    step
+                }
+                6 | 7 => {
+                    let step = format!("{} reorg {}", n, node);
+                    call(cluster.client.post(format!("{}/chain/resolve", url)), &step).await;
+                    step
+                }
+                8 => {
+                    let step = format!("{} announce {} -> {}", n, other, node);
+                    let tip = cluster.node(other).tip();
+                    call(cluster.client.post(format!("{}/blocks/announce", url)).json(&tip), &step).await;
+                    step
+                }
+                9 => {
+                    let step = format!("{} gc {}", n, node);
+                    call(cluster.client.post(format!("{}/admin/gc", url)), &step).await;
+                    step
+                }
+                10 => {
+                    let step = format!("{} rebuild {}", n, node);
+                    call(cluster.client.post(format!("{}/admin/rebuild", url)), &step).await;
+                    step
+                }
+                11 => {
+                    // Alterna partição e reconexão, para que os dois lados criem forks de verdade
+                    if partitioned {
+                        cluster.heal().await;
+                    } else {
+                        cluster.partition(&[0]);
+                    }
+                    partitioned = !partitioned;
+                    format!("{} partition={}", n, partitioned)
+                }
+                _ => {
+                    cluster.restart(node).await;
+                    nonces.retain(|&(i, _), _| i != node);
+                    partitioned = false;
+                    format!("{} reset {}", n, node)
+                }
+            };
+            assert_intact(&cluster, &step);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn random_operations_keep_invariants() {
+        for seed in SEEDS {
+            run(seed).await;
+        }
+    }
+
+    // Transação pendente nos dois nós e incluída só na cadeia de um: a troca de cadeia no outro, por
+    // /chain/resolve ou /chain/import, tem de tirá-la do mempool
+    async fn pending_elsewhere_mined_here() -> (Cluster, Transaction) {
+        let cluster = Cluster::start(2).await;
+        cluster.partition(&[0]);
+        let tx = Transaction::sign(&test_key(1), "bob", 5, 0);
+        for node in &cluster.nodes {
+            node.state.mempool.lock().unwrap().submit(tx.clone()).unwrap();
+        }
+        cluster.mine(1).await;
+        cluster.mine(1).await;
+        let mined = cluster.node(1).state.chain.lock().unwrap().blocks().iter().any(|b| b.transactions.contains(&tx));
+        assert!(mined, "o nó 1 deveria ter minerado a transação");
+        cluster.connect(0, 1).await;
+        (cluster, tx)
+    }
+
+    fn assert_confirmed(cluster: &Cluster, tx: &Transaction) {
+        let state = &cluster.node(0).state;
+        assert_eq!(state.chain.lock().unwrap().tip().hash, cluster.node(1).tip().hash);
+        assert!(state.mempool.lock().unwrap().pending(usize::MAX).iter().all(|t| t.id() != tx.id()));
+        assert!(check_all(state).is_empty());
+    }
+
+    #[tokio::test]
+    async fn reorg_confirms_mempool() {
+        let (cluster, tx) = pending_elsewhere_mined_here().await;
+        let request = cluster.client.post(format!("{}/chain/resolve", cluster.node(0).url));
+        call(request, "reorg").await;
+        assert_confirmed(&cluster, &tx);
+    }
+
+    #[tokio::test]
+    async fn import_confirms_mempool() {
+        let (cluster, tx) = pending_elsewhere_mined_here().await;
+        let export = cluster.client.get(format!("{}/chain/export?format=archive", cluster.node(1).url));
+        let (_, archive) = call(export, "export").await;
+        let request = cluster.client.post(format!("{}/chain/import", cluster.node(0).url)).body(archive);
+        assert_eq!(call(request, "import").await.0, 200);
+        assert_confirmed(&cluster, &tx);
+    }
+}
@@ -0,0 +1,98 @@
+// src/jsonld.rs
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use blockchain_core::snapshot::cumulative_work;
+use blockchain_core::Block;
+use serde::Deserialize;
+
+use crate::state::AppState;
+
+// Vocabulário próprio; URN para não depender de um domínio publicado
+const VOCAB: &str = "urn:proof-of-prime:vocab#";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+// Blocos listados por padrão e no máximo
+const JSONLD_BLOCKS: usize = 100;
+const JSONLD_MAX_BLOCKS: usize = 1000;
+
+/// Termos do documento: cada bloco vira triplas `(urn do bloco, blockchain:prime, "p"^^xsd:integer)` e
+/// afins, e `prevBlock` liga ao bloco anterior pela URN.
+fn context() -> serde_json::Value {
+    let typed = |id: &str, kind: &str| serde_json::json!({ "@id": format!("blockchain:{}", id), "@type": kind });
+    serde_json::json!({
+        "blockchain": VOCAB,
+        "xsd": XSD,
+        "Chain": "blockchain:Chain",
+        "Block": "blockchain:Block",
+        "chainId": typed("chainId", "xsd:string"),
+        "height": typed("height", "xsd:integer"),
+        "tip": typed("tip", "@id"),
+        "genesis": typed("genesis", "@id"),
+        "cumulativeWork": typed("cumulativeWork", "xsd:double"),
+        "primorialHash": typed("primorialHash", "xsd:string"),
+        "twinPrimeDensity": typed("twinPrimeDensity", "xsd:double"),
+        "index": typed("index", "xsd:integer"),
+        "prime": typed("prime", "xsd:integer"),
+        "hash": typed("hash", "xsd:string"),
+        "timestamp": typed("timestamp", "xsd:integer"),
+        "rulesVersion": typed("rulesVersion", "xsd:integer"),
+        "transactionCount": typed("transactionCount", "xsd:integer"),
+        "prevBlock": typed("prevBlock", "@id"),
+        "block": { "@id": "blockchain:block", "@type": "@id", "@container": "@list" },
+    })
+}
+
+fn block_uri(chain: &str, hash: &str) -> String {
+    format!("urn:proof-of-prime:{}:block:{}", chain, hash)
+}
+
+fn block_node(chain: &str, block: &Block) -> serde_json::Value {
+    let mut node = serde_json::json!({
+        "@id": block_uri(chain, &block.hash),
+        "@type": "Block",
+        "index": block.index,
+        "prime": block.prime,
+        "hash": block.hash,
+        "timestamp": block.timestamp,
+        "rulesVersion": block.rules_version,
+        "transactionCount": block.transactions.len(),
+    });
+    // O gênesis não tem anterior
+    if block.index > 0 {
+        node["prevBlock"] = block_uri(chain, &block.prev_hash).into();
+    }
+    node
+}
+
+#[derive(Deserialize)]
+pub struct JsonLdQuery {
+    count: Option<usize>,
+}
+
+/// Estatísticas da cadeia em JSON-LD, com os `count` blocos mais recentes (padrão 100, até 1000)
+/// em `block`, do mais antigo ao mais novo.
+pub async fn stats_jsonld_handler(State(state): State<AppState>, Query(query): Query<JsonLdQuery>) -> Response {
+    let count = query.count.unwrap_or(JSONLD_BLOCKS).min(JSONLD_MAX_BLOCKS);
+    let chain = &state.namespace;
+    let document = {
+        let guard = state.chain.lock().unwrap();
+        let blocks = guard.blocks();
+        let recent = &blocks[blocks.len().saturating_sub(count)..];
+        serde_json::json!({
+            "@context": context(),
+            "@id": format!("urn:proof-of-prime:{}", chain),
+            "@type": "Chain",
+            "chainId": chain,
+            "height": guard.height(),
+            "genesis": block_uri(chain, &blocks[0].hash),
+            "tip": block_uri(chain, &guard.tip().hash),
+            "cumulativeWork": cumulative_work(blocks),
+            "primorialHash": guard.primorial_hash(),
+            "twinPrimeDensity": guard.twin_prime_density().density,
+            "block": recent.iter().map(|b| block_node(chain, b)).collect::<Vec<_>>(),
+        })
+    };
+    ([(header::CONTENT_TYPE, "application/ld+json")], document.to_string()).into_response()
+}
@@ -0,0 +1,657 @@
+// src/lib.rs
+//! Nó Proof-of-Prime: estado, rotas e tarefas de fundo. O binário (src/main.rs) só lê a configuração e
+//! serve `node_router`; os testes de integração sobem nós pelo `testkit`.
+use axum::{
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::StatusCode,
+    middleware::{from_extractor_with_state, from_fn, from_fn_with_state},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use blockchain_core::chain::TwinPrimeDensity;
+use blockchain_core::{
+    measure_throughput, simulate, tx_proof, AlgorithmConfig, Block, ChainState, DifficultyOverride, MerkleTree,
+    OverrideError, ReceiptStats, Scenario,
+};
+use log::info;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+// Importa o middleware
+mod alerts;
+mod audit;
+mod archive;
+mod bandwidth;
+mod blocks;
+mod compression;
+mod condensed;
+pub mod config;
+mod consistency;
+mod deadline;
+mod emission;
+mod errors;
+mod events;
+mod gc;
+mod handshake;
+mod health;
+mod history;
+mod invariants;
+mod jsonld;
+mod mempool;
+mod metrics;
+mod miner;
+mod middleware;
+mod miners;
+mod namespaces;
+mod orphans;
+mod peers;
+mod precompute;
+mod prime;
+mod projection;
+mod quarantine;
+mod ratelimit;
+mod receipts;
+mod rules;
+mod shed;
+mod shutdown;
+mod slo;
+pub mod state;
+mod store;
+mod sync;
+mod templates;
+#[cfg(any(test, feature = "testkit"))]
+pub mod testkit;
+mod tip;
+mod webhooks;
+pub use config::Config;
+use consistency::{stamp_position, RequirePosition};
+use errors::ApiError;
+use projection::{FieldsQuery, Projection};
+pub use state::AppState;
+
+#[derive(Deserialize)]
+struct MineQuery {
+    // Semente para repetir uma mineração; sem ela vale MINING_SEED
+    seed: Option<u64>,
+}
+
+/// Endereço que recebe a recompensa dos blocos de /mine: COINBASE_ADDRESS ou a chave pública do nó.
+fn coinbase_address(state: &AppState) -> String {
+    let node = || hex::encode(state.node_key.verifying_key().to_bytes());
+    state.config.coinbase_address.clone().unwrap_or_else(node)
+}
+
+async fn mine_handler(
+    State(state): State<AppState>,
+    Query(query): Query<MineQuery>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let transactions = state.mempool.lock().unwrap().pending(state.config.block_max_transactions);
+    let (template, difficulty) = {
+        let guard = state.chain.lock().unwrap();
+        let mut template = guard.template().transactions(transactions);
+        // A coinbase só entra no hash com as regras v6; antes delas o bloco é recusado com ela
+        if guard.next_rules_version() >= 6 {
+            template = template.coinbase(coinbase_address(&state));
+        }
+        (template, guard.difficulty.clone())
+    };
+
+    let (start, started_at) = (Instant::now(), sync::now_secs());
+    let workers = state.miner.threads();
+    let seed = query.seed.or_else(|| state.config.mining_seed.next());
+    let (new_block, stats, winning_worker, race) = match seed {
+        Some(seed) => {
+            // O timestamp é fixado no início para o bloco não depender de quando cada worker achou
+            let race = state
+                .miner
+                .mine_seeded(&state.namespace, template.stamp_now(), difficulty, workers, seed)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            state.jobs.lock().unwrap().record_mining(seed, started_at, race.stats.candidates);
+            (race.block.clone(), race.stats.clone(), race.worker, Some(race))
+        }
+        None => {
+            let (block, stats, worker) = state
+                .miner
+                .mine(&state.namespace, template, difficulty, workers, state.pool.clone())
+                .await
+                .map_err(IntoResponse::into_response)?;
+            (block, stats, worker, None)
+        }
+    };
+    let duration = start.elapsed().as_secs_f64();
+
+    let (height, difficulty) = {
+        let mut guard = state.chain.lock().unwrap();
+        guard
+            .append(new_block.clone())
+            .map_err(|e| ApiError::new(StatusCode::CONFLICT, e.to_string()))?;
+        guard.record_candidates(stats.candidates);
+        guard.record_worker_win(winning_worker);
+        // Sob o lock da cadeia, para nenhuma leitura ver a transação na cadeia e no mempool ao mesmo tempo
+        state.mempool.lock().unwrap().confirm(&new_block.transactions);
+        let receipt_stats = ReceiptStats::new(&stats, (duration * 1000.0) as u64);
+        state.mined_blocks.lock().unwrap().insert(new_block.hash.clone(), receipt_stats);
+        let decision = guard.adjust_difficulty(duration);
+        info!(target: "difficulty", "{}", serde_json::to_string(&decision).unwrap_or_default());
+        (guard.height(), guard.difficulty.clone())
+    };
+    invariants::debug_check(&state, "mine");
+    state.metrics.lock().unwrap().record_mining(&stats);
+    if let Some(pool) = &state.pool {
+        pool.invalidate(difficulty.generation);
+    }
+    let _ = state.events.send(new_block.clone());
+
+    Ok(Json(serde_json::json!({
+        "index": new_block.index,
+        "prime": new_block.prime,
+        "digits": new_block.prime.to_string().len(),
+        "duration": format!("{:.3}s", duration),
+        "height": height,
+        "chain": state.namespace,
+        "stats": {
+            "candidates": stats.candidates,
+            "gcd_rejected": stats.gcd_rejected,
+            "parity_rejected": stats.parity_rejected,
+            "trial_division_rejected": stats.trial_division_rejected,
+            "heuristic_rejected": stats.heuristic_rejected,
+            "miller_rabin_rejected": stats.miller_rabin_rejected,
+            "hash_target_rejected": stats.hash_target_rejected,
+            "pool_hits": stats.pool_hits,
+            "pool_hit_rate": format!("{:.4}", stats.pool_hit_rate()),
+            "probability": format!("{:.5}", stats.probability),
+            "generator": stats.generator,
+            "generator_tuples_per_sec": format!("{:.0}", stats.generator_throughput())
+        },
+        "difficulty": {
+            "n_limit": difficulty.n_limit,
+            "min_digits": difficulty.min_digits,
+            "min_prob": format!("{:.4}", difficulty.min_prob_f64()),
+            "hash_scale": difficulty.hash_scale
+        },
+        "seed": race.as_ref().map(|r| r.seed),
+        "winning_worker": winning_worker,
+        "workers": race.as_ref().map(|r| &r.workers),
+    })))
+}
+
+// Blocos pela projeção de `?fields=`
+fn projected(projection: &Projection, blocks: Vec<Block>) -> Response {
+    Json(blocks.into_iter().map(|block| projection.view(block)).collect::<Vec<_>>()).into_response()
+}
+
+async fn chain_handler(
+    State(state): State<AppState>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Response, Response> {
+    let projection = Projection::from_query(&fields).map_err(IntoResponse::into_response)?;
+    let blocks = state.chain.lock().unwrap().blocks().to_vec();
+    Ok(projected(&projection, blocks))
+}
+
+#[derive(Deserialize)]
+struct TailQuery {
+    count: Option<usize>,
+}
+
+async fn chain_tail_handler(
+    State(state): State<AppState>,
+    Query(query): Query<TailQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Response, Response> {
+    let projection = Projection::from_query(&fields).map_err(IntoResponse::into_response)?;
+    let count = query.count.unwrap_or(10).min(100);
+    let blocks = {
+        let guard = state.chain.lock().unwrap();
+        let blocks = guard.blocks();
+        blocks[blocks.len().saturating_sub(count)..].to_vec()
+    };
+    Ok(projected(&projection, blocks))
+}
+
+async fn block_handler(
+    State(state): State<AppState>,
+    Path(index): Path<usize>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Response, Response> {
+    let projection = Projection::from_query(&fields).map_err(IntoResponse::into_response)?;
+    let block = state.chain.lock().unwrap().blocks().get(index).cloned();
+    let block = block.ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("Block {} not found", index)))?;
+    Ok(Json(projection.view(block)).into_response())
+}
+
+/// Tamanho do bloco em JSON, CBOR e binário (bincode), serializado só em memória.
+async fn block_size_handler(
+    State(state): State<AppState>,
+    Path(index): Path<usize>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let block = state.chain.lock().unwrap().blocks().get(index).cloned();
+    let block = block.ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("Block {} not found", index)))?;
+
+    let json_bytes = serde_json::to_vec(&block).map(|bytes| bytes.len());
+    let mut cbor = Vec::new();
+    let cbor_bytes = ciborium::into_writer(&block, &mut cbor).map(|_| cbor.len());
+    let raw_binary_bytes = bincode::serialized_size(&block);
+    match (json_bytes, cbor_bytes, raw_binary_bytes) {
+        (Ok(json_bytes), Ok(cbor_bytes), Ok(raw_binary_bytes)) => Ok(Json(serde_json::json!({
+            "index": block.index,
+            "json_bytes": json_bytes,
+            "cbor_bytes": cbor_bytes,
+            "raw_binary_bytes": raw_binary_bytes,
+        }))),
+        _ => Err(ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to serialize block").into_response()),
+    }
+}
+
+async fn block_compact_handler(
+    State(state): State<AppState>,
+    Path(index): Path<usize>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let block = state.chain.lock().unwrap().blocks().get(index).cloned();
+    let block = block.ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("Block {} not found", index)))?;
+    Ok(Json(serde_json::json!({ "index": block.index, "compact": block.to_compact_string() })))
+}
+
+// Árvore dos hashes até o bloco `index`
+fn hash_tree(chain: &ChainState, index: u64) -> MerkleTree {
+    let hashes: Vec<String> = chain.blocks()[..=index as usize].iter().map(|b| b.hash.clone()).collect();
+    MerkleTree::build(&hashes)
+}
+
+/// Árvore de Merkle de todos os hashes de bloco, em vetor plano (raiz em 0, filhos em 2i+1 e 2i+2).
+async fn hash_tree_handler(
+    State(state): State<AppState>,
+    Query(query): Query<history::AtHeight>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let tree = {
+        let guard = state.chain.lock().unwrap();
+        let index = history::resolve(&guard, query.at_height).map_err(IntoResponse::into_response)?;
+        hash_tree(&guard, index)
+    };
+    Ok(Json(serde_json::json!({
+        "root": tree.root(),
+        "leaves": tree.leaf_count(),
+        "padded_leaves": tree.width(),
+        "depth": tree.width().trailing_zeros(),
+        "tree": tree.nodes(),
+    })))
+}
+
+async fn merkle_proof_handler(
+    State(state): State<AppState>,
+    Path(index): Path<usize>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let tree = {
+        let guard = state.chain.lock().unwrap();
+        hash_tree(&guard, guard.tip().index)
+    };
+    let proof = tree
+        .proof(index)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("Block {} not found", index)))?;
+    Ok(Json(serde_json::json!({
+        "index": index,
+        "hash": tree.nodes()[tree.width() - 1 + index],
+        "root": tree.root(),
+        "proof": proof,
+    })))
+}
+
+/// Prova de inclusão de uma transação na raiz `tx_root` do cabeçalho do bloco.
+async fn tx_proof_handler(
+    State(state): State<AppState>,
+    Path((index, txid)): Path<(usize, String)>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let block = state.chain.lock().unwrap().blocks().get(index).cloned();
+    let block = block.ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("Block {} not found", index)))?;
+    let (position, proof) = tx_proof(&block.transactions, &txid).ok_or_else(|| {
+        ApiError::new(StatusCode::NOT_FOUND, format!("Transaction {} not found in block {}", txid, index))
+    })?;
+    Ok(Json(serde_json::json!({
+        "index": block.index,
+        "txid": txid,
+        "position": position,
+        "tx_root": block.tx_root,
+        "proof": proof,
+    })))
+}
+
+// Arquivos de /chain/import podem passar muito do limite padrão de 2 MB
+const IMPORT_BODY_LIMIT: usize = 256 * 1024 * 1024;
+
+// Blocos exibidos em /chain/graph-json e tamanho do prefixo usado como id
+const GRAPH_BLOCKS: usize = 50;
+const GRAPH_ID_LEN: usize = 16;
+
+fn hash_prefix(hash: &str) -> &str {
+    hash.get(..GRAPH_ID_LEN).unwrap_or(hash)
+}
+
+/// Últimos blocos como grafo de nós e ligações, no formato de `d3.forceSimulation`.
+/// Ligações para blocos fora da janela são omitidas, senão o D3 não encontra o nó de origem.
+async fn graph_json_handler(
+    State(state): State<AppState>,
+    Query(query): Query<history::AtHeight>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let guard = state.chain.lock().unwrap();
+    let index = history::resolve(&guard, query.at_height).map_err(IntoResponse::into_response)?;
+    let blocks = &guard.blocks()[..=index as usize];
+    let window = &blocks[blocks.len().saturating_sub(GRAPH_BLOCKS)..];
+    let nodes: Vec<_> = window
+        .iter()
+        .map(|b| serde_json::json!({ "id": hash_prefix(&b.hash), "prime": b.prime, "index": b.index }))
+        .collect();
+    let links: Vec<_> = window
+        .windows(2)
+        .map(|pair| serde_json::json!({ "source": hash_prefix(&pair[1].prev_hash), "target": hash_prefix(&pair[1].hash) }))
+        .collect();
+    Ok(Json(serde_json::json!({ "nodes": nodes, "links": links })))
+}
+
+async fn primorial_hash_handler(
+    State(state): State<AppState>,
+    Query(query): Query<history::AtHeight>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let guard = state.chain.lock().unwrap();
+    let derived = history::derived(&state, &guard, query.at_height).map_err(IntoResponse::into_response)?;
+    Ok(Json(serde_json::json!({
+        "primorial_hash": derived.primorial_hash(),
+        "primes": derived.height(),
+    })))
+}
+
+async fn twin_prime_density_handler(
+    State(state): State<AppState>,
+    Query(query): Query<history::AtHeight>,
+) -> Result<Json<TwinPrimeDensity>, Response> {
+    let guard = state.chain.lock().unwrap();
+    let derived = history::derived(&state, &guard, query.at_height).map_err(IntoResponse::into_response)?;
+    Ok(Json(derived.twin_prime_density()))
+}
+
+async fn runtime_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "mining": state.miner.stats(), "jobs": state.jobs.lock().unwrap().list() }))
+}
+
+async fn difficulty_handler(
+    State(state): State<AppState>,
+) -> Json<serde_json::Value> {
+    let guard = state.chain.lock().unwrap();
+    Json(serde_json::json!({
+        "version": guard.difficulty.generation,
+        "difficulty": guard.difficulty,
+        "algorithm": guard.difficulty_algorithm(),
+        "digit_floor": guard.difficulty.min_digits,
+        "hash_scale": guard.next_hash_scale(),
+        "hash_target": guard.difficulty.hash_target(),
+        "expected_candidates": guard.difficulty.expected_candidates(),
+        "last_decision": guard.difficulty_history().next_back(),
+        "history_len": guard.difficulty_history().count(),
+        "mining_intensity": state.config.mining_intensity,
+        "mining_hours": state.config.mining_schedule,
+    }))
+}
+
+/// Altera campos da dificuldade de uma vez, sob a trava da cadeia: o conjunto é validado antes de qualquer
+/// campo mudar, e com `if_version` uma edição concorrente recebe 409 em vez de ser sobrescrita.
+async fn override_difficulty_handler(
+    State(state): State<AppState>,
+    Json(change): Json<DifficultyOverride>,
+) -> Response {
+    let result = state.chain.lock().unwrap().override_difficulty(&change);
+    let decision = match result {
+        Ok(decision) => decision,
+        Err(OverrideError::VersionConflict { expected, current }) => {
+            return ApiError::json(StatusCode::CONFLICT, serde_json::json!({
+                "error": format!("Difficulty changed since version {}", expected),
+                "version": current,
+            }))
+                .into_response();
+        }
+        Err(OverrideError::Infeasible(problems)) => {
+            return ApiError::json(StatusCode::UNPROCESSABLE_ENTITY, serde_json::json!({
+                "error": "Difficulty override is infeasible; nothing was changed",
+                "problems": problems,
+            }))
+                .into_response();
+        }
+    };
+    if let Some(pool) = &state.pool {
+        pool.invalidate(decision.after.generation);
+    }
+    invariants::debug_check(&state, "difficulty_override");
+    info!(target: "difficulty", "{}", serde_json::to_string(&decision).unwrap_or_default());
+    Json(serde_json::json!({
+        "version": decision.after.generation,
+        "difficulty": decision.after,
+        "decision": decision,
+    }))
+    .into_response()
+}
+
+/// Troca o algoritmo de reajuste da cadeia; vale a partir do próximo bloco.
+async fn set_difficulty_algorithm_handler(
+    State(state): State<AppState>,
+    Json(body): Json<AlgorithmConfig>,
+) -> Response {
+    let algorithm = match body.build() {
+        Ok(algorithm) => algorithm,
+        Err(e) => return ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, e).into_response(),
+    };
+    state.chain.lock().unwrap().set_difficulty_algorithm(algorithm);
+    info!("Algoritmo de dificuldade trocado para {}", body.name());
+    Json(body).into_response()
+}
+
+/// Simula o reajuste de dificuldade pelo cenário do corpo, partindo da dificuldade atual da cadeia
+/// (que só é lida); nenhum bloco é minerado.
+async fn simulate_handler(State(state): State<AppState>, Json(scenario): Json<Scenario>) -> Response {
+    let (start, rules_version) = {
+        let guard = state.chain.lock().unwrap();
+        (guard.difficulty.clone(), guard.next_rules_version())
+    };
+    let run = move || simulate(&scenario, &start, rules_version);
+    match tokio::task::spawn_blocking(run).await {
+        Ok(Ok(simulation)) => Json(simulation).into_response(),
+        Ok(Err(e)) => ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, e).into_response(),
+        Err(_) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Simulation failed").into_response(),
+    }
+}
+
+/// Calibra a dificuldade de uma cadeia nova por um benchmark curto de uma thread, escalado para as
+/// threads do pool na intensidade configurada.
+async fn calibrate_fresh_chain(chain: &mut ChainState, config: &Config) {
+    let (template, difficulty) = (chain.template(), chain.difficulty.clone());
+    let duration = Duration::from_secs_f64(config.bootstrap_calibration_secs);
+    let benchmark = move || measure_throughput(&template, &difficulty, duration);
+    let Ok(per_thread) = tokio::task::spawn_blocking(benchmark).await else { return };
+    let throughput = per_thread * config.mining_threads as f64 * config.mining_intensity.value();
+    if let Some(decision) = chain.bootstrap_difficulty(throughput) {
+        info!(
+            "Dificuldade calibrada ({:.0} candidatos/s): min_digits {}, n_limit {}, hash_scale {}",
+            throughput, decision.after.min_digits, decision.after.n_limit, decision.after.hash_scale
+        );
+    }
+}
+
+/// Monta a cadeia default pela configuração e liga as tarefas de fundo (sinais, alertas, sonda de
+/// carga, bootstrap dos peers).
+pub async fn start_node(config: &Config) -> AppState {
+    let mut chain = ChainState::new();
+    chain.set_epoch_size(config.epoch_size);
+    chain.set_difficulty_algorithm(config.difficulty_algorithm.build().expect("validado na configuração"));
+    for &(version, height) in &config.rules_activation {
+        if let Err(e) = chain.schedule_rules(version, height) {
+            panic!("RULES_ACTIVATION inválido ({}:{}): {}", version, height, e);
+        }
+    }
+    chain.set_emission(config.emission).expect("cadeia recém-criada está vazia");
+    chain.set_prime_width(config.prime_width).expect("cadeia recém-criada está vazia");
+    if config.bootstrap_calibration_secs > 0.0 {
+        calibrate_fresh_chain(&mut chain, config).await;
+    }
+    let state = AppState::new(chain, config);
+    namespaces::register_default(&state);
+    tokio::spawn(shutdown::on_signal(state.clone()));
+    tokio::spawn(alerts::alert_loop(state.clone()));
+    if config.shed_delay_ms > 0 {
+        tokio::spawn(shed::probe_loop(state.clone()));
+    }
+    if !config.bootstrap_peers.is_empty() {
+        tokio::spawn(sync::bootstrap(state.clone(), config.bootstrap_peers.clone()));
+    }
+    state
+}
+
+/// Rotas do nó; `/chains/<nome>/...` ou X-Chain escolhem a cadeia, e sem eles vale a default.
+pub fn node_router(state: AppState) -> Router {
+    Router::new()
+        .route("/admin/chains", post(namespaces::create_chain_handler).get(namespaces::list_chains_handler))
+        .route("/admin/chains/:name", delete(namespaces::delete_chain_handler))
+        .route("/admin/invariants", get(invariants::invariants_handler))
+        .route_layer(from_fn_with_state(state.clone(), middleware::authorize))
+        .route_layer(from_fn_with_state(state.clone(), audit::audit))
+        .route_layer(from_fn_with_state(state.clone(), slo::track))
+        .route_layer(from_fn_with_state(state.clone(), errors::render))
+        .route_layer(from_fn(compression::compress))
+        .route_layer(from_fn_with_state(state.clone(), bandwidth::meter))
+        .fallback(namespaces::dispatch)
+        .with_state(state)
+}
+
+/// Rotas de uma cadeia, ligadas ao estado dela.
+fn app_router(state: AppState) -> Router {
+    // Rotas que alteram a cadeia devolvem X-Chain-Position
+    let writes = Router::new()
+        .route("/mine", get(mine_handler))
+        .route("/chain/resolve", post(sync::resolve_handler))
+        .route("/mine/submit", post(templates::submit_handler))
+        .route("/blocks/compact", post(templates::submit_compact_handler))
+        .route("/blocks/announce", post(orphans::announce_handler))
+        .route(
+            "/chain/import",
+            post(archive::import_handler).layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT)),
+        )
+        .route_layer(from_fn_with_state(state.clone(), stamp_position));
+
+    // Rotas de leitura honram X-Require-Position
+    let reads = Router::new()
+        .route("/chain", get(chain_handler))
+        .route("/chain/tail", get(chain_tail_handler))
+        .route("/tip", get(tip::tip_handler))
+        .route("/chain/summary", get(history::summary_handler))
+        .route("/chain/as-of/:height/summary", get(history::as_of_summary_handler))
+        .route("/balance/:address", get(history::balance_handler))
+        .route("/chain/primorial-hash", get(primorial_hash_handler))
+        .route("/chain/twin-prime-density", get(twin_prime_density_handler))
+        .route("/chain/graph-json", get(graph_json_handler))
+        .route("/chain/hash-tree", get(hash_tree_handler))
+        .route("/chain/export", get(archive::export_handler))
+        .route("/chain/export/sqlite", get(archive::export_sqlite_handler))
+        .route("/chain/stats/json-ld", get(jsonld::stats_jsonld_handler))
+        .route("/chain/orphan-pool", get(orphans::orphan_pool_handler))
+        .route("/chain/epoch/:n", get(blocks::epoch_handler))
+        .route("/chain/energy-estimate", get(blocks::energy_estimate_handler))
+        .route("/chain/rolling-window-stats", get(blocks::rolling_window_stats_handler))
+        .route("/chain/entropy-vs-height", get(blocks::entropy_vs_height_handler))
+        .route("/chain/prime-bits", get(blocks::prime_bits_handler))
+        .route("/chain/mining-fairness", get(blocks::mining_fairness_handler))
+        .route("/chain/dag-ancestors/:index/:depth", get(blocks::dag_ancestors_handler))
+        .route("/chain/finality-score/:index", get(blocks::finality_score_handler))
+        .route("/chain/genesis-distance/:index", get(blocks::genesis_distance_handler))
+        .route("/chain/condensed-proof", get(condensed::condensed_proof_handler))
+        .route("/emission", get(emission::emission_handler))
+        .route("/chain/prime-counting-function", get(prime::prime_counting_handler))
+        .route("/chain/prime-pattern-search", get(prime::prime_pattern_search_handler))
+        .route("/difficulty", get(difficulty_handler).put(override_difficulty_handler))
+        .route("/events", get(events::events_handler))
+        .route("/block/:index", get(block_handler))
+        .route("/block/:index/raw-bytes", get(block_size_handler))
+        .route("/block/:index/compact", get(block_compact_handler))
+        .route("/block/:index/merkle-proof", get(merkle_proof_handler))
+        .route("/block/:index/proof/:txid", get(tx_proof_handler))
+        .route("/block/:index/gcd-test", get(blocks::gcd_test_handler))
+        .route("/block/:index/residue-symbol", get(blocks::residue_symbol_handler))
+        .route("/block/:index/timing-attack-resistance", get(blocks::timing_report_handler))
+        .route("/block/:index/receipt", get(receipts::receipt_handler))
+        .route("/receipts/verify", post(receipts::verify_receipt_handler))
+        .route("/blocks", get(blocks::blocks_by_time_handler))
+        .route("/prime/sexy-pairs", get(prime::sexy_pairs_handler))
+        .route("/prime/polignac/:d", get(prime::polignac_handler))
+        .route("/prime/riemann-zeta/:s", get(prime::riemann_zeta_handler))
+        .route("/prime/abc-triple-search", get(prime::abc_triple_search_handler))
+        .route_layer(from_extractor_with_state::<RequirePosition, _>(state.clone()));
+
+    // Trabalho limitado por requisição, mas sem prazo: o limite é na frequência
+    let cpu_bound = Router::new()
+        .route("/primes/next", get(prime::next_prime_handler))
+        .route("/primes/check", get(prime::check_prime_handler))
+        .route_layer(from_fn_with_state(state.clone(), ratelimit::limit_cpu));
+
+    let admin = Router::new()
+        .route("/peers", post(peers::add_peer_handler).get(peers::list_peers_handler))
+        .route("/admin/quarantine", get(quarantine::list_quarantine_handler))
+        .route("/admin/quarantine/:id", delete(quarantine::delete_quarantine_handler))
+        .route("/admin/rules", get(rules::rules_handler).put(rules::schedule_rules_handler))
+        .route("/admin/runtime", get(runtime_handler))
+        .route("/admin/difficulty", put(set_difficulty_algorithm_handler))
+        .route("/admin/simulate", post(simulate_handler))
+        .route("/admin/emission", put(emission::set_emission_handler))
+        .route("/admin/rebuild", post(health::rebuild_handler))
+        .route("/admin/gc", get(gc::gc_handler).post(gc::run_gc_handler))
+        .route("/admin/config", get(alerts::config_handler).patch(alerts::patch_config_handler))
+        .route("/admin/webhooks", post(webhooks::add_webhook_handler).get(webhooks::list_webhooks_handler))
+        .route("/admin/webhooks/:id", delete(webhooks::delete_webhook_handler))
+        .route("/alerts", get(alerts::list_alerts_handler))
+        .route("/admin/audit-log", get(audit::audit_log_handler))
+        .route("/admin/slo", get(slo::slo_handler))
+        .route("/miners", post(miners::register_miner_handler).get(miners::list_miners_handler))
+        .route("/mine/template", get(templates::template_handler))
+        .route("/mine/challenge", get(templates::challenge_handler))
+        .route("/chain/compress", post(archive::compress_handler))
+        .route("/transactions", post(mempool::submit_transaction_handler))
+        .route("/mempool", get(mempool::mempool_handler))
+        .route("/transactions/batch", post(mempool::submit_batch_handler));
+
+    Router::new()
+        .route("/", get(|| async { "Proof-of-Prime Blockchain Node" }))
+        .route("/healthz", get(health::healthz_handler))
+        .route("/handshake", get(handshake::handshake_handler))
+        .route("/metrics", get(metrics::prometheus_handler))
+        .route("/stats", get(metrics::stats_handler))
+        .route("/health/deep", get(health::deep_health_handler))
+        .route("/health/deep/:task_id", get(health::deep_health_task_handler))
+        .route("/prime/wilson/:n", get(prime::wilson_handler))
+        .route("/prime/wilson-quotient/:p", get(prime::wilson_quotient_handler))
+        .route("/prime/euler-product/:n", get(prime::euler_product_handler))
+        .route("/prime/fermat/:n", get(prime::fermat_handler))
+        .route("/prime/aks-check/:n", get(prime::aks_handler))
+        .route("/prime/Ramanujan-sum/:q/:n", get(prime::ramanujan_sum_handler))
+        .route("/prime/totient/:n", get(prime::totient_handler))
+        .route("/prime/pollard-rho/:n", get(prime::pollard_rho_handler))
+        .route("/prime/smooth-check/:n", get(prime::smooth_check_handler))
+        .route("/prime/continued-fraction/:p", get(prime::continued_fraction_handler))
+        .route("/prime/primitive-root/:p", get(prime::primitive_root_handler))
+        .route("/prime/quadratic-residues/:p", get(prime::quadratic_residues_handler))
+        .route("/prime/lehmer-gcd/:a/:b", get(prime::lehmer_gcd_handler))
+        .route("/prime/is-dh-safe/:p", get(prime::dh_safe_handler))
+        .route("/prime/batch-verify", post(prime::batch_verify_handler))
+        .merge(cpu_bound)
+        .merge(admin)
+        .merge(writes)
+        .merge(reads)
+        // A auditoria fica por fora para registrar também as tentativas negadas
+        .route_layer(from_fn_with_state(state.clone(), middleware::authorize))
+        .route_layer(from_fn_with_state(state.clone(), audit::audit))
+        // Descarta antes da autorização; o SLO, por fora, conta os 503
+        .route_layer(from_fn_with_state(state.clone(), shed::shed))
+        .route_layer(from_fn_with_state(state.clone(), slo::track))
+        // Id da requisição e corpo dos erros; a auditoria, por dentro, registra o mesmo id
+        .route_layer(from_fn_with_state(state.clone(), errors::render))
+        // Gzip do corpo final, erros inclusos
+        .route_layer(from_fn(compression::compress))
+        // Mede o corpo que de fato sai, já comprimido, inclusive o das respostas recusadas por dentro
+        .route_layer(from_fn_with_state(state.clone(), bandwidth::meter))
+        .with_state(state)
+}
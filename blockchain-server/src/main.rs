@@ -0,0 +1,10 @@
+// src/main.rs
+use blockchain_server::{node_router, start_node, Config};
+use shuttle_axum::ShuttleAxum;
+
+#[shuttle_runtime::main]
+async fn axum() -> ShuttleAxum {
+    let config = Config::from_env();
+    let state = start_node(&config).await;
+    Ok(node_router(state).into())
+}
@@ -0,0 +1,285 @@
+// src/mempool.rs
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use blockchain_core::{Block, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use crate::errors::ApiError;
+use crate::invariants::debug_check;
+use crate::state::AppState;
+
+// Máximo de transações por chamada em /transactions/batch
+pub const MAX_BATCH: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxError {
+    InvalidSignature,
+    BadNonce { expected: u64, found: u64 },
+    Duplicate,
+    MempoolFull { capacity: usize },
+}
+
+impl fmt::Display for TxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxError::InvalidSignature => write!(f, "signature does not match the sender key"),
+            TxError::BadNonce { expected, found } => write!(f, "invalid nonce: expected {}, found {}", expected, found),
+            TxError::Duplicate => write!(f, "transaction is already in the mempool"),
+            TxError::MempoolFull { capacity } => write!(f, "mempool is full ({} transactions)", capacity),
+        }
+    }
+}
+
+impl TxError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            TxError::InvalidSignature => "invalid_signature",
+            TxError::BadNonce { .. } => "bad_nonce",
+            TxError::Duplicate => "duplicate",
+            TxError::MempoolFull { .. } => "mempool_full",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            TxError::InvalidSignature => StatusCode::UNPROCESSABLE_ENTITY,
+            TxError::BadNonce { .. } | TxError::Duplicate => StatusCode::CONFLICT,
+            TxError::MempoolFull { .. } => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+/// Transações aceitas e ainda não mineradas, com o próximo nonce esperado de cada remetente.
+#[derive(Debug)]
+pub struct Mempool {
+    capacity: usize,
+    transactions: VecDeque<Transaction>,
+    ids: HashSet<String>,
+    next_nonce: HashMap<String, u64>,
+}
+
+impl Mempool {
+    pub fn new(capacity: usize) -> Self {
+        Mempool { capacity, transactions: VecDeque::new(), ids: HashSet::new(), next_nonce: HashMap::new() }
+    }
+
+    pub fn size(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// Valida assinatura, nonce e capacidade e, se tudo passar, enfileira. Devolve o id.
+    pub fn submit(&mut self, tx: Transaction) -> Result<String, TxError> {
+        if !tx.verify_signature() {
+            return Err(TxError::InvalidSignature);
+        }
+        let id = tx.id();
+        if self.ids.contains(&id) {
+            return Err(TxError::Duplicate);
+        }
+        let expected = self.next_nonce.get(&tx.from).copied().unwrap_or(0);
+        if tx.nonce != expected {
+            return Err(TxError::BadNonce { expected, found: tx.nonce });
+        }
+        if self.transactions.len() >= self.capacity {
+            return Err(TxError::MempoolFull { capacity: self.capacity });
+        }
+        self.next_nonce.insert(tx.from.clone(), expected + 1);
+        self.ids.insert(id.clone());
+        self.transactions.push_back(tx);
+        Ok(id)
+    }
+
+    /// As `limit` transações mais antigas, na ordem de chegada (que respeita os nonces de cada remetente).
+    pub fn pending(&self, limit: usize) -> Vec<Transaction> {
+        self.transactions.iter().take(limit).cloned().collect()
+    }
+
+    /// Retira as transações que entraram num bloco, venha ele de onde vier, e avança o nonce esperado
+    /// de cada remetente para depois do incluído: uma transação já na cadeia não volta a ser aceita.
+    pub fn confirm(&mut self, mined: &[Transaction]) {
+        for tx in mined {
+            let next = self.next_nonce.entry(tx.from.clone()).or_insert(0);
+            *next = (*next).max(tx.nonce.saturating_add(1));
+        }
+        let mined: HashSet<String> = mined.iter().map(Transaction::id).collect();
+        if mined.is_empty() {
+            return;
+        }
+        self.transactions.retain(|tx| !mined.contains(&tx.id()));
+        self.ids.retain(|id| !mined.contains(id));
+    }
+
+    /// Mempool vazio que já conhece os nonces usados nos blocos da cadeia carregada.
+    pub fn for_chain(capacity: usize, blocks: &[Block]) -> Self {
+        let mut mempool = Mempool::new(capacity);
+        mempool.confirm_chain(blocks);
+        mempool
+    }
+
+    /// `confirm` de todos os blocos, para uma cadeia carregada ou trocada inteira.
+    pub fn confirm_chain(&mut self, blocks: &[Block]) {
+        for block in blocks {
+            self.confirm(&block.transactions);
+        }
+    }
+
+    /// Inconsistências internas: índice de ids diferente da fila, fila acima da capacidade ou transação
+    /// pendente com nonce que o remetente ainda não deveria ter usado.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let queued: HashSet<String> = self.transactions.iter().map(Transaction::id).collect();
+        if queued != self.ids || queued.len() != self.transactions.len() {
+            let (ids, queued) = (self.ids.len(), self.transactions.len());
+            problems.push(format!("id index has {} entries for {} queued transactions", ids, queued));
+        }
+        if self.transactions.len() > self.capacity {
+            problems.push(format!("{} transactions queued, capacity is {}", self.transactions.len(), self.capacity));
+        }
+        for tx in &self.transactions {
+            let next = self.next_nonce.get(&tx.from).copied().unwrap_or(0);
+            if tx.nonce >= next {
+                let id = tx.id();
+                problems.push(format!("transaction {} has nonce {} but {} expects {}", id, tx.nonce, tx.from, next));
+            }
+        }
+        problems
+    }
+
+}
+
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum TxResult {
+    Accepted { id: String },
+    Rejected { error: &'static str, message: String },
+}
+
+impl From<Result<String, TxError>> for TxResult {
+    fn from(result: Result<String, TxError>) -> Self {
+        match result {
+            Ok(id) => TxResult::Accepted { id },
+            Err(e) => TxResult::Rejected { error: e.code(), message: e.to_string() },
+        }
+    }
+}
+
+pub async fn submit_transaction_handler(
+    State(state): State<AppState>,
+    Json(tx): Json<Transaction>,
+) -> Response {
+    let result = state.mempool.lock().unwrap().submit(tx);
+    debug_check(&state, "transaction");
+    state.metrics.lock().unwrap().record_transactions(result.is_ok() as u64, result.is_err() as u64);
+    match result {
+        Ok(id) => (StatusCode::ACCEPTED, Json(TxResult::Accepted { id })).into_response(),
+        Err(e) => (e.status(), Json(TxResult::from(Err(e)))).into_response(),
+    }
+}
+
+pub async fn mempool_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let mempool = state.mempool.lock().unwrap();
+    Json(serde_json::json!({ "size": mempool.size(), "capacity": mempool.capacity }))
+}
+
+#[derive(Deserialize)]
+pub struct Batch {
+    transactions: Vec<Transaction>,
+}
+
+/// Cada item é validado sozinho; os válidos entram no mempool mesmo que outros falhem.
+pub async fn submit_batch_handler(
+    State(state): State<AppState>,
+    Json(batch): Json<Batch>,
+) -> Response {
+    if batch.transactions.len() > MAX_BATCH {
+        let error = format!("batch has {} transactions, maximum is {}", batch.transactions.len(), MAX_BATCH);
+        return ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, error).into_response();
+    }
+    let results: Vec<TxResult> = {
+        let mut mempool = state.mempool.lock().unwrap();
+        batch.transactions.into_iter().map(|tx| mempool.submit(tx).into()).collect()
+    };
+    debug_check(&state, "transaction_batch");
+    let accepted = results.iter().filter(|r| matches!(r, TxResult::Accepted { .. })).count();
+    state.metrics.lock().unwrap().record_transactions(accepted as u64, (results.len() - accepted) as u64);
+    Json(serde_json::json!({
+        "accepted": accepted,
+        "rejected": results.len() - accepted,
+        "results": results,
+    }))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{test_clock, test_config, test_router, test_state, ADMIN_KEY};
+    use axum::body::Body;
+    use axum::http::Request;
+    use blockchain_core::testkit::test_key;
+    use blockchain_core::ChainState;
+    use tower::ServiceExt;
+
+    async fn post_batch(capacity: usize, transactions: &[Transaction]) -> (StatusCode, serde_json::Value, AppState) {
+        let config = crate::Config { mempool_capacity: capacity, ..test_config() };
+        let state = test_state(ChainState::new(), &config, test_clock());
+        let body = serde_json::json!({ "transactions": transactions }).to_string();
+        let request = Request::post("/transactions/batch")
+            .header("x-api-key", ADMIN_KEY)
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = test_router(state.clone()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap(), state)
+    }
+
+    #[tokio::test]
+    async fn mixed_batch_keeps_order_and_partial_success() {
+        let (alice, bob) = (test_key(1), test_key(2));
+        let mut forged = Transaction::sign(&bob, "carol", 5, 0);
+        forged.amount = 500;
+        let batch = [
+            Transaction::sign(&alice, "bob", 1, 0),
+            forged,
+            Transaction::sign(&alice, "bob", 1, 5),
+            Transaction::sign(&alice, "bob", 1, 0),
+            Transaction::sign(&alice, "bob", 2, 1),
+            Transaction::sign(&bob, "carol", 3, 0),
+        ];
+        let (status, body, state) = post_batch(2, &batch).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((body["accepted"].as_u64(), body["rejected"].as_u64()), (Some(2), Some(4)));
+        let outcomes: Vec<&str> = body["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r.get("error").unwrap_or(&r["status"]).as_str().unwrap())
+            .collect();
+        assert_eq!(outcomes, ["accepted", "invalid_signature", "bad_nonce", "duplicate", "accepted", "mempool_full"]);
+        assert_eq!(body["results"][0]["id"], batch[0].id());
+        let pending: Vec<String> = state.mempool.lock().unwrap().pending(10).iter().map(Transaction::id).collect();
+        assert_eq!(pending, [batch[0].id(), batch[4].id()]);
+    }
+
+    #[tokio::test]
+    async fn batch_is_capped_at_500() {
+        let key = test_key(3);
+        let batch: Vec<Transaction> = (0..MAX_BATCH as u64 + 1).map(|n| Transaction::sign(&key, "bob", 1, n)).collect();
+        let (status, body, state) = post_batch(1000, &batch).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(body["error"].as_str().unwrap().contains("maximum is 500"));
+        assert_eq!(state.mempool.lock().unwrap().size(), 0);
+
+        let (status, body, state) = post_batch(1000, &batch[..MAX_BATCH]).await;
+        assert_eq!((status, body["accepted"].as_u64()), (StatusCode::OK, Some(500)));
+        assert_eq!(state.mempool.lock().unwrap().size(), 500);
+    }
+}
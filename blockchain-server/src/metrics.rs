@@ -0,0 +1,265 @@
+// src/metrics.rs
+use axum::{extract::State, http::header, response::IntoResponse, Json};
+use blockchain_core::{DutyCycle, MiningStats};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::bandwidth::BandwidthReport;
+use crate::state::AppState;
+
+/// Contadores monotônicos acumulados desde o primeiro deploy.
+/// Campos novos ficam em zero ao ler snapshots antigos.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Counters {
+    pub blocks_mined: u64,
+    pub blocks_submitted: u64,
+    pub candidates: u64,
+    pub gcd_rejected: u64,
+    pub parity_rejected: u64,
+    pub trial_division_rejected: u64,
+    pub heuristic_rejected: u64,
+    pub miller_rabin_rejected: u64,
+    pub hash_target_rejected: u64,
+    pub pool_hits: u64,
+    pub chain_replacements: u64,
+    pub transactions_accepted: u64,
+    pub transactions_rejected: u64,
+    pub derived_state_drift: u64,
+    pub orphans_collected: u64,
+}
+
+impl Counters {
+    fn named(&self) -> [(&'static str, &'static str, u64); 15] {
+        [
+            ("blocks_mined", "Blocks mined by this node", self.blocks_mined),
+            ("blocks_submitted", "Blocks accepted from external miners", self.blocks_submitted),
+            ("candidates", "Candidate tuples examined", self.candidates),
+            ("gcd_rejected", "Candidates rejected by the gcd filter", self.gcd_rejected),
+            ("parity_rejected", "Candidates rejected for even witness", self.parity_rejected),
+            ("trial_division_rejected", "Candidates rejected by trial division", self.trial_division_rejected),
+            ("heuristic_rejected", "Candidates rejected by the prime heuristic", self.heuristic_rejected),
+            ("miller_rabin_rejected", "Candidates rejected by Miller-Rabin", self.miller_rabin_rejected),
+            ("hash_target_rejected", "Primes whose block hash missed the v4 target", self.hash_target_rejected),
+            ("pool_hits", "Candidates taken from the precomputed pool", self.pool_hits),
+            ("chain_replacements", "Times the local chain was replaced by a peer chain", self.chain_replacements),
+            ("transactions_accepted", "Transactions admitted to the mempool", self.transactions_accepted),
+            ("transactions_rejected", "Transactions rejected by the mempool", self.transactions_rejected),
+            ("derived_state_drift", "Derived structures repaired by /admin/rebuild", self.derived_state_drift),
+            ("orphans_collected", "Orphans dropped below the max reorg depth", self.orphans_collected),
+        ]
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    counters: Counters,
+    path: Option<PathBuf>,
+}
+
+impl Metrics {
+    /// Restaura o snapshot salvo; um arquivo corrompido é ignorado e a contagem recomeça.
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let counters = path
+            .as_ref()
+            .filter(|p| p.exists())
+            .and_then(|p| match fs::read(p).map_err(|e| e.to_string()).and_then(|bytes| {
+                serde_json::from_slice(&bytes).map_err(|e| e.to_string())
+            }) {
+                Ok(counters) => Some(counters),
+                Err(e) => {
+                    warn!("Snapshot de métricas corrompido em {}: {}", p.display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Metrics { counters, path }
+    }
+
+    pub fn counters(&self) -> &Counters {
+        &self.counters
+    }
+
+    pub fn counters_mut(&mut self) -> &mut Counters {
+        &mut self.counters
+    }
+
+    pub fn record_mining(&mut self, stats: &MiningStats) {
+        let c = &mut self.counters;
+        c.blocks_mined += 1;
+        c.candidates += stats.candidates;
+        c.gcd_rejected += stats.gcd_rejected;
+        c.parity_rejected += stats.parity_rejected;
+        c.trial_division_rejected += stats.trial_division_rejected;
+        c.heuristic_rejected += stats.heuristic_rejected;
+        c.miller_rabin_rejected += stats.miller_rabin_rejected;
+        c.hash_target_rejected += stats.hash_target_rejected;
+        c.pool_hits += stats.pool_hits;
+    }
+
+    pub fn record_transactions(&mut self, accepted: u64, rejected: u64) {
+        self.counters.transactions_accepted += accepted;
+        self.counters.transactions_rejected += rejected;
+    }
+
+    pub fn persist(&self) {
+        let Some(path) = &self.path else { return };
+        // Grava ao lado e renomeia, para um desligamento no meio não deixar o arquivo truncado
+        let tmp = path.with_extension("json.tmp");
+        let result = serde_json::to_vec(&self.counters)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| fs::write(&tmp, bytes).map_err(|e| e.to_string()))
+            .and_then(|_| fs::rename(&tmp, path).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("Falha ao persistir métricas em {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Grava o snapshot a cada `interval`.
+pub async fn snapshot_loop(state: AppState, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        state.metrics.lock().unwrap().persist();
+    }
+}
+
+#[derive(Serialize)]
+pub struct Stats {
+    #[serde(flatten)]
+    counters: Counters,
+    mining_intensity: f64,
+    // Fração do tempo de parede que as fatias de mineração passaram trabalhando
+    mining_duty_cycle: DutyCycle,
+    // Bytes de corpo servidos pelo nó, em todas as cadeias
+    bandwidth: BandwidthReport,
+}
+
+pub async fn stats_handler(State(state): State<AppState>) -> Json<Stats> {
+    Json(Stats {
+        counters: state.metrics.lock().unwrap().counters().clone(),
+        mining_intensity: state.config.mining_intensity.value(),
+        mining_duty_cycle: state.miner.duty_cycle(),
+        bandwidth: state.bandwidth.lock().unwrap().report(),
+    })
+}
+
+/// Formato de exposição do Prometheus; público, como /healthz, para o scraper.
+pub async fn prometheus_handler(State(state): State<AppState>) -> impl IntoResponse {
+    // Uma série por cadeia, com o rótulo chain
+    let chains: Vec<_> = state
+        .chains
+        .lock()
+        .unwrap()
+        .states()
+        .into_iter()
+        .map(|ns| {
+            let counters = ns.metrics.lock().unwrap().counters().named();
+            let height = ns.chain.lock().unwrap().height();
+            (ns.namespace, counters, height)
+        })
+        .collect();
+    let miner = state.miner.stats();
+    let mut body = String::new();
+    for (i, (name, help, _)) in Counters::default().named().iter().enumerate() {
+        body.push_str(&format!(
+            "# HELP proof_of_prime_{name}_total {help}\n# TYPE proof_of_prime_{name}_total counter\n"
+        ));
+        for (chain, counters, _) in &chains {
+            let value = counters[i].2;
+            body.push_str(&format!("proof_of_prime_{name}_total{{chain=\"{chain}\"}} {value}\n"));
+        }
+    }
+    body.push_str(
+        "# HELP proof_of_prime_chain_height Blocks in the local chain\n# TYPE proof_of_prime_chain_height gauge\n",
+    );
+    for (chain, _, height) in &chains {
+        body.push_str(&format!("proof_of_prime_chain_height{{chain=\"{chain}\"}} {height}\n"));
+    }
+    for (name, help, value) in [
+        ("mining_threads_busy", "Mining pool threads running a job", miner.busy),
+        ("mining_jobs_queued", "Mining jobs waiting for a thread", miner.queued),
+    ] {
+        body.push_str(&format!(
+            "# HELP proof_of_prime_{name} {help}\n# TYPE proof_of_prime_{name} gauge\nproof_of_prime_{name} {value}\n"
+        ));
+    }
+    let load = state.shedder.lock().unwrap().status();
+    for (name, help, kind, value) in [
+        ("scheduler_delay_ms", "Latest Tokio scheduler delay measured by the probe", "gauge", load.scheduler_delay_ms),
+        ("load_shedding", "1 while low-priority routes are shed", "gauge", load.shedding as u64),
+        ("load_shed_requests_total", "Low-priority requests rejected while shedding", "counter", load.shed_requests),
+        ("mining_worker_panics_total", "Mining workers that panicked", "counter", miner.worker_panics),
+    ] {
+        body.push_str(&format!(
+            "# HELP proof_of_prime_{name} {help}\n# TYPE proof_of_prime_{name} {kind}\nproof_of_prime_{name} {value}\n"
+        ));
+    }
+    state.bandwidth.lock().unwrap().prometheus(&mut body);
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Arquivo próprio de cada teste, apagado antes de usar
+    fn snapshot_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("metrics-{}-{}.json", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn mined(candidates: u64) -> MiningStats {
+        MiningStats { candidates, gcd_rejected: candidates / 2, pool_hits: 1, ..MiningStats::default() }
+    }
+
+    #[test]
+    fn counters_continue_after_reload() {
+        let path = snapshot_path("reload");
+        let mut metrics = Metrics::load(Some(path.clone()));
+        metrics.record_mining(&mined(40));
+        metrics.record_mining(&mined(10));
+        metrics.record_transactions(3, 1);
+        metrics.persist();
+
+        // Como num novo deploy: a contagem segue de onde parou
+        let mut restarted = Metrics::load(Some(path.clone()));
+        let c = restarted.counters();
+        assert_eq!((c.blocks_mined, c.candidates, c.gcd_rejected, c.pool_hits), (2, 50, 25, 2));
+        assert_eq!((c.transactions_accepted, c.transactions_rejected), (3, 1));
+        restarted.record_mining(&mined(8));
+        restarted.persist();
+        let c = Metrics::load(Some(path.clone())).counters().clone();
+        assert_eq!((c.blocks_mined, c.candidates), (3, 58));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn corrupt_snapshot_starts_from_zero() {
+        let path = snapshot_path("corrupt");
+        fs::write(&path, b"{\"blocks_mined\": 7,").unwrap();
+        let mut metrics = Metrics::load(Some(path.clone()));
+        assert_eq!(metrics.counters().blocks_mined, 0);
+
+        // O próximo snapshot substitui o arquivo estragado
+        metrics.record_mining(&mined(5));
+        metrics.persist();
+        assert_eq!(Metrics::load(Some(path.clone())).counters().blocks_mined, 1);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn old_snapshots_miss_new_counters() {
+        let path = snapshot_path("old");
+        fs::write(&path, br#"{"blocks_mined": 4, "candidates": 90}"#).unwrap();
+        let c = Metrics::load(Some(path.clone())).counters().clone();
+        assert_eq!((c.blocks_mined, c.candidates, c.orphans_collected), (4, 90, 0));
+        let _ = fs::remove_file(path);
+    }
+}
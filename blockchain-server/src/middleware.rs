@@ -0,0 +1,247 @@
+// src/middleware.rs
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+
+/// Papéis em ordem crescente: cada um inclui os anteriores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Public,
+    Read,
+    Mine,
+    Admin,
+}
+
+impl Role {
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Public => "public",
+            Role::Read => "read",
+            Role::Mine => "mine",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "public" => Ok(Role::Public),
+            "read" => Ok(Role::Read),
+            "mine" => Ok(Role::Mine),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("unknown role {:?} (expected public, read, mine or admin)", other)),
+        }
+    }
+}
+
+/// Grupos de rotas cuja exigência de papel é configurável.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteGroup {
+    ReadChain,
+    ReadStats,
+    Mine,
+    Admin,
+    Peer,
+}
+
+impl RouteGroup {
+    const ALL: [RouteGroup; 5] =
+        [RouteGroup::ReadChain, RouteGroup::ReadStats, RouteGroup::Mine, RouteGroup::Admin, RouteGroup::Peer];
+
+    fn default_role(self) -> Role {
+        match self {
+            RouteGroup::ReadChain | RouteGroup::ReadStats => Role::Read,
+            RouteGroup::Mine => Role::Mine,
+            RouteGroup::Admin | RouteGroup::Peer => Role::Admin,
+        }
+    }
+}
+
+impl fmt::Display for RouteGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RouteGroup::ReadChain => "read_chain",
+            RouteGroup::ReadStats => "read_stats",
+            RouteGroup::Mine => "mine",
+            RouteGroup::Admin => "admin",
+            RouteGroup::Peer => "peer",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for RouteGroup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RouteGroup::ALL.into_iter().find(|g| g.to_string() == s.trim()).ok_or_else(|| {
+            format!("unknown route group {:?} (expected read_chain, read_stats, mine, admin or peer)", s.trim())
+        })
+    }
+}
+
+/// Papel exigido por grupo de rotas; alterável em tempo de execução por /admin/config.
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteRoles(BTreeMap<RouteGroup, Role>);
+
+impl Default for RouteRoles {
+    fn default() -> Self {
+        RouteRoles(RouteGroup::ALL.into_iter().map(|g| (g, g.default_role())).collect())
+    }
+}
+
+impl RouteRoles {
+    /// Lê `grupo=papel,grupo=papel`; grupos ausentes mantêm o padrão.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut roles = RouteRoles::default();
+        for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (group, role) = pair.split_once('=').ok_or_else(|| format!("expected group=role, got {:?}", pair))?;
+            roles.0.insert(group.parse()?, role.parse()?);
+        }
+        Ok(roles)
+    }
+
+    /// Aplica uma alteração parcial; nada muda se algum nome for inválido.
+    pub fn apply(&mut self, patch: &BTreeMap<String, String>) -> Result<(), String> {
+        let parsed = patch
+            .iter()
+            .map(|(group, role)| Ok((group.parse()?, role.parse()?)))
+            .collect::<Result<Vec<(RouteGroup, Role)>, String>>()?;
+        self.0.extend(parsed);
+        Ok(())
+    }
+
+    pub fn required(&self, group: RouteGroup) -> Role {
+        self.0.get(&group).copied().unwrap_or(Role::Admin)
+    }
+}
+
+// Grupo de cada rota; `None` para as sempre públicas
+fn route_group(method: &Method, path: &str) -> Option<RouteGroup> {
+    if matches!(path, "/" | "/healthz" | "/metrics" | "/handshake") {
+        return None;
+    }
+    let group = match (method.as_str(), path) {
+        ("GET", "/stats" | "/alerts" | "/health/deep" | "/health/deep/:task_id") => RouteGroup::ReadStats,
+        ("GET", "/mine" | "/mine/template" | "/mine/challenge" | "/tip")
+        | ("POST", "/mine/submit" | "/blocks/compact" | "/miners" | "/transactions" | "/transactions/batch") => {
+            RouteGroup::Mine
+        }
+        (_, "/peers") | ("POST", "/blocks/announce" | "/chain/resolve" | "/chain/import") => RouteGroup::Peer,
+        ("POST", "/chain/compress") | ("GET", "/miners") | ("PUT", "/difficulty") => RouteGroup::Admin,
+        (_, p) if p.starts_with("/admin/") => RouteGroup::Admin,
+        ("GET", p) if ["/chain", "/block", "/prime", "/balance"].iter().any(|prefix| p.starts_with(prefix)) => {
+            RouteGroup::ReadChain
+        }
+        ("GET", "/difficulty" | "/emission" | "/mempool" | "/events")
+        | ("POST", "/prime/batch-verify" | "/receipts/verify") => RouteGroup::ReadChain,
+        // Rota nova sem grupo: exige admin até ser classificada
+        _ => RouteGroup::Admin,
+    };
+    Some(group)
+}
+
+// Papel concedido pela chave em X-API-Key
+fn key_role(state: &AppState, key: &str) -> Option<Role> {
+    state.config.api_keys.iter().find(|(k, _)| k == key).map(|(_, role)| *role)
+}
+
+/// Autorização única de todas as rotas: resolve o grupo pela rota casada e compara o papel da chave
+/// com o exigido pelo mapa atual.
+pub async fn authorize(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let path = req.extensions().get::<MatchedPath>().map(|p| p.as_str().to_string());
+    let Some(group) = path.as_deref().and_then(|p| route_group(req.method(), p)) else {
+        return next.run(req).await;
+    };
+    let required = state.route_roles.lock().unwrap().required(group);
+    if required == Role::Public {
+        return next.run(req).await;
+    }
+    let Some(key) = req.headers().get("x-api-key").and_then(|v| v.to_str().ok()) else {
+        return ApiError::new(StatusCode::BAD_REQUEST, "Missing X-API-Key header").into_response();
+    };
+    let Some(role) = key_role(&state, key) else {
+        return ApiError::new(StatusCode::UNAUTHORIZED, "Invalid API key").into_response();
+    };
+    if role < required {
+        return ApiError::new(StatusCode::FORBIDDEN, format!("Route group {} requires role {}", group, required.name()))
+            .into_response();
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{test_node, ADMIN_KEY, READ_KEY};
+    use axum::body::Body;
+    use axum::Router;
+    use blockchain_core::ChainState;
+    use tower::ServiceExt;
+
+    async fn status(router: &Router, request: axum::http::request::Builder, body: Body) -> StatusCode {
+        router.clone().oneshot(request.body(body).unwrap()).await.unwrap().status()
+    }
+
+    async fn get_chain(router: &Router, key: Option<&str>) -> StatusCode {
+        let mut request = Request::get("/chain");
+        if let Some(key) = key {
+            request = request.header("x-api-key", key);
+        }
+        status(router, request, Body::empty()).await
+    }
+
+    async fn patch_roles(router: &Router, roles: serde_json::Value) -> StatusCode {
+        let request = Request::patch("/admin/config").header("x-api-key", ADMIN_KEY);
+        let request = request.header("content-type", "application/json");
+        status(router, request, Body::from(serde_json::json!({ "route_roles": roles }).to_string())).await
+    }
+
+    #[tokio::test]
+    async fn chain_flips_between_public_and_read() {
+        let (router, _, _) = test_node(ChainState::new());
+        assert_eq!(get_chain(&router, None).await, StatusCode::BAD_REQUEST);
+        assert_eq!(get_chain(&router, Some("wrong")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(get_chain(&router, Some(READ_KEY)).await, StatusCode::OK);
+
+        assert_eq!(patch_roles(&router, serde_json::json!({ "read_chain": "public" })).await, StatusCode::OK);
+        assert_eq!(get_chain(&router, None).await, StatusCode::OK);
+        // Só o grupo alterado fica aberto
+        let stats = status(&router, Request::get("/stats"), Body::empty()).await;
+        assert_eq!(stats, StatusCode::BAD_REQUEST);
+
+        assert_eq!(patch_roles(&router, serde_json::json!({ "read_chain": "admin" })).await, StatusCode::OK);
+        assert_eq!(get_chain(&router, Some(READ_KEY)).await, StatusCode::FORBIDDEN);
+        assert_eq!(get_chain(&router, Some(ADMIN_KEY)).await, StatusCode::OK);
+
+        // Um nome inválido recusa a alteração inteira
+        let invalid = serde_json::json!({ "read_chain": "read", "stats": "public" });
+        assert_eq!(patch_roles(&router, invalid).await, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(get_chain(&router, Some(READ_KEY)).await, StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn misconfigured_roles_fail_to_parse() {
+        let roles = RouteRoles::parse("read_chain=public, admin=admin").unwrap();
+        assert_eq!(roles.required(RouteGroup::ReadChain), Role::Public);
+        assert_eq!(roles.required(RouteGroup::ReadStats), Role::Read);
+        assert!(RouteRoles::parse("chain=public").unwrap_err().contains("unknown route group"));
+        assert!(RouteRoles::parse("read_chain=everyone").unwrap_err().contains("unknown role"));
+        assert!(RouteRoles::parse("read_chain").unwrap_err().contains("expected group=role"));
+    }
+}
@@ -0,0 +1,577 @@
+// src/miner.rs
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use blockchain_core::block::BlockBuilder;
+use blockchain_core::{
+    mine_template, Block, CandidatePool, Clock, Difficulty, DutyCycle, DutyMeter, Intensity, MiningStats, RaceResult,
+    SeededRace, SystemClock, Throttle, MAX_WORKERS,
+};
+use log::{info, warn};
+use serde::Serialize;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tokio::sync::mpsc as tokio_mpsc;
+use tokio::time::{timeout_at, Instant};
+
+use crate::errors::ApiError;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+// O que um worker reportou: o resultado (None se parou antes) ou a causa do pânico
+type WorkerOutcome<T> = Result<Option<T>, String>;
+
+/// Por que uma mineração terminou sem bloco.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MiningError {
+    // Todos os workers entraram em pânico; uma causa por worker
+    AllWorkersFailed { causes: Vec<String> },
+    // Parada pelo desligamento do pool
+    Cancelled,
+    // A cadeia foi substituída durante a mineração; o bloco não teria mais onde entrar
+    Stale,
+    // MINING_TIMEOUT_SECS esgotado antes de algum worker achar um primo
+    TimedOut,
+    // A dificuldade atual não tem solução, então nem começa
+    InfeasibleDifficulty { problems: Vec<String> },
+    // Workers sumiram sem reportar nada
+    ChannelClosed,
+}
+
+impl IntoResponse for MiningError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            MiningError::AllWorkersFailed { causes } => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                serde_json::json!({
+                    "error": "All mining workers failed",
+                    "kind": "all_workers_failed",
+                    "causes": causes,
+                }),
+            ),
+            MiningError::Cancelled => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                serde_json::json!({ "error": "Miner is shutting down", "kind": "cancelled" }),
+            ),
+            MiningError::Stale => (
+                StatusCode::CONFLICT,
+                serde_json::json!({ "error": "Chain was replaced while mining", "kind": "stale" }),
+            ),
+            MiningError::TimedOut => (
+                StatusCode::REQUEST_TIMEOUT,
+                serde_json::json!({ "error": "Mining timed out before a prime was found", "kind": "timed_out" }),
+            ),
+            MiningError::InfeasibleDifficulty { problems } => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                serde_json::json!({
+                    "error": "Current difficulty is infeasible",
+                    "kind": "infeasible_difficulty",
+                    "problems": problems,
+                }),
+            ),
+            MiningError::ChannelClosed => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                serde_json::json!({ "error": "Mining workers exited without reporting", "kind": "channel_closed" }),
+            ),
+        };
+        // `kind` também continua no corpo, para os clientes que já o liam
+        let kind = body["kind"].as_str().unwrap_or_default().to_string();
+        ApiError::json(status, body).with_code(kind).into_response()
+    }
+}
+
+fn panic_cause(payload: Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|cause| cause.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panic with a non-string payload".to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MinerStats {
+    pub threads: usize,
+    pub busy: usize,
+    pub queued: usize,
+    pub shutting_down: bool,
+    pub intensity: f64,
+    pub worker_panics: u64,
+}
+
+// Parada de uma mineração em andamento, com a cadeia para a qual ela minera
+struct StopSignal {
+    chain: String,
+    stop: Arc<AtomicBool>,
+    // Acionada por `cancel_chain`, e não pelo desligamento nem pelo prazo
+    stale: AtomicBool,
+}
+
+/// Pool de threads exclusivo da mineração, fora do pool de bloqueio do Tokio.
+pub struct Miner {
+    threads: usize,
+    queue: Mutex<Option<mpsc::Sender<Job>>>,
+    handles: Mutex<Vec<JoinHandle<()>>>,
+    busy: Arc<AtomicUsize>,
+    queued: Arc<AtomicUsize>,
+    // Sinais de parada das minerações em andamento, acionados no desligamento e na troca de cadeia
+    cancels: Mutex<Vec<Arc<StopSignal>>>,
+    shutting_down: AtomicBool,
+    intensity: Intensity,
+    clock: Arc<dyn Clock>,
+    // Ciclo de trabalho medido de todas as fatias de mineração, inclusive a pré-computação
+    meter: Arc<DutyMeter>,
+    // Prazo de cada mineração; None espera quanto for preciso
+    timeout: Option<Duration>,
+    // Workers que entraram em pânico desde o início do nó
+    panics: Arc<AtomicU64>,
+}
+
+impl Miner {
+    pub fn new(threads: usize, intensity: Intensity, timeout: Option<Duration>) -> Self {
+        // Acima de MAX_WORKERS as vitórias dos workers a mais não teriam onde ser contadas
+        let threads = threads.clamp(1, MAX_WORKERS);
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let busy = Arc::new(AtomicUsize::new(0));
+        let queued = Arc::new(AtomicUsize::new(0));
+        let handles = (0..threads)
+            .map(|i| {
+                let receiver = receiver.clone();
+                let busy = busy.clone();
+                let queued = queued.clone();
+                thread::Builder::new()
+                    .name(format!("miner-{}", i))
+                    .spawn(move || loop {
+                        let job = receiver.lock().unwrap().recv();
+                        let Ok(job) = job else { break };
+                        queued.fetch_sub(1, Ordering::Relaxed);
+                        busy.fetch_add(1, Ordering::Relaxed);
+                        job();
+                        busy.fetch_sub(1, Ordering::Relaxed);
+                    })
+                    .expect("thread de mineração")
+            })
+            .collect();
+        Miner {
+            threads,
+            queue: Mutex::new(Some(sender)),
+            handles: Mutex::new(handles),
+            busy,
+            queued,
+            cancels: Mutex::new(Vec::new()),
+            shutting_down: AtomicBool::new(false),
+            intensity,
+            clock: Arc::new(SystemClock),
+            meter: Arc::new(DutyMeter::default()),
+            timeout,
+            panics: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Troca o relógio do ritmo e da janela de mineração, para testes com tempo simulado.
+    #[cfg(any(test, feature = "testkit"))]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn stats(&self) -> MinerStats {
+        MinerStats {
+            threads: self.threads,
+            busy: self.busy.load(Ordering::Relaxed),
+            queued: self.queued.load(Ordering::Relaxed),
+            shutting_down: self.shutting_down.load(Ordering::Relaxed),
+            intensity: self.intensity.value(),
+            worker_panics: self.panics.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Ritmo para uma fatia de mineração na intensidade configurada.
+    pub fn throttle(&self) -> Throttle {
+        Throttle::new(self.intensity, self.clock.clone(), self.meter.clone())
+    }
+
+    pub fn duty_cycle(&self) -> DutyCycle {
+        self.meter.duty_cycle()
+    }
+
+    fn submit(&self, job: Job) -> bool {
+        let queue = self.queue.lock().unwrap();
+        let Some(sender) = queue.as_ref() else { return false };
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        if sender.send(job).is_err() {
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+            return false;
+        }
+        true
+    }
+
+    // Envia `workers` tarefas ao pool; um pânico vira a causa reportada em vez de derrubar a thread
+    fn spawn_workers<T, F>(&self, workers: usize, work: F) -> tokio_mpsc::Receiver<WorkerOutcome<T>>
+    where
+        T: Send + 'static,
+        F: Fn(usize, &mut Throttle) -> Option<T> + Send + Sync + 'static,
+    {
+        let (tx, rx) = tokio_mpsc::channel(workers);
+        let work = Arc::new(work);
+        for worker in 0..workers {
+            let (tx, work, panics) = (tx.clone(), work.clone(), self.panics.clone());
+            let mut throttle = self.throttle();
+            self.submit(Box::new(move || {
+                let outcome = panic::catch_unwind(AssertUnwindSafe(|| work(worker, &mut throttle))).map_err(|payload| {
+                    panics.fetch_add(1, Ordering::Relaxed);
+                    let cause = panic_cause(payload);
+                    warn!("Worker de mineração {} entrou em pânico: {}", worker, cause);
+                    cause
+                });
+                let _ = tx.blocking_send(outcome);
+            }));
+        }
+        rx
+    }
+
+    // Junta os relatos até todos chegarem, ou só até o primeiro resultado com `first_wins`; sem resultado,
+    // decide o erro. O prazo aciona `stop` e vira TimedOut.
+    async fn collect<T>(
+        &self,
+        mut rx: tokio_mpsc::Receiver<WorkerOutcome<T>>,
+        workers: usize,
+        stop: &AtomicBool,
+        first_wins: bool,
+    ) -> Result<Vec<T>, MiningError> {
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let (mut found, mut causes, mut reported, mut timed_out) = (Vec::new(), Vec::new(), 0, false);
+        loop {
+            let next = match deadline {
+                Some(deadline) => match timeout_at(deadline, rx.recv()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        // Os workers param e ainda reportam o que tinham
+                        stop.store(true, Ordering::Relaxed);
+                        timed_out = true;
+                        rx.recv().await
+                    }
+                },
+                None => rx.recv().await,
+            };
+            let Some(outcome) = next else { break };
+            reported += 1;
+            match outcome {
+                Ok(Some(result)) => {
+                    found.push(result);
+                    if first_wins {
+                        break;
+                    }
+                }
+                Ok(None) => {}
+                Err(cause) => causes.push(cause),
+            }
+        }
+        if !found.is_empty() {
+            return Ok(found);
+        }
+        Err(if timed_out {
+            MiningError::TimedOut
+        } else if causes.len() == workers {
+            MiningError::AllWorkersFailed { causes }
+        } else if self.shutting_down.load(Ordering::Acquire) {
+            MiningError::Cancelled
+        } else if reported < workers {
+            MiningError::ChannelClosed
+        } else {
+            MiningError::Cancelled
+        })
+    }
+
+    // Registra o sinal de parada, já acionado se o pool estiver encerrando
+    fn register_stop(&self, chain: &str) -> Arc<StopSignal> {
+        let signal = Arc::new(StopSignal {
+            chain: chain.to_string(),
+            stop: Arc::new(AtomicBool::new(false)),
+            stale: AtomicBool::new(false),
+        });
+        self.cancels.lock().unwrap().push(signal.clone());
+        if self.shutting_down.load(Ordering::Acquire) {
+            signal.stop.store(true, Ordering::Relaxed);
+        }
+        signal
+    }
+
+    // Tira o sinal da lista; uma mineração sem bloco parada por `cancel_chain` vira Stale
+    fn release_stop<T>(&self, signal: &Arc<StopSignal>, result: Result<T, MiningError>) -> Result<T, MiningError> {
+        self.cancels.lock().unwrap().retain(|s| !Arc::ptr_eq(s, signal));
+        result.map_err(|e| if signal.stale.load(Ordering::Acquire) { MiningError::Stale } else { e })
+    }
+
+    /// Para as minerações em andamento da cadeia `chain`, que terminam com `MiningError::Stale`; chamada
+    /// sob o lock da cadeia, no momento da troca. Devolve quantas foram paradas.
+    pub fn cancel_chain(&self, chain: &str) -> usize {
+        let cancels = self.cancels.lock().unwrap();
+        let stale: Vec<_> = cancels.iter().filter(|s| s.chain == chain).collect();
+        for signal in &stale {
+            signal.stale.store(true, Ordering::Release);
+            signal.stop.store(true, Ordering::Relaxed);
+        }
+        stale.len()
+    }
+
+    /// Minera com `workers` tarefas concorrentes; a primeira a achar um primo vence. Devolve também o
+    /// índice dela.
+    pub async fn mine(
+        &self,
+        chain: &str,
+        template: BlockBuilder,
+        difficulty: Difficulty,
+        workers: usize,
+        pool: Option<Arc<CandidatePool>>,
+    ) -> Result<(Block, MiningStats, usize), MiningError> {
+        let problems = difficulty.problems();
+        if !problems.is_empty() {
+            return Err(MiningError::InfeasibleDifficulty { problems });
+        }
+        let workers = workers.max(1);
+        let signal = self.register_stop(chain);
+        let worker_stop = signal.stop.clone();
+        let rx = self.spawn_workers(workers, move |worker, throttle| {
+            mine_template(&template, &difficulty, &worker_stop, pool.as_deref(), throttle)
+                .map(|(block, stats)| (block, stats, worker))
+        });
+        let result = self.collect(rx, workers, &signal.stop, true).await;
+        // Encerra os workers que perderam a corrida
+        signal.stop.store(true, Ordering::Relaxed);
+        self.release_stop(&signal, result).map(|mut found| found.swap_remove(0))
+    }
+
+    /// Como `mine`, com `workers` fluxos ChaCha20 derivados de `seed` e vencedor reprodutível (ver
+    /// `SeededRace`); espera todos os workers pararem antes de decidir.
+    pub async fn mine_seeded(
+        &self,
+        chain: &str,
+        template: BlockBuilder,
+        difficulty: Difficulty,
+        workers: usize,
+        seed: u64,
+    ) -> Result<RaceResult, MiningError> {
+        let problems = difficulty.problems();
+        if !problems.is_empty() {
+            return Err(MiningError::InfeasibleDifficulty { problems });
+        }
+        let workers = workers.max(1);
+        let race = Arc::new(SeededRace::new(seed));
+        let signal = self.register_stop(chain);
+        let (worker_race, worker_stop) = (race.clone(), signal.stop.clone());
+        let rx = self.spawn_workers(workers, move |worker, throttle| {
+            Some(worker_race.run(worker, &template, &difficulty, &worker_stop, throttle))
+        });
+        let runs = self.collect(rx, workers, &signal.stop, false).await;
+        // Todas as corridas pararam sem bloco: só acontece quando `stop` foi acionado
+        let settled = runs.and_then(|runs| {
+            race.settle(runs).ok_or(if self.shutting_down.load(Ordering::Acquire) {
+                MiningError::Cancelled
+            } else {
+                MiningError::TimedOut
+            })
+        });
+        self.release_stop(&signal, settled)
+    }
+
+    /// Cancela as minerações em andamento, fecha a fila e espera todas as threads.
+    pub fn shutdown(&self) {
+        self.shutting_down.store(true, Ordering::Release);
+        for signal in self.cancels.lock().unwrap().iter() {
+            signal.stop.store(true, Ordering::Relaxed);
+        }
+        self.queue.lock().unwrap().take();
+        let handles: Vec<_> = self.handles.lock().unwrap().drain(..).collect();
+        let joined = handles.len();
+        for handle in handles {
+            let _ = handle.join();
+        }
+        info!("Pool de mineração encerrado ({} threads)", joined);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Alvo de hash alto o bastante para a busca não terminar sozinha durante o teste
+    fn slow_template() -> BlockBuilder {
+        BlockBuilder::on(&Block::genesis()).rules_version(4).hash_scale(1 << 40)
+    }
+
+    #[tokio::test]
+    async fn cancel_chain_stops_only_that_chain() {
+        let miner = Arc::new(Miner::new(2, Intensity::FULL, None));
+        let (m1, m2) = (miner.clone(), miner.clone());
+        let difficulty = Difficulty::default();
+        let d = difficulty.clone();
+        let first = tokio::spawn(async move { m1.mine("x", slow_template(), d, 2, None).await });
+        let second = tokio::spawn(async move { m2.mine_seeded("x", slow_template(), difficulty, 2, 5).await });
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        assert_eq!(miner.cancel_chain("y"), 0);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!first.is_finished() && !second.is_finished(), "outra cadeia não pode parar estas buscas");
+
+        assert_eq!(miner.cancel_chain("x"), 2);
+        assert_eq!(first.await.unwrap().unwrap_err(), MiningError::Stale);
+        assert_eq!(second.await.unwrap().unwrap_err(), MiningError::Stale);
+        assert_eq!(miner.cancel_chain("x"), 0);
+        miner.shutdown();
+    }
+
+    /// Mesma semente, modelo, dificuldade e 4 workers: mesmo bloco vencedor e mesmos contadores por worker.
+    #[tokio::test]
+    async fn seeded_race_is_reproducible() {
+        let miner = Miner::new(4, Intensity::FULL, None);
+        let template = BlockBuilder::on(&Block::genesis()).timestamp(1_760_000_000_000);
+        let difficulty = Difficulty { n_limit: 1000, min_digits: 6, min_prob: 0, ..Difficulty::default() };
+        let race = |seed| miner.mine_seeded("x", template.clone(), difficulty.clone(), 4, seed);
+        let (first, again, other) = (race(177).await.unwrap(), race(177).await.unwrap(), race(178).await.unwrap());
+
+        assert_eq!((first.seed, again.seed), (177, 177));
+        assert_eq!((&first.block.hash, first.worker), (&again.block.hash, again.worker));
+        assert_eq!(first.stats.candidates, again.stats.candidates);
+        assert_eq!(first.workers.len(), 4);
+        let counters = |race: &RaceResult| serde_json::to_value(&race.workers).unwrap();
+        assert_eq!(counters(&first), counters(&again));
+        assert_ne!(first.block.hash, other.block.hash);
+        miner.shutdown();
+    }
+
+    // Espera até o pool ter `busy` threads ocupadas
+    async fn wait_busy(miner: &Miner, busy: usize) {
+        for _ in 0..100 {
+            if miner.stats().busy == busy {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("o pool não chegou a {} threads ocupadas: {:?}", busy, miner.stats());
+    }
+
+    /// Com todas as threads de mineração ocupadas e tarefas na fila, a exportação SQLite (no pool de
+    /// bloqueio do Tokio) não espera por elas.
+    #[tokio::test]
+    async fn saturated_pool_leaves_sqlite_alone() {
+        use crate::testkit::{test_clock, test_config, test_router, test_state, READ_KEY};
+        use axum::body::Body;
+        use axum::http::Request;
+        use blockchain_core::ChainState;
+        use tower::ServiceExt;
+
+        let mut state = test_state(ChainState::new(), &test_config(), test_clock());
+        let miner = Arc::new(Miner::new(2, Intensity::FULL, None));
+        state.miner = miner.clone();
+        let m = miner.clone();
+        let mining = tokio::spawn(async move {
+            m.mine("default", slow_template(), Difficulty::default(), 4, None).await
+        });
+        wait_busy(&miner, 2).await;
+        assert_eq!(miner.stats().queued, 2);
+
+        let started = std::time::Instant::now();
+        let request = Request::get("/chain/export/sqlite").header("x-api-key", READ_KEY).body(Body::empty()).unwrap();
+        let response = test_router(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(bytes.starts_with(b"SQLite format 3"));
+        assert!(started.elapsed() < Duration::from_secs(2), "a exportação esperou a mineração");
+        assert!(!mining.is_finished());
+
+        let m = miner.clone();
+        tokio::task::spawn_blocking(move || m.shutdown()).await.unwrap();
+        assert_eq!(mining.await.unwrap().unwrap_err(), MiningError::Cancelled);
+    }
+
+    /// `shutdown` só volta depois que cada thread terminou a tarefa em mãos e saiu.
+    #[tokio::test]
+    async fn shutdown_joins_every_thread() {
+        let miner = Miner::new(3, Intensity::FULL, None);
+        let finished = Arc::new(AtomicUsize::new(0));
+        for _ in 0..3 {
+            let finished = finished.clone();
+            assert!(miner.submit(Box::new(move || {
+                thread::sleep(Duration::from_millis(300));
+                finished.fetch_add(1, Ordering::SeqCst);
+            })));
+        }
+        wait_busy(&miner, 3).await;
+
+        miner.shutdown();
+        assert_eq!(finished.load(Ordering::SeqCst), 3);
+        assert!(miner.handles.lock().unwrap().is_empty());
+        let stats = miner.stats();
+        assert!(stats.shutting_down && stats.busy == 0 && stats.queued == 0);
+        assert!(!miner.submit(Box::new(|| {})), "a fila fechada recusa tarefas");
+        let result = miner.mine("x", slow_template(), Difficulty::default(), 1, None).await;
+        assert_eq!(result.unwrap_err(), MiningError::Cancelled);
+    }
+
+    async fn body(response: Response) -> serde_json::Value {
+        serde_json::from_slice(&axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+    }
+
+    // Gerador que quebra como um `gen_range` com intervalo vazio: só o worker `healthy` sai com um valor
+    fn broken_generator(healthy: Option<usize>) -> impl Fn(usize, &mut Throttle) -> Option<u64> + Send + Sync {
+        move |worker, _| {
+            use rand::Rng;
+            let end = if Some(worker) == healthy { worker as u64 + 1 } else { worker as u64 };
+            Some(rand::thread_rng().gen_range(worker as u64..end))
+        }
+    }
+
+    /// Os pânicos dos workers viram um 500 estruturado com a causa de cada um, e o pool segue servindo.
+    #[tokio::test]
+    async fn panicking_workers_give_a_structured_500() {
+        let miner = Miner::new(2, Intensity::FULL, None);
+        let rx = miner.spawn_workers(2, broken_generator(None));
+        let error = miner.collect(rx, 2, &AtomicBool::new(false), true).await.unwrap_err();
+        let MiningError::AllWorkersFailed { causes } = &error else { panic!("erro inesperado: {:?}", error) };
+        assert_eq!(causes.len(), 2);
+        assert!(causes.iter().all(|cause| cause.contains("empty range")), "{:?}", causes);
+        assert_eq!(miner.stats().worker_panics, 2);
+
+        let response = error.clone().into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = body(response).await;
+        assert_eq!((&body["kind"], &body["code"]), (&body["code"], &serde_json::json!("all_workers_failed")));
+        assert_eq!(body["causes"], serde_json::json!(causes));
+
+        // As threads sobreviveram: com um worker quebrado e outro são, o são responde
+        let rx = miner.spawn_workers(2, broken_generator(Some(1)));
+        assert_eq!(miner.collect(rx, 2, &AtomicBool::new(false), false).await, Ok(vec![1]));
+        assert_eq!(miner.stats().worker_panics, 3);
+        miner.shutdown();
+    }
+
+    #[tokio::test]
+    async fn timeout_and_infeasible_difficulty_map_to_their_statuses() {
+        let miner = Miner::new(1, Intensity::FULL, Some(Duration::from_millis(200)));
+        let error = miner.mine("x", slow_template(), Difficulty::default(), 1, None).await.unwrap_err();
+        assert_eq!(error, MiningError::TimedOut);
+        assert_eq!(error.into_response().status(), StatusCode::REQUEST_TIMEOUT);
+
+        let infeasible = Difficulty { min_digits: 0, ..Difficulty::default() };
+        let error = miner.mine("x", slow_template(), infeasible, 1, None).await.unwrap_err();
+        assert!(matches!(&error, MiningError::InfeasibleDifficulty { problems } if problems[0].contains("min_digits")));
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body(response).await["kind"], "infeasible_difficulty");
+        miner.shutdown();
+    }
+}
@@ -0,0 +1,62 @@
+// src/miners.rs
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use blockchain_core::signature::parse_public_key;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+
+/// Minerador externo autorizado a enviar blocos assinados.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinerRecord {
+    pub pubkey: String,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct MinerRegistry {
+    miners: BTreeMap<String, MinerRecord>,
+}
+
+impl MinerRegistry {
+    pub fn register(&mut self, miner: MinerRecord) -> MinerRecord {
+        self.miners.insert(miner.pubkey.clone(), miner.clone());
+        miner
+    }
+
+    pub fn is_registered(&self, pubkey: &str) -> bool {
+        self.miners.contains_key(&pubkey.to_lowercase())
+    }
+
+    pub fn list(&self) -> Vec<MinerRecord> {
+        self.miners.values().cloned().collect()
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RegisterMiner {
+    pubkey: String,
+    name: Option<String>,
+}
+
+pub async fn register_miner_handler(
+    State(state): State<AppState>,
+    Json(body): Json<RegisterMiner>,
+) -> Result<Json<MinerRecord>, Response> {
+    let pubkey = body.pubkey.to_lowercase();
+    if parse_public_key(&pubkey).is_none() {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "pubkey must be a hex ed25519 public key").into());
+    }
+    let miner = MinerRecord { pubkey, name: body.name };
+    Ok(Json(state.miners.lock().unwrap().register(miner)))
+}
+
+pub async fn list_miners_handler(State(state): State<AppState>) -> Json<Vec<MinerRecord>> {
+    Json(state.miners.lock().unwrap().list())
+}
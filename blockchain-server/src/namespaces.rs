@@ -0,0 +1,366 @@
+// src/namespaces.rs
+use axum::{
+    body::Body,
+    extract::{Path, Request, State},
+    http::{uri::PathAndQuery, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use blockchain_core::{ChainState, EmissionSchedule, PrimeWidth};
+use log::info;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tower::ServiceExt;
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+use crate::{gc, metrics, orphans, precompute};
+
+pub const DEFAULT_CHAIN: &str = "default";
+const CHAIN_HEADER: &str = "x-chain";
+const NAME_MAX_LEN: usize = 32;
+
+/// Cadeia independente com estado, rotas e tarefas de fundo próprias.
+struct Namespace {
+    state: AppState,
+    router: Router,
+    tasks: Vec<JoinHandle<()>>,
+    created_at: u64,
+}
+
+impl Drop for Namespace {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Cadeias do nó por nome; `default` existe sempre e responde sem prefixo nem cabeçalho.
+pub struct ChainRegistry {
+    max: usize,
+    chains: BTreeMap<String, Namespace>,
+}
+
+impl ChainRegistry {
+    pub fn new(max: usize) -> Self {
+        ChainRegistry { max: max.max(1), chains: BTreeMap::new() }
+    }
+
+    fn insert(&mut self, state: AppState) -> Result<(), ApiError> {
+        if self.chains.contains_key(&state.namespace) {
+            return Err(ApiError::new(StatusCode::CONFLICT, format!("chain {} already exists", state.namespace)));
+        }
+        if self.chains.len() >= self.max {
+            return Err(ApiError::new(StatusCode::CONFLICT, format!("chain limit reached (max {})", self.max)));
+        }
+        let namespace = Namespace {
+            router: crate::app_router(state.clone()),
+            tasks: spawn_background(&state),
+            created_at: crate::sync::now_secs(),
+            state,
+        };
+        self.chains.insert(namespace.state.namespace.clone(), namespace);
+        Ok(())
+    }
+
+    fn router(&self, name: &str) -> Option<Router> {
+        self.chains.get(name).map(|ns| ns.router.clone())
+    }
+
+    /// Estados de todas as cadeias, em ordem de nome.
+    pub fn states(&self) -> Vec<AppState> {
+        self.chains.values().map(|ns| ns.state.clone()).collect()
+    }
+}
+
+// Laços por cadeia; abortados quando a cadeia é removida
+fn spawn_background(state: &AppState) -> Vec<JoinHandle<()>> {
+    let mut tasks = vec![tokio::spawn(orphans::adopt_loop(state.clone()))];
+    if let Some(pool) = state.pool.clone() {
+        tasks.push(tokio::spawn(precompute::precompute_loop(state.clone(), pool)));
+    }
+    if state.config.metrics_snapshot_secs > 0 {
+        let interval = Duration::from_secs(state.config.metrics_snapshot_secs);
+        tasks.push(tokio::spawn(metrics::snapshot_loop(state.clone(), interval)));
+    }
+    if state.config.gc_interval_secs > 0 {
+        let interval = Duration::from_secs(state.config.gc_interval_secs);
+        tasks.push(tokio::spawn(gc::gc_loop(state.clone(), interval)));
+    }
+    tasks
+}
+
+/// Registra a cadeia `default` do nó.
+pub fn register_default(state: &AppState) {
+    let mut chains = state.chains.lock().unwrap();
+    if let Err(e) = chains.insert(state.clone()) {
+        panic!("cadeia default: {}", e);
+    }
+}
+
+fn valid_name(name: &str) -> bool {
+    (1..=NAME_MAX_LEN).contains(&name.len())
+        && name.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+}
+
+// Escolhe a cadeia por `/chains/<nome>/...` (que é removido do caminho) ou pelo cabeçalho X-Chain
+fn select_chain(req: &mut Request) -> Result<String, String> {
+    if let Some(rest) = req.uri().path().strip_prefix("/chains/") {
+        let (name, path) = rest.split_once('/').map_or((rest, "/".to_string()), |(n, p)| (n, format!("/{}", p)));
+        let name = name.to_string();
+        let path_and_query = match req.uri().query() {
+            Some(query) => format!("{}?{}", path, query),
+            None => path,
+        };
+        let path_and_query: PathAndQuery =
+            path_and_query.parse().map_err(|_| format!("invalid path for chain {}", name))?;
+        *req.uri_mut() = Uri::from(path_and_query);
+        return Ok(name);
+    }
+    match req.headers().get(CHAIN_HEADER) {
+        Some(value) => value.to_str().map(str::to_string).map_err(|_| "invalid X-Chain header".to_string()),
+        None => Ok(DEFAULT_CHAIN.to_string()),
+    }
+}
+
+/// Encaminha a requisição para as rotas da cadeia selecionada.
+pub async fn dispatch(State(state): State<AppState>, mut req: Request) -> Response {
+    let name = match select_chain(&mut req) {
+        Ok(name) => name,
+        Err(e) => return ApiError::new(StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let Some(router) = state.chains.lock().unwrap().router(&name) else {
+        return ApiError::new(StatusCode::NOT_FOUND, format!("Unknown chain: {}", name)).into_response();
+    };
+    match router.oneshot(req.map(Body::new)).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InitialDifficulty {
+    n_limit: Option<u64>,
+    min_digits: Option<u32>,
+    min_prob: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct Activation {
+    version: u32,
+    activation_height: u64,
+}
+
+/// Configuração própria da nova cadeia; campos ausentes herdam a do nó.
+/// Todas as cadeias partem do mesmo gênesis, que é fixo no núcleo.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateChain {
+    name: String,
+    difficulty: Option<InitialDifficulty>,
+    rules_activation: Option<Vec<Activation>>,
+    mempool_capacity: Option<usize>,
+    candidate_pool_size: Option<usize>,
+    template_window: Option<u64>,
+    emission: Option<EmissionSchedule>,
+    prime_width: Option<PrimeWidth>,
+}
+
+fn chain_view(name: &str, ns: &Namespace) -> serde_json::Value {
+    let guard = ns.state.chain.lock().unwrap();
+    serde_json::json!({
+        "name": name,
+        "height": guard.height(),
+        "difficulty": guard.difficulty,
+        "mempool_size": ns.state.mempool.lock().unwrap().size(),
+        "data_dir": ns.state.config.data_dir,
+        "created_at": ns.created_at,
+    })
+}
+
+pub async fn create_chain_handler(
+    State(state): State<AppState>,
+    Json(body): Json<CreateChain>,
+) -> Response {
+    if !valid_name(&body.name) {
+        return ApiError::new(StatusCode::BAD_REQUEST, "name must be 1-32 characters of a-z, 0-9 or -").into_response();
+    }
+    let mut config = (*state.config).clone();
+    if let Some(activations) = body.rules_activation {
+        config.rules_activation = activations.iter().map(|a| (a.version, a.activation_height)).collect();
+    }
+    if let Some(capacity) = body.mempool_capacity { config.mempool_capacity = capacity; }
+    if let Some(size) = body.candidate_pool_size { config.candidate_pool_size = size; }
+    if let Some(window) = body.template_window { config.template_window = window; }
+    if let Some(emission) = body.emission { config.emission = emission; }
+    if let Some(width) = body.prime_width { config.prime_width = width; }
+    config.bootstrap_peers.clear();
+
+    let mut chain = ChainState::new();
+    chain.set_epoch_size(config.epoch_size);
+    chain.set_difficulty_algorithm(config.difficulty_algorithm.build().expect("validado na configuração"));
+    for &(version, height) in &config.rules_activation {
+        if let Err(e) = chain.schedule_rules(version, height) {
+            let message = format!("rules_activation {}:{}: {}", version, height, e);
+            return ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, message).into_response();
+        }
+    }
+    if let Err(e) = chain.set_emission(config.emission) {
+        return ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, format!("emission: {}", e)).into_response();
+    }
+    chain.set_prime_width(config.prime_width).expect("cadeia recém-criada está vazia");
+    if let Some(initial) = body.difficulty {
+        if let Some(v) = initial.n_limit {
+            if v == 0 {
+                return ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "difficulty.n_limit must be at least 1")
+                    .into_response();
+            }
+            chain.difficulty.n_limit = v;
+        }
+        if let Some(v) = initial.min_digits {
+            let max_digits = config.prime_width.max_min_digits();
+            if !(1..=max_digits).contains(&v) {
+                let message = format!("difficulty.min_digits must be between 1 and {}", max_digits);
+                return ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, message).into_response();
+            }
+            chain.difficulty.min_digits = v;
+        }
+        if let Some(v) = initial.min_prob { chain.difficulty.min_prob = v; }
+    }
+
+    let name = body.name;
+    let namespace = state.namespace(&name, chain, config);
+    let mut chains = state.chains.lock().unwrap();
+    if let Err(e) = chains.insert(namespace) {
+        return e.into_response();
+    }
+    info!("Cadeia {} criada", name);
+    (StatusCode::CREATED, Json(chain_view(&name, &chains.chains[&name]))).into_response()
+}
+
+pub async fn list_chains_handler(State(state): State<AppState>) -> Json<Vec<serde_json::Value>> {
+    let chains = state.chains.lock().unwrap();
+    Json(chains.chains.iter().map(|(name, ns)| chain_view(name, ns)).collect())
+}
+
+/// Remove a cadeia e para suas tarefas; os arquivos em disco são mantidos.
+pub async fn delete_chain_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Response {
+    if name == DEFAULT_CHAIN {
+        return ApiError::new(StatusCode::BAD_REQUEST, "the default chain cannot be deleted").into_response();
+    }
+    let Some(removed) = state.chains.lock().unwrap().chains.remove(&name) else {
+        return ApiError::new(StatusCode::NOT_FOUND, format!("Unknown chain: {}", name)).into_response();
+    };
+    removed.state.metrics.lock().unwrap().persist();
+    info!("Cadeia {} removida", name);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testkit::{test_clock, test_config, test_router, test_state, ADMIN_KEY, MINE_KEY};
+    use axum::body::Body;
+    use axum::http::{request::Builder, Request, StatusCode};
+    use blockchain_core::testkit::trivial_difficulty;
+    use blockchain_core::ChainState;
+    use std::path::Path;
+    use tower::ServiceExt;
+
+    async fn call(router: &axum::Router, request: Builder, body: Body) -> (StatusCode, serde_json::Value) {
+        let response = router.clone().oneshot(request.body(body).unwrap()).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    fn blocks_mined(path: &Path) -> u64 {
+        let snapshot: serde_json::Value = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        snapshot["blocks_mined"].as_u64().unwrap()
+    }
+
+    #[tokio::test]
+    async fn second_chain_is_isolated() {
+        let dir = std::env::temp_dir().join(format!("namespaces-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = crate::Config { data_dir: Some(dir.clone()), ..test_config() };
+        let mut chain = ChainState::new();
+        chain.difficulty = trivial_difficulty();
+        let state = test_state(chain, &config, test_clock());
+        let router = test_router(state.clone());
+
+        let create = Request::post("/admin/chains").header("x-api-key", ADMIN_KEY);
+        let create = create.header("content-type", "application/json");
+        let body = serde_json::json!({
+            "name": "experiments",
+            "difficulty": { "n_limit": 2000, "min_digits": 5, "min_prob": 0 },
+        });
+        let (status, created) = call(&router, create, Body::from(body.to_string())).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", created);
+        assert_eq!(created["data_dir"].as_str().map(Path::new), Some(dir.join("chains").join("experiments").as_path()));
+
+        let list = |router: axum::Router| async move {
+            let request = Request::get("/admin/chains").header("x-api-key", ADMIN_KEY);
+            let (_, chains) = call(&router, request, Body::empty()).await;
+            let view = |name: &str| chains.as_array().unwrap().iter().find(|c| c["name"] == name).unwrap().clone();
+            (view("default"), view("experiments"))
+        };
+
+        // A mineração na default mexe só na altura e na dificuldade dela
+        for _ in 0..2 {
+            let (status, _) = call(&router, Request::get("/mine").header("x-api-key", MINE_KEY), Body::empty()).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (default, experiments) = list(router.clone()).await;
+        assert_eq!((default["height"].as_u64(), experiments["height"].as_u64()), (Some(3), Some(1)));
+        assert_eq!(experiments["difficulty"], created["difficulty"]);
+        assert_ne!(default["difficulty"], created["difficulty"]);
+
+        // E a da experiments (pelo cabeçalho) não mexe na default
+        let mine = Request::get("/mine").header("x-api-key", MINE_KEY).header("x-chain", "experiments");
+        assert_eq!(call(&router, mine, Body::empty()).await.0, StatusCode::OK);
+        let (default_after, experiments) = list(router.clone()).await;
+        assert_eq!(default_after, default);
+        assert_eq!(experiments["height"], 2);
+        assert_ne!(experiments["difficulty"], created["difficulty"]);
+
+        // O prefixo chega à mesma cadeia, que não tem os blocos da default
+        let chain = Request::get("/chains/experiments/chain").header("x-api-key", ADMIN_KEY);
+        let (_, blocks) = call(&router, chain, Body::empty()).await;
+        let default_tip = state.chain.lock().unwrap().tip().hash.clone();
+        assert_eq!(blocks.as_array().unwrap().len(), 2);
+        assert!(blocks.as_array().unwrap().iter().all(|b| b["hash"] != default_tip.as_str()));
+
+        // Cada cadeia grava as próprias métricas no próprio diretório
+        for ns in state.chains.lock().unwrap().states() {
+            ns.metrics.lock().unwrap().persist();
+        }
+        assert_eq!(blocks_mined(&dir.join("metrics.json")), 2);
+        assert_eq!(blocks_mined(&dir.join("chains").join("experiments").join("metrics.json")), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn impossible_initial_difficulty_is_422() {
+        let router = test_router(test_state(ChainState::new(), &test_config(), test_clock()));
+        for (difficulty, field) in [
+            (serde_json::json!({ "n_limit": 0 }), "n_limit"),
+            (serde_json::json!({ "min_digits": 0 }), "min_digits"),
+            (serde_json::json!({ "min_digits": 20 }), "min_digits"),
+        ] {
+            let create = Request::post("/admin/chains").header("x-api-key", ADMIN_KEY);
+            let create = create.header("content-type", "application/json");
+            let body = serde_json::json!({ "name": "hostile", "difficulty": difficulty });
+            let (status, error) = call(&router, create, Body::from(body.to_string())).await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert!(error["error"].as_str().unwrap().contains(field), "{}", error);
+        }
+    }
+}
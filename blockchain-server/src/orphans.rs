@@ -0,0 +1,211 @@
+// src/orphans.rs
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use blockchain_core::Block;
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::errors::ApiError;
+use crate::invariants::debug_check;
+use crate::state::AppState;
+
+// Órfãos mantidos por cadeia; anúncios além disso são recusados
+const ORPHAN_POOL_MAX: usize = 256;
+
+/// Blocos anunciados cujo pai ainda não chegou, indexados pelo `prev_hash`.
+#[derive(Debug, Default)]
+pub struct OrphanPool {
+    blocks: HashMap<String, Block>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanView {
+    pub index: u64,
+    pub hash: String,
+    pub prev_hash: String,
+}
+
+impl OrphanPool {
+    // Um órfão por pai: o primeiro anunciado fica
+    fn insert(&mut self, block: Block) -> Result<(), &'static str> {
+        if self.blocks.contains_key(&block.prev_hash) {
+            return Err("An orphan with this prev_hash is already pending");
+        }
+        if self.blocks.len() >= ORPHAN_POOL_MAX {
+            return Err("Orphan pool is full");
+        }
+        self.blocks.insert(block.prev_hash.clone(), block);
+        Ok(())
+    }
+
+    /// Remove os órfãos cujo ponto de fork (a altura do pai) está mais de `max_depth` blocos abaixo
+    /// de `tip_index`: um reorg até lá não seria aceito, então o pai nunca vai ligar.
+    pub fn collect(&mut self, tip_index: u64, max_depth: u64) -> Vec<OrphanView> {
+        let stale: Vec<String> = self
+            .blocks
+            .iter()
+            .filter(|(_, b)| tip_index.saturating_sub(b.index.saturating_sub(1)) > max_depth)
+            .map(|(prev_hash, _)| prev_hash.clone())
+            .collect();
+        let mut removed: Vec<OrphanView> = stale
+            .iter()
+            .filter_map(|prev_hash| self.blocks.remove(prev_hash))
+            .map(|b| OrphanView { index: b.index, hash: b.hash, prev_hash: b.prev_hash })
+            .collect();
+        removed.sort_by_key(|o| o.index);
+        removed
+    }
+
+    fn take(&mut self, prev_hash: &str) -> Option<Block> {
+        self.blocks.remove(prev_hash)
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Inconsistências do pool: chave diferente do `prev_hash` do bloco ou pool acima do limite.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems: Vec<String> = self
+            .blocks
+            .iter()
+            .filter(|(key, block)| **key != block.prev_hash)
+            .map(|(key, block)| format!("orphan {} is keyed by {} but extends {}", block.hash, key, block.prev_hash))
+            .collect();
+        if self.blocks.len() > ORPHAN_POOL_MAX {
+            problems.push(format!("{} orphans, limit is {}", self.blocks.len(), ORPHAN_POOL_MAX));
+        }
+        problems
+    }
+
+    fn list(&self) -> Vec<OrphanView> {
+        let mut orphans: Vec<OrphanView> = self
+            .blocks
+            .values()
+            .map(|b| OrphanView { index: b.index, hash: b.hash.clone(), prev_hash: b.prev_hash.clone() })
+            .collect();
+        orphans.sort_by_key(|o| o.index);
+        orphans
+    }
+}
+
+/// Anexa em sequência os órfãos que passaram a ligar na ponta. Inválidos são descartados.
+pub fn adopt(state: &AppState) -> Vec<Block> {
+    let mut adopted = Vec::new();
+    let mut guard = state.chain.lock().unwrap();
+    let mut orphans = state.orphans.lock().unwrap();
+    while let Some(orphan) = orphans.take(&guard.tip().hash) {
+        match guard.append(orphan.clone()) {
+            Ok(()) => adopted.push(orphan),
+            Err(e) => warn!("Órfão {} descartado: {}", orphan.index, e),
+        }
+    }
+    drop(orphans);
+    let mut mempool = state.mempool.lock().unwrap();
+    for block in &adopted {
+        mempool.confirm(&block.transactions);
+    }
+    drop(mempool);
+    drop(guard);
+    if !adopted.is_empty() {
+        debug_check(state, "adopt");
+    }
+    for block in &adopted {
+        info!("Órfão {} anexado à cadeia", block.index);
+        let _ = state.events.send(block.clone());
+    }
+    adopted
+}
+
+/// Adota órfãos a cada bloco anexado, venha ele de mineração, submissão, anúncio ou troca de cadeia.
+pub async fn adopt_loop(state: AppState) {
+    let mut events = state.events.subscribe();
+    loop {
+        match events.recv().await {
+            Ok(_) | Err(RecvError::Lagged(_)) => {
+                adopt(&state);
+            }
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+/// Recebe um bloco anunciado por um peer. Se ligar na ponta é anexado; se o pai for desconhecido,
+/// fica no pool de órfãos até o pai chegar.
+pub async fn announce_handler(State(state): State<AppState>, Json(block): Json<Block>) -> Response {
+    let mut guard = state.chain.lock().unwrap();
+    let tip = guard.tip().clone();
+    if block.prev_hash == tip.hash {
+        if let Err(e) = guard.append(block.clone()) {
+            return ApiError::json(StatusCode::UNPROCESSABLE_ENTITY, serde_json::json!({
+                "error": e.to_string(),
+                "invariant": e.invariant(),
+            }))
+                .into_response();
+        }
+        state.mempool.lock().unwrap().confirm(&block.transactions);
+        drop(guard);
+        debug_check(&state, "announce");
+        info!("Bloco {} anunciado anexado", block.index);
+        let _ = state.events.send(block.clone());
+        let adopted = adopt(&state);
+        let height = state.chain.lock().unwrap().height();
+        return Json(serde_json::json!({
+            "status": "appended",
+            "index": block.index,
+            "hash": block.hash,
+            "height": height,
+            "adopted": adopted.iter().map(|b| b.index).collect::<Vec<_>>(),
+        }))
+        .into_response();
+    }
+    if guard.blocks().iter().any(|b| b.hash == block.prev_hash) {
+        return ApiError::json(StatusCode::CONFLICT, serde_json::json!({
+            "error": "Block does not extend the tip",
+            "status": "stale",
+            "tip_index": tip.index,
+        }))
+            .into_response();
+    }
+
+    // Sem o pai só dá para conferir o próprio bloco. A cadeia segue travada para o pai não chegar
+    // entre a checagem e a inserção no pool.
+    if let Err(e) = block.verify_contents() {
+        return ApiError::json(StatusCode::UNPROCESSABLE_ENTITY, serde_json::json!({
+            "error": e.to_string(),
+            "invariant": e.invariant(),
+        }))
+            .into_response();
+    }
+    let mut orphans = state.orphans.lock().unwrap();
+    if let Err(e) = orphans.insert(block.clone()) {
+        return ApiError::new(StatusCode::CONFLICT, e).into_response();
+    }
+    drop(orphans);
+    drop(guard);
+    debug_check(&state, "announce");
+    let pool_size = state.orphans.lock().unwrap().len();
+    info!("Bloco {} guardado como órfão (pai {} desconhecido)", block.index, block.prev_hash);
+    (StatusCode::ACCEPTED, Json(serde_json::json!({
+        "status": "orphan",
+        "index": block.index,
+        "hash": block.hash,
+        "pool_size": pool_size,
+    })))
+        .into_response()
+}
+
+pub async fn orphan_pool_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let orphans = state.orphans.lock().unwrap();
+    Json(serde_json::json!({
+        "size": orphans.len(),
+        "capacity": ORPHAN_POOL_MAX,
+        "orphans": orphans.list(),
+    }))
+}
@@ -0,0 +1,234 @@
+// src/peers.rs
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use blockchain_core::Block;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::errors::ApiError;
+use crate::handshake::{fetch_handshake, mismatches, Handshake, Mismatch};
+use crate::state::AppState;
+use crate::store;
+use crate::sync::now_secs;
+
+// Cadeias inválidas toleradas antes de remover o peer do registro
+const MAX_INVALID_CHAINS: u32 = 3;
+// Idade máxima do último handshake antes de repeti-lo na checagem de saúde da sincronização
+const HANDSHAKE_REFRESH_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Peer {
+    pub url: String,
+    pub trust: i32,
+    pub healthy: bool,
+    pub invalid_chains: u32,
+    // Identidade declarada no último handshake
+    pub node_pubkey: String,
+    pub software_version: String,
+    pub handshake_at: u64,
+    // Divergências do último handshake; enquanto houver, o peer fica fora da sincronização
+    pub incompatible: Vec<Mismatch>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Penalty {
+    Demoted,
+    Removed,
+}
+
+impl Peer {
+    fn record_handshake(&mut self, handshake: &Handshake, mismatches: Vec<Mismatch>, now: u64) {
+        self.node_pubkey = handshake.node_pubkey.clone();
+        self.software_version = handshake.software_version.clone();
+        self.handshake_at = now;
+        self.healthy = mismatches.is_empty();
+        self.incompatible = mismatches;
+    }
+}
+
+fn normalize(url: &str) -> String {
+    url.trim_end_matches('/').to_string()
+}
+
+#[derive(Debug, Default)]
+pub struct PeerRegistry {
+    peers: BTreeMap<String, Peer>,
+    // peers.json da cadeia; None em memória
+    path: Option<PathBuf>,
+}
+
+impl PeerRegistry {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        PeerRegistry { peers: store::load(path.as_deref(), "Registro de peers"), path }
+    }
+
+    fn persist(&self) {
+        store::save(self.path.as_deref(), &self.peers, "o registro de peers");
+    }
+
+    /// Registra (ou atualiza) um peer cujo handshake foi compatível.
+    pub fn add(&mut self, url: &str, handshake: &Handshake, now: u64) -> Peer {
+        let url = normalize(url);
+        let peer = self.peers.entry(url.clone()).or_insert(Peer {
+            url,
+            trust: 0,
+            healthy: true,
+            invalid_chains: 0,
+            node_pubkey: String::new(),
+            software_version: String::new(),
+            handshake_at: 0,
+            incompatible: Vec::new(),
+        });
+        peer.record_handshake(handshake, Vec::new(), now);
+        let peer = peer.clone();
+        self.persist();
+        peer
+    }
+
+    /// Esquece o peer; `false` se ele não estava registrado. Só os testes particionam a rede.
+    #[cfg(any(test, feature = "testkit"))]
+    pub fn remove(&mut self, url: &str) -> bool {
+        let removed = self.peers.remove(&normalize(url)).is_some();
+        if removed {
+            self.persist();
+        }
+        removed
+    }
+
+    pub fn list(&self) -> Vec<Peer> {
+        self.peers.values().cloned().collect()
+    }
+
+    pub fn urls(&self) -> Vec<String> {
+        self.peers.keys().cloned().collect()
+    }
+
+    /// Vence o handshake de todos os peers, para o próximo resolve refazê-lo sem esperar o intervalo.
+    #[cfg(any(test, feature = "testkit"))]
+    pub fn expire_handshakes(&mut self) {
+        for peer in self.peers.values_mut() {
+            peer.handshake_at = 0;
+        }
+    }
+
+    pub fn handshake_due(&self, url: &str, now: u64) -> bool {
+        self.peers.get(url).is_some_and(|p| now.saturating_sub(p.handshake_at) >= HANDSHAKE_REFRESH_SECS)
+    }
+
+    /// Guarda o resultado de um novo handshake; um peer incompatível deixa de ser saudável.
+    pub fn record_handshake(&mut self, url: &str, handshake: &Handshake, mismatches: Vec<Mismatch>, now: u64) {
+        if let Some(peer) = self.peers.get_mut(url) {
+            peer.record_handshake(handshake, mismatches, now);
+            self.persist();
+        }
+    }
+
+    pub fn incompatibility(&self, url: &str) -> Option<Vec<Mismatch>> {
+        self.peers.get(url).filter(|p| !p.incompatible.is_empty()).map(|p| p.incompatible.clone())
+    }
+
+    pub fn mark_healthy(&mut self, url: &str, healthy: bool) {
+        if let Some(peer) = self.peers.get_mut(url).filter(|p| p.healthy != healthy) {
+            peer.healthy = healthy;
+            self.persist();
+        }
+    }
+
+    /// Penaliza um peer que serviu uma cadeia inválida; reincidentes são removidos.
+    pub fn record_invalid_chain(&mut self, url: &str) -> Penalty {
+        let Some(peer) = self.peers.get_mut(url) else { return Penalty::Removed };
+        peer.invalid_chains += 1;
+        peer.trust -= 10;
+        peer.healthy = false;
+        let penalty = if peer.invalid_chains >= MAX_INVALID_CHAINS {
+            self.peers.remove(url);
+            Penalty::Removed
+        } else {
+            Penalty::Demoted
+        };
+        self.persist();
+        penalty
+    }
+
+    pub fn record_valid_chain(&mut self, url: &str) {
+        if let Some(peer) = self.peers.get_mut(url) {
+            peer.trust += 1;
+            peer.healthy = true;
+            self.persist();
+        }
+    }
+}
+
+pub async fn fetch_chain(client: &reqwest::Client, url: &str, api_key: &str) -> Result<Vec<Block>, String> {
+    let response = client
+        .get(format!("{}/chain", url))
+        .header("x-api-key", api_key)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("peer answered {}", response.status()));
+    }
+    response.json().await.map_err(|e| e.to_string())
+}
+
+#[derive(Deserialize)]
+pub struct AddPeer {
+    url: String,
+}
+
+#[derive(Debug)]
+pub enum Registration {
+    Registered(Peer),
+    Unreachable(String),
+    Incompatible(Vec<Mismatch>),
+}
+
+/// Faz o handshake com `url` e só registra o peer se cadeia, gênesis e regras forem compatíveis.
+pub async fn register(state: &AppState, url: &str) -> Registration {
+    let url = normalize(url);
+    let handshake = match fetch_handshake(&state.http, &url).await {
+        Ok(handshake) => handshake,
+        Err(error) => return Registration::Unreachable(error),
+    };
+    let found = mismatches(state, &handshake);
+    if !found.is_empty() {
+        return Registration::Incompatible(found);
+    }
+    Registration::Registered(state.peers.lock().unwrap().add(&url, &handshake, now_secs()))
+}
+
+pub async fn add_peer_handler(
+    State(state): State<AppState>,
+    Json(body): Json<AddPeer>,
+) -> Result<Json<Peer>, Response> {
+    if !(body.url.starts_with("http://") || body.url.starts_with("https://")) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Peer URL must be http(s)").into());
+    }
+    match register(&state, &body.url).await {
+        Registration::Registered(peer) => Ok(Json(peer)),
+        Registration::Unreachable(error) => {
+            Err(ApiError::new(StatusCode::BAD_GATEWAY, format!("Peer handshake failed: {}", error)).into_response())
+        }
+        Registration::Incompatible(found) => {
+            let fields: Vec<&str> = found.iter().map(|m| m.field.as_str()).collect();
+            let body = serde_json::json!({
+                "error": format!("Peer is incompatible: {} mismatch", fields.join(", ")),
+                "mismatches": found,
+            });
+            Err(ApiError::json(StatusCode::CONFLICT, body).into_response())
+        }
+    }
+}
+
+pub async fn list_peers_handler(
+    State(state): State<AppState>,
+) -> Json<Vec<Peer>> {
+    Json(state.peers.lock().unwrap().list())
+}
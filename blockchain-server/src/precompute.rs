@@ -0,0 +1,70 @@
+// src/precompute.rs
+use blockchain_core::CandidatePool;
+use log::info;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task;
+
+use crate::state::AppState;
+
+// Tuplas geradas por lote; pequeno para devolver a thread com frequência
+const BATCH_ATTEMPTS: usize = 256;
+const BATCH_PAUSE: Duration = Duration::from_millis(5);
+const FULL_PAUSE: Duration = Duration::from_millis(250);
+// Fora do horário de MINING_HOURS
+const SCHEDULE_PAUSE: Duration = Duration::from_secs(30);
+
+/// Tarefa de baixa prioridade que mantém o pool de candidatos cheio entre minerações,
+/// só no horário configurado e na intensidade de mineração do nó.
+pub async fn precompute_loop(state: AppState, pool: Arc<CandidatePool>) {
+    info!("Pré-computação de candidatos ativada (capacidade {})", pool.stats().capacity);
+    loop {
+        if !state.config.mining_schedule.is_open(state.miner.clock().now()) {
+            tokio::time::sleep(SCHEDULE_PAUSE).await;
+            continue;
+        }
+        if pool.is_full() {
+            tokio::time::sleep(FULL_PAUSE).await;
+            continue;
+        }
+        let difficulty = state.chain.lock().unwrap().difficulty.clone();
+        pool.invalidate(difficulty.generation);
+
+        let worker_pool = pool.clone();
+        let mut throttle = state.miner.throttle();
+        let _ = task::spawn_blocking(move || {
+            worker_pool.fill(&difficulty, BATCH_ATTEMPTS);
+            throttle.pace();
+        })
+        .await;
+        tokio::time::sleep(BATCH_PAUSE).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{test_clock, test_config, test_state};
+    use blockchain_core::testkit::trivial_difficulty;
+    use blockchain_core::{ChainState, MiningSchedule};
+
+    // Enche o pool por 300 ms com o relógio do nó às 22h UTC (início de `test_clock`)
+    async fn precompute_for_a_while(hours: &str) -> usize {
+        let config = crate::Config { mining_schedule: MiningSchedule::parse(hours).unwrap(), ..test_config() };
+        let mut chain = ChainState::new();
+        chain.difficulty = trivial_difficulty();
+        let state = test_state(chain, &config, test_clock());
+        let pool = Arc::new(CandidatePool::new(8));
+        let task = tokio::spawn(precompute_loop(state.clone(), pool.clone()));
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        task.abort();
+        state.miner.shutdown();
+        pool.len()
+    }
+
+    #[tokio::test]
+    async fn schedule_gate_blocks_precompute_outside_the_window() {
+        assert_eq!(precompute_for_a_while("9-17").await, 0);
+        assert!(precompute_for_a_while("21-23").await > 0);
+    }
+}
@@ -0,0 +1,820 @@
+// src/prime.rs
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use blockchain_core::math::{
+    aks_cancellable, euclid_gcd, euler_product, euler_totient, factorize, fermat_test, is_carmichael, is_prime_gap,
+    is_sexy_prime, lehmer_gcd, mobius, next_prime, pollard_rho_iterations, prime_pi_cancellable, primitive_root,
+    quadratic_residues, ramanujan_sum, sieve, sieve_cancellable, smooth_numbers, sqrt_continued_fraction, trial_factor,
+    wilson_check, wilson_quotient_mod, zeta_euler_product, EULER_GAMMA,
+};
+use blockchain_core::{bpsw, miller_rabin_deterministic, Block, ChainState};
+use num::Integer;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::MutexGuard;
+
+use crate::deadline::Deadline;
+use crate::errors::ApiError;
+use crate::history::{self, AtHeight, BeyondTip};
+use crate::state::AppState;
+
+// Limite para não calcular fatoriais grandes demais
+const WILSON_MAX_N: u64 = 10_000;
+// Primos pequenos em /prime/wilson-quotient; o fatorial é reduzido módulo p²
+const WILSON_QUOTIENT_MAX_P: u64 = 1000;
+// Limite do crivo usado em /prime/euler-product
+const EULER_MAX_N: u64 = 10_000_000;
+// A checagem de Carmichael fatora n por divisão por tentativa
+const FERMAT_MAX_N: u64 = 1_000_000_000_000;
+// O AKS cresce rápido com n; acima disso nem o prazo da requisição costuma bastar
+const AKS_MAX_N: u64 = 1_000_000;
+// Primos do crivo usados como referência em /prime/riemann-zeta
+const ZETA_SIEVE_N: u64 = 1_000_000;
+// Cada primo minerado testa até d / 2 ímpares entre p e p + d
+const POLIGNAC_MAX_D: u64 = 1000;
+// π(x) usa O(√x) de memória e O(x^(3/4)) de tempo; acima disso nem o prazo costuma bastar
+const PRIME_PI_MAX_X: u64 = 100_000_000_000_000;
+// Com q até i64::MAX a soma cabe em i64 mesmo negativa
+const RAMANUJAN_MAX_Q: u64 = i64::MAX as u64;
+// Mesmo teto de /prime/fermat, que também fatora n
+const TOTIENT_MAX_N: u64 = 1_000_000_000_000;
+// Mesmo teto de /prime/totient, com a mesma fatoração
+const SMOOTH_MAX_N: u64 = TOTIENT_MAX_N;
+const SMOOTH_DEFAULT_BOUND: u64 = 1000;
+// Tamanho mínimo de módulo Diffie-Hellman recomendado hoje; p precisa ser ao menos 2^1023
+const DH_MIN_BITS: u32 = 1024;
+// Termos do período de √p devolvidos por /prime/continued-fraction; o período pode chegar perto de 2√p
+const CF_MAX_PERIOD: usize = 100;
+// Abaixo disso /prime/quadratic-residues lista todos os resíduos; acima, só os menores
+const QR_FULL_BELOW: u64 = 1000;
+const QR_SAMPLE: usize = 20;
+// Números aceitos por requisição em /prime/batch-verify
+const BATCH_VERIFY_MAX: usize = 1000;
+// Maior primo dos termos lisos de /prime/abc-triple-search; com 13 são 1,4 milhão de candidatos por primo
+const ABC_SMOOTH_BOUNDS: [u64; 6] = [2, 3, 5, 7, 11, 13];
+const ABC_DEFAULT_SMOOTH: u64 = 7;
+const ABC_DEFAULT_LIMIT: usize = 100;
+const ABC_MAX_LIMIT: usize = 1000;
+
+pub async fn wilson_handler(
+    Path(n): Path<u64>,
+) -> Result<Json<serde_json::Value>, Response> {
+    if n > WILSON_MAX_N {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("n must be at most {}", WILSON_MAX_N)).into());
+    }
+    let wilson = wilson_check(n);
+    let miller_rabin = miller_rabin_deterministic(n);
+    Ok(Json(serde_json::json!({
+        "n": n,
+        "wilson_check": wilson,
+        "miller_rabin_check": miller_rabin,
+        "agree": wilson == miller_rabin,
+    })))
+}
+
+#[derive(Serialize)]
+pub struct WilsonQuotient {
+    pub p: u64,
+    pub quotient_mod_p: u64,
+    // W(p) ≡ 0 (mod p); os únicos conhecidos são 5, 13 e 563
+    pub is_wilson_prime: bool,
+}
+
+/// Quociente de Wilson `((p-1)! + 1) / p` módulo o primo `p`, inteiro justamente por `p` ser primo.
+pub async fn wilson_quotient_handler(Path(p): Path<u64>) -> Result<Json<WilsonQuotient>, Response> {
+    if p > WILSON_QUOTIENT_MAX_P {
+        let message = format!("p must be at most {}", WILSON_QUOTIENT_MAX_P);
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, message).into());
+    }
+    if !miller_rabin_deterministic(p) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("p must be prime, got {}", p)).into());
+    }
+    let quotient_mod_p = wilson_quotient_mod(p);
+    Ok(Json(WilsonQuotient { p, quotient_mod_p, is_wilson_prime: quotient_mod_p == 0 }))
+}
+
+/// Compara a estimativa de π(n) obtida do produto de Euler (via Mertens: ln n ≈ produto / e^γ)
+/// com `n / ln n` e com a contagem exata do crivo.
+pub async fn euler_product_handler(
+    deadline: Deadline,
+    Path(n): Path<u64>,
+) -> Result<Json<serde_json::Value>, Response> {
+    if !(2..=EULER_MAX_N).contains(&n) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("n must be between 2 and {}", EULER_MAX_N)).into());
+    }
+    let primes = deadline
+        .run(move |token| sieve_cancellable(n, token))
+        .await
+        .map_err(IntoResponse::into_response)?;
+    let product = euler_product(&primes);
+    let actual = primes.len() as f64;
+    let estimate = n as f64 * EULER_GAMMA.exp() / product;
+    let n_over_ln_n = n as f64 / (n as f64).ln();
+    Ok(Json(serde_json::json!({
+        "n": n,
+        "euler_product": product,
+        "prime_pi_estimate": estimate,
+        "n_over_ln_n": n_over_ln_n,
+        "actual_pi_n": primes.len(),
+        "relative_error": {
+            "prime_pi_estimate": (estimate - actual) / actual,
+            "n_over_ln_n": (n_over_ln_n - actual) / actual,
+        },
+    })))
+}
+
+/// Critério de Fermat nas bases 2, 3 e 5, com Miller-Rabin para separar primos de pseudoprimos.
+pub async fn fermat_handler(
+    Path(n): Path<u64>,
+) -> Result<Json<serde_json::Value>, Response> {
+    if n > FERMAT_MAX_N {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("n must be at most {}", FERMAT_MAX_N)).into());
+    }
+    let bases = [2, 3, 5].map(|base| (base.to_string(), fermat_test(n, base)));
+    let is_prime = miller_rabin_deterministic(n);
+    Ok(Json(serde_json::json!({
+        "n": n,
+        "fermat_bases": serde_json::Map::from_iter(bases.iter().map(|(b, ok)| (b.clone(), (*ok).into()))),
+        // Composto que engana as três bases
+        "fermat_pseudoprime": !is_prime && bases.iter().all(|(_, ok)| *ok),
+        "is_carmichael": is_carmichael(n),
+        "is_prime": is_prime,
+    })))
+}
+
+/// Soma de Ramanujan c_q(n), pela fórmula fechada com φ e μ sobre a fatoração de q.
+pub async fn ramanujan_sum_handler(
+    Path((q, n)): Path<(u64, u64)>,
+) -> Result<Json<serde_json::Value>, Response> {
+    if !(1..=RAMANUJAN_MAX_Q).contains(&q) {
+        let message = format!("q must be between 1 and {}", RAMANUJAN_MAX_Q);
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, message).into());
+    }
+    let m = q / n.gcd(&q);
+    Ok(Json(serde_json::json!({
+        "q": q,
+        "n": n,
+        "sum": ramanujan_sum(q, n) as i64,
+        "totient_q": euler_totient(q),
+        "mobius_q_over_gcd": mobius(m),
+    })))
+}
+
+/// Confere se `p` serve de módulo Diffie-Hellman: primo, seguro (`(p-1)/2` também primo) e com pelo
+/// menos `DH_MIN_BITS` bits. Os primos minerados cabem em u64, então nunca passam da terceira condição:
+/// com 64 bits o logaritmo discreto sai em ~2^32 passos de baby-step giant-step, mesmo com p seguro.
+pub async fn dh_safe_handler(Path(p): Path<u64>) -> Json<serde_json::Value> {
+    let bits = u64::BITS - p.leading_zeros();
+    let is_prime = bpsw(p);
+    let safe_prime = is_prime && p > 2 && bpsw((p - 1) / 2);
+    let mut reasons = Vec::new();
+    if !is_prime {
+        reasons.push(format!("{} is not prime", p));
+    } else if !safe_prime {
+        reasons.push(format!("(p-1)/2 = {} is not prime, so p is not a safe prime", (p - 1) / 2));
+    }
+    if bits < DH_MIN_BITS {
+        reasons.push(format!(
+            "p has {} bits; at least {} are needed (mined primes fit in 64 bits, far too small for DH)",
+            bits, DH_MIN_BITS
+        ));
+    }
+    Json(serde_json::json!({
+        "p": p,
+        "bits": bits,
+        "is_prime": is_prime,
+        "safe_prime": safe_prime,
+        "is_safe": reasons.is_empty(),
+        "reasons": reasons,
+    }))
+}
+
+/// Menor raiz primitiva módulo o primo `p`, base de parâmetros Diffie-Hellman a partir de primos minerados.
+pub async fn primitive_root_handler(Path(p): Path<u64>) -> Result<Json<serde_json::Value>, Response> {
+    if !miller_rabin_deterministic(p) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("p must be prime, got {}", p)).into());
+    }
+    let (root, checked) = primitive_root(p);
+    Ok(Json(serde_json::json!({ "p": p, "primitive_root": root, "checked": checked })))
+}
+
+/// Resíduos quadráticos não nulos módulo o primo `p`, `{x² mod p : 1 ≤ x ≤ (p-1)/2}`, em ordem crescente:
+/// todos para `p < 1000`, senão a contagem `(p-1)/2` e os 20 menores.
+pub async fn quadratic_residues_handler(Path(p): Path<u64>) -> Result<Json<serde_json::Value>, Response> {
+    if !miller_rabin_deterministic(p) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("p must be prime, got {}", p)).into());
+    }
+    let count = if p == 2 { 1 } else { (p - 1) / 2 };
+    let listed = if p < QR_FULL_BELOW { count as usize } else { QR_SAMPLE };
+    Ok(Json(serde_json::json!({
+        "p": p,
+        "count": count,
+        "residues": quadratic_residues(p, listed),
+        "truncated": p >= QR_FULL_BELOW,
+    })))
+}
+
+#[derive(Serialize)]
+pub struct LehmerGcd {
+    pub a: u128,
+    pub b: u128,
+    pub result: u128,
+    pub algorithm: &'static str,
+    // O mesmo MDC por Euclides, uma divisão por passo
+    pub euclidean_check: u128,
+}
+
+/// MDC de `a` e `b` em u128 pelo algoritmo de Lehmer, conferido contra Euclides.
+pub async fn lehmer_gcd_handler(Path((a, b)): Path<(u128, u128)>) -> Json<LehmerGcd> {
+    Json(LehmerGcd { a, b, result: lehmer_gcd(a, b), algorithm: "lehmer", euclidean_check: euclid_gcd(a, b) })
+}
+
+/// φ(n) = n ∏ (1 - 1/p) sobre os primos da fatoração de n.
+pub async fn totient_handler(Path(n): Path<u64>) -> Result<Json<serde_json::Value>, Response> {
+    if !(1..=TOTIENT_MAX_N).contains(&n) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("n must be between 1 and {}", TOTIENT_MAX_N)).into());
+    }
+    Ok(Json(serde_json::json!({
+        "n": n,
+        "totient": euler_totient(n),
+        "factors": factorize(n),
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct SmoothQuery {
+    bound: Option<u64>,
+}
+
+/// `n` é B-liso se nenhum fator primo passa de `bound` (padrão 1000), pela fatoração de /prime/totient.
+/// 1 não tem fatores primos: é liso para qualquer `bound` e `largest_factor` sai `null`.
+pub async fn smooth_check_handler(
+    Path(n): Path<u64>,
+    Query(query): Query<SmoothQuery>,
+) -> Result<Json<serde_json::Value>, Response> {
+    if !(1..=SMOOTH_MAX_N).contains(&n) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("n must be between 1 and {}", SMOOTH_MAX_N)).into());
+    }
+    let bound = query.bound.unwrap_or(SMOOTH_DEFAULT_BOUND);
+    // Os fatores saem em ordem crescente
+    let largest_factor = factorize(n).last().map(|&(p, _)| p);
+    Ok(Json(serde_json::json!({
+        "n": n,
+        "is_smooth": largest_factor.is_none_or(|p| p <= bound),
+        "largest_factor": largest_factor,
+        "smoothness_bound": bound,
+    })))
+}
+
+/// Um fator não trivial de `n` pelo rho de Pollard com ciclo de Floyd; `null` se `n` é primo.
+pub async fn pollard_rho_handler(Path(n): Path<u64>) -> Result<Json<Option<serde_json::Value>>, Response> {
+    if n < 2 {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("n must be at least 2, got {}", n)).into());
+    }
+    Ok(Json(pollard_rho_iterations(n).map(|(factor, iterations)| {
+        serde_json::json!({ "n": n, "factor": factor, "cofactor": n / factor, "iterations": iterations })
+    })))
+}
+
+/// Fração contínua periódica de √p, `[a0, [a1, ..., ak]]`, com o período limitado a 100 termos. `period`
+/// é o comprimento do período, ou `null` quando ele passa do limite e `cf` traz só o começo.
+pub async fn continued_fraction_handler(Path(p): Path<u64>) -> Result<Json<serde_json::Value>, Response> {
+    if !miller_rabin_deterministic(p) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("p must be prime, got {}", p)).into());
+    }
+    let (a0, period, complete) = sqrt_continued_fraction(p, CF_MAX_PERIOD);
+    Ok(Json(serde_json::json!({
+        "p": p,
+        "cf": [serde_json::json!(a0), serde_json::json!(period)],
+        "period": complete.then_some(period.len()),
+        "truncated": !complete,
+    })))
+}
+
+// Valor de query como u64; ausente ou fora da largura suportada vira 400
+fn parse_u64_param(name: &str, value: Option<&str>) -> Result<u64, String> {
+    let value = value.ok_or_else(|| format!("{} is required", name))?;
+    value.trim().parse().map_err(|_| format!("{} must be an unsigned 64-bit integer, got {:?}", name, value))
+}
+
+#[derive(Deserialize)]
+pub struct NextPrimeQuery {
+    after: Option<String>,
+}
+
+/// Menor primo estritamente maior que `after`, procurado até `PRIME_SEARCH_SPAN` adiante.
+pub async fn next_prime_handler(
+    State(state): State<AppState>,
+    Query(query): Query<NextPrimeQuery>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let after = parse_u64_param("after", query.after.as_deref())
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+    let span = state.config.prime_search_span;
+    let Some(prime) = next_prime(after, span) else {
+        return Err(ApiError::json(StatusCode::UNPROCESSABLE_ENTITY, serde_json::json!({
+            "error": "No prime found within the search span",
+            "after": after,
+            "span": span,
+            "searched_to": after.saturating_add(span),
+        }))
+            .into_response());
+    };
+    Ok(Json(serde_json::json!({ "after": after, "prime": prime, "gap": prime - after })))
+}
+
+#[derive(Deserialize)]
+pub struct CheckPrimeQuery {
+    n: Option<String>,
+}
+
+/// Primalidade pelo BPSW e, para compostos, os fatores pequenos que a divisão por tentativa acha
+/// dentro de `PRIME_CHECK_BUDGET` divisões.
+pub async fn check_prime_handler(
+    State(state): State<AppState>,
+    Query(query): Query<CheckPrimeQuery>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let n = parse_u64_param("n", query.n.as_deref())
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+    let is_prime = bpsw(n);
+    if is_prime {
+        return Ok(Json(serde_json::json!({ "n": n, "is_prime": true })));
+    }
+    let budget = state.config.prime_check_budget;
+    let trial = trial_factor(n, budget);
+    Ok(Json(serde_json::json!({
+        "n": n,
+        "is_prime": false,
+        "factors": trial.factors,
+        "cofactor": trial.cofactor,
+        "complete": trial.complete,
+        "divisions": trial.divisions,
+        "budget": budget,
+        "budget_exhausted": !trial.complete,
+    })))
+}
+
+/// π(x) para o primo da ponta (ou do bloco em `at_height`), comparado com a estimativa `x / ln x` do
+/// teorema dos números primos.
+pub async fn prime_counting_handler(
+    State(state): State<AppState>,
+    deadline: Deadline,
+    Query(at): Query<AtHeight>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let prime = {
+        let guard = state.chain.lock().unwrap();
+        let index = history::resolve(&guard, at.at_height).map_err(IntoResponse::into_response)?;
+        guard.blocks()[index as usize].prime
+    };
+    let Some(x) = prime.as_u64().filter(|&x| x <= PRIME_PI_MAX_X) else {
+        let message = format!("tip prime {} is above the π(x) limit of {}", prime, PRIME_PI_MAX_X);
+        return Err(ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, message).into());
+    };
+    let pi_x = deadline
+        .run(move |token| prime_pi_cancellable(x, token))
+        .await
+        .map_err(IntoResponse::into_response)?;
+    let pnt_estimate = x as f64 / (x as f64).ln();
+    Ok(Json(serde_json::json!({
+        "x": x,
+        "pi_x": pi_x,
+        "pnt_estimate": pnt_estimate,
+        "relative_error": (pnt_estimate - pi_x as f64) / pi_x as f64,
+    })))
+}
+
+/// AKS, o terceiro teste determinístico ao lado de Miller-Rabin e BPSW; `steps` conta as
+/// multiplicações de polinômios módulo `(X^r - 1, n)`.
+pub async fn aks_handler(
+    deadline: Deadline,
+    Path(n): Path<u64>,
+) -> Result<Json<serde_json::Value>, Response> {
+    if n > AKS_MAX_N {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("n must be at most {}", AKS_MAX_N)).into());
+    }
+    let aks = deadline
+        .run(move |token| aks_cancellable(n, token))
+        .await
+        .map_err(IntoResponse::into_response)?;
+    Ok(Json(serde_json::json!({
+        "n": n,
+        "is_prime": aks.is_prime,
+        "test": "aks",
+        "steps": aks.steps,
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct BatchVerify {
+    numbers: Vec<u64>,
+}
+
+#[derive(Serialize)]
+pub struct Primality {
+    n: u64,
+    is_prime: bool,
+}
+
+/// BPSW em cada número, em paralelo; a ordem da resposta é a da requisição.
+pub async fn batch_verify_handler(
+    Json(body): Json<BatchVerify>,
+) -> Result<Json<Vec<Primality>>, Response> {
+    if body.numbers.len() > BATCH_VERIFY_MAX {
+        let message = format!("at most {} numbers per request, got {}", BATCH_VERIFY_MAX, body.numbers.len());
+        return Err(ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, message).into());
+    }
+    let results = tokio::task::spawn_blocking(move || {
+        body.numbers.par_iter().map(|&n| Primality { n, is_prime: bpsw(n) }).collect()
+    })
+    .await
+    .expect("Falha na verificação em lote");
+    Ok(Json(results))
+}
+
+// Primo e índice de cada bloco; as análises deste módulo são em u64, e primos acima dela (cadeias u128)
+// ficam de fora
+fn mined_u64(blocks: &[Block]) -> impl DoubleEndedIterator<Item = (u64, u64)> + '_ {
+    blocks.iter().filter_map(|b| Some((b.prime.as_u64()?, b.index)))
+}
+
+// Primos minerados até o bloco `index` (`?at_height=`), sem o gênesis, e o hash desse bloco para conferir
+// depois que a cadeia é a mesma
+struct MinedPrefix {
+    index: u64,
+    hash: String,
+    primes: Vec<(u64, u64)>,
+}
+
+fn mined_until(chain: &ChainState, at_height: Option<u64>) -> Result<MinedPrefix, BeyondTip> {
+    let index = history::resolve(chain, at_height)?;
+    let blocks = &chain.blocks()[..=index as usize];
+    Ok(MinedPrefix { index, hash: blocks[index as usize].hash.clone(), primes: mined_u64(&blocks[1..]).collect() })
+}
+
+// Trava a cadeia de novo para os blocos de `q` depois da busca; 409 se ela foi trocada no meio
+fn relock<'a>(state: &'a AppState, index: u64, hash: &str) -> Result<MutexGuard<'a, ChainState>, ApiError> {
+    let guard = state.chain.lock().unwrap();
+    if guard.blocks().get(index as usize).is_none_or(|b| b.hash != hash) {
+        return Err(ApiError::new(StatusCode::CONFLICT, "Chain was replaced during the search; try again"));
+    }
+    Ok(guard)
+}
+
+// Bloco que minerou `q` até o bloco `index`, pelo índice primo → bloco das estruturas derivadas: ele
+// guarda o primeiro bloco de cada primo, então basta o limite
+fn mined_block(chain: &ChainState, q: u64, index: u64) -> Option<u64> {
+    chain.derived().block_of_prime(q as u128).filter(|&block| block <= index)
+}
+
+#[derive(Serialize)]
+pub struct SexyPair {
+    p: u64,
+    p_plus_6: u64,
+    p_block: u64,
+    p6_block: Option<u64>,
+}
+
+/// Primos minerados `p` com `p + 6` também primo, indicando o bloco de `p + 6` se ele foi minerado.
+pub async fn sexy_pairs_handler(
+    State(state): State<AppState>,
+    Query(at): Query<AtHeight>,
+) -> Result<Json<Vec<SexyPair>>, Response> {
+    let guard = state.chain.lock().unwrap();
+    let MinedPrefix { index, primes, .. } = mined_until(&guard, at.at_height).map_err(IntoResponse::into_response)?;
+    let pairs = primes
+        .into_iter()
+        .filter(|&(p, _)| is_sexy_prime(p))
+        .map(|(p, block)| SexyPair { p, p_plus_6: p + 6, p_block: block, p6_block: mined_block(&guard, p + 6, index) })
+        .collect();
+    Ok(Json(pairs))
+}
+
+#[derive(Serialize)]
+pub struct PolignacPair {
+    p: u64,
+    p_plus_d: u64,
+    p_block: u64,
+    pd_block: Option<u64>,
+}
+
+/// Conjectura de Polignac: primos minerados `p` cujo próximo primo é exatamente `p + d`.
+pub async fn polignac_handler(
+    State(state): State<AppState>,
+    deadline: Deadline,
+    Path(d): Path<u64>,
+    Query(at): Query<AtHeight>,
+) -> Result<Json<serde_json::Value>, Response> {
+    if d == 0 || !d.is_multiple_of(2) || d > POLIGNAC_MAX_D {
+        let message = format!("d must be even and between 2 and {}", POLIGNAC_MAX_D);
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, message).into());
+    }
+    let prefix = mined_until(&state.chain.lock().unwrap(), at.at_height).map_err(IntoResponse::into_response)?;
+    let MinedPrefix { index, hash, primes } = prefix;
+    let found = deadline
+        .run(move |token| {
+            let mut found = Vec::new();
+            for (p, block) in primes {
+                if token.is_cancelled() {
+                    return None;
+                }
+                if is_prime_gap(p, d) {
+                    found.push((p, block));
+                }
+                token.advance(1);
+            }
+            Some(found)
+        })
+        .await
+        .map_err(IntoResponse::into_response)?;
+    let guard = relock(&state, index, &hash)?;
+    let pairs: Vec<PolignacPair> = found
+        .into_iter()
+        .map(|(p, block)| {
+            let pd_block = mined_block(&guard, p + d, index);
+            PolignacPair { p, p_plus_d: p + d, p_block: block, pd_block }
+        })
+        .collect();
+    Ok(Json(serde_json::json!({ "d": d, "count": pairs.len(), "pairs": pairs })))
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrimePattern {
+    Twin,
+    Cousin,
+    Sexy,
+}
+
+impl PrimePattern {
+    fn gap(self) -> u64 {
+        match self {
+            PrimePattern::Twin => 2,
+            PrimePattern::Cousin => 4,
+            PrimePattern::Sexy => 6,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PatternQuery {
+    pattern: PrimePattern,
+}
+
+#[derive(Serialize)]
+pub struct PatternPair {
+    p: u64,
+    q: u64,
+    p_block: u64,
+    q_block: Option<u64>,
+}
+
+/// Primos minerados `p` com `q = p + gap` também primo (gêmeos: 2, primos: 4, sexy: 6); o bloco de `q`
+/// sai do índice primo → bloco das estruturas derivadas, sem varrer a cadeia de novo.
+pub async fn prime_pattern_search_handler(
+    State(state): State<AppState>,
+    deadline: Deadline,
+    Query(query): Query<PatternQuery>,
+    Query(at): Query<AtHeight>,
+) -> Result<Json<Vec<PatternPair>>, Response> {
+    let gap = query.pattern.gap();
+    let prefix = mined_until(&state.chain.lock().unwrap(), at.at_height).map_err(IntoResponse::into_response)?;
+    let MinedPrefix { index, hash, primes } = prefix;
+    let found = deadline
+        .run(move |token| {
+            let mut found = Vec::new();
+            for (p, block) in primes {
+                if token.is_cancelled() {
+                    return None;
+                }
+                if let Some(q) = p.checked_add(gap).filter(|&q| miller_rabin_deterministic(q)) {
+                    found.push((p, q, block));
+                }
+                token.advance(1);
+            }
+            Some(found)
+        })
+        .await
+        .map_err(IntoResponse::into_response)?;
+    let guard = relock(&state, index, &hash)?;
+    let pairs = found
+        .into_iter()
+        .map(|(p, q, block)| PatternPair { p, q, p_block: block, q_block: mined_block(&guard, q, index) })
+        .collect();
+    Ok(Json(pairs))
+}
+
+#[derive(Deserialize)]
+pub struct AbcQuery {
+    smooth: Option<u64>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct AbcTriple {
+    a: u64,
+    b: u64,
+    c: u64,
+    radical: u64,
+    quality: f64,
+    prime: u64,
+    block: u64,
+}
+
+/// Triplas abc `a + b = c` com `mdc(a, b) = 1` e `c > rad(abc)` em que `a` ou `b` é um primo minerado
+/// `p` (um `c` primo daria `rad(abc) >= c`). Os outros dois termos são números sem fator primo acima de
+/// `smooth` (padrão 7, até 13), o que deixa a busca finita: para cada `x` liso, `x + p` também precisa
+/// ser. Ordenadas pela qualidade `q = ln c / ln rad(abc)`, as `limit` melhores.
+pub async fn abc_triple_search_handler(
+    State(state): State<AppState>,
+    deadline: Deadline,
+    Query(query): Query<AbcQuery>,
+    Query(at): Query<AtHeight>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let smooth = query.smooth.unwrap_or(ABC_DEFAULT_SMOOTH);
+    if !ABC_SMOOTH_BOUNDS.contains(&smooth) {
+        let message = format!("smooth must be one of {:?}", ABC_SMOOTH_BOUNDS);
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, message).into());
+    }
+    let limit = query.limit.unwrap_or(ABC_DEFAULT_LIMIT).min(ABC_MAX_LIMIT);
+    // Primeiro bloco de cada primo distinto
+    let mined: BTreeMap<u64, u64> = {
+        let guard = state.chain.lock().unwrap();
+        let index = history::resolve(&guard, at.at_height).map_err(IntoResponse::into_response)?;
+        mined_u64(&guard.blocks()[1..=index as usize]).rev().collect()
+    };
+    let checked = mined.len();
+    let mut triples = deadline
+        .run(move |token| {
+            let partners = smooth_numbers(&sieve(smooth), u64::MAX);
+            let radicals: HashMap<u64, u64> = partners.iter().copied().collect();
+            let mut triples = Vec::new();
+            for (&p, &block) in &mined {
+                if token.is_cancelled() {
+                    return None;
+                }
+                for &(x, rad_x) in &partners {
+                    let Some(c) = x.checked_add(p) else { break };
+                    let Some(&rad_c) = radicals.get(&c) else { continue };
+                    let radical = p as u128 * rad_x as u128 * rad_c as u128;
+                    if c as u128 > radical && x.gcd(&p) == 1 {
+                        let quality = (c as f64).ln() / (radical as f64).ln();
+                        let (a, b) = (x.min(p), x.max(p));
+                        triples.push(AbcTriple { a, b, c, radical: radical as u64, quality, prime: p, block });
+                    }
+                }
+                token.advance(1);
+            }
+            Some(triples)
+        })
+        .await
+        .map_err(IntoResponse::into_response)?;
+    triples.sort_by(|x, y| y.quality.total_cmp(&x.quality));
+    let count = triples.len();
+    triples.truncate(limit);
+    Ok(Json(serde_json::json!({
+        "smooth": smooth,
+        "checked_primes": checked,
+        "count": count,
+        "triples": triples,
+    })))
+}
+
+/// ζ(s) pelo produto de Euler restrito aos primos da cadeia, ao lado do mesmo produto sobre
+/// todos os primos até 10^6. Para s = 2 inclui o valor exato π²/6.
+pub async fn riemann_zeta_handler(
+    State(state): State<AppState>,
+    Path(s): Path<f64>,
+) -> Result<Json<serde_json::Value>, Response> {
+    if !s.is_finite() || s <= 1.0 {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "s must be a real number greater than 1").into());
+    }
+    let chain_primes: Vec<u64> = {
+        let guard = state.chain.lock().unwrap();
+        let distinct: BTreeSet<u64> = mined_u64(guard.blocks()).map(|(p, _)| p).collect();
+        distinct.into_iter().collect()
+    };
+    let (partial_product, primes_used) = zeta_euler_product(&chain_primes, s);
+    let reference = tokio::task::spawn_blocking(|| sieve(ZETA_SIEVE_N)).await.expect("Falha no crivo");
+    let (euler_product_approx, _) = zeta_euler_product(&reference, s);
+    let mut body = serde_json::json!({
+        "s": s,
+        "partial_product": partial_product,
+        "euler_product_approx": euler_product_approx,
+        "primes_used": primes_used,
+    });
+    if s == 2.0 {
+        let exact = std::f64::consts::PI.powi(2) / 6.0;
+        body["analytical"] = exact.into();
+        body["relative_error"] = ((partial_product - exact) / exact).into();
+    }
+    Ok(Json(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testkit::{test_clock, test_config, test_router, test_state, READ_KEY};
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use blockchain_core::{BlockBuilder, ChainState};
+    use tower::ServiceExt;
+
+    fn router(config: crate::Config) -> axum::Router {
+        test_router(test_state(ChainState::new(), &config, test_clock()))
+    }
+
+    // Primos 5, 11 e 7 nos blocos 1 a 3 (testemunha a·d + b·c)
+    fn mined_router() -> axum::Router {
+        let mut chain = ChainState::new();
+        for p in [5, 11, 7] {
+            chain.append(BlockBuilder::on(chain.tip()).timestamp(1).witness(p - 1, 1, 1, 1).build()).unwrap();
+        }
+        test_router(test_state(chain, &test_config(), test_clock()))
+    }
+
+    async fn get(router: &axum::Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let request = Request::get(uri).header("x-api-key", READ_KEY).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn next_prime_reports_the_gap_and_the_span() {
+        let router = router(crate::Config { prime_search_span: 1_000, ..test_config() });
+        let (status, body) = get(&router, "/primes/next?after=1693182318746371").await;
+        // A lacuna de 1132 passa do span de 1000
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!((body["span"].as_u64(), body["searched_to"].as_u64()), (Some(1_000), Some(1_693_182_318_747_371)));
+
+        let (status, body) = get(&router, "/primes/next?after=12345678901").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((body["prime"].as_u64(), body["gap"].as_u64()), (Some(12_345_678_923), Some(22)));
+        assert_eq!(get(&router, "/primes/next?after=18446744073709551616").await.0, StatusCode::BAD_REQUEST);
+        assert_eq!(get(&router, "/primes/next").await.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn check_reports_factors_within_the_budget() {
+        let router = router(crate::Config { prime_check_budget: 1_000, ..test_config() });
+        let (_, prime) = get(&router, "/primes/check?n=18446744073709551557").await;
+        assert_eq!(prime, serde_json::json!({ "n": 18_446_744_073_709_551_557u64, "is_prime": true }));
+        let (_, small) = get(&router, "/primes/check?n=5292").await;
+        assert_eq!(small["factors"], serde_json::json!([[2, 2], [3, 3], [7, 2]]));
+        assert_eq!(small["complete"], true);
+
+        // 1000003 * 1000033: o orçamento acaba antes do menor fator
+        let (_, semiprime) = get(&router, "/primes/check?n=1000036000099").await;
+        assert_eq!((&semiprime["is_prime"], &semiprime["budget_exhausted"]), (&false.into(), &true.into()));
+        assert_eq!(semiprime["cofactor"], 1_000_036_000_099u64);
+        assert_eq!(semiprime["divisions"], 1_000);
+    }
+
+    #[tokio::test]
+    async fn cpu_routes_are_rate_limited() {
+        let router = router(crate::Config { cpu_rate_limit: 2, ..test_config() });
+        for _ in 0..2 {
+            assert_eq!(get(&router, "/primes/check?n=97").await.0, StatusCode::OK);
+        }
+        let request = Request::get("/primes/next?after=97").header("x-api-key", READ_KEY).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers()[header::RETRY_AFTER].to_str().unwrap().parse::<u64>().unwrap() >= 1);
+    }
+
+    #[tokio::test]
+    async fn mined_prime_scans_follow_at_height() {
+        let router = mined_router();
+        let pair = |body: &serde_json::Value, p: u64, key: &str| {
+            body.as_array().unwrap().iter().find(|pair| pair["p"] == p).map(|pair| pair[key].clone())
+        };
+        // 5 + 6 = 11 foi minerado no bloco 2, que ainda não existe na altura 1
+        let (_, sexy) = get(&router, "/prime/sexy-pairs").await;
+        assert_eq!(sexy.as_array().unwrap().len(), 3);
+        assert_eq!(pair(&sexy, 5, "p6_block"), Some(2.into()));
+        let (_, sexy) = get(&router, "/prime/sexy-pairs?at_height=1").await;
+        assert_eq!((sexy.as_array().unwrap().len(), pair(&sexy, 5, "p6_block")), (1, Some(serde_json::Value::Null)));
+
+        let (_, polignac) = get(&router, "/prime/polignac/2").await;
+        assert_eq!((polignac["count"].as_u64(), pair(&polignac["pairs"], 5, "pd_block")), (Some(2), Some(3.into())));
+        let (_, polignac) = get(&router, "/prime/polignac/2?at_height=2").await;
+        assert_eq!(pair(&polignac["pairs"], 5, "pd_block"), Some(serde_json::Value::Null));
+
+        let (_, twins) = get(&router, "/chain/prime-pattern-search?pattern=twin").await;
+        assert_eq!(pair(&twins, 5, "q_block"), Some(3.into()));
+        assert_eq!(pair(&twins, 11, "q_block"), Some(serde_json::Value::Null));
+        let (_, twins) = get(&router, "/chain/prime-pattern-search?pattern=twin&at_height=2").await;
+        assert_eq!(pair(&twins, 5, "q_block"), Some(serde_json::Value::Null));
+
+        let beyond = ["/prime/sexy-pairs?", "/prime/polignac/2?", "/chain/prime-pattern-search?pattern=twin&"];
+        for uri in beyond.map(|uri| format!("{}at_height=4", uri)) {
+            let (status, body) = get(&router, &uri).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            assert_eq!(body["error"], "Height 4 is beyond the tip (3)");
+        }
+    }
+}
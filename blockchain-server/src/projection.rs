@@ -0,0 +1,257 @@
+// src/projection.rs
+//! `?fields=` nas rotas de bloco: só os campos pedidos saem no JSON, e `index` sempre.
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use blockchain_core::Block;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde::Deserialize;
+
+use crate::errors::ApiError;
+use crate::sync::now_secs;
+
+/// Campo que pode ser pedido; `Digits` e `AgeSecs` são calculados a partir do bloco.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockField {
+    Index,
+    PrevHash,
+    Prime,
+    A,
+    B,
+    C,
+    D,
+    Hash,
+    RulesVersion,
+    Timestamp,
+    TxRoot,
+    Transactions,
+    HashScale,
+    Work,
+    Coinbase,
+    Reward,
+    Digits,
+    AgeSecs,
+}
+
+impl BlockField {
+    // Na ordem em que saem na resposta, a mesma do bloco completo
+    pub const ALL: [BlockField; 18] = [
+        BlockField::Index,
+        BlockField::PrevHash,
+        BlockField::Prime,
+        BlockField::A,
+        BlockField::B,
+        BlockField::C,
+        BlockField::D,
+        BlockField::Hash,
+        BlockField::RulesVersion,
+        BlockField::Timestamp,
+        BlockField::TxRoot,
+        BlockField::Transactions,
+        BlockField::HashScale,
+        BlockField::Work,
+        BlockField::Coinbase,
+        BlockField::Reward,
+        BlockField::Digits,
+        BlockField::AgeSecs,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BlockField::Index => "index",
+            BlockField::PrevHash => "prev_hash",
+            BlockField::Prime => "prime",
+            BlockField::A => "a",
+            BlockField::B => "b",
+            BlockField::C => "c",
+            BlockField::D => "d",
+            BlockField::Hash => "hash",
+            BlockField::RulesVersion => "rules_version",
+            BlockField::Timestamp => "timestamp",
+            BlockField::TxRoot => "tx_root",
+            BlockField::Transactions => "transactions",
+            BlockField::HashScale => "hash_scale",
+            BlockField::Work => "work",
+            BlockField::Coinbase => "coinbase",
+            BlockField::Reward => "reward",
+            BlockField::Digits => "digits",
+            BlockField::AgeSecs => "age_secs",
+        }
+    }
+
+    fn parse(name: &str) -> Option<BlockField> {
+        BlockField::ALL.into_iter().find(|field| field.name() == name)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct FieldsQuery {
+    // Nomes separados por vírgula; sem o parâmetro, o bloco completo
+    fields: Option<String>,
+}
+
+/// Nome em `?fields=` que não é campo de bloco.
+#[derive(Debug)]
+pub struct UnknownField(pub String);
+
+impl IntoResponse for UnknownField {
+    fn into_response(self) -> Response {
+        let valid: Vec<&str> = BlockField::ALL.iter().map(|field| field.name()).collect();
+        let body = serde_json::json!({ "error": format!("Unknown field {:?}", self.0), "valid_fields": valid });
+        ApiError::json(StatusCode::BAD_REQUEST, body).into_response()
+    }
+}
+
+/// Campos pedidos, já validados; `None` devolve o bloco completo.
+#[derive(Debug, Clone, Default)]
+pub struct Projection {
+    fields: Option<Vec<BlockField>>,
+}
+
+impl Projection {
+    /// Lê `?fields=`; nome desconhecido responde 400 com a lista dos válidos.
+    pub fn from_query(query: &FieldsQuery) -> Result<Projection, UnknownField> {
+        let Some(list) = &query.fields else { return Ok(Projection::default()) };
+        let mut requested = vec![BlockField::Index];
+        for name in list.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            requested.push(BlockField::parse(name).ok_or_else(|| UnknownField(name.to_string()))?);
+        }
+        let fields = BlockField::ALL.into_iter().filter(|field| requested.contains(field)).collect();
+        Ok(Projection { fields: Some(fields) })
+    }
+
+    /// O bloco como sai na resposta; `age_secs` é contado a partir de agora.
+    pub fn view(&self, block: Block) -> ProjectedBlock<'_> {
+        ProjectedBlock { block, fields: self.fields.as_deref(), now_secs: now_secs() }
+    }
+}
+
+pub struct ProjectedBlock<'a> {
+    block: Block,
+    fields: Option<&'a [BlockField]>,
+    now_secs: u64,
+}
+
+impl Serialize for ProjectedBlock<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(fields) = self.fields else { return self.block.serialize(serializer) };
+        let block = &self.block;
+        let mut map = serializer.serialize_map(Some(fields.len()))?;
+        for &field in fields {
+            let name = field.name();
+            match field {
+                BlockField::Index => map.serialize_entry(name, &block.index)?,
+                BlockField::PrevHash => map.serialize_entry(name, &block.prev_hash)?,
+                BlockField::Prime => map.serialize_entry(name, &block.prime)?,
+                BlockField::A => map.serialize_entry(name, &block.a)?,
+                BlockField::B => map.serialize_entry(name, &block.b)?,
+                BlockField::C => map.serialize_entry(name, &block.c)?,
+                BlockField::D => map.serialize_entry(name, &block.d)?,
+                BlockField::Hash => map.serialize_entry(name, &block.hash)?,
+                BlockField::RulesVersion => map.serialize_entry(name, &block.rules_version)?,
+                BlockField::Timestamp => map.serialize_entry(name, &block.timestamp)?,
+                BlockField::TxRoot => map.serialize_entry(name, &block.tx_root)?,
+                BlockField::Transactions => map.serialize_entry(name, &block.transactions)?,
+                BlockField::HashScale => map.serialize_entry(name, &block.hash_scale)?,
+                BlockField::Work => map.serialize_entry(name, &block.work)?,
+                BlockField::Coinbase => map.serialize_entry(name, &block.coinbase)?,
+                BlockField::Reward => map.serialize_entry(name, &block.reward)?,
+                BlockField::Digits => map.serialize_entry(name, &block.prime.digits())?,
+                // O gênesis e os blocos anteriores ao campo têm timestamp 0: sem idade
+                BlockField::AgeSecs => map.serialize_entry(
+                    name,
+                    &(block.timestamp > 0).then(|| self.now_secs.saturating_sub(block.timestamp / 1000)),
+                )?,
+            }
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testkit::{test_node, READ_KEY};
+    use axum::{body::Body, extract::Request, http::StatusCode, Router};
+    use blockchain_core::{BlockBuilder, ChainState};
+    use tower::ServiceExt;
+
+    use crate::sync::now_secs;
+
+    // Três blocos de primo 101, o último minerado há 100 s
+    fn router() -> Router {
+        let mut chain = ChainState::new();
+        let start = (now_secs() - 300) * 1000;
+        for i in 1..=3 {
+            let block = BlockBuilder::on(chain.tip()).timestamp(start + i * 100_000).witness(1, 100, 1, 1).build();
+            chain.append(block).unwrap();
+        }
+        test_node(chain).0
+    }
+
+    async fn get(router: &Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let request = Request::get(uri).header("x-api-key", READ_KEY).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    // Em ordem alfabética, como o `serde_json::Value` as guarda
+    fn keys(block: &serde_json::Value) -> Vec<&str> {
+        block.as_object().unwrap().keys().map(String::as_str).collect()
+    }
+
+    #[tokio::test]
+    async fn projected_blocks_have_exactly_the_requested_keys() {
+        let router = router();
+        let (_, block) = get(&router, "/block/2?fields=timestamp,prime").await;
+        assert_eq!(keys(&block), ["index", "prime", "timestamp"]);
+        assert_eq!(block["index"], 2);
+
+        let (_, empty) = get(&router, "/block/2?fields=").await;
+        assert_eq!(keys(&empty), ["index"]);
+        let (_, full) = get(&router, "/block/2").await;
+        assert!(keys(&full).contains(&"prev_hash") && keys(&full).contains(&"hash"));
+
+        let (_, chain) = get(&router, "/chain?fields=hash").await;
+        assert_eq!(chain.as_array().unwrap().len(), 4);
+        assert!(chain.as_array().unwrap().iter().all(|block| keys(block) == ["hash", "index"]));
+        let (_, tail) = get(&router, "/chain/tail?count=2&fields=digits").await;
+        let indices: Vec<_> = tail.as_array().unwrap().iter().map(|block| block["index"].clone()).collect();
+        assert_eq!(indices, [2, 3]);
+        assert!(tail.as_array().unwrap().iter().all(|block| keys(block) == ["digits", "index"]));
+
+        // A paginação de /blocks continua valendo com a projeção
+        let (_, page) = get(&router, "/blocks?from_ts=1&limit=2&fields=prime").await;
+        assert_eq!((&page["total"], &page["next_offset"]), (&3.into(), &2.into()));
+        assert!(page["items"].as_array().unwrap().iter().all(|block| keys(block) == ["index", "prime"]));
+    }
+
+    #[tokio::test]
+    async fn derived_fields_are_computed() {
+        let router = router();
+        let (_, blocks) = get(&router, "/chain?fields=digits,age_secs").await;
+        let blocks = blocks.as_array().unwrap();
+        // O gênesis tem timestamp 0: sem idade
+        assert_eq!((&blocks[0]["digits"], &blocks[0]["age_secs"]), (&1.into(), &serde_json::Value::Null));
+        for (block, age) in blocks[1..].iter().zip([200, 100, 0]) {
+            assert_eq!(block["digits"], 3);
+            let measured = block["age_secs"].as_u64().unwrap();
+            assert!((age..=age + 2).contains(&measured), "idade {} para {}", measured, age);
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_field_lists_the_valid_ones() {
+        let router = router();
+        for uri in ["/chain?fields=index,nonce", "/chain/tail?fields=nonce", "/block/1?fields=nonce"] {
+            let (status, body) = get(&router, uri).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(body["error"], "Unknown field \"nonce\"");
+            let valid = body["valid_fields"].as_array().unwrap();
+            assert_eq!(valid.len(), 18);
+            assert!(valid.contains(&"age_secs".into()) && valid.contains(&"digits".into()));
+        }
+    }
+}
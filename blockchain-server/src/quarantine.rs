@@ -0,0 +1,114 @@
+// src/quarantine.rs
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use blockchain_core::Block;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+
+// Entradas mantidas; as mais antigas são descartadas
+const CAPACITY: usize = 100;
+
+/// Cadeia de um peer, que venceria a local na escolha de fork, mas falhou na validação.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    pub id: u64,
+    pub peer: String,
+    pub peer_height: usize,
+    pub fork_point: u64,
+    pub invalid_block: Option<Block>,
+    pub invariant: String,
+    pub error: String,
+    pub detected_at: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Snapshot {
+    next_id: u64,
+    entries: VecDeque<QuarantineEntry>,
+}
+
+#[derive(Debug, Default)]
+pub struct Quarantine {
+    snapshot: Snapshot,
+    path: Option<PathBuf>,
+}
+
+impl Quarantine {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let snapshot = path
+            .as_ref()
+            .filter(|p| p.exists())
+            .and_then(|p| match fs::read(p).map_err(|e| e.to_string()).and_then(|bytes| {
+                serde_json::from_slice(&bytes).map_err(|e| e.to_string())
+            }) {
+                Ok(snapshot) => Some(snapshot),
+                Err(e) => {
+                    warn!("Quarentena corrompida em {}: {}", p.display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Quarantine { snapshot, path }
+    }
+
+    pub fn add(&mut self, mut entry: QuarantineEntry) -> u64 {
+        entry.id = self.snapshot.next_id;
+        self.snapshot.next_id += 1;
+        if self.snapshot.entries.len() == CAPACITY {
+            self.snapshot.entries.pop_front();
+        }
+        self.snapshot.entries.push_back(entry);
+        self.persist();
+        self.snapshot.next_id - 1
+    }
+
+    pub fn entries(&self) -> Vec<QuarantineEntry> {
+        self.snapshot.entries.iter().cloned().collect()
+    }
+
+    pub fn remove(&mut self, id: u64) -> bool {
+        let before = self.snapshot.entries.len();
+        self.snapshot.entries.retain(|e| e.id != id);
+        let removed = self.snapshot.entries.len() != before;
+        if removed {
+            self.persist();
+        }
+        removed
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.path else { return };
+        let result = serde_json::to_vec_pretty(&self.snapshot)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| fs::write(path, bytes).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("Falha ao persistir a quarentena em {}: {}", path.display(), e);
+        }
+    }
+}
+
+pub async fn list_quarantine_handler(
+    State(state): State<AppState>,
+) -> Json<Vec<QuarantineEntry>> {
+    Json(state.quarantine.lock().unwrap().entries())
+}
+
+pub async fn delete_quarantine_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, ApiError> {
+    if state.quarantine.lock().unwrap().remove(id) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::new(StatusCode::NOT_FOUND, format!("Quarantine entry {} not found", id)))
+    }
+}
@@ -0,0 +1,67 @@
+// src/ratelimit.rs
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use blockchain_core::{Clock, SystemClock};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+
+// Cliente sem X-API-Key (rotas liberadas ao público)
+const ANONYMOUS: &str = "anonymous";
+
+/// Balde de fichas por cliente: `per_minute` requisições por minuto, com rajada do mesmo tamanho.
+/// Os clientes são as chaves configuradas mais o anônimo, então o mapa não cresce com o tráfego.
+pub struct RateLimiter {
+    clock: Arc<dyn Clock>,
+    per_minute: u32,
+    // Fichas restantes e o instante em que foram contadas
+    buckets: HashMap<String, (f64, Duration)>,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32) -> Self {
+        RateLimiter::with_clock(per_minute, Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(per_minute: u32, clock: Arc<dyn Clock>) -> Self {
+        RateLimiter { clock, per_minute, buckets: HashMap::new() }
+    }
+
+    /// Consome uma ficha de `client`; sem ficha, devolve os segundos até a próxima.
+    pub fn take(&mut self, client: &str) -> Result<(), u64> {
+        let now = self.clock.now();
+        let capacity = self.per_minute as f64;
+        let (tokens, counted_at) = self.buckets.entry(client.to_string()).or_insert((capacity, now));
+        let refill = now.saturating_sub(*counted_at).as_secs_f64() * capacity / 60.0;
+        *tokens = (*tokens + refill).min(capacity);
+        *counted_at = now;
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            return Ok(());
+        }
+        Err(((1.0 - *tokens) * 60.0 / capacity).ceil() as u64)
+    }
+}
+
+/// Limita as rotas que gastam CPU por requisição; fica por dentro da autorização.
+pub async fn limit_cpu(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    if state.config.cpu_rate_limit == 0 {
+        return next.run(req).await;
+    }
+    // Chaves desconhecidas contam como anônimas, senão bastaria trocar de chave para fugir do limite
+    let key = req.headers().get("x-api-key").and_then(|v| v.to_str().ok());
+    let client = key.filter(|key| state.config.api_keys.iter().any(|(k, _)| k == key)).unwrap_or(ANONYMOUS);
+    if let Err(retry_after) = state.cpu_limiter.lock().unwrap().take(client) {
+        let message = format!("Rate limit of {} requests per minute exceeded", state.config.cpu_rate_limit);
+        let error = ApiError::new(StatusCode::TOO_MANY_REQUESTS, message);
+        return ([(header::RETRY_AFTER, retry_after.to_string())], error).into_response();
+    }
+    next.run(req).await
+}
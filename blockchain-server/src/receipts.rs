@@ -0,0 +1,60 @@
+// src/receipts.rs
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use blockchain_core::{sign_receipt, verify_receipt, MiningReceipt, ReceiptBody};
+use serde::Deserialize;
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+use crate::sync::now_secs;
+
+/// Recibo assinado pelo nó para o bloco `index`. Blocos que o nó não minerou (sincronizados, submetidos
+/// ou de uma cadeia que substituiu a local) saem com `mined_locally: false` e sem os números da mineração.
+pub async fn receipt_handler(
+    State(state): State<AppState>,
+    Path(index): Path<usize>,
+) -> Result<Json<MiningReceipt>, Response> {
+    let block = state
+        .chain
+        .lock()
+        .unwrap()
+        .blocks()
+        .get(index)
+        .cloned()
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("Block {} not found", index)))?;
+    // Pelo hash: após uma troca de cadeia a mesma altura pode ser de outro bloco
+    let stats = state.mined_blocks.lock().unwrap().get(&block.hash).cloned();
+    let body = ReceiptBody {
+        chain_id: state.namespace.clone(),
+        hash: block.hash,
+        height: block.index,
+        timestamp: block.timestamp,
+        mined_locally: stats.is_some(),
+        stats,
+        node_pubkey: String::new(),
+        issued_at: now_secs(),
+    };
+    Ok(Json(sign_receipt(&state.node_key, body)))
+}
+
+#[derive(Deserialize)]
+pub struct VerifyReceiptRequest {
+    receipt: MiningReceipt,
+    // Padrão: a chave deste nó
+    pubkey: Option<String>,
+}
+
+pub async fn verify_receipt_handler(
+    State(state): State<AppState>,
+    Json(request): Json<VerifyReceiptRequest>,
+) -> Json<serde_json::Value> {
+    let pubkey = request.pubkey.unwrap_or_else(|| hex::encode(state.node_key.verifying_key().to_bytes()));
+    Json(serde_json::json!({
+        "valid": verify_receipt(&request.receipt, &pubkey),
+        "pubkey": pubkey,
+    }))
+}
@@ -0,0 +1,53 @@
+// src/rules.rs
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use blockchain_core::LATEST_RULES_VERSION;
+use log::info;
+use serde::Deserialize;
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+
+fn rules_view(state: &AppState) -> serde_json::Value {
+    let guard = state.chain.lock().unwrap();
+    serde_json::json!({
+        "next_block_version": guard.next_rules_version(),
+        "latest_known_version": LATEST_RULES_VERSION,
+        "prime_width": guard.rules().prime_width(),
+        "activations": guard
+            .rules()
+            .activations()
+            .iter()
+            .map(|(version, height)| serde_json::json!({ "version": version, "activation_height": height }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+pub async fn rules_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(rules_view(&state))
+}
+
+#[derive(Deserialize)]
+pub struct ScheduleRules {
+    version: u32,
+    activation_height: u64,
+}
+
+/// Agenda a ativação futura de uma versão de regras.
+pub async fn schedule_rules_handler(
+    State(state): State<AppState>,
+    Json(body): Json<ScheduleRules>,
+) -> Result<Json<serde_json::Value>, Response> {
+    state
+        .chain
+        .lock()
+        .unwrap()
+        .schedule_rules(body.version, body.activation_height)
+        .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+    info!("Regras v{} agendadas para a altura {}", body.version, body.activation_height);
+    Ok(Json(rules_view(&state)))
+}
@@ -0,0 +1,203 @@
+// src/shed.rs
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use log::{info, warn};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+use crate::sync::now_secs;
+
+// Período da sonda; o atraso do escalonador é o quanto o despertar passa disso
+const PROBE_INTERVAL: Duration = Duration::from_millis(100);
+const RETRY_AFTER_SECS: u64 = 5;
+
+/// Rotas dispensáveis sob carga: análises, exportações e benchmarks. Mineração, anexação de blocos,
+/// saúde, a ponta da cadeia e a sincronização com peers nunca são descartadas.
+fn low_priority(path: &str) -> bool {
+    matches!(
+        path,
+        "/chain/export"
+            | "/chain/export/sqlite"
+            | "/chain/stats/json-ld"
+            | "/chain/graph-json"
+            | "/chain/hash-tree"
+            | "/chain/as-of/:height/summary"
+            | "/chain/primorial-hash"
+            | "/chain/twin-prime-density"
+            | "/chain/epoch/:n"
+            | "/chain/energy-estimate"
+            | "/chain/rolling-window-stats"
+            | "/chain/entropy-vs-height"
+            | "/chain/prime-bits"
+            | "/chain/dag-ancestors/:index/:depth"
+            | "/chain/prime-counting-function"
+            | "/chain/prime-pattern-search"
+            | "/block/:index/gcd-test"
+            | "/block/:index/residue-symbol"
+            | "/block/:index/timing-attack-resistance"
+    ) || path.starts_with("/prime/")
+        || path.starts_with("/primes/")
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ShedStatus {
+    pub shedding: bool,
+    pub scheduler_delay_ms: u64,
+    pub threshold_ms: u64,
+    // Unix do início do descarte em curso
+    pub since: Option<u64>,
+    pub shed_requests: u64,
+}
+
+/// Descarte de carga com histerese: liga quando o atraso chega a `threshold_ms` e só desliga depois
+/// de `recovery` seguidos abaixo da metade dele.
+pub struct LoadShedder {
+    status: ShedStatus,
+    recovery: Duration,
+    calm_since: Option<Instant>,
+}
+
+impl LoadShedder {
+    pub fn new(threshold_ms: u64, recovery: Duration) -> Self {
+        LoadShedder { status: ShedStatus { threshold_ms, ..ShedStatus::default() }, recovery, calm_since: None }
+    }
+
+    pub fn status(&self) -> ShedStatus {
+        self.status.clone()
+    }
+
+    pub fn observe(&mut self, delay: Duration, now: Instant) {
+        let status = &mut self.status;
+        status.scheduler_delay_ms = delay.as_millis() as u64;
+        if status.threshold_ms == 0 {
+            return;
+        }
+        if !status.shedding {
+            if status.scheduler_delay_ms >= status.threshold_ms {
+                let delay = status.scheduler_delay_ms;
+                warn!("Atraso do escalonador em {} ms; descartando rotas de baixa prioridade", delay);
+                status.shedding = true;
+                status.since = Some(now_secs());
+                self.calm_since = None;
+            }
+        } else if status.scheduler_delay_ms < status.threshold_ms / 2 {
+            let calm_since = *self.calm_since.get_or_insert(now);
+            if now.duration_since(calm_since) >= self.recovery {
+                info!("Atraso do escalonador normalizado; descarte de carga encerrado");
+                status.shedding = false;
+                status.since = None;
+            }
+        } else {
+            self.calm_since = None;
+        }
+    }
+
+    // Conta o descarte quando recusa
+    fn admit(&mut self) -> bool {
+        if self.status.shedding {
+            self.status.shed_requests += 1;
+        }
+        !self.status.shedding
+    }
+}
+
+/// Amostra o atraso do escalonador a cada `PROBE_INTERVAL`.
+pub async fn probe_loop(state: AppState) {
+    loop {
+        let started = Instant::now();
+        tokio::time::sleep(PROBE_INTERVAL).await;
+        let delay = started.elapsed().saturating_sub(PROBE_INTERVAL);
+        state.shedder.lock().unwrap().observe(delay, Instant::now());
+    }
+}
+
+/// Recusa com 503 as rotas de baixa prioridade enquanto o descarte estiver ligado.
+pub async fn shed(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let low = req.extensions().get::<MatchedPath>().is_some_and(|p| low_priority(p.as_str()));
+    if low && !state.shedder.lock().unwrap().admit() {
+        let message = "Node is under load; low-priority routes are temporarily unavailable";
+        let error = ApiError::new(StatusCode::SERVICE_UNAVAILABLE, message);
+        return ([(header::RETRY_AFTER, RETRY_AFTER_SECS.to_string())], error).into_response();
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{test_clock, test_config, test_router, test_state, READ_KEY};
+    use axum::body::Body;
+    use blockchain_core::ChainState;
+    use tower::ServiceExt;
+
+    async fn get(router: &axum::Router, uri: &str) -> (Response, String) {
+        let request = Request::get(uri).header("x-api-key", READ_KEY).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let (parts, body) = response.into_parts();
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        (Response::from_parts(parts, Body::empty()), String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    // Espera a sonda levar o descarte a `shedding`, com até 3 s de folga
+    async fn wait_for(state: &AppState, shedding: bool) {
+        for _ in 0..150 {
+            if state.shedder.lock().unwrap().status().shedding == shedding {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("descarte não chegou a {}: {:?}", shedding, state.shedder.lock().unwrap().status());
+    }
+
+    #[test]
+    fn hysteresis_needs_a_calm_stretch() {
+        let mut shedder = LoadShedder::new(100, Duration::from_secs(2));
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        shedder.observe(Duration::from_millis(99), at(0));
+        assert!(!shedder.status().shedding);
+        shedder.observe(Duration::from_millis(100), at(0));
+        assert!(shedder.status().shedding);
+        // Abaixo do limite mas acima da metade não conta como calma
+        shedder.observe(Duration::from_millis(60), at(5));
+        shedder.observe(Duration::from_millis(10), at(6));
+        shedder.observe(Duration::from_millis(70), at(7));
+        shedder.observe(Duration::from_millis(10), at(8));
+        assert!(shedder.status().shedding);
+        shedder.observe(Duration::from_millis(10), at(10));
+        assert!(!shedder.status().shedding);
+    }
+
+    /// Um bloqueio no único worker do runtime atrasa a sonda: a rota de análise sai com 503 e a saúde
+    /// continua respondendo, até o atraso normalizar.
+    #[tokio::test(flavor = "current_thread")]
+    async fn busy_runtime_sheds_low_priority_routes() {
+        let config = crate::Config { shed_delay_ms: 100, shed_recovery_secs: 1, ..test_config() };
+        let state = test_state(ChainState::new(), &config, test_clock());
+        let router = test_router(state.clone());
+        tokio::spawn(probe_loop(state.clone()));
+        assert_eq!(get(&router, "/chain/primorial-hash").await.0.status(), StatusCode::OK);
+
+        tokio::spawn(async { std::thread::sleep(Duration::from_millis(400)) });
+        wait_for(&state, true).await;
+        let (response, _) = get(&router, "/chain/primorial-hash").await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], RETRY_AFTER_SECS.to_string());
+        let (health, body) = get(&router, "/healthz").await;
+        assert_eq!(health.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["load_shedding"]["shedding"], true);
+        assert!(body["load_shedding"]["scheduler_delay_ms"].as_u64().unwrap() >= 100);
+        assert!(get(&router, "/metrics").await.1.contains("load_shed_requests_total 1"));
+
+        // Sem bloqueio, a sonda volta ao normal e o descarte desliga depois da recuperação
+        wait_for(&state, false).await;
+        assert_eq!(get(&router, "/chain/primorial-hash").await.0.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,29 @@
+// src/shutdown.rs
+use log::info;
+
+use crate::state::AppState;
+
+/// Ao receber SIGTERM ou Ctrl-C: para a mineração, grava as métricas e encerra o processo,
+/// já que instalar o handler substitui o encerramento padrão do sinal.
+pub async fn on_signal(state: AppState) {
+    let ctrl_c = tokio::signal::ctrl_c();
+    #[cfg(unix)]
+    {
+        let mut term = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("handler de SIGTERM");
+        tokio::select! {
+            _ = ctrl_c => {}
+            _ = term.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = ctrl_c.await;
+
+    let miner = state.miner.clone();
+    let _ = tokio::task::spawn_blocking(move || miner.shutdown()).await;
+    for ns in state.chains.lock().unwrap().states() {
+        ns.metrics.lock().unwrap().persist();
+    }
+    info!("Métricas gravadas no desligamento");
+    std::process::exit(0);
+}
@@ -0,0 +1,326 @@
+// src/slo.rs
+use axum::{
+    extract::{MatchedPath, Query, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use blockchain_core::{Clock, SystemClock};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+
+// Minutos guardados por rota; a janela máxima do relatório
+const KEPT_MINUTES: u64 = 60;
+const WINDOWS_MINUTES: [u64; 3] = [5, 15, 60];
+// Limites superiores (ms) das faixas do histograma; a última faixa pega o que passar do maior
+const LATENCY_BOUNDS_MS: [u64; 14] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000, 10_000, 30_000];
+
+/// Objetivo de uma rota: p99 até `p99_ms` e fração de respostas 5xx até `max_error_rate`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SloTarget {
+    pub p99_ms: u64,
+    pub max_error_rate: f64,
+}
+
+impl Default for SloTarget {
+    fn default() -> Self {
+        SloTarget { p99_ms: 1000, max_error_rate: 0.01 }
+    }
+}
+
+impl SloTarget {
+    /// Lê `p99_ms` ou `p99_ms:taxa_de_erro`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (p99, rate) = spec.split_once(':').map_or((spec, None), |(p, r)| (p, Some(r)));
+        let p99_ms = p99.trim().parse().map_err(|_| format!("expected latency in ms, got {:?}", p99))?;
+        let max_error_rate = match rate {
+            Some(rate) => rate.trim().parse().map_err(|_| format!("expected error rate, got {:?}", rate))?,
+            None => SloTarget::default().max_error_rate,
+        };
+        if !(0.0..=1.0).contains(&max_error_rate) {
+            return Err(format!("error rate must be between 0 and 1, got {}", max_error_rate));
+        }
+        Ok(SloTarget { p99_ms, max_error_rate })
+    }
+}
+
+/// Objetivos por rota (`MÉTODO /rota`), com um padrão para as demais.
+#[derive(Debug, Clone, Default)]
+pub struct SloTargets {
+    pub default: SloTarget,
+    pub routes: BTreeMap<String, SloTarget>,
+}
+
+impl SloTargets {
+    /// Lê `MÉTODO /rota=alvo,MÉTODO /rota=alvo`, com cada alvo no formato de `SloTarget::parse`.
+    pub fn parse(default: &str, spec: &str) -> Result<Self, String> {
+        let default = if default.trim().is_empty() { SloTarget::default() } else { SloTarget::parse(default)? };
+        let mut routes = BTreeMap::new();
+        for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (route, target) = pair.split_once('=').ok_or_else(|| format!("expected route=target, got {:?}", pair))?;
+            routes.insert(route.trim().to_string(), SloTarget::parse(target)?);
+        }
+        Ok(SloTargets { default, routes })
+    }
+
+    fn get(&self, route: &str) -> SloTarget {
+        self.routes.get(route).copied().unwrap_or(self.default)
+    }
+}
+
+// Requisições de uma rota num minuto
+#[derive(Debug, Clone, Copy, Default)]
+struct MinuteBucket {
+    minute: u64,
+    latencies: [u64; LATENCY_BOUNDS_MS.len() + 1],
+    // 1xx a 5xx
+    statuses: [u64; 5],
+    max_ms: u64,
+}
+
+impl MinuteBucket {
+    fn merge(&mut self, other: &MinuteBucket) {
+        for (mine, theirs) in self.latencies.iter_mut().zip(&other.latencies) {
+            *mine += theirs;
+        }
+        for (mine, theirs) in self.statuses.iter_mut().zip(&other.statuses) {
+            *mine += theirs;
+        }
+        self.max_ms = self.max_ms.max(other.max_ms);
+    }
+
+    fn requests(&self) -> u64 {
+        self.statuses.iter().sum()
+    }
+
+    // Limite superior da faixa que contém o quantil `q`; na faixa aberta, o maior tempo visto
+    fn percentile(&self, q: f64) -> Option<u64> {
+        let total = self.latencies.iter().sum::<u64>();
+        if total == 0 {
+            return None;
+        }
+        let rank = ((q * total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, &count) in self.latencies.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(LATENCY_BOUNDS_MS.get(i).map_or(self.max_ms, |&bound| bound.min(self.max_ms)));
+            }
+        }
+        Some(self.max_ms)
+    }
+}
+
+/// Resumo de uma rota numa janela.
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteSlo {
+    pub route: String,
+    pub requests: u64,
+    pub rate_per_sec: f64,
+    pub error_rate: f64,
+    pub p50_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+    pub p99_ms: Option<u64>,
+    pub statuses: BTreeMap<String, u64>,
+    pub target: SloTarget,
+    pub breach: bool,
+}
+
+/// Latência e classe de status por rota em baldes de um minuto, numa roda de `KEPT_MINUTES` posições:
+/// a memória é fixa por rota, e as rotas são as do roteador, não as URLs recebidas.
+pub struct SloTracker {
+    clock: Arc<dyn Clock>,
+    targets: SloTargets,
+    routes: BTreeMap<String, [MinuteBucket; KEPT_MINUTES as usize]>,
+}
+
+impl SloTracker {
+    pub fn new(targets: SloTargets) -> Self {
+        SloTracker::with_clock(targets, Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(targets: SloTargets, clock: Arc<dyn Clock>) -> Self {
+        SloTracker { clock, targets, routes: BTreeMap::new() }
+    }
+
+    fn minute(&self) -> u64 {
+        self.clock.now().as_secs() / 60
+    }
+
+    pub fn record(&mut self, route: &str, status: StatusCode, latency_ms: u64) {
+        let minute = self.minute();
+        let wheel = self.routes.entry(route.to_string()).or_insert([MinuteBucket::default(); KEPT_MINUTES as usize]);
+        let bucket = &mut wheel[(minute % KEPT_MINUTES) as usize];
+        if bucket.minute != minute {
+            *bucket = MinuteBucket { minute, ..MinuteBucket::default() };
+        }
+        let band = LATENCY_BOUNDS_MS.iter().position(|&bound| latency_ms <= bound).unwrap_or(LATENCY_BOUNDS_MS.len());
+        bucket.latencies[band] += 1;
+        let class = (status.as_u16() / 100).clamp(1, 5) as usize;
+        bucket.statuses[class - 1] += 1;
+        bucket.max_ms = bucket.max_ms.max(latency_ms);
+    }
+
+    /// Rotas com requisições nos últimos `minutes` minutos, contando o minuto corrente.
+    pub fn report(&self, minutes: u64) -> Vec<RouteSlo> {
+        let now = self.minute();
+        let oldest = now.saturating_sub(minutes.clamp(1, KEPT_MINUTES) - 1);
+        self.routes
+            .iter()
+            .filter_map(|(route, wheel)| {
+                let mut total = MinuteBucket::default();
+                for bucket in wheel.iter().filter(|b| (oldest..=now).contains(&b.minute)) {
+                    total.merge(bucket);
+                }
+                let requests = total.requests();
+                if requests == 0 {
+                    return None;
+                }
+                let error_rate = total.statuses[4] as f64 / requests as f64;
+                let p99_ms = total.percentile(0.99);
+                let target = self.targets.get(route);
+                Some(RouteSlo {
+                    route: route.clone(),
+                    requests,
+                    rate_per_sec: requests as f64 / (minutes * 60) as f64,
+                    error_rate,
+                    p50_ms: total.percentile(0.50),
+                    p95_ms: total.percentile(0.95),
+                    p99_ms,
+                    statuses: (1..=5)
+                        .filter(|&class| total.statuses[class - 1] > 0)
+                        .map(|class| (format!("{}xx", class), total.statuses[class - 1]))
+                        .collect(),
+                    target,
+                    breach: p99_ms.is_some_and(|p99| p99 > target.p99_ms) || error_rate > target.max_error_rate,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Mede cada requisição casada com uma rota; fica por fora da autorização para contar também as negadas.
+pub async fn track(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let Some(path) = req.extensions().get::<MatchedPath>().map(|p| p.as_str().to_string()) else {
+        return next.run(req).await;
+    };
+    let route = format!("{} {}", req.method(), path);
+    let started = Instant::now();
+    let response = next.run(req).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    state.slo.lock().unwrap().record(&route, response.status(), latency_ms);
+    response
+}
+
+#[derive(Deserialize)]
+pub struct SloQuery {
+    window: Option<String>,
+}
+
+/// p50/p95/p99, taxa de requisições e de erros (5xx) por rota na janela `5m`, `15m` ou `60m`.
+pub async fn slo_handler(State(state): State<AppState>, Query(query): Query<SloQuery>) -> Response {
+    let window = query.window.as_deref().unwrap_or("5m");
+    let Some(minutes) = WINDOWS_MINUTES.into_iter().find(|m| format!("{}m", m) == window) else {
+        return ApiError::new(StatusCode::BAD_REQUEST, "window must be one of 5m, 15m, 60m").into_response();
+    };
+    let routes = state.slo.lock().unwrap().report(minutes);
+    let breaches = routes.iter().filter(|r| r.breach).count();
+    Json(serde_json::json!({ "window": window, "breaches": breaches, "routes": routes })).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{test_clock, test_config, test_router, test_state, ADMIN_KEY};
+    use axum::body::Body;
+    use blockchain_core::ChainState;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    const ROUTE: &str = "GET /chain";
+
+    fn targets() -> SloTargets {
+        SloTargets::parse("1000:0.05", "GET /chain=5000:0.05").unwrap()
+    }
+
+    // 90 respostas em 5 ms, 8 em 150 ms e 2 em 3 s
+    fn drive(tracker: &mut SloTracker, route: &str) {
+        let latencies = [(90, 5), (8, 150), (2, 3000)];
+        for (count, ms) in latencies {
+            for _ in 0..count {
+                tracker.record(route, StatusCode::OK, ms);
+            }
+        }
+    }
+
+    #[test]
+    fn percentiles_come_from_the_bands() {
+        let mut tracker = SloTracker::with_clock(targets(), test_clock());
+        drive(&mut tracker, ROUTE);
+        drive(&mut tracker, "GET /tip");
+        let report = tracker.report(5);
+        let chain = report.iter().find(|r| r.route == ROUTE).unwrap();
+        // p95 cai na faixa até 200 ms; p99 na até 5 s, limitada ao maior tempo visto
+        assert_eq!((chain.p50_ms, chain.p95_ms, chain.p99_ms), (Some(5), Some(200), Some(3000)));
+        assert_eq!(chain.requests, 100);
+        assert!((chain.rate_per_sec - 100.0 / 300.0).abs() < 1e-9);
+        // O mesmo p99 só estoura o alvo padrão de 1 s
+        assert!(!chain.breach);
+        assert!(report.iter().find(|r| r.route == "GET /tip").unwrap().breach);
+    }
+
+    #[test]
+    fn error_rate_and_windows_follow_the_clock() {
+        let clock = test_clock();
+        let mut tracker = SloTracker::with_clock(targets(), clock.clone());
+        for status in [StatusCode::OK; 18].into_iter().chain([StatusCode::INTERNAL_SERVER_ERROR; 2]) {
+            tracker.record(ROUTE, status, 1);
+        }
+        let chain = &tracker.report(5)[0];
+        assert_eq!(chain.error_rate, 0.1);
+        assert_eq!(chain.statuses, BTreeMap::from([("2xx".to_string(), 18), ("5xx".to_string(), 2)]));
+        assert!(chain.breach);
+
+        // Dez minutos depois, a janela de 5 minutos vê só o tráfego novo
+        clock.advance(Duration::from_secs(600));
+        tracker.record(ROUTE, StatusCode::OK, 1);
+        assert_eq!(tracker.report(5)[0].requests, 1);
+        assert!(!tracker.report(5)[0].breach);
+        assert_eq!(tracker.report(15)[0].requests, 21);
+        // Uma hora depois o balde antigo é reaproveitado, sem crescer
+        clock.advance(Duration::from_secs(3600));
+        assert!(tracker.report(60).is_empty());
+        tracker.record(ROUTE, StatusCode::OK, 1);
+        assert_eq!(tracker.report(60)[0].requests, 1);
+        assert_eq!(tracker.routes[ROUTE].len(), KEPT_MINUTES as usize);
+    }
+
+    #[tokio::test]
+    async fn report_selects_the_window() {
+        let clock = test_clock();
+        let state = test_state(ChainState::new(), &test_config(), clock.clone());
+        let mut tracker = SloTracker::with_clock(targets(), clock);
+        drive(&mut tracker, "GET /tip");
+        *state.slo.lock().unwrap() = tracker;
+        let router = test_router(state);
+        let get = |window: &str| {
+            let request = Request::get(format!("/admin/slo?window={}", window)).header("x-api-key", ADMIN_KEY);
+            router.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let response = get("15m").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!((body["window"].as_str(), body["breaches"].as_u64()), (Some("15m"), Some(1)));
+        assert_eq!(body["routes"][0]["route"], "GET /tip");
+        assert_eq!(body["routes"][0]["p99_ms"], 3000);
+        assert_eq!(get("10m").await.unwrap().status(), StatusCode::BAD_REQUEST);
+    }
+}
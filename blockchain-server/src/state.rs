@@ -0,0 +1,165 @@
+// src/state.rs
+use blockchain_core::signature::SigningKey;
+use blockchain_core::{Block, CandidatePool, ChainState, ReceiptStats};
+use log::warn;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, Semaphore};
+
+use crate::alerts::Alerts;
+use crate::audit::AuditLog;
+use crate::bandwidth::{BandwidthTracker, BlockSizes};
+use crate::config::Config;
+use crate::deadline::Jobs;
+use crate::gc::GcReport;
+use crate::handshake::load_node_key;
+use crate::health::DeepHealthTasks;
+use crate::history::HistoryCache;
+use crate::mempool::Mempool;
+use crate::metrics::Metrics;
+use crate::middleware::RouteRoles;
+use crate::miner::Miner;
+use crate::miners::MinerRegistry;
+use crate::namespaces::{ChainRegistry, DEFAULT_CHAIN};
+use crate::peers::PeerRegistry;
+use crate::orphans::OrphanPool;
+use crate::quarantine::Quarantine;
+use crate::ratelimit::RateLimiter;
+use crate::shed::LoadShedder;
+use crate::slo::SloTracker;
+use crate::templates::TemplateRegistry;
+use crate::webhooks::WebhookRegistry;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub config: Arc<Config>,
+    pub chain: Arc<Mutex<ChainState>>,
+    // Notifica cada bloco anexado à cadeia local
+    pub events: broadcast::Sender<Block>,
+    // Presente apenas quando a pré-computação está ativada
+    pub pool: Option<Arc<CandidatePool>>,
+    pub peers: Arc<Mutex<PeerRegistry>>,
+    pub quarantine: Arc<Mutex<Quarantine>>,
+    pub http: reqwest::Client,
+    pub health_tasks: Arc<Mutex<DeepHealthTasks>>,
+    pub templates: Arc<Mutex<TemplateRegistry>>,
+    pub miners: Arc<Mutex<MinerRegistry>>,
+    pub mempool: Arc<Mutex<Mempool>>,
+    pub orphans: Arc<Mutex<OrphanPool>>,
+    // Números da mineração dos blocos minerados por este nó, pelo hash, para os recibos
+    pub mined_blocks: Arc<Mutex<HashMap<String, ReceiptStats>>>,
+    // Última passada do coletor de órfãos desta cadeia
+    pub last_gc: Arc<Mutex<Option<GcReport>>>,
+    // Saldos e estruturas derivadas de prefixos já consultados com ?at_height=
+    pub history: Arc<Mutex<HistoryCache>>,
+    pub metrics: Arc<Mutex<Metrics>>,
+    pub miner: Arc<Miner>,
+    pub webhooks: Arc<Mutex<WebhookRegistry>>,
+    pub alerts: Arc<Mutex<Alerts>>,
+    pub audit: Arc<Mutex<AuditLog>>,
+    pub jobs: Arc<Mutex<Jobs>>,
+    pub route_roles: Arc<Mutex<RouteRoles>>,
+    pub slo: Arc<Mutex<SloTracker>>,
+    // Bytes servidos por rota (do nó) e o tamanho cacheado de cada bloco (da cadeia)
+    pub bandwidth: Arc<Mutex<BandwidthTracker>>,
+    pub block_sizes: Arc<Mutex<BlockSizes>>,
+    pub cpu_limiter: Arc<Mutex<RateLimiter>>,
+    pub shedder: Arc<Mutex<LoadShedder>>,
+    // Vagas de espera de GET /tip
+    pub tip_polls: Arc<Semaphore>,
+    // Identidade do nó anunciada em /handshake
+    pub node_key: Arc<SigningKey>,
+    // Nome da cadeia deste estado; as demais ficam no registro compartilhado
+    pub namespace: String,
+    pub chains: Arc<Mutex<ChainRegistry>>,
+}
+
+impl AppState {
+    pub fn new(chain: ChainState, config: &Config) -> Self {
+        let (events, _) = broadcast::channel(64);
+        let pool = (config.candidate_pool_size > 0)
+            .then(|| Arc::new(CandidatePool::new(config.candidate_pool_size)));
+        let quarantine = Quarantine::load(config.data_file("quarantine.json"));
+        let metrics = Metrics::load(config.data_file("metrics.json"));
+        let mempool = Mempool::for_chain(config.mempool_capacity, chain.blocks());
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("cliente HTTP");
+        AppState {
+            config: Arc::new(config.clone()),
+            chain: Arc::new(Mutex::new(chain)),
+            events,
+            pool,
+            peers: Arc::new(Mutex::new(PeerRegistry::load(config.data_file("peers.json")))),
+            quarantine: Arc::new(Mutex::new(quarantine)),
+            http,
+            health_tasks: Arc::new(Mutex::new(DeepHealthTasks::default())),
+            templates: Arc::new(Mutex::new(TemplateRegistry::default())),
+            miners: Arc::new(Mutex::new(MinerRegistry::default())),
+            mempool: Arc::new(Mutex::new(mempool)),
+            orphans: Arc::new(Mutex::new(OrphanPool::default())),
+            last_gc: Arc::new(Mutex::new(None)),
+            history: Arc::new(Mutex::new(HistoryCache::new(config.history_cache_entries))),
+            mined_blocks: Arc::new(Mutex::new(HashMap::new())),
+            metrics: Arc::new(Mutex::new(metrics)),
+            miner: Arc::new(Miner::new(config.mining_threads, config.mining_intensity, config.mining_timeout())),
+            webhooks: Arc::new(Mutex::new(WebhookRegistry::load(config.data_file("webhooks.json")))),
+            alerts: Arc::new(Mutex::new(Alerts::new(config.alerts.clone()))),
+            audit: Arc::new(Mutex::new(AuditLog::default())),
+            jobs: Arc::new(Mutex::new(Jobs::load(config.data_file("jobs.json")))),
+            route_roles: Arc::new(Mutex::new(config.route_roles.clone())),
+            slo: Arc::new(Mutex::new(SloTracker::new(config.slo_targets.clone()))),
+            bandwidth: Arc::new(Mutex::new(BandwidthTracker::new())),
+            block_sizes: Arc::new(Mutex::new(BlockSizes::default())),
+            cpu_limiter: Arc::new(Mutex::new(RateLimiter::new(config.cpu_rate_limit))),
+            shedder: Arc::new(Mutex::new(LoadShedder::new(
+                config.shed_delay_ms,
+                Duration::from_secs(config.shed_recovery_secs),
+            ))),
+            tip_polls: Arc::new(Semaphore::new(config.tip_long_polls_max)),
+            node_key: Arc::new(load_node_key(config)),
+            namespace: DEFAULT_CHAIN.to_string(),
+            chains: Arc::new(Mutex::new(ChainRegistry::new(config.chain_namespaces_max))),
+        }
+    }
+
+    /// Estado de outra cadeia do nó: cadeia, eventos, mempool, órfãos e sua coleta, blocos minerados, modelos,
+    /// cache de prefixos, tamanhos dos blocos, peers e métricas próprios, gravados em `<DATA_DIR>/chains/<nome>`.
+    /// Mineradores, webhooks, alertas, auditoria, trabalhos canceláveis, papéis das rotas, SLOs, a contagem de
+    /// banda, o limite de CPU, o descarte de carga, as vagas de espera de /tip, a chave e o pool de threads
+    /// são do nó.
+    pub fn namespace(&self, name: &str, chain: ChainState, config: Config) -> Self {
+        let mut config = config;
+        config.data_dir = self.config.data_dir.as_ref().map(|dir| dir.join("chains").join(name));
+        if let Some(dir) = &config.data_dir {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                warn!("Não foi possível criar {}: {}", dir.display(), e);
+            }
+        }
+        let (events, _) = broadcast::channel(64);
+        let pool = (config.candidate_pool_size > 0)
+            .then(|| Arc::new(CandidatePool::new(config.candidate_pool_size)));
+        let mempool = Mempool::for_chain(config.mempool_capacity, chain.blocks());
+        AppState {
+            chain: Arc::new(Mutex::new(chain)),
+            events,
+            pool,
+            peers: Arc::new(Mutex::new(PeerRegistry::load(config.data_file("peers.json")))),
+            quarantine: Arc::new(Mutex::new(Quarantine::load(config.data_file("quarantine.json")))),
+            health_tasks: Arc::new(Mutex::new(DeepHealthTasks::default())),
+            templates: Arc::new(Mutex::new(TemplateRegistry::default())),
+            mempool: Arc::new(Mutex::new(mempool)),
+            orphans: Arc::new(Mutex::new(OrphanPool::default())),
+            last_gc: Arc::new(Mutex::new(None)),
+            history: Arc::new(Mutex::new(HistoryCache::new(config.history_cache_entries))),
+            block_sizes: Arc::new(Mutex::new(BlockSizes::default())),
+            mined_blocks: Arc::new(Mutex::new(HashMap::new())),
+            metrics: Arc::new(Mutex::new(Metrics::load(config.data_file("metrics.json")))),
+            namespace: name.to_string(),
+            config: Arc::new(config),
+            ..self.clone()
+        }
+    }
+}
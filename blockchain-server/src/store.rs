@@ -0,0 +1,131 @@
+// src/store.rs
+//! Arquivos JSON auxiliares em DATA_DIR (peers, webhooks, trabalhos), regravados a cada mudança.
+//! Sem DATA_DIR o caminho é `None` e tudo fica só em memória.
+use log::warn;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Conteúdo de `path`, ou o padrão se não houver arquivo; um arquivo corrompido é avisado e ignorado.
+pub fn load<T: DeserializeOwned + Default>(path: Option<&Path>, what: &str) -> T {
+    let Some(path) = path.filter(|p| p.exists()) else { return T::default() };
+    let parsed = fs::read(path)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()));
+    parsed.unwrap_or_else(|e| {
+        warn!("{} corrompido em {}: {}", what, path.display(), e);
+        T::default()
+    })
+}
+
+/// Grava `value` num arquivo temporário e o renomeia, para uma queda no meio não deixar JSON truncado.
+pub fn save<T: Serialize>(path: Option<&Path>, value: &T, what: &str) {
+    let Some(path) = path else { return };
+    let temp = path.with_extension("json.tmp");
+    let result = serde_json::to_vec_pretty(value)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| fs::write(&temp, bytes).map_err(|e| e.to_string()))
+        .and_then(|()| fs::rename(&temp, path).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        warn!("Falha ao persistir {} em {}: {}", what, path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deadline::{Deadline, JobOutcome, REQUEST_TIMEOUT};
+    use crate::handshake::Handshake;
+    use crate::peers::Penalty;
+    use crate::state::AppState;
+    use crate::testkit::{test_clock, test_config, test_state};
+    use axum::extract::FromRequestParts;
+    use axum::http::Request;
+    use blockchain_core::ChainState;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn node(data_dir: Option<PathBuf>) -> AppState {
+        let config = crate::Config { data_dir, ..test_config() };
+        test_state(ChainState::new(), &config, test_clock())
+    }
+
+    fn handshake() -> Handshake {
+        Handshake {
+            chain_id: "default".to_string(),
+            genesis_hash: ChainState::new().tip().hash.clone(),
+            height: 1,
+            rules_version: 1,
+            node_pubkey: "ab".repeat(32),
+            software_version: "0.1.0".to_string(),
+        }
+    }
+
+    // Um trabalho que fica em andamento até o teste acabar, como num nó que caiu no meio dele
+    async fn start_endless_job(state: &AppState) {
+        let request = Request::post("/chain/resolve").header(REQUEST_TIMEOUT, "60000").body(()).unwrap();
+        let (mut parts, _) = request.into_parts();
+        let Ok(deadline) = Deadline::from_request_parts(&mut parts, state).await;
+        tokio::spawn(async move { deadline.within(std::future::pending::<()>()).await });
+        for _ in 0..100 {
+            if !state.jobs.lock().unwrap().list().is_empty() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("o trabalho não começou");
+    }
+
+    #[tokio::test]
+    async fn peers_webhooks_and_jobs_survive_a_restart() {
+        let dir = std::env::temp_dir().join(format!("store-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let state = node(Some(dir.clone()));
+
+        let url = "http://peer.example:8000";
+        state.peers.lock().unwrap().add(&format!("{}/", url), &handshake(), 1_700_000_000);
+        assert_eq!(state.peers.lock().unwrap().record_invalid_chain(url), Penalty::Demoted);
+        // Porta 1 recusa a conexão: a entrega falha e conta
+        let hook_url = "http://127.0.0.1:1/hook".to_string();
+        let hook = state.webhooks.lock().unwrap().add(hook_url.clone(), vec!["block".into()], Some("s3cret".into()));
+        crate::webhooks::dispatch(&state, "block", serde_json::json!({ "index": 1 }));
+        for _ in 0..200 {
+            if state.webhooks.lock().unwrap().list()[0].failures == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        start_endless_job(&state).await;
+
+        let restored = node(Some(dir.clone()));
+        let peers = restored.peers.lock().unwrap().list();
+        assert_eq!(peers.len(), 1);
+        let peer = &peers[0];
+        assert_eq!((peer.url.as_str(), peer.node_pubkey.as_str()), (url, "ab".repeat(32).as_str()));
+        assert_eq!((peer.trust, peer.healthy, peer.invalid_chains), (-10, false, 1));
+        assert_eq!((peer.software_version.as_str(), peer.handshake_at), ("0.1.0", 1_700_000_000));
+
+        let hooks = restored.webhooks.lock().unwrap().list();
+        assert_eq!(hooks.len(), 1);
+        assert_eq!((hooks[0].id, &hooks[0].url, &hooks[0].events), (hook.id, &hook_url, &vec!["block".to_string()]));
+        assert!(hooks[0].has_secret);
+        assert_eq!(hooks[0].failures, 1);
+        let saved = std::fs::read_to_string(dir.join("webhooks.json")).unwrap();
+        assert!(saved.contains("s3cret"), "o segredo precisa sobreviver para as próximas entregas");
+
+        let jobs = restored.jobs.lock().unwrap().list();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!((jobs[0].operation.as_str(), jobs[0].outcome), ("/chain/resolve", JobOutcome::Interrupted));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn without_data_dir_nothing_is_kept() {
+        let state = node(None);
+        state.peers.lock().unwrap().add("http://peer.example:8000", &handshake(), 1);
+        state.webhooks.lock().unwrap().add("http://hook.example".into(), vec!["block".into()], None);
+        let restored = node(None);
+        assert!(restored.peers.lock().unwrap().list().is_empty());
+        assert!(restored.webhooks.lock().unwrap().list().is_empty());
+    }
+}
@@ -0,0 +1,226 @@
+// src/sync.rs
+use axum::{extract::State, Json};
+use blockchain_core::{fork_choice, Block, CancelToken, ChainError, ChainState, ChainSummary, Preference};
+use log::{info, warn};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::bandwidth::BlockSizes;
+use crate::deadline::{Cancelled, Deadline};
+use crate::handshake::{fetch_handshake, mismatches, Mismatch};
+use crate::invariants::debug_check;
+use crate::peers::{fetch_chain, register, Penalty, Registration};
+use crate::quarantine::QuarantineEntry;
+use crate::state::AppState;
+use crate::webhooks;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PeerOutcome {
+    Unreachable { error: String },
+    Incompatible { mismatches: Vec<Mismatch> },
+    NotHeavier { height: usize, work: u64 },
+    Valid { height: usize },
+    Quarantined { height: usize, quarantine_id: u64, penalty: Penalty },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerReport {
+    pub peer: String,
+    #[serde(flatten)]
+    pub outcome: PeerOutcome,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolveReport {
+    pub replaced: bool,
+    pub height: usize,
+    pub source: Option<String>,
+    pub peers: Vec<PeerReport>,
+}
+
+pub(crate) fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// Índice do último bloco em comum entre as duas cadeias
+fn fork_point(local: &[Block], remote: &[Block]) -> u64 {
+    let common = local.iter().zip(remote).take_while(|(a, b)| a.hash == b.hash).count();
+    common.saturating_sub(1) as u64
+}
+
+/// Consulta todos os peers e adota a cadeia válida que vence a local e as dos outros peers em
+/// `fork_choice`. Cadeias que venceriam porém inválidas vão para a quarentena; `token` interrompe a validação.
+pub async fn resolve(state: &AppState, token: Arc<CancelToken>) -> ResolveReport {
+    let urls = state.peers.lock().unwrap().urls();
+    let mut reports = Vec::new();
+    let mut best: Option<(String, ChainState)> = None;
+
+    for url in urls {
+        // Repete o handshake vencido: o peer pode ter sido reiniciado em outra cadeia
+        if state.peers.lock().unwrap().handshake_due(&url, now_secs()) {
+            match fetch_handshake(&state.http, &url).await {
+                Ok(handshake) => {
+                    let found = mismatches(state, &handshake);
+                    if !found.is_empty() {
+                        warn!("Peer {} ficou incompatível: {:?}", url, found);
+                    }
+                    state.peers.lock().unwrap().record_handshake(&url, &handshake, found, now_secs());
+                }
+                Err(error) => {
+                    state.peers.lock().unwrap().mark_healthy(&url, false);
+                    reports.push(PeerReport { peer: url, outcome: PeerOutcome::Unreachable { error } });
+                    continue;
+                }
+            }
+        }
+        if let Some(mismatches) = state.peers.lock().unwrap().incompatibility(&url) {
+            reports.push(PeerReport { peer: url, outcome: PeerOutcome::Incompatible { mismatches } });
+            continue;
+        }
+        let blocks = match fetch_chain(&state.http, &url, &state.config.peer_api_key).await {
+            Ok(blocks) => blocks,
+            Err(error) => {
+                state.peers.lock().unwrap().mark_healthy(&url, false);
+                reports.push(PeerReport { peer: url, outcome: PeerOutcome::Unreachable { error } });
+                continue;
+            }
+        };
+        // O trabalho sai dos primos, então dá para descartar a cadeia antes de validá-la
+        let summary = ChainSummary::of(&blocks);
+        let height = summary.height;
+        let rival = best
+            .as_ref()
+            .map(|(_, chain)| chain.summary())
+            .unwrap_or_else(|| state.chain.lock().unwrap().summary());
+        if fork_choice(&summary, &rival) != Preference::First {
+            state.peers.lock().unwrap().mark_healthy(&url, true);
+            reports.push(PeerReport { peer: url, outcome: PeerOutcome::NotHeavier { height, work: summary.work } });
+            continue;
+        }
+
+        let validated = blocks.clone();
+        let rules = state.chain.lock().unwrap().rules().clone();
+        let validation_token = token.clone();
+        let validation = move || ChainState::from_blocks_cancellable(validated, rules, &validation_token);
+        match tokio::task::spawn_blocking(validation).await {
+            Ok(Ok(candidate)) => {
+                state.peers.lock().unwrap().record_valid_chain(&url);
+                reports.push(PeerReport { peer: url.clone(), outcome: PeerOutcome::Valid { height } });
+                best = Some((url, candidate));
+            }
+            // Interrompida: nada se sabe sobre a cadeia do peer
+            Ok(Err(ChainError::Cancelled { .. })) => break,
+            Ok(Err(error)) => {
+                let fork_point = fork_point(state.chain.lock().unwrap().blocks(), &blocks);
+                let entry = QuarantineEntry {
+                    id: 0,
+                    peer: url.clone(),
+                    peer_height: height,
+                    fork_point,
+                    invalid_block: blocks.get(error.index()).cloned(),
+                    invariant: error.invariant().to_string(),
+                    error: error.to_string(),
+                    detected_at: now_secs(),
+                };
+                let quarantine_id = state.quarantine.lock().unwrap().add(entry);
+                let penalty = state.peers.lock().unwrap().record_invalid_chain(&url);
+                warn!("Cadeia inválida de {} em quarentena: {}", url, error);
+                reports.push(PeerReport {
+                    peer: url,
+                    outcome: PeerOutcome::Quarantined { height, quarantine_id, penalty },
+                });
+            }
+            Err(e) => {
+                reports.push(PeerReport {
+                    peer: url,
+                    outcome: PeerOutcome::Unreachable { error: e.to_string() },
+                });
+            }
+        }
+    }
+
+    let mut source = None;
+    if let Some((url, candidate)) = best {
+        if replace_chain(state, &url, candidate).await {
+            source = Some(url);
+        }
+    }
+    let replaced = source.is_some();
+    if replaced {
+        debug_check(state, "reorg");
+    }
+
+    let height = state.chain.lock().unwrap().height();
+    ResolveReport { replaced, height, source, peers: reports }
+}
+
+/// Troca a cadeia local por `candidate`, já validada, se ela ainda vencer. As estruturas derivadas (índices,
+/// épocas, tamanhos dos blocos) são montadas fora do lock e entram junto com a cadeia numa única troca sob
+/// ele, então cada leitura vê a cadeia antiga inteira ou a nova inteira. Na troca as minerações desta cadeia
+/// são paradas; depois dela sai um único evento `chain_replaced`.
+async fn replace_chain(state: &AppState, url: &str, candidate: ChainState) -> bool {
+    let epoch_size = state.chain.lock().unwrap().epoch_size();
+    let prepare = move || {
+        let mut candidate = candidate;
+        candidate.set_epoch_size(epoch_size);
+        let mut sizes = BlockSizes::default();
+        sizes.total(candidate.blocks());
+        (candidate, sizes)
+    };
+    let Ok((mut candidate, sizes)) = tokio::task::spawn_blocking(prepare).await else { return false };
+
+    let (previous, event) = {
+        let mut guard = state.chain.lock().unwrap();
+        // A cadeia local pode ter crescido enquanto validávamos
+        if fork_choice(&candidate.summary(), &guard.summary()) != Preference::First {
+            return false;
+        }
+        // Só refaz as épocas se o tamanho mudou depois da preparação
+        candidate.set_epoch_size(guard.epoch_size());
+        candidate.inherit_difficulty(&guard);
+        let previous = std::mem::replace(&mut *guard, candidate);
+        *state.block_sizes.lock().unwrap() = sizes;
+        state.mempool.lock().unwrap().confirm_chain(guard.blocks());
+        let cancelled_mining = state.miner.cancel_chain(&state.namespace);
+        state.metrics.lock().unwrap().counters_mut().chain_replacements += 1;
+        info!("Cadeia substituída pela de {} (altura {})", url, guard.height());
+        let _ = state.events.send(guard.tip().clone());
+        let event = serde_json::json!({
+            "chain": state.namespace,
+            "source": url,
+            "fork_point": fork_point(previous.blocks(), guard.blocks()),
+            "previous_height": previous.height(),
+            "previous_tip_hash": previous.tip().hash,
+            "height": guard.height(),
+            "tip_hash": guard.tip().hash,
+            "cancelled_mining_jobs": cancelled_mining,
+        });
+        (previous, event)
+    };
+    // A cadeia antiga é liberada fora do lock
+    drop(previous);
+    webhooks::dispatch(state, "chain_replaced", event);
+    true
+}
+
+/// Registra os peers configurados que passam no handshake e sincroniza uma vez na inicialização.
+pub async fn bootstrap(state: AppState, peers: Vec<String>) {
+    for url in &peers {
+        match register(&state, url).await {
+            Registration::Registered(_) => {}
+            Registration::Unreachable(error) => warn!("Peer {} ignorado, handshake falhou: {}", url, error),
+            Registration::Incompatible(found) => warn!("Peer {} ignorado, incompatível: {:?}", url, found),
+        }
+    }
+    let report = resolve(&state, Arc::new(CancelToken::new())).await;
+    info!("Sincronização inicial concluída: altura {}, substituída: {}", report.height, report.replaced);
+}
+
+pub async fn resolve_handler(
+    State(state): State<AppState>,
+    deadline: Deadline,
+) -> Result<Json<ResolveReport>, Cancelled> {
+    deadline.within(resolve(&state, deadline.token())).await.map(Json)
+}
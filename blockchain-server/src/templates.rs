@@ -0,0 +1,253 @@
+// src/templates.rs
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use blockchain_core::signature::verify_block_signature;
+use blockchain_core::{Block, Difficulty};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::errors::ApiError;
+use crate::invariants::debug_check;
+use crate::state::AppState;
+use crate::sync::now_secs;
+
+// Segundos que um desafio de /mine/challenge continua válido
+const CHALLENGE_TTL_SECS: u64 = 60;
+
+/// Trabalho entregue a mineradores externos. Vale enquanto a ponta não passar de `expires_after_height`
+/// e, para desafios, até o instante `expires_at` (unix, segundos).
+#[derive(Debug, Clone, Serialize)]
+pub struct Template {
+    pub template_id: String,
+    pub index: u64,
+    pub prev_hash: String,
+    pub rules_version: u32,
+    pub difficulty: Difficulty,
+    // Alvo de hash que o bloco deve declarar (regras v4); 0 antes delas
+    pub hash_scale: u64,
+    // Recompensa que o bloco deve declarar (regras v6), paga a quem ele puser em `coinbase`; 0 antes delas
+    pub reward: u64,
+    pub issued_at_height: u64,
+    pub expires_after_height: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+impl Template {
+    fn is_live(&self, tip_index: u64, now: u64) -> bool {
+        tip_index <= self.expires_after_height && self.expires_at.is_none_or(|at| now <= at)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TemplateRegistry {
+    templates: HashMap<String, Template>,
+}
+
+impl TemplateRegistry {
+    fn issue(&mut self, template: Template, tip_index: u64) -> Template {
+        // Descarta os modelos cuja janela já passou
+        let now = now_secs();
+        self.templates.retain(|_, t| t.is_live(tip_index, now));
+        self.templates.insert(template.template_id.clone(), template.clone());
+        template
+    }
+
+    fn get(&self, template_id: &str) -> Option<Template> {
+        self.templates.get(template_id).cloned()
+    }
+}
+
+fn issue_template(state: &AppState, expires_at: Option<u64>) -> (Template, Block) {
+    let guard = state.chain.lock().unwrap();
+    let tip = guard.tip();
+    let template = Template {
+        template_id: Uuid::new_v4().to_string(),
+        index: tip.index + 1,
+        prev_hash: tip.hash.clone(),
+        rules_version: guard.next_rules_version(),
+        difficulty: guard.difficulty.clone(),
+        hash_scale: guard.next_hash_scale(),
+        reward: guard.next_reward(),
+        issued_at_height: tip.index,
+        expires_after_height: tip.index + state.config.template_window,
+        expires_at,
+    };
+    (state.templates.lock().unwrap().issue(template, tip.index), tip.clone())
+}
+
+pub async fn template_handler(State(state): State<AppState>) -> Json<Template> {
+    Json(issue_template(&state, None).0)
+}
+
+/// Desafio de mineração com validade de CHALLENGE_TTL_SECS; resgatado em /mine/submit.
+pub async fn challenge_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let (challenge, prev) = issue_template(&state, Some(now_secs() + CHALLENGE_TTL_SECS));
+    Json(serde_json::json!({
+        "challenge_id": challenge.template_id,
+        "prev_hash": prev.hash,
+        "prev_prime": prev.prime,
+        "prev_index": prev.index,
+        "expires_at": challenge.expires_at,
+        "rules_version": challenge.rules_version,
+        "difficulty": challenge.difficulty,
+        "hash_scale": challenge.hash_scale,
+        "reward": challenge.reward,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct CompactSubmission {
+    compact: String,
+    template_id: Option<String>,
+    miner_pubkey: Option<String>,
+    signature: Option<String>,
+}
+
+/// Bloco em `Block::to_compact_string`, aceito com as mesmas regras de /mine/submit.
+pub async fn submit_compact_handler(
+    State(state): State<AppState>,
+    Json(body): Json<CompactSubmission>,
+) -> Result<Json<serde_json::Value>, Response> {
+    let block = Block::from_compact_string(&body.compact).map_err(|e| {
+        reject(StatusCode::BAD_REQUEST, serde_json::json!({ "error": format!("Invalid compact block: {}", e) }))
+    })?;
+    let submission = Submission {
+        template_id: body.template_id,
+        block,
+        miner_pubkey: body.miner_pubkey,
+        signature: body.signature,
+    };
+    accept_submission(&state, submission).await
+}
+
+#[derive(Deserialize)]
+pub struct Submission {
+    #[serde(alias = "challenge_id")]
+    pub template_id: Option<String>,
+    pub block: Block,
+    pub miner_pubkey: Option<String>,
+    pub signature: Option<String>,
+}
+
+fn reject(status: StatusCode, body: serde_json::Value) -> Response {
+    ApiError::json(status, body).into_response()
+}
+
+/// Recebe um bloco minerado fora do nó. O bloco é aceito se o modelo ainda estiver na janela,
+/// ou, com modelo vencido, se vier assinado por um minerador registrado e ainda apontar para a ponta.
+pub async fn submit_handler(
+    State(state): State<AppState>,
+    Json(submission): Json<Submission>,
+) -> Result<Json<serde_json::Value>, Response> {
+    accept_submission(&state, submission).await
+}
+
+/// Validação e anexação compartilhadas por /mine/submit e /blocks/compact.
+pub async fn accept_submission(
+    state: &AppState,
+    submission: Submission,
+) -> Result<Json<serde_json::Value>, Response> {
+    let block = submission.block;
+    let template = submission.template_id.as_deref().and_then(|id| state.templates.lock().unwrap().get(id));
+
+    let signed = match (&submission.miner_pubkey, &submission.signature) {
+        (Some(pubkey), Some(signature)) => {
+            if !state.miners.lock().unwrap().is_registered(pubkey) {
+                return Err(reject(StatusCode::FORBIDDEN, serde_json::json!({ "error": "Miner pubkey is not registered" })));
+            }
+            if !verify_block_signature(pubkey, signature, &block) {
+                return Err(reject(StatusCode::FORBIDDEN, serde_json::json!({ "error": "Invalid miner signature" })));
+            }
+            true
+        }
+        _ => false,
+    };
+
+    let mut guard = state.chain.lock().unwrap();
+    let tip = guard.tip().clone();
+
+    let now = now_secs();
+    let template_valid = template.as_ref().is_some_and(|t| {
+        t.is_live(tip.index, now) && t.index == block.index && t.prev_hash == block.prev_hash
+    });
+    if !template_valid && !signed {
+        let (status, error) = match (&submission.template_id, &template) {
+            (None, _) => (StatusCode::BAD_REQUEST, "Submission needs a template_id or a miner signature"),
+            (Some(_), None) => (StatusCode::NOT_FOUND, "Unknown or pruned template"),
+            (Some(_), Some(t)) if t.is_live(tip.index, now) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "Block does not match the template index and prev_hash")
+            }
+            (Some(_), Some(_)) => (StatusCode::GONE, "Template expired; sign the block with a registered miner key"),
+        };
+        return Err(reject(status, serde_json::json!({ "error": error, "tip_index": tip.index })));
+    }
+
+    if block.prev_hash != tip.hash {
+        // Bloco atrasado: informa onde ele ainda poderia entrar como candidato de fork
+        let parent = guard.blocks().iter().find(|b| b.hash == block.prev_hash).map(|b| b.index);
+        return Err(match parent {
+            Some(fork_point) => reject(StatusCode::CONFLICT, serde_json::json!({
+                "error": "Block is stale: the tip moved",
+                "status": "stale",
+                "fork_point": fork_point,
+                "earliest_adoption_height": fork_point + 1,
+                "tip_index": tip.index,
+                "blocks_behind": tip.index - fork_point,
+            })),
+            None => reject(StatusCode::UNPROCESSABLE_ENTITY, serde_json::json!({
+                "error": "Block parent is not part of this chain",
+                "tip_index": tip.index,
+            })),
+        });
+    }
+
+    let min_digits = template.as_ref().map_or(guard.difficulty.min_digits, |t| t.difficulty.min_digits);
+    if block.prime.digits() < min_digits {
+        return Err(reject(StatusCode::UNPROCESSABLE_ENTITY, serde_json::json!({
+            "error": format!("prime has {} digits, difficulty requires {}", block.prime.digits(), min_digits),
+        })));
+    }
+
+    // As regras só exigem o alvo que o bloco declara; o nó exige o da sua dificuldade
+    let hash_scale = template.as_ref().map_or(guard.next_hash_scale(), |t| t.hash_scale);
+    if block.hash_scale < hash_scale {
+        return Err(reject(StatusCode::UNPROCESSABLE_ENTITY, serde_json::json!({
+            "error": format!("hash_scale {} is below the difficulty's {}", block.hash_scale, hash_scale),
+        })));
+    }
+
+    if let Err(e) = guard.append(block.clone()) {
+        return Err(reject(StatusCode::UNPROCESSABLE_ENTITY, serde_json::json!({
+            "error": e.to_string(),
+            "invariant": e.invariant(),
+        })));
+    }
+    state.mempool.lock().unwrap().confirm(&block.transactions);
+    let height = guard.height();
+    drop(guard);
+    debug_check(state, "submit");
+
+    let via = match &template {
+        Some(t) if template_valid && t.expires_at.is_some() => "challenge",
+        _ if template_valid => "template",
+        _ => "signature",
+    };
+    info!("Bloco {} recebido de minerador externo ({})", block.index, via);
+    state.metrics.lock().unwrap().counters_mut().blocks_submitted += 1;
+    let _ = state.events.send(block.clone());
+
+    Ok(Json(serde_json::json!({
+        "accepted": true,
+        "index": block.index,
+        "hash": block.hash,
+        "height": height,
+        "via": via,
+    })))
+}
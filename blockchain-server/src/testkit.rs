@@ -0,0 +1,256 @@
+// src/testkit.rs
+//! Nó pronto para testes: armazenamento só em memória, chaves de API conhecidas, uma thread de mineração
+//! com semente fixa e relógio manual. As cadeias vêm de `blockchain_core::testkit::ChainBuilder`.
+//! `Cluster` sobe vários desses nós no mesmo processo, cada um servindo HTTP numa porta efêmera.
+// Cada teste usa só parte destes atalhos
+#![allow(dead_code)]
+use axum::Router;
+use blockchain_core::testkit::{trivial_difficulty, ManualClock};
+use blockchain_core::{fork_choice, Block, ChainState, Intensity, Preference};
+use std::sync::{Arc, Once};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+use crate::config::{Config, MiningSeed};
+use crate::miner::Miner;
+use crate::middleware::Role;
+use crate::state::AppState;
+
+pub const ADMIN_KEY: &str = "test-admin-key";
+pub const MINE_KEY: &str = "test-mine-key";
+pub const READ_KEY: &str = "test-read-key";
+
+// Instante inicial do relógio manual, igual ao primeiro bloco de `GenesisConfig::default()`
+const CLOCK_START_MS: u64 = 1_700_000_000_000;
+// Pausa entre rodadas de sincronização do cluster, e o prazo dos cenários para convergir
+const SYNC_POLL: Duration = Duration::from_millis(50);
+const CONVERGENCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Configuração de `Config::from_env` sem diretório de dados, peers nem calibração, com as chaves acima.
+pub fn test_config() -> Config {
+    // from_env exige API_KEY; o valor é descartado logo abaixo
+    static API_KEY: Once = Once::new();
+    API_KEY.call_once(|| {
+        if std::env::var_os("API_KEY").is_none() {
+            std::env::set_var("API_KEY", ADMIN_KEY);
+        }
+    });
+    Config {
+        data_dir: None,
+        bootstrap_peers: Vec::new(),
+        bootstrap_calibration_secs: 0.0,
+        mining_threads: 1,
+        mining_seed: MiningSeed::Fixed(0),
+        mining_intensity: Intensity::FULL,
+        peer_api_key: ADMIN_KEY.to_string(),
+        node_key: None,
+        api_keys: vec![
+            (ADMIN_KEY.to_string(), Role::Admin),
+            (MINE_KEY.to_string(), Role::Mine),
+            (READ_KEY.to_string(), Role::Read),
+        ],
+        ..Config::from_env()
+    }
+}
+
+/// Relógio manual no instante do primeiro bloco das cadeias de teste.
+pub fn test_clock() -> Arc<ManualClock> {
+    Arc::new(ManualClock::at(Duration::from_millis(CLOCK_START_MS)))
+}
+
+/// Estado do nó sobre `chain`, com a mineração seguindo `clock` e a cadeia já registrada como default.
+pub fn test_state(chain: ChainState, config: &Config, clock: Arc<ManualClock>) -> AppState {
+    let mut state = AppState::new(chain, config);
+    let miner = Miner::new(config.mining_threads, config.mining_intensity, config.mining_timeout());
+    state.miner = Arc::new(miner.with_clock(clock));
+    crate::namespaces::register_default(&state);
+    state
+}
+
+/// Rotas completas do nó, com autorização, auditoria e SLOs, como em produção.
+pub fn test_router(state: AppState) -> Router {
+    crate::node_router(state)
+}
+
+/// Atalho para o caso comum: `test_config`, `test_clock` e as rotas.
+pub fn test_node(chain: ChainState) -> (Router, AppState, Arc<ManualClock>) {
+    let clock = test_clock();
+    let state = test_state(chain, &test_config(), clock.clone());
+    (test_router(state.clone()), state, clock)
+}
+
+/// Nó de um `Cluster`: estado próprio e um servidor axum de verdade em 127.0.0.1, parado ao ser descartado.
+pub struct ClusterNode {
+    pub url: String,
+    pub state: AppState,
+    pub clock: Arc<ManualClock>,
+    server: JoinHandle<()>,
+}
+
+impl ClusterNode {
+    /// Sobe um nó no gênesis com dificuldade trivial; `seed` separa a mineração dele da dos outros nós.
+    pub async fn start(seed: u64) -> ClusterNode {
+        let mut chain = ChainState::new();
+        chain.difficulty = trivial_difficulty();
+        let config = Config { mining_seed: MiningSeed::Fixed(seed), ..test_config() };
+        let clock = test_clock();
+        let state = test_state(chain, &config, clock.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("porta efêmera");
+        let url = format!("http://{}", listener.local_addr().expect("endereço local"));
+        let router = test_router(state.clone());
+        let server = tokio::spawn(async move {
+            let _ = axum::serve(listener, router).await;
+        });
+        ClusterNode { url, state, clock, server }
+    }
+
+    pub fn tip(&self) -> Block {
+        self.state.chain.lock().unwrap().tip().clone()
+    }
+}
+
+impl Drop for ClusterNode {
+    fn drop(&mut self) {
+        self.server.abort();
+        self.state.miner.shutdown();
+    }
+}
+
+/// Nós no mesmo processo ligados só por HTTP: peers entram por POST /peers (com o handshake) e a
+/// cadeia anda de um nó a outro por POST /chain/resolve, como entre nós reais.
+pub struct Cluster {
+    pub nodes: Vec<ClusterNode>,
+    pub client: reqwest::Client,
+    // Nós trocados por `restart`, para que cada substituto minere com semente nova
+    restarts: u64,
+}
+
+impl Cluster {
+    /// Sobe `n` nós, cada um registrado como peer de todos os outros.
+    pub async fn start(n: usize) -> Cluster {
+        let mut nodes = Vec::with_capacity(n);
+        for seed in 0..n as u64 {
+            nodes.push(ClusterNode::start(seed).await);
+        }
+        let cluster = Cluster { nodes, client: reqwest::Client::new(), restarts: 0 };
+        cluster.heal().await;
+        cluster
+    }
+
+    pub fn node(&self, i: usize) -> &ClusterNode {
+        &self.nodes[i]
+    }
+
+    /// Envia com a chave `key`; resposta fora de 2xx vira erro com o status e o corpo.
+    pub async fn send(&self, request: reqwest::RequestBuilder, key: &str) -> Result<serde_json::Value, String> {
+        let response = request.header("x-api-key", key).send().await.map_err(|e| e.to_string())?;
+        let status = response.status();
+        let body = response.text().await.map_err(|e| e.to_string())?;
+        if !status.is_success() {
+            return Err(format!("{}: {}", status, body));
+        }
+        serde_json::from_str(&body).map_err(|e| e.to_string())
+    }
+
+    /// Registra o nó `b` como peer do nó `a`.
+    pub async fn connect(&self, a: usize, b: usize) {
+        let request = self.client.post(format!("{}/peers", self.nodes[a].url));
+        let body = serde_json::json!({ "url": self.nodes[b].url });
+        if let Err(e) = self.send(request.json(&body), ADMIN_KEY).await {
+            panic!("nó {} não registrou o nó {}: {}", a, b, e);
+        }
+    }
+
+    /// Liga todos os pares de nós; desfaz `partition`.
+    pub async fn heal(&self) {
+        for a in 0..self.nodes.len() {
+            for b in (0..self.nodes.len()).filter(|&b| b != a) {
+                self.connect(a, b).await;
+            }
+        }
+    }
+
+    /// Separa `group` do resto: cada lado esquece os peers do outro.
+    pub fn partition(&self, group: &[usize]) {
+        for (a, node) in self.nodes.iter().enumerate() {
+            let mut peers = node.state.peers.lock().unwrap();
+            for (b, other) in self.nodes.iter().enumerate() {
+                if group.contains(&a) != group.contains(&b) {
+                    peers.remove(&other.url);
+                }
+            }
+        }
+    }
+
+    /// Troca o nó `i` por um novo no gênesis, como um reinício com armazenamento vazio. O novo nó tem
+    /// outro endereço; os demais esquecem o antigo e todos os pares são ligados de novo.
+    pub async fn restart(&mut self, i: usize) {
+        let seed = self.nodes.len() as u64 + self.restarts;
+        self.restarts += 1;
+        let old = std::mem::replace(&mut self.nodes[i], ClusterNode::start(seed).await);
+        for node in &self.nodes {
+            node.state.peers.lock().unwrap().remove(&old.url);
+        }
+        self.heal().await;
+    }
+
+    /// Minera um bloco por GET /mine no nó `i` e devolve a nova ponta dele.
+    pub async fn mine(&self, i: usize) -> Block {
+        let request = self.client.get(format!("{}/mine", self.nodes[i].url));
+        if let Err(e) = self.send(request, MINE_KEY).await {
+            panic!("mineração no nó {} falhou: {}", i, e);
+        }
+        self.nodes[i].tip()
+    }
+
+    /// Uma rodada de POST /chain/resolve em cada nó, em ordem.
+    pub async fn resolve_all(&self) {
+        for (i, node) in self.nodes.iter().enumerate() {
+            let request = self.client.post(format!("{}/chain/resolve", node.url));
+            if let Err(e) = self.send(request, ADMIN_KEY).await {
+                panic!("sincronização do nó {} falhou: {}", i, e);
+            }
+        }
+    }
+
+    pub fn tips(&self) -> Vec<String> {
+        self.nodes.iter().map(|node| node.tip().hash).collect()
+    }
+
+    pub fn converged(&self) -> bool {
+        self.tips().windows(2).all(|pair| pair[0] == pair[1])
+    }
+
+    /// Ponta que `fork_choice` escolhe entre as dos nós `a` e `b`.
+    pub fn preferred_tip(&self, a: usize, b: usize) -> String {
+        let (first, second) = (self.nodes[a].state.chain.lock().unwrap(), self.nodes[b].state.chain.lock().unwrap());
+        match fork_choice(&first.summary(), &second.summary()) {
+            Preference::First | Preference::Same => first.tip().hash.clone(),
+            Preference::Second => second.tip().hash.clone(),
+        }
+    }
+
+    /// Repete `resolve_all` até todos os nós terem a mesma ponta, que é devolvida; ao fim do prazo,
+    /// as pontas de cada nó.
+    pub async fn wait_for_convergence(&self, timeout: Duration) -> Result<String, Vec<String>> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if self.converged() {
+                return Ok(self.nodes[0].tip().hash);
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(self.tips());
+            }
+            self.resolve_all().await;
+            tokio::time::sleep(SYNC_POLL).await;
+        }
+    }
+
+    /// Como `wait_for_convergence`, em pânico se os nós não convergirem em CONVERGENCE_TIMEOUT.
+    pub async fn assert_converged(&self) -> String {
+        self.wait_for_convergence(CONVERGENCE_TIMEOUT)
+            .await
+            .unwrap_or_else(|tips| panic!("nós não convergiram: {:?}", tips))
+    }
+}
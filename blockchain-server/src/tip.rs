@@ -0,0 +1,146 @@
+// src/tip.rs
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{timeout_at, Instant};
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+
+// Espera padrão e máxima de GET /tip, em segundos
+const DEFAULT_WAIT_SECS: u64 = 30;
+const MAX_WAIT_SECS: u64 = 60;
+const RETRY_AFTER_SECS: u64 = 1;
+
+#[derive(Deserialize)]
+pub struct TipQuery {
+    wait: Option<u64>,
+    known_hash: Option<String>,
+}
+
+/// Ponta da cadeia para mineradores que não mantêm SSE nem WebSocket. Sem `known_hash`, ou se a ponta
+/// já é outra, responde na hora; senão espera até `wait` segundos (padrão 30, no máximo 60) por uma
+/// ponta nova e devolve 304 se nada mudar. Esperas simultâneas além de `TIP_LONG_POLLS_MAX` recebem 503.
+pub async fn tip_handler(State(state): State<AppState>, Query(query): Query<TipQuery>) -> Response {
+    // Inscreve antes de ler a ponta, para não perder um bloco anexado entre uma coisa e outra
+    let mut events = state.events.subscribe();
+    let tip = state.chain.lock().unwrap().tip().clone();
+    let Some(known) = query.known_hash.filter(|known| *known == tip.hash) else {
+        return Json(tip).into_response();
+    };
+    let wait = query.wait.unwrap_or(DEFAULT_WAIT_SECS).min(MAX_WAIT_SECS);
+    if wait == 0 {
+        return StatusCode::NOT_MODIFIED.into_response();
+    }
+    // A vaga é devolvida quando a resposta sai, inclusive se o cliente desconectar
+    let Ok(_slot) = state.tip_polls.clone().try_acquire_owned() else {
+        let message = format!("Too many tip long-polls in progress (max {})", state.config.tip_long_polls_max);
+        let error = ApiError::new(StatusCode::SERVICE_UNAVAILABLE, message);
+        return ([(header::RETRY_AFTER, RETRY_AFTER_SECS.to_string())], error).into_response();
+    };
+
+    let deadline = Instant::now() + Duration::from_secs(wait);
+    loop {
+        match timeout_at(deadline, events.recv()).await {
+            Err(_) | Ok(Err(RecvError::Closed)) => return StatusCode::NOT_MODIFIED.into_response(),
+            // O evento pode ser de uma cadeia já substituída: vale a ponta atual
+            Ok(Ok(_) | Err(RecvError::Lagged(_))) => {
+                let tip = state.chain.lock().unwrap().tip().clone();
+                if tip.hash != known {
+                    return Json(tip).into_response();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{test_clock, test_config, test_router, test_state, MINE_KEY};
+    use axum::{body::Body, extract::Request, Router};
+    use blockchain_core::ChainState;
+    use tower::ServiceExt;
+
+    fn router(tip_long_polls_max: usize) -> Router {
+        let config = crate::Config { tip_long_polls_max, ..test_config() };
+        test_router(test_state(ChainState::new(), &config, test_clock()))
+    }
+
+    async fn get(router: &Router, uri: &str) -> Response {
+        let request = Request::get(uri).header("x-api-key", MINE_KEY).body(Body::empty()).unwrap();
+        router.clone().oneshot(request).await.unwrap()
+    }
+
+    async fn tip_hash(router: &Router) -> String {
+        let bytes = axum::body::to_bytes(get(router, "/tip").await.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()["hash"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn stale_hash_answers_at_once() {
+        let router = router(4);
+        let response = get(&router, "/tip?known_hash=old&wait=60").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let known = tip_hash(&router).await;
+        let response = get(&router, &format!("/tip?known_hash={}&wait=0", known)).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn waiter_wakes_when_a_block_is_mined() {
+        let router = router(4);
+        let known = tip_hash(&router).await;
+        let waiter = tokio::spawn({
+            let router = router.clone();
+            async move { get(&router, &format!("/tip?known_hash={}&wait=30", known)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!waiter.is_finished(), "a ponta ainda é a conhecida");
+
+        let started = std::time::Instant::now();
+        assert_eq!(get(&router, "/mine").await.status(), StatusCode::OK);
+        let response = tokio::time::timeout(Duration::from_secs(5), waiter).await.unwrap().unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(started.elapsed() < Duration::from_secs(2), "a espera deveria sair logo depois do bloco");
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let block: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(block["hash"].as_str(), Some(tip_hash(&router).await.as_str()));
+        assert_eq!(block["index"], 1);
+    }
+
+    #[tokio::test]
+    async fn unchanged_tip_times_out_with_304() {
+        let router = router(4);
+        let known = tip_hash(&router).await;
+        let started = std::time::Instant::now();
+        let response = get(&router, &format!("/tip?known_hash={}&wait=1", known)).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn waits_beyond_the_cap_get_503() {
+        let router = router(1);
+        let uri = format!("/tip?known_hash={}&wait=2", tip_hash(&router).await);
+        let waiter = tokio::spawn({
+            let (router, uri) = (router.clone(), uri.clone());
+            async move { get(&router, &uri).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let response = get(&router, &uri).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+
+        // Com a primeira espera encerrada, a vaga volta
+        assert_eq!(waiter.await.unwrap().status(), StatusCode::NOT_MODIFIED);
+        let response = get(&router, &uri.replace("wait=2", "wait=1")).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+}
@@ -0,0 +1,166 @@
+// src/webhooks.rs
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::errors::ApiError;
+use crate::state::AppState;
+use crate::store;
+
+// Cabeçalho com o segredo do webhook, para o receptor conferir a origem
+const TOKEN_HEADER: &str = "x-webhook-token";
+
+/// URL que recebe um POST JSON para cada evento dos tipos assinados.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: u64,
+    pub url: String,
+    pub events: Vec<String>,
+    // Enviado em X-Webhook-Token; nunca sai nas respostas da API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    // Entregas que falharam desde o cadastro
+    #[serde(default)]
+    pub failures: u64,
+}
+
+/// Webhook como a API o mostra, sem o segredo.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookView {
+    pub id: u64,
+    pub url: String,
+    pub events: Vec<String>,
+    pub has_secret: bool,
+    pub failures: u64,
+}
+
+impl Webhook {
+    fn view(&self) -> WebhookView {
+        WebhookView {
+            id: self.id,
+            url: self.url.clone(),
+            events: self.events.clone(),
+            has_secret: self.secret.is_some(),
+            failures: self.failures,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Snapshot {
+    next_id: u64,
+    hooks: Vec<Webhook>,
+}
+
+#[derive(Debug, Default)]
+pub struct WebhookRegistry {
+    snapshot: Snapshot,
+    // webhooks.json do nó; None em memória
+    path: Option<PathBuf>,
+}
+
+impl WebhookRegistry {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        WebhookRegistry { snapshot: store::load(path.as_deref(), "Registro de webhooks"), path }
+    }
+
+    fn persist(&self) {
+        store::save(self.path.as_deref(), &self.snapshot, "os webhooks");
+    }
+
+    pub fn add(&mut self, url: String, events: Vec<String>, secret: Option<String>) -> WebhookView {
+        let hook = Webhook { id: self.snapshot.next_id, url, events, secret, failures: 0 };
+        self.snapshot.next_id += 1;
+        let view = hook.view();
+        self.snapshot.hooks.push(hook);
+        self.persist();
+        view
+    }
+
+    pub fn remove(&mut self, id: u64) -> bool {
+        let before = self.snapshot.hooks.len();
+        self.snapshot.hooks.retain(|h| h.id != id);
+        let removed = self.snapshot.hooks.len() != before;
+        if removed {
+            self.persist();
+        }
+        removed
+    }
+
+    pub fn list(&self) -> Vec<WebhookView> {
+        self.snapshot.hooks.iter().map(Webhook::view).collect()
+    }
+
+    fn subscribed(&self, event: &str) -> Vec<Webhook> {
+        self.snapshot.hooks.iter().filter(|h| h.events.iter().any(|e| e == event)).cloned().collect()
+    }
+
+    fn record_failure(&mut self, id: u64) {
+        if let Some(hook) = self.snapshot.hooks.iter_mut().find(|h| h.id == id) {
+            hook.failures += 1;
+            self.persist();
+        }
+    }
+}
+
+/// Entrega `payload` a todos os webhooks do tipo `event`, sem bloquear quem chamou.
+pub fn dispatch(state: &AppState, event: &str, payload: serde_json::Value) {
+    let body = serde_json::json!({ "event": event, "payload": payload });
+    for hook in state.webhooks.lock().unwrap().subscribed(event) {
+        let client = state.http.clone();
+        let webhooks = state.webhooks.clone();
+        let body = body.clone();
+        let event = event.to_string();
+        tokio::spawn(async move {
+            let mut request = client.post(&hook.url).header("x-event-type", &event).json(&body);
+            if let Some(secret) = &hook.secret {
+                request = request.header(TOKEN_HEADER, secret);
+            }
+            if let Err(e) = request.send().await.and_then(|r| r.error_for_status()) {
+                warn!("Falha ao entregar o evento {} para {}: {}", event, hook.url, e);
+                webhooks.lock().unwrap().record_failure(hook.id);
+            }
+        });
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AddWebhook {
+    url: String,
+    events: Vec<String>,
+    secret: Option<String>,
+}
+
+pub async fn add_webhook_handler(
+    State(state): State<AppState>,
+    Json(body): Json<AddWebhook>,
+) -> Result<Json<WebhookView>, Response> {
+    if !body.url.starts_with("http://") && !body.url.starts_with("https://") {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "url must start with http:// or https://").into());
+    }
+    if body.events.is_empty() {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "events must not be empty").into());
+    }
+    Ok(Json(state.webhooks.lock().unwrap().add(body.url, body.events, body.secret)))
+}
+
+pub async fn list_webhooks_handler(State(state): State<AppState>) -> Json<Vec<WebhookView>> {
+    Json(state.webhooks.lock().unwrap().list())
+}
+
+pub async fn delete_webhook_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, ApiError> {
+    if state.webhooks.lock().unwrap().remove(id) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::new(StatusCode::NOT_FOUND, format!("Webhook {} not found", id)))
+    }
+}
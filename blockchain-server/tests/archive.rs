@@ -0,0 +1,65 @@
+// tests/archive.rs
+//! Exportação com manifesto de um nó e importação em outro, limpa, adulterada e truncada.
+use blockchain_server::testkit::{Cluster, ADMIN_KEY};
+
+async fn export(cluster: &Cluster, segment_size: usize) -> String {
+    let url = format!("{}/chain/export?format=archive&segment_size={}", cluster.node(0).url, segment_size);
+    let response = cluster.client.get(url).header("x-api-key", ADMIN_KEY).send().await.unwrap();
+    assert!(response.status().is_success());
+    response.text().await.unwrap()
+}
+
+async fn import(cluster: &Cluster, archive: String) -> (u16, serde_json::Value) {
+    let request = cluster.client.post(format!("{}/chain/import", cluster.node(1).url)).body(archive);
+    let response = request.header("x-api-key", ADMIN_KEY).send().await.unwrap();
+    (response.status().as_u16(), response.json().await.unwrap())
+}
+
+// Troca um caractere base64 da linha `line` por outro válido, alterando um byte do segmento
+fn flip_byte(archive: &str, line: usize) -> String {
+    let mut lines: Vec<String> = archive.lines().map(String::from).collect();
+    let mut chars: Vec<char> = lines[line].chars().collect();
+    chars[10] = if chars[10] == 'A' { 'B' } else { 'A' };
+    lines[line] = chars.into_iter().collect();
+    lines.join("\n") + "\n"
+}
+
+#[tokio::test]
+async fn tampered_segment_is_named_and_nothing_applied() {
+    let cluster = Cluster::start(2).await;
+    for _ in 0..5 {
+        cluster.mine(0).await;
+    }
+    let archive = export(&cluster, 2).await;
+    let manifest: serde_json::Value = serde_json::from_str(archive.lines().next().unwrap()).unwrap();
+    assert_eq!((manifest["height"].as_u64(), manifest["segments"].as_array().unwrap().len()), (Some(6), 3));
+    assert_eq!(manifest["tip_hash"], cluster.node(0).tip().hash);
+
+    // O segmento 1 fica na terceira linha, depois do manifesto e do segmento 0
+    let (status, body) = import(&cluster, flip_byte(&archive, 2)).await;
+    assert_eq!(status, 422);
+    assert!(body["error"].as_str().unwrap().starts_with("segment 1: sha256 mismatch"), "{}", body);
+    assert_eq!(cluster.node(1).state.chain.lock().unwrap().height(), 1);
+
+    let truncated: String = archive.lines().take(3).map(|l| format!("{}\n", l)).collect();
+    let (status, body) = import(&cluster, truncated).await;
+    assert_eq!(status, 422);
+    assert_eq!(body["error"], "archive truncated: manifest lists 3 segments, found 2");
+    assert_eq!(cluster.node(1).state.chain.lock().unwrap().height(), 1);
+}
+
+#[tokio::test]
+async fn clean_round_trip() {
+    let cluster = Cluster::start(2).await;
+    for _ in 0..5 {
+        cluster.mine(0).await;
+    }
+    let (status, body) = import(&cluster, export(&cluster, 2).await).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!((body["height"].as_u64(), body["segments"].as_u64()), (Some(6), Some(3)));
+    assert_eq!(cluster.node(1).tip().hash, cluster.node(0).tip().hash);
+    let hashes = |i: usize| -> Vec<String> {
+        cluster.node(i).state.chain.lock().unwrap().blocks().iter().map(|b| b.hash.clone()).collect()
+    };
+    assert_eq!(hashes(1), hashes(0));
+}
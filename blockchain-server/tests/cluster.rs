@@ -0,0 +1,104 @@
+// tests/cluster.rs
+//! Nós no mesmo processo, ligados por HTTP, convergindo pela escolha de fork.
+use blockchain_core::Block;
+use blockchain_server::testkit::{Cluster, READ_KEY};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Um bloco minerado num nó chega aos outros dois.
+#[tokio::test]
+async fn propagation() {
+    let cluster = Cluster::start(3).await;
+    let block = cluster.mine(0).await;
+    assert_eq!(cluster.assert_converged().await, block.hash);
+    assert!(cluster.nodes.iter().all(|node| node.state.chain.lock().unwrap().height() == 2));
+}
+
+/// Dois nós isolados mineram cadeias concorrentes da mesma altura; ligados de novo, os dois ficam com
+/// a que vence `fork_choice`.
+#[tokio::test]
+async fn competing_forks() {
+    let cluster = Cluster::start(2).await;
+    cluster.partition(&[0]);
+    for _ in 0..2 {
+        cluster.mine(0).await;
+        cluster.mine(1).await;
+    }
+    cluster.resolve_all().await;
+    assert!(!cluster.converged(), "nós isolados não deveriam trocar cadeias");
+    let expected = cluster.preferred_tip(0, 1);
+    cluster.heal().await;
+    assert_eq!(cluster.assert_converged().await, expected);
+}
+
+/// Um nó separado da maioria fica para trás enquanto os dois lados mineram, e reconverge na cadeia
+/// vencedora quando a partição acaba.
+#[tokio::test]
+async fn partition_recovery() {
+    let cluster = Cluster::start(3).await;
+    cluster.mine(0).await;
+    cluster.assert_converged().await;
+    cluster.partition(&[2]);
+    cluster.mine(0).await;
+    cluster.mine(0).await;
+    cluster.mine(2).await;
+    cluster.resolve_all().await;
+    assert_eq!(cluster.node(1).tip().hash, cluster.node(0).tip().hash);
+    assert_ne!(cluster.node(2).tip().hash, cluster.node(0).tip().hash);
+    let expected = cluster.preferred_tip(0, 2);
+    cluster.heal().await;
+    assert_eq!(cluster.assert_converged().await, expected);
+}
+
+// Pares (altura, hash da ponta) lidos de um nó por /chain/summary, /chain/tail e /balance até `stop`; a
+// resposta de /balance só traz a altura
+async fn read_tips(url: String, stop: Arc<AtomicBool>) -> Vec<(u64, Option<String>)> {
+    let client = reqwest::Client::new();
+    let get = |path: &str| client.get(format!("{}{}", url, path)).header("x-api-key", READ_KEY).send();
+    let mut seen = Vec::new();
+    while !stop.load(Ordering::Relaxed) {
+        let summary: serde_json::Value = get("/chain/summary").await.unwrap().json().await.unwrap();
+        seen.push((summary["height"].as_u64().unwrap(), summary["tip_hash"].as_str().map(String::from)));
+        let tail: Vec<Block> = get("/chain/tail?count=1").await.unwrap().json().await.unwrap();
+        seen.push((tail[0].index, Some(tail[0].hash.clone())));
+        let balance: serde_json::Value = get("/balance/nobody").await.unwrap().json().await.unwrap();
+        seen.push((balance["height"].as_u64().unwrap(), None));
+    }
+    seen
+}
+
+/// Leituras contínuas de /chain/summary, /chain/tail e /balance durante uma troca de cadeia por POST
+/// /chain/resolve: toda resposta é da cadeia antiga inteira ou da nova inteira, nunca a altura de uma
+/// com a ponta da outra.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn replacement_keeps_reads_consistent() {
+    let cluster = Cluster::start(2).await;
+    cluster.partition(&[0]);
+    let old = cluster.mine(0).await;
+    for _ in 0..3 {
+        cluster.mine(1).await;
+    }
+    let new = cluster.node(1).tip();
+    cluster.connect(0, 1).await;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let readers: Vec<_> =
+        (0..4).map(|_| tokio::spawn(read_tips(cluster.node(0).url.clone(), stop.clone()))).collect();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    cluster.resolve_all().await;
+    assert_eq!(cluster.node(0).tip().hash, new.hash);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    stop.store(true, Ordering::Relaxed);
+
+    let mut seen = Vec::new();
+    for reader in readers {
+        seen.extend(reader.await.expect("leitor"));
+    }
+    for (height, hash) in &seen {
+        let consistent = |tip: &Block| *height == tip.index && hash.as_ref().is_none_or(|h| *h == tip.hash);
+        assert!(consistent(&old) || consistent(&new), "leitura misturada: altura {} com ponta {:?}", height, hash);
+    }
+    assert!(seen.iter().any(|(height, _)| *height == old.index), "nenhuma leitura antes da troca");
+    assert!(seen.iter().any(|(height, _)| *height == new.index), "nenhuma leitura depois da troca");
+}
@@ -0,0 +1,58 @@
+// tests/consistency.rs
+//! X-Chain-Position nas escritas e X-Require-Position nas leituras, entre um nó e uma réplica atrasada.
+use blockchain_server::testkit::{Cluster, ADMIN_KEY, MINE_KEY, READ_KEY};
+use reqwest::StatusCode;
+use std::time::{Duration, Instant};
+
+/// Minera no nó `i` e devolve o token da resposta.
+async fn mine_position(cluster: &Cluster, i: usize) -> String {
+    let response = cluster.client.get(format!("{}/mine", cluster.node(i).url)).header("x-api-key", MINE_KEY);
+    let response = response.send().await.unwrap();
+    assert!(response.status().is_success());
+    let position = response.headers()["x-chain-position"].to_str().unwrap().to_string();
+    assert_eq!(position, format!("2:{}", cluster.node(i).tip().hash));
+    position
+}
+
+fn read_tail(cluster: &Cluster, i: usize, position: &str) -> reqwest::RequestBuilder {
+    let url = format!("{}/chain/tail", cluster.node(i).url);
+    cluster.client.get(url).header("x-api-key", READ_KEY).header("x-require-position", position)
+}
+
+#[tokio::test]
+async fn own_position_is_satisfied_immediately() {
+    let cluster = Cluster::start(1).await;
+    let position = mine_position(&cluster, 0).await;
+    let started = Instant::now();
+    let response = read_tail(&cluster, 0, &position).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(started.elapsed() < Duration::from_millis(500), "a leitura no próprio nó não deveria esperar");
+
+    let response = read_tail(&cluster, 0, "nonsense").send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn lagging_replica_answers_412() {
+    let cluster = Cluster::start(2).await;
+    let position = mine_position(&cluster, 0).await;
+    let response = read_tail(&cluster, 1, &position).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+}
+
+/// A réplica sincroniza enquanto a leitura espera, e a leitura sai com a posição alcançada.
+#[tokio::test]
+async fn lagging_replica_waits_for_sync() {
+    let cluster = Cluster::start(2).await;
+    let position = mine_position(&cluster, 0).await;
+    let read = tokio::spawn(read_tail(&cluster, 1, &position).send());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!read.is_finished(), "a réplica ainda não tem o bloco");
+
+    let resolve = cluster.client.post(format!("{}/chain/resolve", cluster.node(1).url));
+    cluster.send(resolve, ADMIN_KEY).await.unwrap();
+    let response = read.await.unwrap().unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let tail: serde_json::Value = response.json().await.unwrap();
+    assert!(tail.to_string().contains(position.split_once(':').unwrap().1));
+}
@@ -0,0 +1,55 @@
+// tests/difficulty.rs
+//! PUT /difficulty: tudo ou nada, com `if_version` contra edições concorrentes.
+use blockchain_server::testkit::{Cluster, ADMIN_KEY, READ_KEY};
+
+async fn difficulty(cluster: &Cluster) -> serde_json::Value {
+    let request = cluster.client.get(format!("{}/difficulty", cluster.node(0).url));
+    cluster.send(request, READ_KEY).await.unwrap()
+}
+
+async fn put(cluster: &Cluster, body: serde_json::Value) -> (u16, serde_json::Value) {
+    let request = cluster.client.put(format!("{}/difficulty", cluster.node(0).url)).json(&body);
+    let response = request.header("x-api-key", ADMIN_KEY).send().await.unwrap();
+    // A recusa do extrator (campo desconhecido) vem em texto
+    (response.status().as_u16(), serde_json::from_str(&response.text().await.unwrap()).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn concurrent_edits_of_one_version_admit_one() {
+    let cluster = Cluster::start(1).await;
+    let version = difficulty(&cluster).await["version"].as_u64().unwrap();
+    let edit = |n_limit: u64| put(&cluster, serde_json::json!({ "n_limit": n_limit, "if_version": version }));
+    let (a, b, c, d) = tokio::join!(edit(200), edit(201), edit(202), edit(203));
+    let results = [a, b, c, d];
+
+    let winners: Vec<_> = results.iter().filter(|(status, _)| *status == 200).collect();
+    assert_eq!(winners.len(), 1, "{:?}", results);
+    assert_eq!(results.iter().filter(|(status, _)| *status == 409).count(), 3);
+    let current = difficulty(&cluster).await;
+    assert_eq!(current["version"], version + 1);
+    assert_eq!(current["version"], winners[0].1["version"]);
+    assert_eq!(current["difficulty"]["n_limit"], winners[0].1["difficulty"]["n_limit"]);
+    assert!(results.iter().filter(|(status, _)| *status == 409).all(|(_, body)| body["version"] == version + 1));
+
+    // O reajuste automático também sobe a versão
+    cluster.mine(0).await;
+    assert!(difficulty(&cluster).await["version"].as_u64().unwrap() > version + 1);
+}
+
+#[tokio::test]
+async fn infeasible_combination_changes_nothing() {
+    let cluster = Cluster::start(1).await;
+    let before = difficulty(&cluster).await;
+    // Cada campo vale sozinho; juntos, o witness não cabe em u64 e há alvo de hash antes da v4
+    let change = serde_json::json!({ "min_digits": 18, "n_limit": 1_000_000, "hash_scale": 4 });
+    let (status, body) = put(&cluster, change).await;
+    assert_eq!(status, 422);
+    let problems = body["problems"].to_string();
+    assert!(problems.contains("n_limit") && problems.contains("hash_scale"), "{}", problems);
+    assert_eq!(difficulty(&cluster).await["difficulty"], before["difficulty"]);
+    assert_eq!(difficulty(&cluster).await["version"], before["version"]);
+
+    let (status, _) = put(&cluster, serde_json::json!({ "n_limit": 300, "surprise": 1 })).await;
+    assert_eq!(status, 422);
+    assert_eq!(difficulty(&cluster).await["version"], before["version"]);
+}
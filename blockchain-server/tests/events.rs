@@ -0,0 +1,58 @@
+// tests/events.rs
+//! GET /events?from_height=N: histórico até a ponta e depois os blocos ao vivo, cada altura uma vez.
+use blockchain_server::testkit::{Cluster, READ_KEY};
+use std::time::Duration;
+
+/// Lê eventos `block` do stream até juntar `count` e devolve a altura (`id`) de cada um.
+async fn read_heights(response: &mut reqwest::Response, count: usize) -> Vec<u64> {
+    let mut buffer = String::new();
+    let mut heights = Vec::new();
+    while heights.len() < count {
+        let chunk = tokio::time::timeout(Duration::from_secs(10), response.chunk()).await;
+        let chunk = chunk.expect("stream parado").unwrap().expect("stream encerrado");
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+        while let Some(end) = buffer.find("\n\n") {
+            let frame: String = buffer.drain(..end + 2).collect();
+            // Comentários de keep-alive não têm `id`
+            let id = frame.lines().find_map(|line| line.strip_prefix("id:"));
+            if let Some(id) = id.filter(|_| frame.contains("event: block")) {
+                heights.push(id.trim().parse().unwrap());
+            }
+        }
+    }
+    heights
+}
+
+async fn connect(cluster: &Cluster, query: &str, last_event_id: Option<u64>) -> reqwest::Response {
+    let mut request = cluster.client.get(format!("{}/events{}", cluster.node(0).url, query));
+    request = request.header("x-api-key", READ_KEY);
+    if let Some(id) = last_event_id {
+        request = request.header("last-event-id", id.to_string());
+    }
+    let response = request.send().await.unwrap();
+    assert!(response.status().is_success());
+    response
+}
+
+#[tokio::test]
+async fn replay_then_live_blocks_exactly_once() {
+    let cluster = Cluster::start(1).await;
+    // Cinco blocos contando o gênesis: alturas 0 a 4
+    for _ in 0..4 {
+        cluster.mine(0).await;
+    }
+    let mut response = connect(&cluster, "?from_height=2", None).await;
+    // Os dois novos saem enquanto o histórico ainda não foi lido
+    cluster.mine(0).await;
+    let tip = cluster.mine(0).await;
+    assert_eq!(tip.index, 6);
+    assert_eq!(read_heights(&mut response, 5).await, vec![2, 3, 4, 5, 6]);
+
+    // Nada repetido nem fora de ordem depois da transição
+    cluster.mine(0).await;
+    assert_eq!(read_heights(&mut response, 1).await, vec![7]);
+
+    // Reconexão pelo Last-Event-ID retoma na altura seguinte, acima do from_height
+    let mut resumed = connect(&cluster, "?from_height=0", Some(5)).await;
+    assert_eq!(read_heights(&mut resumed, 2).await, vec![6, 7]);
+}
@@ -0,0 +1,82 @@
+// tests/handshake.rs
+//! POST /peers só registra quem passa no handshake; o handshake vencido é refeito no resolve.
+use axum::{routing::get, Json, Router};
+use blockchain_server::testkit::{Cluster, ADMIN_KEY};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+type Shared = Arc<Mutex<serde_json::Value>>;
+
+/// Peer falso que só responde GET /handshake, com o conteúdo atual de `handshake`.
+async fn mock_peer(handshake: Shared) -> String {
+    let router = Router::new().route(
+        "/handshake",
+        get(move || async move { Json(handshake.lock().unwrap().clone()) }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    url
+}
+
+// O handshake do próprio nó, com outra chave: compatível por construção
+async fn compatible(cluster: &Cluster) -> serde_json::Value {
+    let mut handshake: serde_json::Value =
+        cluster.client.get(format!("{}/handshake", cluster.node(0).url)).send().await.unwrap().json().await.unwrap();
+    handshake["node_pubkey"] = "ab".repeat(32).into();
+    handshake["software_version"] = "mock".into();
+    handshake
+}
+
+async fn add_peer(cluster: &Cluster, url: &str) -> (u16, serde_json::Value) {
+    let request = cluster.client.post(format!("{}/peers", cluster.node(0).url));
+    let response = request.header("x-api-key", ADMIN_KEY).json(&serde_json::json!({ "url": url })).send().await;
+    let response = response.unwrap();
+    (response.status().as_u16(), response.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn compatible_peer_is_stored_with_its_identity() {
+    let cluster = Cluster::start(1).await;
+    let url = mock_peer(Arc::new(Mutex::new(compatible(&cluster).await))).await;
+    let (status, peer) = add_peer(&cluster, &url).await;
+    assert_eq!(status, 200, "{}", peer);
+    assert_eq!(peer["node_pubkey"], "ab".repeat(32));
+    assert_eq!(peer["software_version"], "mock");
+    assert_eq!(cluster.node(0).state.peers.lock().unwrap().urls(), vec![url]);
+}
+
+#[tokio::test]
+async fn wrong_genesis_or_rules_are_rejected_by_field() {
+    let cluster = Cluster::start(1).await;
+    for (field, value) in [("genesis_hash", serde_json::json!("00".repeat(32))), ("rules_version", 99.into())] {
+        let mut handshake = compatible(&cluster).await;
+        handshake[field] = value.clone();
+        let url = mock_peer(Arc::new(Mutex::new(handshake))).await;
+        let (status, body) = add_peer(&cluster, &url).await;
+        assert_eq!(status, 409);
+        assert!(body["error"].as_str().unwrap().contains(field), "{}", body);
+        assert_eq!(body["mismatches"].as_array().unwrap().len(), 1);
+        assert_eq!(body["mismatches"][0]["field"], field);
+        assert_eq!(body["mismatches"][0]["remote"], value.as_str().map_or(value.to_string(), String::from));
+    }
+    assert!(cluster.node(0).state.peers.lock().unwrap().urls().is_empty());
+}
+
+/// O peer reiniciado noutra cadeia é pego no handshake seguinte e sai da sincronização.
+#[tokio::test]
+async fn reset_peer_is_caught_on_rehandshake() {
+    let cluster = Cluster::start(1).await;
+    let handshake: Shared = Arc::new(Mutex::new(compatible(&cluster).await));
+    let url = mock_peer(handshake.clone()).await;
+    assert_eq!(add_peer(&cluster, &url).await.0, 200);
+
+    handshake.lock().unwrap()["genesis_hash"] = "11".repeat(32).into();
+    cluster.node(0).state.peers.lock().unwrap().expire_handshakes();
+    let resolve = cluster.client.post(format!("{}/chain/resolve", cluster.node(0).url));
+    let report = cluster.send(resolve, ADMIN_KEY).await.unwrap();
+    assert_eq!(report["peers"][0]["status"], "incompatible");
+    assert_eq!(report["peers"][0]["mismatches"][0]["field"], "genesis_hash");
+    let peers = cluster.client.get(format!("{}/peers", cluster.node(0).url));
+    assert_eq!(cluster.send(peers, ADMIN_KEY).await.unwrap()[0]["healthy"], false);
+}
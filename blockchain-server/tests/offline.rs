@@ -0,0 +1,125 @@
+// tests/offline.rs
+//! Minerador sem rede: busca o modelo, minera e assina fora do nó, e outra máquina envia o bloco depois.
+use blockchain_core::block::BlockBuilder;
+use blockchain_core::signature::sign_block;
+use blockchain_core::testkit::test_key;
+use blockchain_core::{mine_template, Block, Difficulty, Throttle};
+use blockchain_server::testkit::{Cluster, ADMIN_KEY, MINE_KEY};
+use std::sync::atomic::AtomicBool;
+
+async fn fetch_template(cluster: &Cluster) -> serde_json::Value {
+    let request = cluster.client.get(format!("{}/mine/template", cluster.node(0).url));
+    cluster.send(request, MINE_KEY).await.unwrap()
+}
+
+// O que a máquina isolada faz só com o modelo em mãos
+fn mine_offline(template: &serde_json::Value) -> Block {
+    let builder = BlockBuilder::new(template["index"].as_u64().unwrap(), template["prev_hash"].as_str().unwrap())
+        .rules_version(template["rules_version"].as_u64().unwrap() as u32)
+        .hash_scale(template["hash_scale"].as_u64().unwrap())
+        .reward(template["reward"].as_u64().unwrap())
+        .stamp_now();
+    let field = |name: &str| template["difficulty"][name].as_u64().unwrap();
+    let difficulty = Difficulty {
+        n_limit: field("n_limit"),
+        min_digits: field("min_digits") as u32,
+        min_prob: field("min_prob"),
+        hash_scale: field("hash_scale"),
+        ..Difficulty::default()
+    };
+    let stop = AtomicBool::new(false);
+    mine_template(&builder, &difficulty, &stop, None, &mut Throttle::unlimited()).unwrap().0
+}
+
+async fn register_miner(cluster: &Cluster) -> String {
+    let pubkey = hex::encode(test_key(7).verifying_key().to_bytes());
+    let request = cluster.client.post(format!("{}/miners", cluster.node(0).url));
+    cluster.send(request.json(&serde_json::json!({ "pubkey": pubkey, "name": "rig" })), ADMIN_KEY).await.unwrap();
+    pubkey
+}
+
+async fn submit(cluster: &Cluster, body: serde_json::Value) -> (u16, serde_json::Value) {
+    let request = cluster.client.post(format!("{}/mine/submit", cluster.node(0).url)).json(&body);
+    let response = request.header("x-api-key", MINE_KEY).send().await.unwrap();
+    (response.status().as_u16(), response.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn template_submission_within_the_window() {
+    let cluster = Cluster::start(1).await;
+    let template = fetch_template(&cluster).await;
+    let block = mine_offline(&template);
+    let body = serde_json::json!({ "template_id": template["template_id"], "block": block });
+    let (status, body) = submit(&cluster, body).await;
+    assert_eq!((status, body["via"].as_str()), (200, Some("template")));
+    assert_eq!(cluster.node(0).tip().hash, block.hash);
+}
+
+/// Sem modelo conhecido (vencido e descartado), a assinatura basta enquanto o bloco apontar para a ponta.
+#[tokio::test]
+async fn signed_block_outlives_its_template() {
+    let cluster = Cluster::start(1).await;
+    let pubkey = register_miner(&cluster).await;
+    let block = mine_offline(&fetch_template(&cluster).await);
+    let signature = sign_block(&test_key(7), &block);
+
+    let unsigned = serde_json::json!({ "template_id": "pruned", "block": block });
+    assert_eq!(submit(&cluster, unsigned).await.0, 404);
+    let forged = sign_block(&test_key(8), &block);
+    let forged = serde_json::json!({ "block": block, "miner_pubkey": pubkey, "signature": forged });
+    assert_eq!(submit(&cluster, forged).await.0, 403);
+
+    let signed =
+        serde_json::json!({ "template_id": "pruned", "block": block, "miner_pubkey": pubkey, "signature": signature });
+    let (status, body) = submit(&cluster, signed).await;
+    assert_eq!((status, body["via"].as_str()), (200, Some("signature")));
+    assert_eq!(cluster.node(0).tip().hash, block.hash);
+}
+
+/// A ponta andou um bloco entre a busca do modelo e o envio: a resposta diz desde onde ele ainda serve
+/// como candidato de fork.
+#[tokio::test]
+async fn tip_advanced_by_one_in_between() {
+    let cluster = Cluster::start(1).await;
+    let pubkey = register_miner(&cluster).await;
+    let template = fetch_template(&cluster).await;
+    let block = mine_offline(&template);
+    let tip = cluster.mine(0).await;
+
+    let signed = serde_json::json!({
+        "template_id": template["template_id"],
+        "block": block,
+        "miner_pubkey": pubkey,
+        "signature": sign_block(&test_key(7), &block),
+    });
+    let (status, body) = submit(&cluster, signed).await;
+    assert_eq!(status, 409);
+    assert_eq!(body["status"], "stale");
+    assert_eq!(body["fork_point"], template["issued_at_height"]);
+    assert_eq!(body["earliest_adoption_height"], block.index);
+    assert_eq!((body["tip_index"].as_u64(), body["blocks_behind"].as_u64()), (Some(tip.index), Some(1)));
+    assert_eq!(cluster.node(0).tip().hash, tip.hash);
+}
+
+/// O modelo vale por `template_window` blocos depois de emitido; sem assinatura, o envio depois disso é 410.
+#[tokio::test]
+async fn template_expires_after_its_height_window() {
+    let cluster = Cluster::start(1).await;
+    let window = cluster.node(0).state.config.template_window;
+    let template = fetch_template(&cluster).await;
+    let issued_at = template["issued_at_height"].as_u64().unwrap();
+    assert_eq!(template["expires_after_height"].as_u64(), Some(issued_at + window));
+    let block = mine_offline(&template);
+    let body = serde_json::json!({ "template_id": template["template_id"], "block": block });
+
+    // Dentro da janela o modelo ainda vale: o bloco só está atrasado
+    cluster.mine(0).await;
+    let (status, response) = submit(&cluster, body.clone()).await;
+    assert_eq!((status, response["status"].as_str()), (409, Some("stale")));
+    for _ in 0..window {
+        cluster.mine(0).await;
+    }
+    let (status, response) = submit(&cluster, body).await;
+    assert_eq!(status, 410);
+    assert!(response["error"].as_str().unwrap().contains("sign the block"));
+}
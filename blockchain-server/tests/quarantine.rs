@@ -0,0 +1,74 @@
+// tests/quarantine.rs
+//! Peer falso que serve uma cadeia mais longa com um bloco de primo composto.
+use axum::{routing::get, Json, Router};
+use blockchain_core::testkit::{ChainBuilder, GenesisConfig, InvalidBlock};
+use blockchain_core::Block;
+use blockchain_server::testkit::{Cluster, ADMIN_KEY};
+use tokio::net::TcpListener;
+
+/// Sobe o peer falso com o handshake de `cluster.node(0)` e devolve a URL dele.
+async fn mock_peer(cluster: &Cluster, blocks: Vec<Block>) -> String {
+    let rules_version = cluster.node(0).state.chain.lock().unwrap().rules().version_at(blocks.len() as u64);
+    let handshake = serde_json::json!({
+        "chain_id": "default",
+        "genesis_hash": blocks[0].hash,
+        "height": blocks.len(),
+        "rules_version": rules_version,
+        "node_pubkey": "00".repeat(32),
+        "software_version": "mock",
+    });
+    let router = Router::new()
+        .route("/handshake", get(move || async move { Json(handshake) }))
+        .route("/chain", get(move || async move { Json(blocks) }));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    url
+}
+
+// Gênesis, um bloco válido e o de primo composto no índice 2
+fn forged_chain() -> Vec<Block> {
+    let builder = ChainBuilder::new(GenesisConfig::default()).mine_n(1, 9);
+    let invalid = InvalidBlock::on(builder.chain(), 9).composite_prime().build();
+    let mut blocks = builder.blocks();
+    blocks.push(invalid);
+    blocks
+}
+
+async fn resolve(cluster: &Cluster) -> serde_json::Value {
+    let request = cluster.client.post(format!("{}/chain/resolve", cluster.node(0).url));
+    cluster.send(request, ADMIN_KEY).await.unwrap()
+}
+
+#[tokio::test]
+async fn composite_prime_is_quarantined() {
+    let cluster = Cluster::start(1).await;
+    let peer = mock_peer(&cluster, forged_chain()).await;
+    let add = cluster.client.post(format!("{}/peers", cluster.node(0).url)).json(&serde_json::json!({ "url": peer }));
+    cluster.send(add, ADMIN_KEY).await.unwrap();
+
+    let report = resolve(&cluster).await;
+    assert_eq!(report["replaced"], false);
+    assert_eq!(report["peers"][0]["status"], "quarantined");
+    assert_eq!(report["peers"][0]["penalty"], "demoted");
+
+    let list = cluster.client.get(format!("{}/admin/quarantine", cluster.node(0).url));
+    let entries = cluster.send(list, ADMIN_KEY).await.unwrap();
+    let entry = &entries[0];
+    assert_eq!(entry["peer"], peer);
+    assert_eq!(entry["invariant"], "primality");
+    assert_eq!(entry["invalid_block"]["index"], 2);
+    assert_eq!(entry["fork_point"], 0);
+    assert_eq!(entry["peer_height"], 3);
+    let peers = cluster.client.get(format!("{}/peers", cluster.node(0).url));
+    assert_eq!(cluster.send(peers, ADMIN_KEY).await.unwrap()[0]["healthy"], false);
+
+    // Reincidente sai do registro; a entrada apagada some da lista
+    resolve(&cluster).await;
+    assert_eq!(resolve(&cluster).await["peers"][0]["penalty"], "removed");
+    assert!(cluster.node(0).state.peers.lock().unwrap().urls().is_empty());
+    let delete = cluster.client.delete(format!("{}/admin/quarantine/{}", cluster.node(0).url, entry["id"]));
+    assert_eq!(delete.header("x-api-key", ADMIN_KEY).send().await.unwrap().status(), 204);
+    let list = cluster.client.get(format!("{}/admin/quarantine", cluster.node(0).url));
+    assert_eq!(cluster.send(list, ADMIN_KEY).await.unwrap().as_array().unwrap().len(), 2);
+}
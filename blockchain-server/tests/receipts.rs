@@ -0,0 +1,53 @@
+// tests/receipts.rs
+//! Recibos assinados pelo nó: o bloco minerado aqui traz os números; o sincronizado, não.
+use blockchain_server::testkit::{Cluster, ADMIN_KEY, READ_KEY};
+
+async fn receipt(cluster: &Cluster, node: usize, index: u64) -> serde_json::Value {
+    let request = cluster.client.get(format!("{}/block/{}/receipt", cluster.node(node).url, index));
+    cluster.send(request, READ_KEY).await.unwrap()
+}
+
+async fn verify(cluster: &Cluster, receipt: &serde_json::Value, pubkey: Option<&str>) -> bool {
+    let body = serde_json::json!({ "receipt": receipt, "pubkey": pubkey });
+    let request = cluster.client.post(format!("{}/receipts/verify", cluster.node(0).url)).json(&body);
+    cluster.send(request, READ_KEY).await.unwrap()["valid"].as_bool().unwrap()
+}
+
+#[tokio::test]
+async fn issue_verify_and_tamper() {
+    let cluster = Cluster::start(2).await;
+    let block = cluster.mine(0).await;
+    let issued = receipt(&cluster, 0, 1).await;
+    assert_eq!((issued["hash"].as_str(), issued["height"].as_u64()), (Some(block.hash.as_str()), Some(1)));
+    assert_eq!(issued["timestamp"], block.timestamp);
+    assert_eq!(issued["mined_locally"], true);
+    assert!(issued["stats"]["candidates"].as_u64().unwrap() >= 1);
+    assert!(issued["stats"]["duration_ms"].is_u64());
+    assert!(verify(&cluster, &issued, None).await);
+
+    let mut forged = issued.clone();
+    forged["stats"]["candidates"] = 999_999.into();
+    assert!(!verify(&cluster, &forged, None).await);
+    // Assinado pelo nó 0, não confere com a chave do nó 1
+    let other = receipt(&cluster, 1, 0).await;
+    assert!(!verify(&cluster, &issued, other["node_pubkey"].as_str()).await);
+    assert!(verify(&cluster, &other, other["node_pubkey"].as_str()).await);
+}
+
+#[tokio::test]
+async fn synced_block_is_not_mined_locally() {
+    let cluster = Cluster::start(2).await;
+    let block = cluster.mine(0).await;
+    let resolve = cluster.client.post(format!("{}/chain/resolve", cluster.node(1).url));
+    cluster.send(resolve, ADMIN_KEY).await.unwrap();
+    assert_eq!(cluster.node(1).tip().hash, block.hash);
+
+    let synced = receipt(&cluster, 1, 1).await;
+    assert_eq!(synced["hash"], block.hash);
+    assert_eq!(synced["mined_locally"], false);
+    assert!(synced.get("stats").is_none());
+    assert!(verify(&cluster, &synced, synced["node_pubkey"].as_str()).await);
+
+    let missing = cluster.client.get(format!("{}/block/9/receipt", cluster.node(0).url));
+    assert_eq!(missing.header("x-api-key", READ_KEY).send().await.unwrap().status(), 404);
+}
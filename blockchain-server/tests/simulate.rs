@@ -0,0 +1,44 @@
+// tests/simulate.rs
+//! POST /admin/simulate: trajetória a partir da dificuldade do nó, sem minerar nem mexer na cadeia.
+use blockchain_server::testkit::{Cluster, ADMIN_KEY, READ_KEY};
+
+fn scenario(blocks: usize) -> serde_json::Value {
+    serde_json::json!({
+        "blocks": blocks,
+        "hashrate": [{ "at_secs": 0.0, "candidates_per_sec": 500.0 }],
+        "algorithm": { "algorithm": "ema" },
+        "seed": 3,
+    })
+}
+
+async fn simulate(cluster: &Cluster, body: serde_json::Value, key: &str) -> (u16, serde_json::Value) {
+    let request = cluster.client.post(format!("{}/admin/simulate", cluster.node(0).url)).json(&body);
+    let response = request.header("x-api-key", key).send().await.unwrap();
+    (response.status().as_u16(), serde_json::from_str(&response.text().await.unwrap()).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn simulation_leaves_the_chain_alone() {
+    let cluster = Cluster::start(1).await;
+    let tip = cluster.node(0).tip();
+    let difficulty = || serde_json::to_value(&cluster.node(0).state.chain.lock().unwrap().difficulty).unwrap();
+    let before = difficulty();
+
+    let (status, body) = simulate(&cluster, scenario(1_000), ADMIN_KEY).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["blocks"].as_array().unwrap().len(), 1_000);
+    assert_eq!(body["start"], before);
+    assert_eq!(simulate(&cluster, scenario(1_000), ADMIN_KEY).await.1, body);
+
+    assert_eq!(cluster.node(0).tip().hash, tip.hash);
+    assert_eq!(difficulty(), before);
+}
+
+#[tokio::test]
+async fn simulation_needs_admin_and_a_valid_scenario() {
+    let cluster = Cluster::start(1).await;
+    assert_eq!(simulate(&cluster, scenario(10), READ_KEY).await.0, 403);
+    let (status, body) = simulate(&cluster, scenario(0), ADMIN_KEY).await;
+    assert_eq!(status, 422);
+    assert!(body["error"].as_str().unwrap().contains("blocks must be between"));
+}
@@ -0,0 +1,13 @@
+#!/usr/bin/env bash
+# Garante que o núcleo continua compilando sem a feature `mining` (sem entropia do sistema nem
+# threads), inclusive para wasm32-unknown-unknown, onde clientes leves o embutem.
+set -euo pipefail
+cd "$(dirname "$0")/.."
+
+cargo test -p blockchain-core --no-default-features
+cargo clippy -p blockchain-core --no-default-features --all-targets -- -D warnings
+if rustup target list --installed | grep -qx wasm32-unknown-unknown; then
+    cargo check -p blockchain-core --no-default-features --target wasm32-unknown-unknown
+else
+    echo "wasm32-unknown-unknown não instalado; pulando (rustup target add wasm32-unknown-unknown)" >&2
+fi